@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+
+use sandman::bed::{ParseOptions, parse_bed6_sink_simd};
+use sandman::pufferfish::pool::BgzfBlockPool;
+use sandman::prelude::*;
+use sandman::testing::{Fixture, SyntheticBed};
+
+// 100k records over two contigs, about 4 MB of text
+fn fixture(kind: BedKind) -> Fixture
+{
+	SyntheticBed::default()
+		.with_kind(kind)
+		.with_records_per_contig(50_000)
+		.fixture()
+		.expect("synthetic fixture")
+}
+
+fn pool() -> Arc<BgzfBlockPool>
+{
+	Arc::new(BgzfBlockPool::new(1024, 64 * 1024))
+}
+
+fn runtime() -> Runtime
+{
+	tokio::runtime::Builder::new_multi_thread().build().expect("tokio runtime")
+}
+
+// decompression, tokenizing and parsing of every record, the reader's throughput ceiling
+fn sequential_scan(c: &mut Criterion)
+{
+	let runtime = runtime();
+	let mut group = c.benchmark_group("sequential_scan");
+
+	for kind in [BedKind::Bed3, BedKind::Bed6, BedKind::Bed12, BedKind::BedMethyl]
+	{
+		let fixture = fixture(kind);
+		group.throughput(Throughput::Bytes(fixture.text.len() as u64));
+
+		group.bench_with_input(BenchmarkId::from_parameter(kind), &fixture, |b, fixture| {
+			b.to_async(&runtime).iter(|| async {
+				let reader = fixture.reader(pool()).await.expect("reader");
+				reader.records(16).count().await
+			})
+		});
+	}
+
+	group.finish();
+}
+
+// Queries should cost in proportion to the blocks under the region, not to the size of
+// the file, so the narrow query here should stay far below a scan.
+fn region_query(c: &mut Criterion)
+{
+	let runtime = runtime();
+	let fixture = fixture(BedKind::Bed6);
+	let reader = runtime.block_on(fixture.reader(pool())).expect("reader");
+
+	let mut group = c.benchmark_group("region_query");
+
+	for width in [1_000u64, 10_000, 100_000]
+	{
+		let region = Region::zero_based("chr1", 400_000, 400_000 + width);
+
+		group.bench_with_input(BenchmarkId::from_parameter(width), &region, |b, region| {
+			b.to_async(&runtime).iter(|| reader.query_region_concurrent(region, 1))
+		});
+	}
+
+	group.finish();
+}
+
+// the same whole-contig query over more readers of its chunks
+fn parallel_query(c: &mut Criterion)
+{
+	let runtime = runtime();
+	let fixture = fixture(BedKind::Bed6);
+	let reader = runtime.block_on(fixture.reader(pool())).expect("reader");
+	let region = Region::zero_based("chr1", 0, 1_000_000);
+
+	let mut group = c.benchmark_group("parallel_query");
+
+	for parallelism in [1usize, 2, 4, 8]
+	{
+		group.bench_with_input(
+			BenchmarkId::from_parameter(parallelism),
+			&parallelism,
+			|b, parallelism| {
+				b.to_async(&runtime).iter(|| reader.query_region_concurrent(&region, *parallelism))
+			},
+		);
+	}
+
+	group.finish();
+}
+
+// the line parser alone over text already in memory, no BGZF or tid store involved
+fn parse_only(c: &mut Criterion)
+{
+	let runtime = runtime();
+	let fixture = fixture(BedKind::Bed6);
+
+	let mut group = c.benchmark_group("parse_only");
+	group.throughput(Throughput::Bytes(fixture.text.len() as u64));
+
+	group.bench_function("bed6", |b| {
+		b.to_async(&runtime).iter(|| async {
+			let mut input = fixture.text.as_slice();
+			let mut records = 0usize;
+
+			while !input.is_empty()
+			{
+				let (rest, parsed) = parse_bed6_sink_simd(input, None, ParseOptions::default())
+					.await
+					.expect("synthetic BED6 parses");
+
+				records += parsed.is_some() as usize;
+				input = rest;
+			}
+
+			records
+		})
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, sequential_scan, region_query, parallel_query, parse_only);
+criterion_main!(benches);
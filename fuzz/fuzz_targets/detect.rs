@@ -0,0 +1,28 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use sandman::prelude::*;
+
+// format detection over the lines of an upload, and the per-kind check it is built from
+fuzz_target!(|data: &[u8]| {
+	let text = String::from_utf8_lossy(data);
+	let lines: Vec<&str> = text.lines().collect();
+
+	let _ = BedKind::detect_report(&lines);
+
+	for line in &lines
+	{
+		for kind in [
+			BedKind::Bed3,
+			BedKind::Bed4,
+			BedKind::Bed5,
+			BedKind::Bed6,
+			BedKind::Bed12,
+			BedKind::BedMethyl,
+		]
+		{
+			let _ = sandman::bed::line_parses_as(line, kind);
+		}
+	}
+});
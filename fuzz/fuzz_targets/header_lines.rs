@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use sandman::bed::TrackType;
+
+// track lines decide how the lines after them parse, browser lines are only skipped
+fuzz_target!(|data: &[u8]| {
+	for line in data.split(|&b| b == b'\n')
+	{
+		let _ = TrackType::from_line(line);
+	}
+});
@@ -0,0 +1,80 @@
+#![no_main]
+
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+use sandman::bed::{
+	ParseOptions, parse_bed3_sink_simd, parse_bed4_sink_simd, parse_bed5_sink_simd,
+	parse_bed6_prefix_sink_simd, parse_bed6_sink_simd, parse_bed12_sink_simd,
+	parse_bedmethyl_sink_simd,
+};
+use sandman::prelude::*;
+
+// the first byte picks the tokenizer mode and UTF-8 policy, the rest is fed to every
+// record parser line by line until one fails
+fuzz_target!(|data: &[u8]| {
+	let Some((&selector, input)) = data.split_first()
+	else
+	{
+		return;
+	};
+
+	let options = ParseOptions {
+		tokenizer_mode: if selector & 1 == 0
+		{
+			TokenizerMode::Tolerant
+		}
+		else
+		{
+			TokenizerMode::StrictTabs
+		},
+		utf8_policy: if selector & 2 == 0
+		{
+			Utf8Policy::Error
+		}
+		else
+		{
+			Utf8Policy::Lossy
+		},
+	};
+
+	block_on(async {
+		macro_rules! drain {
+			($parser:ident) => {
+				let mut rest = input;
+
+				while !rest.is_empty()
+				{
+					match $parser(rest, None, options).await
+					{
+						Ok((next, parsed)) =>
+						{
+							if let Some((_, _, _, _, value)) = parsed
+							{
+								if let Some(bed12) = value.bed12()
+								{
+									let _ = bed12.validate();
+									let _ = bed12.blocks().count();
+									let _ = bed12.introns().count();
+								}
+							}
+
+							let parser = stringify!($parser);
+							assert!(next.len() < rest.len(), "{parser} made no progress");
+							rest = next;
+						}
+						Err(_) => break,
+					}
+				}
+			};
+		}
+
+		drain!(parse_bed3_sink_simd);
+		drain!(parse_bed4_sink_simd);
+		drain!(parse_bed5_sink_simd);
+		drain!(parse_bed6_sink_simd);
+		drain!(parse_bed6_prefix_sink_simd);
+		drain!(parse_bed12_sink_simd);
+		drain!(parse_bedmethyl_sink_simd);
+	});
+});
@@ -0,0 +1,71 @@
+#![no_main]
+
+use std::io::{Cursor, Write};
+
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+
+use sandman::tabix::{LoadOptions, Reader};
+
+// The first byte picks eager or lazy loading and whether the rest is wrapped in a BGZF
+// block first, raw input rarely gets past decompression to the index parser.
+fuzz_target!(|data: &[u8]| {
+	let Some((&selector, input)) = data.split_first()
+	else
+	{
+		return;
+	};
+
+	let bytes = if selector & 1 == 0
+	{
+		bgzf(input)
+	}
+	else
+	{
+		input.to_vec()
+	};
+	let options = LoadOptions::default().with_lazy(selector & 2 != 0);
+
+	let Ok(reader) = block_on(Reader::from_reader_with(Cursor::new(bytes), &options))
+	else
+	{
+		return;
+	};
+
+	let index = reader.index();
+
+	for tid in index.seqnames.clone()
+	{
+		let _ = index.offsets_for_tid(&tid);
+		let _ = index.offsets_for_tid_region(&tid, 0u64, 1u64 << 20);
+		let _ = index.plan_region_query(&tid, 0u64, 1u64 << 20);
+		let _ = index.record_count(&tid);
+		let _ = index.extent_bounds(&tid);
+	}
+
+	let _ = index.total_records();
+});
+
+// one BGZF block holding data, up to the 64 KiB a block takes, then the EOF marker
+fn bgzf(data: &[u8]) -> Vec<u8>
+{
+	let data = &data[..data.len().min(0xf000)];
+
+	let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+	encoder.write_all(data).expect("writes to a Vec succeed");
+	let deflated = encoder.finish().expect("writes to a Vec succeed");
+
+	let mut block = vec![0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0];
+	block.extend_from_slice(&((deflated.len() + 25) as u16).to_le_bytes());
+	block.extend_from_slice(&deflated);
+	block.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+	block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+	block.extend_from_slice(&[
+		0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0, 0x1b, 0, 3, 0, 0, 0, 0,
+		0, 0, 0, 0, 0,
+	]);
+	block
+}
@@ -0,0 +1,55 @@
+/* C interface to sandman, built with the capi feature:
+ *
+ *   cargo rustc --release --features capi --crate-type cdylib
+ *
+ * or --crate-type staticlib, then link against target/release/libsandman. See src/capi for
+ * how long each pointer handed out stays valid.
+ */
+
+#ifndef SANDMAN_H
+#define SANDMAN_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct SandmanReader SandmanReader;
+
+typedef struct SandmanRecord
+{
+	const char *tid;
+	uint64_t start;
+	uint64_t end;
+	/* '+', '-' or '.' */
+	char strand;
+	/* NULL when the format has no name column */
+	const char *name;
+	bool has_score;
+	float score;
+} SandmanRecord;
+
+/* NULL on failure, see sandman_last_error */
+SandmanReader *sandman_reader_open(const char *path);
+
+/* 1 when a record was written, 0 at end of file or of the last query, -1 on error. The
+ * record's strings stay valid until the next call on the same reader. */
+int sandman_reader_next(SandmanReader *reader, SandmanRecord *record);
+
+/* Records overlapping chrom:start-end, 0-based half-open, for sandman_reader_next to hand
+ * out. A NULL chrom leaves query mode. The number of records found, or -1 on error. */
+int64_t sandman_reader_query(SandmanReader *reader, const char *chrom, uint64_t start,
+	uint64_t end);
+
+void sandman_reader_free(SandmanReader *reader);
+
+/* the message for the last failed call on this thread, NULL if none */
+const char *sandman_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
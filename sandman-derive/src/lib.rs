@@ -0,0 +1,123 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, parse_macro_input};
+
+// #[derive(BedFields)] reads BED6 followed by one column per field, in declaration order.
+// Fields parse with FromStr and are written back with Display, #[bed(rename = "qValue")]
+// sets the column name used in errors.
+#[proc_macro_derive(BedFields, attributes(bed))]
+pub fn derive_bed_fields(input: TokenStream) -> TokenStream
+{
+	let input = parse_macro_input!(input as DeriveInput);
+
+	match expand(&input)
+	{
+		Ok(tokens) => tokens.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2>
+{
+	let Data::Struct(data) = &input.data
+	else
+	{
+		return Err(syn::Error::new_spanned(input, "BedFields can only be derived for structs"));
+	};
+
+	let Fields::Named(fields) = &data.fields
+	else
+	{
+		return Err(syn::Error::new_spanned(input, "BedFields needs named fields"));
+	};
+
+	// the reader is generic over the type itself, there is nothing to fill parameters in with
+	if !input.generics.params.is_empty()
+	{
+		return Err(syn::Error::new_spanned(
+			&input.generics,
+			"BedFields cannot be derived for generic structs",
+		));
+	}
+
+	let mut idents = Vec::new();
+	let mut columns = Vec::new();
+
+	for field in &fields.named
+	{
+		let ident = field.ident.clone().expect("named field");
+		let mut column = ident.to_string();
+
+		for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("bed"))
+		{
+			attr.parse_nested_meta(|meta| {
+				if meta.path.is_ident("rename")
+				{
+					column = meta.value()?.parse::<LitStr>()?.value();
+					Ok(())
+				}
+				else
+				{
+					Err(meta.error("unknown bed attribute, expected rename"))
+				}
+			})?;
+		}
+
+		idents.push(ident);
+		columns.push(column);
+	}
+
+	let name = &input.ident;
+	let indices = 0..idents.len();
+
+	Ok(quote! {
+		impl ::sandman::bed::BedPlusColumns for #name
+		{
+			const COLUMNS: &'static [&'static str] = &[#(#columns),*];
+
+			fn parse_columns(columns: &[&str]) -> ::sandman::error::Result<Self>
+			{
+				Ok(Self {
+					#(#idents: ::sandman::bed::parse_column(columns[#indices], #columns)?,)*
+				})
+			}
+
+			fn write_columns(&self, out: &mut ::std::string::String)
+			{
+				use ::std::fmt::Write;
+
+				#(let _ = write!(out, "\t{}", self.#idents);)*
+			}
+		}
+
+		impl<Tid> ::sandman::bed::BedFieldsSink<Tid> for #name
+		where
+			Tid: ::std::fmt::Debug
+				+ ::std::clone::Clone
+				+ ::std::marker::Send
+				+ ::std::marker::Sync
+				+ ::std::cmp::PartialEq,
+		{
+			const KIND: ::sandman::bed::BedKind = ::sandman::bed::BedKind::Bed6;
+
+			async fn parse_sink<'a>(
+				input: &'a [u8],
+				filter_ctx: ::std::option::Option<&::sandman::filtering::ReadFilterContext>,
+				options: ::sandman::bed::ParseOptions,
+			) -> ::sandman::error::Result<(
+				&'a [u8],
+				::std::option::Option<(
+					::std::borrow::Cow<'a, str>,
+					::sandman::bed::Strand,
+					u64,
+					u64,
+					::sandman::bed::BedSinkValue,
+				)>,
+			)>
+			{
+				::sandman::bed::parse_bed_plus_sink::<Self>(input, filter_ctx, options).await
+			}
+		}
+	})
+}
@@ -0,0 +1,144 @@
+mod psl;
+
+pub use psl::Psl;
+
+use futures::stream::{self, Stream, StreamExt};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::bed::{Bed12Columns, BedKind, BedRecord, BedSinkValue, ItemRgb, Strand, parsed_lines};
+use crate::error;
+use crate::liftover::{Chain, ChainParser};
+
+// which assembly of a pairwise alignment positions are taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side
+{
+	Target,
+	Query,
+}
+
+// Every alignment in a PSL file, the psLayout header skipped. The stream ends after the
+// first error.
+pub fn read_psl<R>(name: &str, reader: R) -> impl Stream<Item = error::Result<Psl>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+{
+	let name = name.to_string();
+
+	parsed_lines(reader, move |line| Psl::parse(&name, line))
+}
+
+// Every chain in a chain file, as it is read rather than the whole file at once. The
+// stream ends after the first error.
+pub fn read_chains<R>(name: &str, reader: R) -> impl Stream<Item = error::Result<Chain>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+{
+	let state = (reader, Some(ChainParser::new(name)), Vec::new());
+
+	stream::unfold(state, |(mut reader, mut parser, mut line)| async move {
+		loop
+		{
+			let active = parser.as_mut()?;
+			line.clear();
+
+			let item = match reader.read_until(b'\n', &mut line).await
+			{
+				Ok(0) =>
+				{
+					let chain = parser.take()?.finish()?;
+					return Some((Ok(chain), (reader, None, line)));
+				}
+				Ok(_) => match active.line(&line)
+				{
+					Ok(Some(chain)) => Ok(chain),
+					Ok(None) => continue,
+					Err(e) => Err(e),
+				},
+				Err(e) => Err(e.into()),
+			};
+
+			if item.is_err()
+			{
+				parser = None;
+			}
+
+			return Some((item, (reader, parser, line)));
+		}
+	})
+}
+
+// PSL alignments as BED12 records on one side
+pub fn psl_records<R>(
+	name: &str,
+	reader: R,
+	side: Side,
+) -> impl Stream<Item = error::Result<BedRecord<String>>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+{
+	read_psl(name, reader).map(move |psl| psl.map(|psl| psl.to_bed_record(side)))
+}
+
+// chains as BED12 records on one side
+pub fn chain_records<R>(
+	name: &str,
+	reader: R,
+	side: Side,
+) -> impl Stream<Item = error::Result<BedRecord<String>>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+{
+	read_chains(name, reader).map(move |chain| chain.map(|chain| chain.to_bed_record(side)))
+}
+
+// a BED12 record over blocks given as absolute 0-based half-open intervals
+pub(crate) fn bed12_record(
+	tid: String,
+	strand: Strand,
+	name: String,
+	mut blocks: Vec<(u64, u64)>,
+) -> BedRecord<String>
+{
+	blocks.sort_unstable();
+
+	let start = blocks.first().map_or(0, |block| block.0);
+	let end = blocks.iter().map(|block| block.1).max().unwrap_or(start);
+
+	let bed12 = Bed12Columns {
+		chrom_start: start,
+		chrom_end: end,
+		thick_start: start,
+		thick_end: end,
+		item_rgb: ItemRgb::Off,
+		item_rgb_raw: "0".into(),
+		block_count: blocks.len() as u32,
+		block_sizes: blocks.iter().map(|(start, end)| (end - start) as u32).collect(),
+		block_starts: blocks.iter().map(|(block_start, _)| (block_start - start) as u32).collect(),
+	};
+
+	BedRecord {
+		tid,
+		strand,
+		start,
+		end,
+		value: BedSinkValue {
+			name: Some(name),
+			score: Some(0),
+			n_valid_cov: None,
+			frac_mod: None,
+			n_mod: None,
+			n_canonical: None,
+			n_other_mod: None,
+			n_delete: None,
+			n_fail: None,
+			n_diff: None,
+			n_nocall: None,
+			kind: BedKind::Bed12,
+			bed12: Some(Box::new(bed12)),
+			extra: None,
+			fields: None,
+			extra_names: &[],
+		},
+	}
+}
@@ -0,0 +1,160 @@
+use crate::alignment::{Side, bed12_record};
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+
+// One alignment from a UCSC PSL file. Starts are 0-based, and block starts on a minus
+// strand count from the end of that sequence, as the file holds them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Psl
+{
+	pub matches: u32,
+	pub mismatches: u32,
+	pub rep_matches: u32,
+	pub n_count: u32,
+	pub q_num_insert: u32,
+	pub q_base_insert: u32,
+	pub t_num_insert: u32,
+	pub t_base_insert: u32,
+	pub q_strand: Strand,
+	// only translated alignments give a target strand, plus otherwise
+	pub t_strand: Strand,
+	pub q_name: String,
+	pub q_size: u64,
+	pub q_start: u64,
+	pub q_end: u64,
+	pub t_name: String,
+	pub t_size: u64,
+	pub t_start: u64,
+	pub t_end: u64,
+	pub block_sizes: Vec<u32>,
+	pub q_starts: Vec<u64>,
+	pub t_starts: Vec<u64>,
+}
+
+impl Psl
+{
+	// None for the psLayout header and column title lines
+	pub(crate) fn parse(name: &str, line: &[u8]) -> error::Result<Option<Self>>
+	{
+		let invalid = || {
+			error::Error::PslFormat(name.to_string(), String::from_utf8_lossy(line).into_owned())
+		};
+
+		let Ok(text) = std::str::from_utf8(line)
+		else
+		{
+			return Err(invalid());
+		};
+
+		let fields: Vec<&str> = text.split('\t').map(str::trim).collect();
+
+		if text.trim().is_empty() || fields[0].parse::<u32>().is_err()
+		{
+			return Ok(None);
+		}
+
+		Self::from_fields(&fields).map(Some).ok_or_else(invalid)
+	}
+
+	fn from_fields(fields: &[&str]) -> Option<Self>
+	{
+		if fields.len() < 21
+		{
+			return None;
+		}
+
+		let list = |field: &str| -> Option<Vec<u64>> {
+			field
+				.split(',')
+				.filter(|value| !value.is_empty())
+				.map(|value| value.parse().ok())
+				.collect()
+		};
+
+		let mut strands = fields[8].bytes();
+		let q_strand = Strand::from(strands.next()?);
+		let t_strand = strands.next().map_or(Strand::Plus, Strand::from);
+
+		let block_count: usize = fields[17].parse().ok()?;
+		let block_sizes: Vec<u32> = list(fields[18])?.into_iter().map(|size| size as u32).collect();
+		let q_starts = list(fields[19])?;
+		let t_starts = list(fields[20])?;
+
+		if block_sizes.len() != block_count
+			|| q_starts.len() != block_count
+			|| t_starts.len() != block_count
+		{
+			return None;
+		}
+
+		Some(Self {
+			matches: fields[0].parse().ok()?,
+			mismatches: fields[1].parse().ok()?,
+			rep_matches: fields[2].parse().ok()?,
+			n_count: fields[3].parse().ok()?,
+			q_num_insert: fields[4].parse().ok()?,
+			q_base_insert: fields[5].parse().ok()?,
+			t_num_insert: fields[6].parse().ok()?,
+			t_base_insert: fields[7].parse().ok()?,
+			q_strand,
+			t_strand,
+			q_name: fields[9].to_string(),
+			q_size: fields[10].parse().ok()?,
+			q_start: fields[11].parse().ok()?,
+			q_end: fields[12].parse().ok()?,
+			t_name: fields[13].to_string(),
+			t_size: fields[14].parse().ok()?,
+			t_start: fields[15].parse().ok()?,
+			t_end: fields[16].parse().ok()?,
+			block_sizes,
+			q_starts,
+			t_starts,
+		})
+	}
+
+	// The alignment as a BED12 record on one side, its blocks the aligned stretches and its
+	// name the sequence on the other side, with score 0. Block sizes are taken as bases on
+	// both sides, so protein alignments come out short on the target.
+	pub fn to_bed_record(&self, side: Side) -> BedRecord<String>
+	{
+		let (tid, other, size, starts, strand) = match side
+		{
+			Side::Target =>
+			{
+				(&self.t_name, &self.q_name, self.t_size, &self.t_starts, self.t_strand)
+			}
+			Side::Query =>
+			{
+				(&self.q_name, &self.t_name, self.q_size, &self.q_starts, self.q_strand)
+			}
+		};
+
+		let blocks = starts
+			.iter()
+			.zip(&self.block_sizes)
+			.map(|(&start, &block_size)| {
+				let end = start + block_size as u64;
+
+				if let Strand::Minus = strand
+				{
+					(size - end, size - start)
+				}
+				else
+				{
+					(start, end)
+				}
+			})
+			.collect();
+
+		let strand = if self.q_strand == self.t_strand
+		{
+			Strand::Plus
+		}
+		else
+		{
+			Strand::Minus
+		};
+
+		bed12_record(tid.clone(), strand, other.clone(), blocks)
+	}
+}
@@ -0,0 +1,122 @@
+use crate::annotation::{SafRecord, bed6_record};
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+
+// One GTF line, 1-based and fully closed as GTF is, attributes in the order written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GtfRecord
+{
+	pub seqname: String,
+	pub source: String,
+	pub feature: String,
+	pub start: u64,
+	pub end: u64,
+	pub score: Option<f64>,
+	pub strand: Strand,
+	pub frame: Option<u8>,
+	pub attributes: Vec<(String, String)>,
+}
+
+impl GtfRecord
+{
+	// None for comment lines
+	pub(crate) fn parse(name: &str, line: &[u8]) -> error::Result<Option<Self>>
+	{
+		let invalid = || {
+			error::Error::GtfFormat(name.to_string(), String::from_utf8_lossy(line).into_owned())
+		};
+
+		let text = std::str::from_utf8(line).map_err(|_| invalid())?.trim_end();
+
+		if text.trim().is_empty() || text.starts_with('#')
+		{
+			return Ok(None);
+		}
+
+		let fields: Vec<&str> = text.splitn(9, '\t').collect();
+
+		let [seqname, source, feature, start, end, score, strand, frame, attributes] = fields[..]
+		else
+		{
+			return Err(invalid());
+		};
+
+		Ok(Some(Self {
+			seqname: seqname.to_string(),
+			source: source.to_string(),
+			feature: feature.to_string(),
+			start: start.parse().map_err(|_| invalid())?,
+			end: end.parse().map_err(|_| invalid())?,
+			score: score.parse().ok(),
+			strand: Strand::from(strand),
+			frame: frame.parse().ok(),
+			attributes: parse_attributes(attributes),
+		}))
+	}
+
+	pub fn attribute(&self, key: &str) -> Option<&str>
+	{
+		self.attributes
+			.iter()
+			.find(|(name, _)| name == key)
+			.map(|(_, value)| value.as_str())
+	}
+
+	pub fn gene_id(&self) -> Option<&str>
+	{
+		self.attribute("gene_id")
+	}
+
+	// SAF keyed by gene_id, as featureCounts builds from exons. None without a gene_id.
+	pub fn to_saf(&self) -> Option<SafRecord>
+	{
+		Some(SafRecord {
+			gene_id: self.gene_id()?.to_string(),
+			chr: self.seqname.clone(),
+			start: self.start,
+			end: self.end,
+			strand: self.strand,
+		})
+	}
+
+	// BED6 named by the attribute given, gene_id when it is missing. Scores are rounded
+	// into the BED range, 0 for ".".
+	pub fn to_bed_record(&self, name_attribute: &str) -> BedRecord<String>
+	{
+		let name = self
+			.attribute(name_attribute)
+			.or(self.gene_id())
+			.unwrap_or(".")
+			.to_string();
+
+		let score = self.score.map_or(0, |score| score.round().clamp(0.0, 1000.0) as u32);
+
+		bed6_record(
+			self.seqname.clone(),
+			self.strand,
+			self.start.saturating_sub(1),
+			self.end,
+			name,
+			score,
+		)
+	}
+}
+
+// key "value"; pairs, values unquoted, and bare values such as tag lists kept as written
+fn parse_attributes(text: &str) -> Vec<(String, String)>
+{
+	text.split(';')
+		.map(str::trim)
+		.filter(|attribute| !attribute.is_empty())
+		.filter_map(|attribute| {
+			let (key, value) = attribute.split_once(char::is_whitespace)?;
+			let value = value.trim();
+			let value = value
+				.strip_prefix('"')
+				.and_then(|value| value.strip_suffix('"'))
+				.unwrap_or(value);
+
+			Some((key.to_string(), value.to_string()))
+		})
+		.collect()
+}
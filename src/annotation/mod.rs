@@ -0,0 +1,86 @@
+mod gtf;
+mod saf;
+
+pub use gtf::GtfRecord;
+pub use saf::{SAF_HEADER, SafRecord};
+
+use futures::stream::{Stream, StreamExt};
+use tokio::io::AsyncBufRead;
+
+use crate::bed::{BedKind, BedRecord, BedSinkValue, Strand, parsed_lines};
+use crate::error;
+
+// every line of a SAF file, the header skipped
+pub fn read_saf<R>(name: &str, reader: R) -> impl Stream<Item = error::Result<SafRecord>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+{
+	let name = name.to_string();
+
+	parsed_lines(reader, move |line| SafRecord::parse(&name, line))
+}
+
+// every feature line of a GTF file
+pub fn read_gtf<R>(name: &str, reader: R) -> impl Stream<Item = error::Result<GtfRecord>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+{
+	let name = name.to_string();
+
+	parsed_lines(reader, move |line| GtfRecord::parse(&name, line))
+}
+
+// The SAF featureCounts would build from a GTF, the lines of feature ("exon" by default
+// there) that carry a gene_id.
+pub fn gtf_to_saf<S>(records: S, feature: &str) -> impl Stream<Item = error::Result<SafRecord>>
+where
+	S: Stream<Item = error::Result<GtfRecord>>,
+{
+	let feature = feature.to_string();
+
+	records.filter_map(move |record| {
+		let saf = match record
+		{
+			Ok(record) if record.feature == feature => record.to_saf().map(Ok),
+			Ok(_) => None,
+			Err(e) => Some(Err(e)),
+		};
+
+		std::future::ready(saf)
+	})
+}
+
+pub(crate) fn bed6_record(
+	tid: String,
+	strand: Strand,
+	start: u64,
+	end: u64,
+	name: String,
+	score: u32,
+) -> BedRecord<String>
+{
+	BedRecord {
+		tid,
+		strand,
+		start,
+		end,
+		value: BedSinkValue {
+			name: Some(name),
+			score: Some(score),
+			n_valid_cov: None,
+			frac_mod: None,
+			n_mod: None,
+			n_canonical: None,
+			n_other_mod: None,
+			n_delete: None,
+			n_fail: None,
+			n_diff: None,
+			n_nocall: None,
+			kind: BedKind::Bed6,
+			bed12: None,
+			extra: None,
+			fields: None,
+			extra_names: &[],
+		},
+	}
+}
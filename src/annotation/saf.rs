@@ -0,0 +1,89 @@
+use std::fmt;
+
+use crate::annotation::bed6_record;
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+
+pub const SAF_HEADER: &str = "GeneID\tChr\tStart\tEnd\tStrand";
+
+// One line of the SAF annotation featureCounts takes, 1-based and fully closed as SAF is.
+// Lines sharing a GeneID make up one meta-feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafRecord
+{
+	pub gene_id: String,
+	pub chr: String,
+	pub start: u64,
+	pub end: u64,
+	pub strand: Strand,
+}
+
+impl SafRecord
+{
+	// None for the header line
+	pub(crate) fn parse(name: &str, line: &[u8]) -> error::Result<Option<Self>>
+	{
+		let invalid = || {
+			error::Error::SafFormat(name.to_string(), String::from_utf8_lossy(line).into_owned())
+		};
+
+		let text = std::str::from_utf8(line).map_err(|_| invalid())?.trim_end();
+
+		if text.trim().is_empty() || text.starts_with('#') || text.starts_with("GeneID\t")
+		{
+			return Ok(None);
+		}
+
+		let fields: Vec<&str> = text.split('\t').collect();
+
+		let [gene_id, chr, start, end, strand, ..] = fields[..]
+		else
+		{
+			return Err(invalid());
+		};
+
+		Ok(Some(Self {
+			gene_id: gene_id.to_string(),
+			chr: chr.to_string(),
+			start: start.parse().map_err(|_| invalid())?,
+			end: end.parse().map_err(|_| invalid())?,
+			strand: Strand::from(strand),
+		}))
+	}
+
+	// a record over one that is 0-based half-open, named by gene_id, default "." for none
+	pub fn from_bed_record<Tid>(record: &BedRecord<Tid>) -> Self
+	where
+		Tid: AsRef<str>,
+	{
+		Self {
+			gene_id: record.value.get_name().unwrap_or(".").to_string(),
+			chr: record.tid.as_ref().to_string(),
+			start: record.start + 1,
+			end: record.end,
+			strand: record.strand,
+		}
+	}
+
+	// BED6 named by GeneID, with score 0
+	pub fn to_bed_record(&self) -> BedRecord<String>
+	{
+		bed6_record(
+			self.chr.clone(),
+			self.strand,
+			self.start.saturating_sub(1),
+			self.end,
+			self.gene_id.clone(),
+			0,
+		)
+	}
+}
+
+// a SAF line without its newline, featureCounts reads "." as either strand
+impl fmt::Display for SafRecord
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "{}\t{}\t{}\t{}\t{}", self.gene_id, self.chr, self.start, self.end, self.strand)
+	}
+}
@@ -1,126 +1,67 @@
 use std::sync::Arc;
 use std::path::Path;
 
+#[cfg(feature = "fs")]
 use tokio::fs::File;
-use tokio::io::{AsyncRead, AsyncSeek, AsyncBufRead};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncBufRead, BufReader as TokioBufReader};
 
 use pufferfish::prelude::*;
 
-use crate::store::TidResolver;
+use crate::store::{DefaultResolver, TidResolver};
 use crate::bed::{Bed3Fields, Bed4Extra, Bed5Extra, Bed6Extra, Bed12Extra, BedMethylExtra};
 use crate::bed::{BedSink, BedFieldsSink};
 use crate::bed::oneshotreader::OneShotBlockReader;
 use crate::bed::SourceId;
+use crate::bed::StreamingReader;
+use crate::bed::UriReader;
 use crate::bed::BedKind;
-
-#[cfg(feature = "interning")]
-use {crate::store::TidStore};
+use crate::bed::{BinnedCounts, ReaderDescription, ReaderStats, Summary};
+use crate::bed::BedRecord;
+use crate::bed::records::record_stream;
+use crate::region::Region;
+use crate::tabix;
+use crate::bed::PeekableReader;
 
 use crate::bed::blocks::BgzfBlock;
-use crate::bed::{detect_format, detect_format_from_reader};
+#[cfg(feature = "fs")]
+use crate::bed::detect_format;
+use crate::bed::detect_format_from_reader;
 use crate::bed::oneshotreader::ReaderOptions;
 
 use crate::error;
+use crate::error::{Context, ResultExt};
 
-#[cfg(not(feature = "interning"))]
+#[cfg(feature = "fs")]
 pub async fn from_path<P>(
 	path: P,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-) -> error::Result<AutoOneShotBlockReader<File, ()>>
+) -> error::Result<AutoOneShotBlockReader<File, DefaultResolver>>
 where
 	P: AsRef<Path> + Copy,
 {
-	let format = detect_format(path).await?;
-
-	let inner = match format
-	{
-		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<File, (), Bed3Fields>::from_path(path, source_id, pool).await?,
-		),
-		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<File, (), Bed4Extra>::from_path(path, source_id, pool).await?,
-		),
-		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<File, (), Bed5Extra>::from_path(path, source_id, pool).await?,
-		),
-		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<File, (), Bed6Extra>::from_path(path, source_id, pool).await?,
-		),
-		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<File, (), Bed12Extra>::from_path(path, source_id, pool).await?,
-		),
-		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<File, (), BedMethylExtra>::from_path(path, source_id, pool)
-				.await?,
-		),
-	};
-
-	Ok(AutoOneShotBlockReader { inner })
+	from_path_with(path, source_id, pool, ReaderOptions::default()).await
 }
 
-#[cfg(not(feature = "interning"))]
+#[cfg(feature = "fs")]
 pub async fn from_path_with_options<P>(
 	path: P,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-	options: ReaderOptions<()>,
-) -> error::Result<AutoOneShotBlockReader<File, ()>>
+	options: ReaderOptions<DefaultResolver>,
+) -> error::Result<AutoOneShotBlockReader<File, DefaultResolver>>
 where
 	P: AsRef<Path> + Copy,
 {
-	let format = detect_format(path).await?;
-
-	let inner = match format
-	{
-		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<File, (), Bed3Fields>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<File, (), Bed4Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<File, (), Bed5Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<File, (), Bed6Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<File, (), Bed12Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<File, (), BedMethylExtra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-	};
-
-	Ok(AutoOneShotBlockReader { inner })
+	from_path_with(path, source_id, pool, options).await
 }
 
-#[cfg(not(feature = "interning"))]
 pub async fn from_reader<R>(
 	name: String,
-	mut reader: R,
+	reader: R,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-) -> error::Result<AutoOneShotBlockReader<R, ()>>
+) -> error::Result<AutoOneShotBlockReader<R, DefaultResolver>>
 where
 	R: AsyncRead
 		+ AsyncSeek
@@ -130,47 +71,16 @@ where
 		+ std::marker::Sync
 		+ 'static,
 {
-	let format = detect_format_from_reader(name.clone(), &mut reader, 10).await?;
-
-	let inner = match format
-	{
-		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<_, (), Bed3Fields>::from_reader(name, reader, source_id, pool)
-				.await,
-		),
-		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<_, (), Bed4Extra>::from_reader(name, reader, source_id, pool)
-				.await,
-		),
-		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<_, (), Bed5Extra>::from_reader(name, reader, source_id, pool)
-				.await,
-		),
-		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<_, (), Bed6Extra>::from_reader(name, reader, source_id, pool)
-				.await,
-		),
-		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<_, (), Bed12Extra>::from_reader(name, reader, source_id, pool)
-				.await,
-		),
-		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<_, (), BedMethylExtra>::from_reader(name, reader, source_id, pool)
-				.await,
-		),
-	};
-
-	Ok(AutoOneShotBlockReader { inner })
+	from_reader_with(name, reader, source_id, pool, ReaderOptions::default()).await
 }
 
-#[cfg(not(feature = "interning"))]
 pub async fn from_reader_with_options<R>(
 	name: String,
-	mut reader: R,
+	reader: R,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-	options: ReaderOptions<()>,
-) -> error::Result<AutoOneShotBlockReader<R, ()>>
+	options: ReaderOptions<DefaultResolver>,
+) -> error::Result<AutoOneShotBlockReader<R, DefaultResolver>>
 where
 	R: AsyncRead
 		+ AsyncSeek
@@ -180,88 +90,49 @@ where
 		+ std::marker::Sync
 		+ 'static,
 {
-	let format = detect_format_from_reader(name.clone(), &mut reader, 10).await?;
-
-	let inner = match format
-	{
-		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<_, (), Bed3Fields>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await,
-		),
-		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<_, (), Bed4Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await,
-		),
-		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<_, (), Bed5Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await,
-		),
-		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<_, (), Bed6Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await,
-		),
-		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<_, (), Bed12Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await,
-		),
-		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<_, (), BedMethylExtra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await,
-		),
-	};
-
-	Ok(AutoOneShotBlockReader { inner })
+	from_reader_with(name, reader, source_id, pool, options).await
 }
 
-#[cfg(feature = "interning")]
-pub async fn from_path<P>(
+#[cfg(feature = "fs")]
+pub async fn from_path_with<P, T>(
 	path: P,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-) -> error::Result<AutoOneShotBlockReader<File, TidStore>>
+	options: ReaderOptions<T>,
+) -> error::Result<AutoOneShotBlockReader<File, T>>
 where
 	P: AsRef<Path> + Copy,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
 {
-	let format = detect_format(path).await?;
-
-	println!("format = {:?}", format);
+	let format = detect_format(path).await.context(|| {
+		Context::new("format detection").with_file(path.as_ref().display().to_string())
+	})?;
+	log::debug!("{}: detected {:?}", path.as_ref().display(), format);
 
 	let inner = match format
 	{
 		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<File, TidStore, Bed3Fields>::from_path(path, source_id, pool)
+			OneShotBlockReader::<File, T, Bed3Fields>::from_path_with(path, source_id, pool, options)
 				.await?,
 		),
 		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<File, TidStore, Bed4Extra>::from_path(path, source_id, pool)
+			OneShotBlockReader::<File, T, Bed4Extra>::from_path_with(path, source_id, pool, options)
 				.await?,
 		),
 		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<File, TidStore, Bed5Extra>::from_path(path, source_id, pool)
+			OneShotBlockReader::<File, T, Bed5Extra>::from_path_with(path, source_id, pool, options)
 				.await?,
 		),
 		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<File, TidStore, Bed6Extra>::from_path(path, source_id, pool)
+			OneShotBlockReader::<File, T, Bed6Extra>::from_path_with(path, source_id, pool, options)
 				.await?,
 		),
 		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<File, TidStore, Bed12Extra>::from_path(path, source_id, pool)
+			OneShotBlockReader::<File, T, Bed12Extra>::from_path_with(path, source_id, pool, options)
 				.await?,
 		),
 		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<File, TidStore, BedMethylExtra>::from_path(path, source_id, pool)
+			OneShotBlockReader::<File, T, BedMethylExtra>::from_path_with(path, source_id, pool, options)
 				.await?,
 		),
 	};
@@ -269,68 +140,13 @@ where
 	Ok(AutoOneShotBlockReader { inner })
 }
 
-#[cfg(feature = "interning")]
-pub async fn from_path_with_options<P>(
-	path: P,
-	source_id: impl Into<Option<SourceId>> + 'static,
-	pool: Arc<pool::BgzfBlockPool>,
-	options: ReaderOptions<TidStore>,
-) -> error::Result<AutoOneShotBlockReader<File, TidStore>>
-where
-	P: AsRef<Path> + Copy,
-{
-	let format = detect_format(path).await?;
-
-	let inner = match format
-	{
-		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<File, TidStore, Bed3Fields>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<File, TidStore, Bed4Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<File, TidStore, Bed5Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<File, TidStore, Bed6Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<File, TidStore, Bed12Extra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<File, TidStore, BedMethylExtra>::from_path_with_options(
-				path, source_id, pool, options,
-			)
-			.await?,
-		),
-	};
-
-	Ok(AutoOneShotBlockReader { inner })
-}
-
-#[cfg(feature = "interning")]
-pub async fn from_reader<R>(
+pub async fn from_reader_with<R, T>(
 	name: String,
 	mut reader: R,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-) -> error::Result<AutoOneShotBlockReader<R, TidStore>>
+	options: ReaderOptions<T>,
+) -> error::Result<AutoOneShotBlockReader<R, T>>
 where
 	R: AsyncRead
 		+ AsyncSeek
@@ -339,44 +155,48 @@ where
 		+ std::marker::Unpin
 		+ std::marker::Sync
 		+ 'static,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
 {
-	let format = detect_format_from_reader(name.clone(), &mut reader, 10).await?;
+	let format = detect_format_from_reader(name.clone(), &mut reader, 10)
+		.await
+		.context(|| Context::new("format detection").with_file(name.clone()))?;
+	log::debug!("{}: detected {:?}", name, format);
 
 	let inner = match format
 	{
 		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<_, TidStore, Bed3Fields>::from_reader(
-				name, reader, source_id, pool,
+			OneShotBlockReader::<_, T, Bed3Fields>::from_reader_with(
+				name, reader, source_id, pool, options,
 			)
 			.await?,
 		),
 		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<_, TidStore, Bed4Extra>::from_reader(
-				name, reader, source_id, pool,
+			OneShotBlockReader::<_, T, Bed4Extra>::from_reader_with(
+				name, reader, source_id, pool, options,
 			)
 			.await?,
 		),
 		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<_, TidStore, Bed5Extra>::from_reader(
-				name, reader, source_id, pool,
+			OneShotBlockReader::<_, T, Bed5Extra>::from_reader_with(
+				name, reader, source_id, pool, options,
 			)
 			.await?,
 		),
 		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<_, TidStore, Bed6Extra>::from_reader(
-				name, reader, source_id, pool,
+			OneShotBlockReader::<_, T, Bed6Extra>::from_reader_with(
+				name, reader, source_id, pool, options,
 			)
 			.await?,
 		),
 		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<_, TidStore, Bed12Extra>::from_reader(
-				name, reader, source_id, pool,
+			OneShotBlockReader::<_, T, Bed12Extra>::from_reader_with(
+				name, reader, source_id, pool, options,
 			)
 			.await?,
 		),
 		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<_, TidStore, BedMethylExtra>::from_reader(
-				name, reader, source_id, pool,
+			OneShotBlockReader::<_, T, BedMethylExtra>::from_reader_with(
+				name, reader, source_id, pool, options,
 			)
 			.await?,
 		),
@@ -385,66 +205,68 @@ where
 	Ok(AutoOneShotBlockReader { inner })
 }
 
-#[cfg(feature = "interning")]
-pub async fn from_reader_with_options<R>(
+pub async fn from_stream<R>(
 	name: String,
-	mut reader: R,
+	reader: R,
 	source_id: impl Into<Option<SourceId>> + 'static,
 	pool: Arc<pool::BgzfBlockPool>,
-	options: ReaderOptions<TidStore>,
-) -> error::Result<AutoOneShotBlockReader<R, TidStore>>
+) -> error::Result<AutoOneShotBlockReader<TokioBufReader<StreamingReader<R>>, DefaultResolver>>
 where
-	R: AsyncRead
-		+ AsyncSeek
-		+ AsyncBufRead
-		+ std::marker::Send
-		+ std::marker::Unpin
-		+ std::marker::Sync
-		+ 'static,
+	R: AsyncRead + std::marker::Send + std::marker::Unpin + std::marker::Sync + 'static,
 {
-	let format = detect_format_from_reader(name.clone(), &mut reader, 10).await?;
+	from_stream_with(name, reader, source_id, pool, ReaderOptions::default()).await
+}
 
-	let inner = match format
+// for inputs that cannot seek, detection rewinds through StreamingReader's peek buffer
+pub async fn from_stream_with<R, T>(
+	name: String,
+	reader: R,
+	source_id: impl Into<Option<SourceId>> + 'static,
+	pool: Arc<pool::BgzfBlockPool>,
+	options: ReaderOptions<T>,
+) -> error::Result<AutoOneShotBlockReader<TokioBufReader<StreamingReader<R>>, T>>
+where
+	R: AsyncRead + std::marker::Send + std::marker::Unpin + std::marker::Sync + 'static,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	let reader = TokioBufReader::new(StreamingReader::new(reader));
+	from_reader_with(name, reader, source_id, pool, options).await
+}
+
+type UriSource = TokioBufReader<UriReader>;
+
+// one location string from a user, "-" for stdin, a path, or a file://, http(s)://,
+// s3:// or gs:// URL, each opened with the backend it needs
+pub async fn from_uri(
+	uri: &str,
+	source_id: impl Into<Option<SourceId>> + 'static,
+	pool: Arc<pool::BgzfBlockPool>,
+) -> error::Result<AutoOneShotBlockReader<UriSource, DefaultResolver>>
+{
+	from_uri_with(uri, source_id, pool, ReaderOptions::default()).await
+}
+
+pub async fn from_uri_with<T>(
+	uri: &str,
+	source_id: impl Into<Option<SourceId>> + 'static,
+	pool: Arc<pool::BgzfBlockPool>,
+	options: ReaderOptions<T>,
+) -> error::Result<AutoOneShotBlockReader<UriSource, T>>
+where
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	let (reader, size) = UriReader::open(uri)
+		.await
+		.context(|| Context::new("open").with_file(uri))?;
+
+	let options = match (options.total_bytes, size)
 	{
-		BedKind::Bed3 => InnerAutoOneShotBlockReader::Bed3(
-			OneShotBlockReader::<_, TidStore, Bed3Fields>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed4 => InnerAutoOneShotBlockReader::Bed4(
-			OneShotBlockReader::<_, TidStore, Bed4Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed5 => InnerAutoOneShotBlockReader::Bed5(
-			OneShotBlockReader::<_, TidStore, Bed5Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed6 => InnerAutoOneShotBlockReader::Bed6(
-			OneShotBlockReader::<_, TidStore, Bed6Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::Bed12 => InnerAutoOneShotBlockReader::Bed12(
-			OneShotBlockReader::<_, TidStore, Bed12Extra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await?,
-		),
-		BedKind::BedMethyl => InnerAutoOneShotBlockReader::BedMethyl(
-			OneShotBlockReader::<_, TidStore, BedMethylExtra>::from_reader_with_options(
-				name, reader, source_id, pool, options,
-			)
-			.await?,
-		),
+		(None, Some(size)) => options.with_total_bytes(size),
+		_ => options,
 	};
 
-	Ok(AutoOneShotBlockReader { inner })
+	let name = uri.to_string();
+	from_reader_with(name, TokioBufReader::new(reader), source_id, pool, options).await
 }
 
 enum InnerAutoOneShotBlockReader<R, T>
@@ -478,6 +300,10 @@ where
 		S: BedSink<T::Tid> + ?Sized;
 
 	fn name(&self) -> String;
+
+	fn stats(&self) -> ReaderStats;
+
+	fn describe(&self) -> ReaderDescription;
 }
 
 pub struct AutoOneShotBlockReader<R, T>
@@ -488,6 +314,356 @@ where
 	inner: InnerAutoOneShotBlockReader<R, T>,
 }
 
+#[cfg(feature = "indexing")]
+impl<T> AutoOneShotBlockReader<File, T>
+where
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	// the inner reader's ensure_indexed, keeping the detected format
+	pub async fn ensure_indexed<D>(self, temp_dir: D) -> error::Result<Self>
+	where
+		D: AsRef<Path>,
+	{
+		let inner = match self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed3(r.ensure_indexed(temp_dir).await?)
+			}
+			InnerAutoOneShotBlockReader::Bed4(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed4(r.ensure_indexed(temp_dir).await?)
+			}
+			InnerAutoOneShotBlockReader::Bed5(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed5(r.ensure_indexed(temp_dir).await?)
+			}
+			InnerAutoOneShotBlockReader::Bed6(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed6(r.ensure_indexed(temp_dir).await?)
+			}
+			InnerAutoOneShotBlockReader::Bed12(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed12(r.ensure_indexed(temp_dir).await?)
+			}
+			InnerAutoOneShotBlockReader::BedMethyl(r) =>
+			{
+				InnerAutoOneShotBlockReader::BedMethyl(r.ensure_indexed(temp_dir).await?)
+			}
+		};
+
+		Ok(Self { inner })
+	}
+}
+
+impl<R, T> AutoOneShotBlockReader<R, T>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	// the inner reader's try_clone, the clone keeps the detected format
+	pub fn try_clone(&self) -> error::Result<Self>
+	{
+		let inner = match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed3(r.try_clone()?)
+			}
+			InnerAutoOneShotBlockReader::Bed4(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed4(r.try_clone()?)
+			}
+			InnerAutoOneShotBlockReader::Bed5(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed5(r.try_clone()?)
+			}
+			InnerAutoOneShotBlockReader::Bed6(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed6(r.try_clone()?)
+			}
+			InnerAutoOneShotBlockReader::Bed12(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed12(r.try_clone()?)
+			}
+			InnerAutoOneShotBlockReader::BedMethyl(r) =>
+			{
+				InnerAutoOneShotBlockReader::BedMethyl(r.try_clone()?)
+			}
+		};
+
+		Ok(Self { inner })
+	}
+
+	// the inner reader's with_reopen, for sources from_reader_with cannot open again itself
+	pub fn with_reopen<G>(self, reopen: G) -> Self
+	where
+		G: Fn() -> error::Result<R> + Send + Sync + 'static,
+	{
+		let inner = match self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed3(r.with_reopen(reopen))
+			}
+			InnerAutoOneShotBlockReader::Bed4(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed4(r.with_reopen(reopen))
+			}
+			InnerAutoOneShotBlockReader::Bed5(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed5(r.with_reopen(reopen))
+			}
+			InnerAutoOneShotBlockReader::Bed6(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed6(r.with_reopen(reopen))
+			}
+			InnerAutoOneShotBlockReader::Bed12(r) =>
+			{
+				InnerAutoOneShotBlockReader::Bed12(r.with_reopen(reopen))
+			}
+			InnerAutoOneShotBlockReader::BedMethyl(r) =>
+			{
+				InnerAutoOneShotBlockReader::BedMethyl(r.with_reopen(reopen))
+			}
+		};
+
+		Self { inner }
+	}
+}
+
+impl<R, T> AutoOneShotBlockReader<R, T>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	pub async fn check_sorted(&mut self) -> error::Result<bool>
+	{
+		match &mut self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.check_sorted().await,
+			InnerAutoOneShotBlockReader::Bed4(r) => r.check_sorted().await,
+			InnerAutoOneShotBlockReader::Bed5(r) => r.check_sorted().await,
+			InnerAutoOneShotBlockReader::Bed6(r) => r.check_sorted().await,
+			InnerAutoOneShotBlockReader::Bed12(r) => r.check_sorted().await,
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.check_sorted().await,
+		}
+	}
+
+	pub fn index(&self) -> Option<&Arc<tabix::Index>>
+	{
+		match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.index(),
+			InnerAutoOneShotBlockReader::Bed4(r) => r.index(),
+			InnerAutoOneShotBlockReader::Bed5(r) => r.index(),
+			InnerAutoOneShotBlockReader::Bed6(r) => r.index(),
+			InnerAutoOneShotBlockReader::Bed12(r) => r.index(),
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.index(),
+		}
+	}
+
+	pub fn is_complete(&self) -> Option<bool>
+	{
+		match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.is_complete(),
+			InnerAutoOneShotBlockReader::Bed4(r) => r.is_complete(),
+			InnerAutoOneShotBlockReader::Bed5(r) => r.is_complete(),
+			InnerAutoOneShotBlockReader::Bed6(r) => r.is_complete(),
+			InnerAutoOneShotBlockReader::Bed12(r) => r.is_complete(),
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.is_complete(),
+		}
+	}
+
+	pub async fn summarize(&mut self) -> error::Result<Summary<T::Tid>>
+	{
+		match &mut self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.summarize().await,
+			InnerAutoOneShotBlockReader::Bed4(r) => r.summarize().await,
+			InnerAutoOneShotBlockReader::Bed5(r) => r.summarize().await,
+			InnerAutoOneShotBlockReader::Bed6(r) => r.summarize().await,
+			InnerAutoOneShotBlockReader::Bed12(r) => r.summarize().await,
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.summarize().await,
+		}
+	}
+
+	pub async fn count_in_region(&mut self, region: &Region) -> error::Result<u64>
+	{
+		match &mut self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.count_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed4(r) => r.count_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed5(r) => r.count_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed6(r) => r.count_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed12(r) => r.count_in_region(region).await,
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.count_in_region(region).await,
+		}
+	}
+
+	pub async fn bases_covered_in_region(&mut self, region: &Region) -> error::Result<u64>
+	{
+		match &mut self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.bases_covered_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed4(r) => r.bases_covered_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed5(r) => r.bases_covered_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed6(r) => r.bases_covered_in_region(region).await,
+			InnerAutoOneShotBlockReader::Bed12(r) => r.bases_covered_in_region(region).await,
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.bases_covered_in_region(region).await,
+		}
+	}
+
+	pub async fn extent(&mut self, tid: &str) -> error::Result<Option<(u64, u64)>>
+	{
+		match &mut self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.extent(tid).await,
+			InnerAutoOneShotBlockReader::Bed4(r) => r.extent(tid).await,
+			InnerAutoOneShotBlockReader::Bed5(r) => r.extent(tid).await,
+			InnerAutoOneShotBlockReader::Bed6(r) => r.extent(tid).await,
+			InnerAutoOneShotBlockReader::Bed12(r) => r.extent(tid).await,
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.extent(tid).await,
+		}
+	}
+
+	pub async fn binned_counts(
+		&mut self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		n_bins: usize,
+	) -> error::Result<BinnedCounts<T::Tid>>
+	{
+		match &mut self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) =>
+			{
+				r.binned_counts(tid, start, end, n_bins).await
+			}
+			InnerAutoOneShotBlockReader::Bed4(r) =>
+			{
+				r.binned_counts(tid, start, end, n_bins).await
+			}
+			InnerAutoOneShotBlockReader::Bed5(r) =>
+			{
+				r.binned_counts(tid, start, end, n_bins).await
+			}
+			InnerAutoOneShotBlockReader::Bed6(r) =>
+			{
+				r.binned_counts(tid, start, end, n_bins).await
+			}
+			InnerAutoOneShotBlockReader::Bed12(r) =>
+			{
+				r.binned_counts(tid, start, end, n_bins).await
+			}
+			InnerAutoOneShotBlockReader::BedMethyl(r) =>
+			{
+				r.binned_counts(tid, start, end, n_bins).await
+			}
+		}
+	}
+
+	pub async fn query_region_concurrent(
+		&self,
+		region: &Region,
+		parallelism: usize,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) =>
+			{
+				r.query_region_concurrent(region, parallelism).await
+			}
+			InnerAutoOneShotBlockReader::Bed4(r) =>
+			{
+				r.query_region_concurrent(region, parallelism).await
+			}
+			InnerAutoOneShotBlockReader::Bed5(r) =>
+			{
+				r.query_region_concurrent(region, parallelism).await
+			}
+			InnerAutoOneShotBlockReader::Bed6(r) =>
+			{
+				r.query_region_concurrent(region, parallelism).await
+			}
+			InnerAutoOneShotBlockReader::Bed12(r) =>
+			{
+				r.query_region_concurrent(region, parallelism).await
+			}
+			InnerAutoOneShotBlockReader::BedMethyl(r) =>
+			{
+				r.query_region_concurrent(region, parallelism).await
+			}
+		}
+	}
+
+	pub async fn sample_region(
+		&self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		max_records: usize,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) =>
+			{
+				r.sample_region(tid, start, end, max_records).await
+			}
+			InnerAutoOneShotBlockReader::Bed4(r) =>
+			{
+				r.sample_region(tid, start, end, max_records).await
+			}
+			InnerAutoOneShotBlockReader::Bed5(r) =>
+			{
+				r.sample_region(tid, start, end, max_records).await
+			}
+			InnerAutoOneShotBlockReader::Bed6(r) =>
+			{
+				r.sample_region(tid, start, end, max_records).await
+			}
+			InnerAutoOneShotBlockReader::Bed12(r) =>
+			{
+				r.sample_region(tid, start, end, max_records).await
+			}
+			InnerAutoOneShotBlockReader::BedMethyl(r) =>
+			{
+				r.sample_region(tid, start, end, max_records).await
+			}
+		}
+	}
+
+	pub fn records(
+		self,
+		blocks_per_batch: usize,
+	) -> impl futures::Stream<Item = error::Result<BedRecord<T::Tid>>>
+	{
+		record_stream(self, blocks_per_batch)
+	}
+
+	pub fn peekable(self, blocks_per_batch: usize) -> PeekableReader<Self, T>
+	{
+		PeekableReader::new(self, blocks_per_batch)
+	}
+
+	pub async fn close(self) -> error::Result<()>
+	{
+		match self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.close().await,
+			InnerAutoOneShotBlockReader::Bed4(r) => r.close().await,
+			InnerAutoOneShotBlockReader::Bed5(r) => r.close().await,
+			InnerAutoOneShotBlockReader::Bed6(r) => r.close().await,
+			InnerAutoOneShotBlockReader::Bed12(r) => r.close().await,
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.close().await,
+		}
+	}
+}
+
 impl<R, T> AutoOneShotBlockReaderTrait<T> for AutoOneShotBlockReader<R, T>
 where
 	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
@@ -506,6 +682,32 @@ where
 		}
 	}
 
+	fn stats(&self) -> ReaderStats
+	{
+		match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.stats(),
+			InnerAutoOneShotBlockReader::Bed4(r) => r.stats(),
+			InnerAutoOneShotBlockReader::Bed5(r) => r.stats(),
+			InnerAutoOneShotBlockReader::Bed6(r) => r.stats(),
+			InnerAutoOneShotBlockReader::Bed12(r) => r.stats(),
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.stats(),
+		}
+	}
+
+	fn describe(&self) -> ReaderDescription
+	{
+		match &self.inner
+		{
+			InnerAutoOneShotBlockReader::Bed3(r) => r.describe(),
+			InnerAutoOneShotBlockReader::Bed4(r) => r.describe(),
+			InnerAutoOneShotBlockReader::Bed5(r) => r.describe(),
+			InnerAutoOneShotBlockReader::Bed6(r) => r.describe(),
+			InnerAutoOneShotBlockReader::Bed12(r) => r.describe(),
+			InnerAutoOneShotBlockReader::BedMethyl(r) => r.describe(),
+		}
+	}
+
 	async fn next_bgzf_blocks<'a>(&'a mut self, n: usize) -> error::Result<Option<BgzfBlock>>
 	{
 		match &mut self.inner
@@ -574,6 +776,16 @@ where
 		self.name.clone()
 	}
 
+	fn stats(&self) -> ReaderStats
+	{
+		OneShotBlockReader::stats(self)
+	}
+
+	fn describe(&self) -> ReaderDescription
+	{
+		OneShotBlockReader::describe(self)
+	}
+
 	async fn next_bgzf_blocks<'a>(&'a mut self, n: usize) -> error::Result<Option<BgzfBlock>>
 	{
 		OneShotBlockReader::next_bgzf_blocks(self, n).await
@@ -0,0 +1,262 @@
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+#[cfg(feature = "bgzf-write")]
+use crate::bed::bgzfwriter::BgzfWriter;
+use crate::bed::{BedRecord, BedWriter, WriterOptions};
+use crate::error;
+use crate::store::TidLookup;
+
+// The output side of AutoOneShotBlockReaderTrait, so pipelines can be generic over where
+// records go. Records are written as BedWriter writes them, their tids looked up in T.
+pub trait AutoWriter<T>
+where
+	T: TidLookup,
+{
+	fn write_record<'a>(
+		&'a mut self,
+		record: &'a BedRecord<T::Tid>,
+	) -> impl Future<Output = error::Result<()>> + 'a;
+
+	fn write_track<'a>(
+		&'a mut self,
+		attributes: &'a [(&'a str, &'a str)],
+	) -> impl Future<Output = error::Result<()>> + 'a;
+
+	fn write_browser<'a>(
+		&'a mut self,
+		line: &'a str,
+	) -> impl Future<Output = error::Result<()>> + 'a;
+
+	fn flush<'a>(&'a mut self) -> impl Future<Output = error::Result<()>> + 'a;
+
+	// flushes and shuts the output down, writing anything a format needs at its end
+	fn close(self) -> impl Future<Output = error::Result<()>>;
+}
+
+// the lines for one call, formatted before any await
+fn format_record<T>(
+	line: &mut Vec<u8>,
+	lookup: &T,
+	options: WriterOptions,
+	record: &BedRecord<T::Tid>,
+) -> error::Result<()>
+where
+	T: TidLookup,
+{
+	let tid = lookup
+		.from_symbol_id(&record.tid)
+		.ok_or_else(|| error::Error::TidNotFound(format!("{:?}", record.tid)))?;
+
+	BedWriter::new(line).with_options(options).write_named(tid, record)?;
+	Ok(())
+}
+
+// uncompressed BED, a line written through to inner for each call
+pub struct PlainBedWriter<W, T = ()>
+{
+	inner: W,
+	lookup: T,
+	options: WriterOptions,
+	line: Vec<u8>,
+}
+
+impl<W> PlainBedWriter<W, ()>
+where
+	W: AsyncWrite + Unpin + Send,
+{
+	pub fn new(inner: W) -> Self
+	{
+		Self {
+			inner,
+			lookup: (),
+			options: WriterOptions::default(),
+			line: Vec::new(),
+		}
+	}
+}
+
+impl<W, T> PlainBedWriter<W, T>
+{
+	// where interned tids are turned back into names, the reader's store
+	pub fn with_lookup<L>(self, lookup: L) -> PlainBedWriter<W, L>
+	where
+		L: TidLookup,
+	{
+		PlainBedWriter {
+			inner: self.inner,
+			lookup,
+			options: self.options,
+			line: self.line,
+		}
+	}
+
+	// how floats are written, see WriterOptions
+	pub fn with_options(mut self, options: WriterOptions) -> Self
+	{
+		self.options = options;
+		self
+	}
+
+	pub fn into_inner(self) -> W
+	{
+		self.inner
+	}
+}
+
+impl<W, T> AutoWriter<T> for PlainBedWriter<W, T>
+where
+	W: AsyncWrite + Unpin + Send,
+	T: TidLookup,
+{
+	async fn write_record<'a>(&'a mut self, record: &'a BedRecord<T::Tid>) -> error::Result<()>
+	{
+		self.line.clear();
+		format_record(&mut self.line, &self.lookup, self.options, record)?;
+
+		Ok(self.inner.write_all(&self.line).await?)
+	}
+
+	async fn write_track<'a>(
+		&'a mut self,
+		attributes: &'a [(&'a str, &'a str)],
+	) -> error::Result<()>
+	{
+		self.line.clear();
+		BedWriter::new(&mut self.line).write_track_line(attributes)?;
+
+		Ok(self.inner.write_all(&self.line).await?)
+	}
+
+	async fn write_browser<'a>(&'a mut self, line: &'a str) -> error::Result<()>
+	{
+		self.line.clear();
+		BedWriter::new(&mut self.line).write_browser_line(line)?;
+
+		Ok(self.inner.write_all(&self.line).await?)
+	}
+
+	async fn flush<'a>(&'a mut self) -> error::Result<()>
+	{
+		Ok(self.inner.flush().await?)
+	}
+
+	async fn close(mut self) -> error::Result<()>
+	{
+		self.inner.flush().await?;
+		Ok(self.inner.shutdown().await?)
+	}
+}
+
+// BGZF compressed BED as bgzip writes it, readable by the readers here and by tabix.
+// Blocks go to inner as they fill, flush leaves a partial block buffered and close writes
+// it along with the end of file marker.
+#[cfg(feature = "bgzf-write")]
+pub struct BgzfBedWriter<W, T = ()>
+{
+	inner: W,
+	lookup: T,
+	options: WriterOptions,
+	line: Vec<u8>,
+	blocks: BgzfWriter<Vec<u8>>,
+}
+
+#[cfg(feature = "bgzf-write")]
+impl<W> BgzfBedWriter<W, ()>
+where
+	W: AsyncWrite + Unpin + Send,
+{
+	pub fn new(inner: W) -> Self
+	{
+		Self {
+			inner,
+			lookup: (),
+			options: WriterOptions::default(),
+			line: Vec::new(),
+			blocks: BgzfWriter::new(Vec::new()),
+		}
+	}
+}
+
+#[cfg(feature = "bgzf-write")]
+impl<W, T> BgzfBedWriter<W, T>
+where
+	W: AsyncWrite + Unpin + Send,
+{
+	// where interned tids are turned back into names, the reader's store
+	pub fn with_lookup<L>(self, lookup: L) -> BgzfBedWriter<W, L>
+	where
+		L: TidLookup,
+	{
+		BgzfBedWriter {
+			inner: self.inner,
+			lookup,
+			options: self.options,
+			line: self.line,
+			blocks: self.blocks,
+		}
+	}
+
+	// how floats are written, see WriterOptions
+	pub fn with_options(mut self, options: WriterOptions) -> Self
+	{
+		self.options = options;
+		self
+	}
+
+	// the formatted line into the current block, passing on any blocks it filled
+	async fn write_line(&mut self) -> error::Result<()>
+	{
+		self.blocks.write_all(&self.line)?;
+
+		let filled = std::mem::take(self.blocks.inner_mut());
+		Ok(self.inner.write_all(&filled).await?)
+	}
+}
+
+#[cfg(feature = "bgzf-write")]
+impl<W, T> AutoWriter<T> for BgzfBedWriter<W, T>
+where
+	W: AsyncWrite + Unpin + Send,
+	T: TidLookup,
+{
+	async fn write_record<'a>(&'a mut self, record: &'a BedRecord<T::Tid>) -> error::Result<()>
+	{
+		self.line.clear();
+		format_record(&mut self.line, &self.lookup, self.options, record)?;
+
+		self.write_line().await
+	}
+
+	async fn write_track<'a>(
+		&'a mut self,
+		attributes: &'a [(&'a str, &'a str)],
+	) -> error::Result<()>
+	{
+		self.line.clear();
+		BedWriter::new(&mut self.line).write_track_line(attributes)?;
+
+		self.write_line().await
+	}
+
+	async fn write_browser<'a>(&'a mut self, line: &'a str) -> error::Result<()>
+	{
+		self.line.clear();
+		BedWriter::new(&mut self.line).write_browser_line(line)?;
+
+		self.write_line().await
+	}
+
+	async fn flush<'a>(&'a mut self) -> error::Result<()>
+	{
+		Ok(self.inner.flush().await?)
+	}
+
+	async fn close(mut self) -> error::Result<()>
+	{
+		let rest = self.blocks.finish()?;
+
+		self.inner.write_all(&rest).await?;
+		self.inner.flush().await?;
+		Ok(self.inner.shutdown().await?)
+	}
+}
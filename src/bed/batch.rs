@@ -0,0 +1,163 @@
+use crate::bed::{BedRecord, BedSink, BedSinkValue, ReaderId, SourceId, Strand};
+
+// Columnar records, one Vec per field, for callers that hand results straight to array
+// based analytics. Fills as a sink like CollectSink, or from collected records. Columns a
+// record's format lacks hold None.
+#[derive(Debug, Clone)]
+pub struct RecordBatch<Tid>
+{
+	pub tids: Vec<Tid>,
+	pub starts: Vec<u64>,
+	pub ends: Vec<u64>,
+	pub strands: Vec<Strand>,
+	pub names: Vec<Option<String>>,
+	pub scores: Vec<Option<u32>>,
+	pub n_valid_cov: Vec<Option<u32>>,
+	pub frac_mod: Vec<Option<f32>>,
+	pub n_mod: Vec<Option<u32>>,
+
+	// sink state, the tid and strand of the current run and the rows whose end has not
+	// been seen yet
+	current: Option<(Tid, Strand)>,
+	start: u64,
+	unfinished: usize,
+}
+
+impl<Tid> Default for RecordBatch<Tid>
+{
+	fn default() -> Self
+	{
+		Self::with_capacity(0)
+	}
+}
+
+impl<Tid> RecordBatch<Tid>
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	pub fn with_capacity(capacity: usize) -> Self
+	{
+		Self {
+			tids: Vec::with_capacity(capacity),
+			starts: Vec::with_capacity(capacity),
+			ends: Vec::with_capacity(capacity),
+			strands: Vec::with_capacity(capacity),
+			names: Vec::with_capacity(capacity),
+			scores: Vec::with_capacity(capacity),
+			n_valid_cov: Vec::with_capacity(capacity),
+			frac_mod: Vec::with_capacity(capacity),
+			n_mod: Vec::with_capacity(capacity),
+			current: None,
+			start: 0,
+			unfinished: 0,
+		}
+	}
+
+	// complete rows only
+	pub fn len(&self) -> usize
+	{
+		self.ends.len()
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.len() == 0
+	}
+
+	pub fn push(&mut self, record: BedRecord<Tid>)
+	{
+		self.push_row(record.tid, record.strand, record.start, &record.value);
+		self.ends.push(record.end);
+	}
+
+	pub fn clear(&mut self)
+	{
+		self.tids.clear();
+		self.starts.clear();
+		self.ends.clear();
+		self.strands.clear();
+		self.names.clear();
+		self.scores.clear();
+		self.n_valid_cov.clear();
+		self.frac_mod.clear();
+		self.n_mod.clear();
+		self.unfinished = 0;
+	}
+
+	// everything but the end, which the sink only learns after the values
+	fn push_row(&mut self, tid: Tid, strand: Strand, start: u64, value: &BedSinkValue)
+	{
+		self.tids.push(tid);
+		self.strands.push(strand);
+		self.starts.push(start);
+		self.names.push(value.name.clone());
+		self.scores.push(value.score);
+		self.n_valid_cov.push(value.n_valid_cov);
+		self.frac_mod.push(value.frac_mod);
+		self.n_mod.push(value.n_mod);
+	}
+}
+
+impl<Tid> FromIterator<BedRecord<Tid>> for RecordBatch<Tid>
+{
+	fn from_iter<I>(records: I) -> Self
+	where
+		I: IntoIterator<Item = BedRecord<Tid>>,
+	{
+		let records = records.into_iter();
+		let mut batch = Self::with_capacity(records.size_hint().0);
+
+		for record in records
+		{
+			batch.push(record);
+		}
+
+		batch
+	}
+}
+
+impl<Tid> BedSink<Tid> for RecordBatch<Tid>
+where
+	Tid: Clone + Send + Sync,
+{
+	fn begin_tid(&mut self, tid: &Tid, strand: &Strand)
+	{
+		self.current = Some((tid.clone(), *strand));
+	}
+
+	fn end_tid(&mut self, _tid: &Tid, _strand: &Strand)
+	{
+		self.current = None;
+	}
+
+	fn begin_position(&mut self, start: u64)
+	{
+		self.start = start;
+	}
+
+	fn end_position(&mut self, end: u64)
+	{
+		self.ends.extend(std::iter::repeat_n(end, self.unfinished));
+		self.unfinished = 0;
+	}
+
+	fn push_value(
+		&mut self,
+		_source_id: &Option<SourceId>,
+		_reader_id: &ReaderId,
+		value: BedSinkValue,
+	)
+	{
+		let Some((tid, strand)) = self.current.clone()
+		else
+		{
+			return;
+		};
+
+		self.push_row(tid, strand, self.start, &value);
+		self.unfinished += 1;
+	}
+}
@@ -5,6 +5,7 @@ use std::fmt;
 use std::sync::atomic::AtomicUsize;
 
 use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncSeek, AsyncSeekExt, AsyncRead, SeekFrom};
+use tokio::io::BufReader as TokioBufReader;
 
 pub use crate::bed::record::*;
 // #[cfg(feature = "bigbed")]
@@ -13,8 +14,14 @@ pub use crate::bed::extra::*;
 // pub use crate::bed::parser::*;
 // use crate::store::TidResolver;
 
+use pufferfish::prelude::*;
+
 use crate::error;
 
+#[cfg_attr(
+	feature = "tracing",
+	tracing::instrument(level = "debug", skip_all, fields(name = %name))
+)]
 pub(crate) async fn detect_format_from_reader<
 	B: AsyncRead + AsyncSeek + Send + Unpin + AsyncBufRead,
 >(
@@ -22,6 +29,39 @@ pub(crate) async fn detect_format_from_reader<
 	reader: &mut B,
 	max_lines: usize,
 ) -> error::Result<BedKind>
+{
+	let is_bgzf = reader.fill_buf().await?.starts_with(&[0x1f, 0x8b]);
+
+	let format = if is_bgzf
+	{
+		// lines live inside the first compressed block
+		let block = reader
+			.read_and_decompress_bgzf_block(Some(is_bgzf_eof))
+			.await
+			.map_err(|_| error::Error::BedFormat(name.clone()))?
+			.ok_or_else(|| error::Error::BedFormat(name.clone()))?;
+
+		let mut block_reader = TokioBufReader::new(std::io::Cursor::new(&block));
+		detect_format_from_lines(&name, &mut block_reader, max_lines).await
+	}
+	else
+	{
+		detect_format_from_lines(&name, reader, max_lines).await
+	};
+
+	reader.seek(SeekFrom::Start(0)).await?;
+
+	#[cfg(feature = "tracing")]
+	tracing::debug!(bgzf = is_bgzf, format = ?format.as_ref().ok(), "detected format");
+
+	format
+}
+
+async fn detect_format_from_lines<B: AsyncBufRead + Unpin>(
+	name: &str,
+	reader: &mut B,
+	max_lines: usize,
+) -> error::Result<BedKind>
 {
 	let mut accumulated = Vec::new();
 	let mut line = String::new();
@@ -32,22 +72,20 @@ pub(crate) async fn detect_format_from_reader<
 		let bytes_read = reader
 			.read_line(&mut line)
 			.await
-			.map_err(|_| error::Error::BedFormat(name.clone()))?;
+			.map_err(|_| error::Error::BedFormat(name.to_string()))?;
 		if bytes_read == 0
 		{
 			break; // EOF
 		}
 
 		accumulated.push(line.clone());
-
-		if let Ok(format) = BedKind::try_from(&accumulated)
-		{
-			reader.seek(SeekFrom::Start(0)).await?;
-			return Ok(format);
-		}
 	}
 
-	Err(error::Error::BedFormat(name))
+	let report = BedKind::detect_report(&accumulated)
+		.map_err(|_| error::Error::BedFormat(name.to_string()))?;
+	report.log_uncertain(name);
+
+	Ok(report.kind)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -142,37 +180,51 @@ impl fmt::Display for BedKind
 	}
 }
 
-impl TryFrom<&Vec<String>> for BedKind
+// The bedMethyl variants in the wild. All of them are read by the BedMethyl parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BedMethylLayout
 {
-	type Error = error::Error;
+	// modkit, 18 tab separated columns
+	Tabbed,
+	// older modkit, columns 10-18 space separated inside the tenth tab delimited field
+	SpaceSeparatedCounts,
+	// ENCODE, 11 columns ending in coverage and percent methylated
+	Legacy,
+}
 
-	fn try_from(bed_lines: &Vec<String>) -> error::Result<Self>
+impl BedMethylLayout
+{
+	pub fn detect(line: &str) -> Option<Self>
 	{
-		for line in bed_lines
-		{
-			let trimmed = line.trim();
+		let line = line.trim_end_matches(['\r', '\n']);
+		let tabbed: Vec<&str> = line.split('\t').collect();
 
-			if trimmed.is_empty()
-			{
-				continue;
-			}
+		let numeric = |field: &str| !field.is_empty() && field.parse::<f64>().is_ok();
 
-			let count = trimmed.split_whitespace().count();
-			let kind = match count
+		match tabbed.len()
+		{
+			18 if tabbed[9..].iter().all(|field| numeric(field)) => Some(Self::Tabbed),
+			10 =>
 			{
-				3 => BedKind::Bed3,
-				4 => BedKind::Bed4,
-				5 => BedKind::Bed5,
-				6 => BedKind::Bed6,
-				12 => BedKind::Bed12,
-				18 => BedKind::BedMethyl,
-				_ => return Err(error::Error::Parse(trimmed.to_string())),
-			};
-
-			return Ok(kind);
+				let counts: Vec<&str> = tabbed[9].split_whitespace().collect();
+				(counts.len() == 9 && counts.iter().all(|field| numeric(field)))
+					.then_some(Self::SpaceSeparatedCounts)
+			}
+			11 if numeric(tabbed[9]) && numeric(tabbed[10]) => Some(Self::Legacy),
+			_ => None,
 		}
+	}
+}
 
-		Err(error::Error::AutoDetect)
+impl TryFrom<&Vec<String>> for BedKind
+{
+	type Error = error::Error;
+
+	// ambiguous lines settle on the kind that parsed most of them, see BedKind::detect to
+	// handle them yourself
+	fn try_from(bed_lines: &Vec<String>) -> error::Result<Self>
+	{
+		BedKind::detect(bed_lines)?.resolve(None)
 	}
 }
 
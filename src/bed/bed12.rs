@@ -0,0 +1,265 @@
+use std::fmt;
+
+use crate::bed::BedRecord;
+use crate::error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb
+{
+	pub const BLACK: Rgb = Rgb(0, 0, 0);
+	pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+	// colour names some track hubs write in place of r,g,b
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		let rgb = match name.to_ascii_lowercase().as_str()
+		{
+			"black" => Rgb(0, 0, 0),
+			"white" => Rgb(255, 255, 255),
+			"red" => Rgb(255, 0, 0),
+			"green" => Rgb(0, 128, 0),
+			"blue" => Rgb(0, 0, 255),
+			"yellow" => Rgb(255, 255, 0),
+			"orange" => Rgb(255, 165, 0),
+			"purple" => Rgb(128, 0, 128),
+			"brown" => Rgb(165, 42, 42),
+			"pink" => Rgb(255, 192, 203),
+			"cyan" => Rgb(0, 255, 255),
+			"magenta" => Rgb(255, 0, 255),
+			"gray" | "grey" => Rgb(128, 128, 128),
+			_ => return None,
+		};
+
+		Some(rgb)
+	}
+
+	// Greys out by score the way UCSC does for tracks with useScore=1, in nine steps from
+	// white (score <= 166) to the full colour (score >= 945).
+	pub fn shade(&self, score: u32) -> Rgb
+	{
+		const STEPS: [u32; 9] = [166, 277, 388, 499, 611, 722, 833, 944, u32::MAX];
+
+		let step = STEPS.iter().position(|&max| score <= max).unwrap_or(8) as u32;
+		let mix = |channel: u8| {
+			let channel = channel as u32;
+			(255 - (255 - channel) * (step + 1) / 9) as u8
+		};
+
+		Rgb(mix(self.0), mix(self.1), mix(self.2))
+	}
+}
+
+impl fmt::Display for Rgb
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "{},{},{}", self.0, self.1, self.2)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemRgb
+{
+	Rgb(u8, u8, u8),
+	// "0", no colour given
+	Off,
+	// anything that is neither, the raw column is still kept on Bed12Columns
+	Invalid,
+}
+
+impl ItemRgb
+{
+	pub fn parse(raw: &str) -> Self
+	{
+		let raw = raw.trim();
+
+		if raw == "0"
+		{
+			return Self::Off;
+		}
+
+		let mut channels = raw.split(',').map(|channel| channel.trim().parse::<u8>());
+
+		match (channels.next(), channels.next(), channels.next(), channels.next())
+		{
+			(Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Self::Rgb(r, g, b),
+			// a single value is a grey level in some writers
+			(Some(Ok(v)), None, None, None) => Self::Rgb(v, v, v),
+			_ => match Rgb::from_name(raw)
+			{
+				Some(Rgb(r, g, b)) => Self::Rgb(r, g, b),
+				None => Self::Invalid,
+			},
+		}
+	}
+
+	pub fn rgb(&self) -> Option<Rgb>
+	{
+		match self
+		{
+			Self::Rgb(r, g, b) => Some(Rgb(*r, *g, *b)),
+			_ => None,
+		}
+	}
+
+	// Colour a browser would draw: shaded by score with useScore, otherwise the item colour,
+	// falling back to the track colour.
+	pub fn display_color(&self, score: Option<u32>, use_score: bool, track_color: Rgb) -> Rgb
+	{
+		let base = self.rgb().unwrap_or(track_color);
+
+		match score
+		{
+			Some(score) if use_score => base.shade(score),
+			_ => base,
+		}
+	}
+}
+
+// The columns after strand in a BED12 line. Intervals returned here are 0-based half-open
+// as written in the file, whatever indexing the reader was opened with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed12Columns
+{
+	pub(crate) chrom_start: u64,
+	pub(crate) chrom_end: u64,
+	pub(crate) thick_start: u64,
+	pub(crate) thick_end: u64,
+	pub(crate) item_rgb: ItemRgb,
+	pub(crate) item_rgb_raw: String,
+	pub(crate) block_count: u32,
+	pub(crate) block_sizes: Vec<u32>,
+	pub(crate) block_starts: Vec<u32>,
+}
+
+impl Bed12Columns
+{
+	pub fn item_rgb(&self) -> ItemRgb
+	{
+		self.item_rgb
+	}
+
+	pub fn item_rgb_raw(&self) -> &str
+	{
+		&self.item_rgb_raw
+	}
+
+	pub fn block_count(&self) -> u32
+	{
+		self.block_count
+	}
+
+	pub fn block_sizes(&self) -> &[u32]
+	{
+		&self.block_sizes
+	}
+
+	pub fn block_starts(&self) -> &[u32]
+	{
+		&self.block_starts
+	}
+
+	// None for non-coding records, where thickStart equals thickEnd
+	pub fn thick_region(&self) -> Option<(u64, u64)>
+	{
+		(self.thick_start < self.thick_end).then_some((self.thick_start, self.thick_end))
+	}
+
+	// absolute exon intervals
+	pub fn blocks(&self) -> impl Iterator<Item = (u64, u64)> + '_
+	{
+		self.block_starts
+			.iter()
+			.zip(&self.block_sizes)
+			.map(|(&start, &size)| {
+				let start = self.chrom_start.saturating_add(start as u64);
+				(start, start.saturating_add(size as u64))
+			})
+	}
+
+	// gaps between consecutive blocks
+	pub fn introns(&self) -> impl Iterator<Item = (u64, u64)> + '_
+	{
+		self.blocks()
+			.zip(self.blocks().skip(1))
+			.map(|((_, end), (next_start, _))| (end, next_start))
+			.filter(|(start, end)| start < end)
+	}
+
+	pub fn validate(&self) -> error::Result<()>
+	{
+		let invalid = |reason: &str| Err(error::Error::InvalidBed12(reason.to_string()));
+
+		if self.block_count == 0
+		{
+			return invalid("blockCount is zero");
+		}
+
+		if self.block_sizes.len() != self.block_count as usize
+			|| self.block_starts.len() != self.block_count as usize
+		{
+			return invalid("blockSizes and blockStarts must have blockCount entries");
+		}
+
+		if self.block_starts[0] != 0
+		{
+			return invalid("the first block must start at chromStart");
+		}
+
+		let mut previous_end = self.chrom_start;
+		for (i, (start, end)) in self.blocks().enumerate()
+		{
+			if i > 0 && start < previous_end
+			{
+				return invalid("blocks overlap or are out of order");
+			}
+			previous_end = end;
+		}
+
+		if previous_end != self.chrom_end
+		{
+			return invalid("the last block must end at chromEnd");
+		}
+
+		if self.thick_start > self.thick_end
+			|| self.thick_start < self.chrom_start
+			|| self.thick_end > self.chrom_end
+		{
+			return invalid("thickStart and thickEnd must lie within the record");
+		}
+
+		Ok(())
+	}
+}
+
+// One BED6 style record per block, in the coordinates of the original record. Records
+// without BED12 columns come back unchanged.
+pub fn bed12_to_bed6<Tid: Clone>(record: &BedRecord<Tid>) -> Vec<BedRecord<Tid>>
+{
+	let Some(columns) = record.value.bed12()
+	else
+	{
+		return vec![record.clone()];
+	};
+
+	// 1 when the reader shifted starts to 1-based
+	let shift = record.start.saturating_sub(columns.chrom_start);
+
+	columns
+		.blocks()
+		.map(|(start, end)| {
+			let mut value = record.value.clone();
+			value.bed12 = None;
+
+			BedRecord {
+				tid: record.tid.clone(),
+				strand: record.strand,
+				start: start + shift,
+				end,
+				value,
+			}
+		})
+		.collect()
+}
@@ -0,0 +1,119 @@
+use std::io::Write;
+
+use crate::bed::{BedRecord, encode_name};
+
+// Two records that belong together, such as a join match or a record and its closest
+// feature. Either side may be missing, BEDPE writes it as ". -1 -1" the way bedtools
+// does for an unmatched side.
+#[derive(Debug, Clone)]
+pub struct PairedRecord<Tid>
+{
+	pub first: Option<BedRecord<Tid>>,
+	pub second: Option<BedRecord<Tid>>,
+	pub name: Option<String>,
+	pub score: Option<u32>,
+	// written after the ten BEDPE columns, such as a distance
+	pub extra: Vec<String>,
+}
+
+impl<Tid> PairedRecord<Tid>
+{
+	pub fn new(first: Option<BedRecord<Tid>>, second: Option<BedRecord<Tid>>) -> Self
+	{
+		Self {
+			first,
+			second,
+			name: None,
+			score: None,
+			extra: Vec::new(),
+		}
+	}
+
+	pub fn with_name(mut self, name: impl Into<String>) -> Self
+	{
+		self.name = Some(name.into());
+		self
+	}
+
+	pub fn with_score(mut self, score: u32) -> Self
+	{
+		self.score = Some(score);
+		self
+	}
+
+	pub fn with_extra(mut self, extra: impl Into<String>) -> Self
+	{
+		self.extra.push(extra.into());
+		self
+	}
+}
+
+// Writes PairedRecords as BEDPE lines, coordinates as the records hold them.
+pub struct BedpeWriter<W>
+{
+	inner: W,
+}
+
+impl<W> BedpeWriter<W>
+where
+	W: Write,
+{
+	pub fn new(inner: W) -> Self
+	{
+		Self { inner }
+	}
+
+	pub fn write<Tid>(&mut self, record: &PairedRecord<Tid>) -> std::io::Result<()>
+	where
+		Tid: AsRef<str>,
+	{
+		let out = &mut self.inner;
+
+		for side in [&record.first, &record.second]
+		{
+			match side
+			{
+				Some(side) => write!(out, "{}\t{}\t{}\t", side.tid.as_ref(), side.start, side.end)?,
+				None => write!(out, ".\t-1\t-1\t")?,
+			}
+		}
+
+		match &record.name
+		{
+			Some(name) => write!(out, "{}", encode_name(name))?,
+			None => write!(out, ".")?,
+		}
+
+		match record.score
+		{
+			Some(score) => write!(out, "\t{score}")?,
+			None => write!(out, "\t.")?,
+		}
+
+		for side in [&record.first, &record.second]
+		{
+			match side
+			{
+				Some(side) => write!(out, "\t{}", side.strand)?,
+				None => write!(out, "\t.")?,
+			}
+		}
+
+		for extra in &record.extra
+		{
+			write!(out, "\t{extra}")?;
+		}
+
+		writeln!(out)
+	}
+
+	pub fn flush(&mut self) -> std::io::Result<()>
+	{
+		self.inner.flush()
+	}
+
+	pub fn into_inner(self) -> W
+	{
+		self.inner
+	}
+}
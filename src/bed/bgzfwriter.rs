@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+
+use crate::bed::framing::BGZF_EOF;
+
+// what bgzip puts in a block, leaving room for deflate to grow incompressible data
+const MAX_BLOCK_DATA: usize = 0xff00;
+
+// Writes BGZF blocks as bgzip does, keeping count of the compressed bytes for virtual
+// offsets.
+pub(crate) struct BgzfWriter<W>
+{
+	inner: W,
+	buffer: Vec<u8>,
+	compressed: u64,
+}
+
+impl<W> BgzfWriter<W>
+where
+	W: Write,
+{
+	pub(crate) fn new(inner: W) -> Self
+	{
+		Self {
+			inner,
+			buffer: Vec::with_capacity(MAX_BLOCK_DATA),
+			compressed: 0,
+		}
+	}
+
+	pub(crate) fn virtual_offset(&self) -> u64
+	{
+		(self.compressed << 16) | self.buffer.len() as u64
+	}
+
+	// the compressed blocks so far, for callers handing them on as they are written
+	pub(crate) fn inner_mut(&mut self) -> &mut W
+	{
+		&mut self.inner
+	}
+
+	pub(crate) fn write_all(&mut self, mut bytes: &[u8]) -> std::io::Result<()>
+	{
+		while !bytes.is_empty()
+		{
+			if self.buffer.len() == MAX_BLOCK_DATA
+			{
+				self.write_block()?;
+			}
+
+			let taken = bytes.len().min(MAX_BLOCK_DATA - self.buffer.len());
+			self.buffer.extend_from_slice(&bytes[..taken]);
+			bytes = &bytes[taken..];
+		}
+
+		Ok(())
+	}
+
+	pub(crate) fn write_block(&mut self) -> std::io::Result<()>
+	{
+		let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(&self.buffer)?;
+		let deflated = encoder.finish()?;
+
+		let block_size = 18 + deflated.len() + 8;
+
+		let mut header = [
+			0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43,
+			0x02, 0x00, 0x00, 0x00,
+		];
+		header[16..].copy_from_slice(&((block_size - 1) as u16).to_le_bytes());
+
+		self.inner.write_all(&header)?;
+		self.inner.write_all(&deflated)?;
+		self.inner.write_all(&crc32fast::hash(&self.buffer).to_le_bytes())?;
+		self.inner.write_all(&(self.buffer.len() as u32).to_le_bytes())?;
+
+		self.compressed += block_size as u64;
+		self.buffer.clear();
+
+		Ok(())
+	}
+
+	pub(crate) fn finish(mut self) -> std::io::Result<W>
+	{
+		if !self.buffer.is_empty()
+		{
+			self.write_block()?;
+		}
+
+		self.inner.write_all(&BGZF_EOF)?;
+		self.inner.flush()?;
+
+		Ok(self.inner)
+	}
+}
@@ -0,0 +1,149 @@
+use crate::bed::{BedSink, BedSinkValue, ReaderId, SourceId, Strand};
+use crate::region::CoordinateSystem;
+
+// Per-bin aggregates over one contig interval, 0-based half-open, filled as a sink through
+// Reader::binned_counts. A record counts toward every bin it overlaps.
+#[derive(Debug, Clone)]
+pub struct BinnedCounts<Tid>
+{
+	pub start: u64,
+	pub end: u64,
+	pub counts: Vec<u64>,
+	// over records that have a score column
+	pub score_sums: Vec<u64>,
+	// over bedMethyl records, see frac_mod_means
+	pub frac_mod_sums: Vec<f64>,
+	pub frac_mod_counts: Vec<u64>,
+
+	// sink state, the record whose end has not been seen yet
+	tid: Tid,
+	system: CoordinateSystem,
+	on_tid: bool,
+	record_start: u64,
+	pending: Vec<(Option<u32>, Option<f32>)>,
+}
+
+impl<Tid> BinnedCounts<Tid>
+{
+	// system is what the records handed in use, so they can be binned 0-based
+	pub fn new(tid: Tid, start: u64, end: u64, n_bins: usize, system: CoordinateSystem) -> Self
+	{
+		Self {
+			start,
+			end,
+			counts: vec![0; n_bins],
+			score_sums: vec![0; n_bins],
+			frac_mod_sums: vec![0.0; n_bins],
+			frac_mod_counts: vec![0; n_bins],
+			tid,
+			system,
+			on_tid: false,
+			record_start: 0,
+			pending: Vec::new(),
+		}
+	}
+
+	pub fn n_bins(&self) -> usize
+	{
+		self.counts.len()
+	}
+
+	// the interval a bin covers, widths differing by at most one
+	pub fn bin_range(&self, bin: usize) -> (u64, u64)
+	{
+		let edge = |bin: usize| {
+			let width = self.end.saturating_sub(self.start) as u128;
+			self.start + (width * bin as u128 / self.n_bins() as u128) as u64
+		};
+
+		(edge(bin), edge(bin + 1))
+	}
+
+	// mean fraction modified of each bin, None for bins without bedMethyl records
+	pub fn frac_mod_means(&self) -> Vec<Option<f64>>
+	{
+		self.frac_mod_sums
+			.iter()
+			.zip(&self.frac_mod_counts)
+			.map(|(sum, count)| (*count > 0).then(|| sum / *count as f64))
+			.collect()
+	}
+
+	// start and end are 0-based half-open
+	pub fn add(&mut self, start: u64, end: u64, score: Option<u32>, frac_mod: Option<f32>)
+	{
+		let (start, end) = (start.max(self.start), end.min(self.end));
+
+		if start >= end || self.counts.is_empty()
+		{
+			return;
+		}
+
+		for bin in self.bin_of(start)..=self.bin_of(end - 1)
+		{
+			self.counts[bin] += 1;
+
+			if let Some(score) = score
+			{
+				self.score_sums[bin] += score as u64;
+			}
+
+			if let Some(frac_mod) = frac_mod
+			{
+				self.frac_mod_sums[bin] += frac_mod as f64;
+				self.frac_mod_counts[bin] += 1;
+			}
+		}
+	}
+
+	// position lies in start..end
+	fn bin_of(&self, position: u64) -> usize
+	{
+		let width = (self.end - self.start) as u128;
+		((position - self.start) as u128 * self.n_bins() as u128 / width) as usize
+	}
+}
+
+impl<Tid> BedSink<Tid> for BinnedCounts<Tid>
+where
+	Tid: PartialEq + Send + Sync,
+{
+	fn begin_tid(&mut self, tid: &Tid, _strand: &Strand)
+	{
+		self.on_tid = *tid == self.tid;
+	}
+
+	fn end_tid(&mut self, _tid: &Tid, _strand: &Strand)
+	{
+		self.on_tid = false;
+	}
+
+	fn begin_position(&mut self, start: u64)
+	{
+		self.record_start = start;
+	}
+
+	fn end_position(&mut self, end: u64)
+	{
+		let (start, end) =
+			self.system.convert(self.record_start, end, CoordinateSystem::ZeroBasedHalfOpen);
+
+		for (score, frac_mod) in std::mem::take(&mut self.pending)
+		{
+			self.add(start, end, score, frac_mod);
+		}
+	}
+
+	fn push_value(
+		&mut self,
+		_source_id: &Option<SourceId>,
+		_reader_id: &ReaderId,
+		value: BedSinkValue,
+	)
+	{
+		if self.on_tid
+		{
+			self.pending.push((value.score, value.frac_mod));
+		}
+	}
+}
@@ -19,6 +19,20 @@ pub struct BgzfBlock
 	pub(crate) filter_ctx: Option<Arc<Mutex<ReadFilterContext>>>,
 	pub(crate) source_id: Option<SourceId>,
 	pub(crate) reader_id: ReaderId,
+
+	// where the payload goes back to once the block is dropped
+	pub(crate) buffers: Option<Arc<BufferPool>>,
+}
+
+impl Drop for BgzfBlock
+{
+	fn drop(&mut self)
+	{
+		if let Some(buffers) = self.buffers.take()
+		{
+			buffers.put(std::mem::take(&mut self.bytes));
+		}
+	}
 }
 
 impl Deref for BgzfBlock
@@ -43,3 +57,58 @@ impl Deref for BgzfBlock
 // 		self.bytes.is_empty()
 // 	}
 // }
+
+// Decompressed payload buffers handed back by dropped blocks, so sequential scans and
+// region queries reuse a few allocations rather than making one per batch. At most
+// capacity buffers are kept, 0 disables reuse.
+#[derive(Debug)]
+pub(crate) struct BufferPool
+{
+	buffers: std::sync::Mutex<Vec<Vec<u8>>>,
+	capacity: usize,
+}
+
+impl BufferPool
+{
+	pub(crate) fn new(capacity: usize) -> Self
+	{
+		Self {
+			buffers: std::sync::Mutex::new(Vec::with_capacity(capacity)),
+			capacity,
+		}
+	}
+
+	// an empty buffer with room for at least size bytes
+	pub(crate) fn take(&self, size: usize) -> Vec<u8>
+	{
+		let reused = self.buffers.lock().ok().and_then(|mut buffers| buffers.pop());
+
+		match reused
+		{
+			Some(mut buffer) =>
+			{
+				buffer.reserve(size);
+				buffer
+			}
+			None => Vec::with_capacity(size),
+		}
+	}
+
+	pub(crate) fn put(&self, mut buffer: Vec<u8>)
+	{
+		if buffer.capacity() == 0
+		{
+			return;
+		}
+
+		buffer.clear();
+
+		if let Ok(mut buffers) = self.buffers.lock()
+		{
+			if buffers.len() < self.capacity
+			{
+				buffers.push(buffer);
+			}
+		}
+	}
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+use crate::bed::{BedSink, BedSinkValue, ModCode, ReaderId, SourceId, Strand};
+
+#[derive(Debug, Clone)]
+pub struct BedRecord<Tid>
+{
+	pub tid: Tid,
+	pub strand: Strand,
+	pub start: u64,
+	pub end: u64,
+	pub value: BedSinkValue,
+}
+
+// Collects sink callbacks into owned records, for callers that want to pull records one at a time
+#[derive(Debug)]
+pub struct CollectSink<Tid>
+{
+	current: Option<(Tid, Strand)>,
+	start: u64,
+	values: Vec<BedSinkValue>,
+	records: VecDeque<BedRecord<Tid>>,
+}
+
+impl<Tid> Default for CollectSink<Tid>
+{
+	fn default() -> Self
+	{
+		Self {
+			current: None,
+			start: 0,
+			values: Vec::new(),
+			records: VecDeque::new(),
+		}
+	}
+}
+
+impl<Tid> CollectSink<Tid>
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	pub fn pop(&mut self) -> Option<BedRecord<Tid>>
+	{
+		self.records.pop_front()
+	}
+
+	pub fn drain(&mut self) -> impl Iterator<Item = BedRecord<Tid>> + '_
+	{
+		self.records.drain(..)
+	}
+
+	pub fn len(&self) -> usize
+	{
+		self.records.len()
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.records.is_empty()
+	}
+
+	pub fn retain_mod_code(&mut self, code: impl Into<ModCode>)
+	{
+		let code = Some(code.into());
+		self.records.retain(|record| record.mod_code() == code);
+	}
+}
+
+impl<Tid> BedSink<Tid> for CollectSink<Tid>
+where
+	Tid: Clone + Send + Sync,
+{
+	fn begin_tid(&mut self, tid: &Tid, strand: &Strand)
+	{
+		self.current = Some((tid.clone(), *strand));
+	}
+
+	fn end_tid(&mut self, _tid: &Tid, _strand: &Strand)
+	{
+		self.current = None;
+	}
+
+	fn begin_position(&mut self, start: u64)
+	{
+		self.start = start;
+	}
+
+	fn end_position(&mut self, end: u64)
+	{
+		let Some((tid, strand)) = &self.current
+		else
+		{
+			self.values.clear();
+			return;
+		};
+
+		for value in self.values.drain(..)
+		{
+			self.records.push_back(BedRecord {
+				tid: tid.clone(),
+				strand: *strand,
+				start: self.start,
+				end,
+				value,
+			});
+		}
+	}
+
+	fn push_value(
+		&mut self,
+		_source_id: &Option<SourceId>,
+		_reader_id: &ReaderId,
+		value: BedSinkValue,
+	)
+	{
+		self.values.push(value);
+	}
+}
@@ -0,0 +1,137 @@
+use crate::bed::{Bed12Columns, BedKind, BedRecord, ItemRgb, Strand};
+use crate::error;
+
+#[derive(Debug, Clone)]
+pub struct Converted<Tid>
+{
+	pub record: BedRecord<Tid>,
+	// true when a field holding data was dropped
+	pub lossy: bool,
+}
+
+// relative width of the plain BED kinds, bedMethyl carries the BED6 columns
+fn columns(kind: BedKind) -> u8
+{
+	match kind
+	{
+		BedKind::Bed3 => 3,
+		BedKind::Bed4 => 4,
+		BedKind::Bed5 => 5,
+		BedKind::Bed6 | BedKind::BedMethyl => 6,
+		BedKind::Bed12 => 12,
+	}
+}
+
+impl<Tid: Clone> BedRecord<Tid>
+{
+	pub fn kind(&self) -> BedKind
+	{
+		self.value.kind()
+	}
+
+	// Widens with BED defaults (name ".", score 0, one block covering the record) or
+	// narrows by dropping columns. Nothing widens into bedMethyl since its counts cannot be
+	// made up.
+	pub fn try_into_kind(self, kind: BedKind) -> error::Result<Converted<Tid>>
+	{
+		let from = self.kind();
+
+		if kind == BedKind::BedMethyl && from != BedKind::BedMethyl
+		{
+			return Err(error::Error::BedMismatch(kind.to_string()));
+		}
+
+		let width = columns(kind);
+		let mut record = self;
+		let mut lossy = false;
+		let value = &mut record.value;
+
+		if width < 4
+		{
+			lossy |= value.name.take().is_some_and(|name| name != ".");
+		}
+		else if value.name.is_none()
+		{
+			value.name = Some(".".into());
+		}
+
+		if width < 5
+		{
+			lossy |= value.score.take().is_some_and(|score| score != 0);
+		}
+		else if value.score.is_none()
+		{
+			value.score = Some(0);
+		}
+
+		if width < 6
+		{
+			lossy |= record.strand != Strand::Both;
+			record.strand = Strand::Both;
+		}
+
+		// the BED6+N columns only mean something to the type they were read with
+		lossy |= value.extra.take().is_some_and(|extra| !extra.is_empty());
+		lossy |= value.fields.take().is_some();
+		value.extra_names = &[];
+
+		if kind != BedKind::BedMethyl
+		{
+			lossy |= value.n_valid_cov.is_some();
+			value.n_valid_cov = None;
+			value.frac_mod = None;
+			value.n_mod = None;
+			value.n_canonical = None;
+			value.n_other_mod = None;
+			value.n_delete = None;
+			value.n_fail = None;
+			value.n_diff = None;
+			value.n_nocall = None;
+		}
+
+		if kind == BedKind::Bed12
+		{
+			if value.bed12.is_none()
+			{
+				let (start, end) = (record.start, record.end);
+
+				value.bed12 = Some(Box::new(Bed12Columns {
+					chrom_start: start,
+					chrom_end: end,
+					thick_start: start,
+					thick_end: start,
+					item_rgb: ItemRgb::Off,
+					item_rgb_raw: "0".into(),
+					block_count: 1,
+					block_sizes: vec![end.saturating_sub(start) as u32],
+					block_starts: vec![0],
+				}));
+			}
+		}
+		else
+		{
+			lossy |= value
+				.bed12
+				.take()
+				.is_some_and(|columns| columns.block_count > 1 || columns.thick_region().is_some());
+		}
+
+		value.kind = kind;
+
+		Ok(Converted { record, lossy })
+	}
+
+	pub fn to_bed3(&self) -> Converted<Tid>
+	{
+		self.clone()
+			.try_into_kind(BedKind::Bed3)
+			.expect("narrowing never fails")
+	}
+
+	pub fn to_bed6(&self) -> Converted<Tid>
+	{
+		self.clone()
+			.try_into_kind(BedKind::Bed6)
+			.expect("BED6 is not bedMethyl")
+	}
+}
@@ -0,0 +1,15 @@
+use crate::bed::{BedKind, ReaderId, SourceId};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReaderDescription
+{
+	// the path a file was opened from as given, or the name handed to from_reader
+	pub name: String,
+	pub kind: BedKind,
+	pub reader_id: ReaderId,
+	pub source_id: Option<SourceId>,
+	pub one_indexed: bool,
+	// None when blocks are decompressed on the calling thread
+	pub decompression_threads: Option<usize>,
+	pub filtered: bool,
+}
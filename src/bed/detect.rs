@@ -0,0 +1,265 @@
+use crate::error;
+use crate::bed::{BedKind, BedMethylLayout};
+
+const KINDS: [BedKind; 6] = [
+	BedKind::Bed3,
+	BedKind::Bed4,
+	BedKind::Bed5,
+	BedKind::Bed6,
+	BedKind::Bed12,
+	BedKind::BedMethyl,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Detection
+{
+	Kind(BedKind),
+	// no single kind parses every examined line, candidates are ordered by how many lines
+	// they parse
+	Ambiguous(Vec<BedKind>),
+}
+
+impl Detection
+{
+	pub fn kind(&self) -> Option<BedKind>
+	{
+		match self
+		{
+			Self::Kind(kind) => Some(*kind),
+			Self::Ambiguous(_) => None,
+		}
+	}
+
+	pub fn candidates(&self) -> &[BedKind]
+	{
+		match self
+		{
+			Self::Kind(kind) => std::slice::from_ref(kind),
+			Self::Ambiguous(candidates) => candidates,
+		}
+	}
+
+	// an ambiguous result settles on preferred when it is a candidate, otherwise on the
+	// kind that parsed the most lines
+	pub fn resolve(&self, preferred: Option<BedKind>) -> error::Result<BedKind>
+	{
+		match self
+		{
+			Self::Kind(kind) => Ok(*kind),
+			Self::Ambiguous(candidates) => preferred
+				.filter(|kind| candidates.contains(kind))
+				.or_else(|| candidates.first().copied())
+				.ok_or(error::Error::AutoDetect),
+		}
+	}
+}
+
+// comments and UCSC track/browser lines carry no columns to detect from
+pub(crate) fn is_data_line(line: &str) -> bool
+{
+	let line = line.trim();
+
+	!line.is_empty()
+		&& !line.starts_with('#')
+		&& !line.starts_with("track")
+		&& !line.starts_with("browser")
+}
+
+// whether the parser for kind would accept the line, column types included
+pub fn line_parses_as(line: &str, kind: BedKind) -> bool
+{
+	if kind == BedKind::BedMethyl
+	{
+		return BedMethylLayout::detect(line).is_some();
+	}
+
+	let fields: Vec<&str> = line.split_whitespace().collect();
+
+	let integer = |i: usize| fields[i].parse::<u64>().is_ok();
+	let score = |i: usize| fields[i].parse::<u32>().is_ok();
+	let strand = |i: usize| matches!(fields[i], "+" | "-" | ".");
+	let list = |i: usize| {
+		fields[i]
+			.split(',')
+			.filter(|value| !value.is_empty())
+			.all(|value| value.parse::<u32>().is_ok())
+	};
+
+	let expected = match kind
+	{
+		BedKind::Bed3 => 3,
+		BedKind::Bed4 => 4,
+		BedKind::Bed5 => 5,
+		BedKind::Bed6 => 6,
+		BedKind::Bed12 => 12,
+		BedKind::BedMethyl => unreachable!(),
+	};
+
+	fields.len() == expected
+		&& integer(1)
+		&& integer(2)
+		&& (expected < 5 || score(4))
+		&& (expected < 6 || strand(5))
+		&& (expected < 12 || (integer(6) && integer(7) && score(9) && list(10) && list(11)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectionReport
+{
+	// the best candidate, what detect_format would have returned
+	pub kind: BedKind,
+	pub detection: Detection,
+	// share of the examined data lines that parse as kind
+	pub confidence: f32,
+	pub lines_examined: usize,
+	pub reasons: Vec<String>,
+}
+
+impl DetectionReport
+{
+	pub fn is_certain(&self) -> bool
+	{
+		self.detection.kind().is_some() && self.reasons.is_empty()
+	}
+
+	pub(crate) fn log_uncertain(&self, name: &str)
+	{
+		if !self.is_certain()
+		{
+			log::warn!(
+				"{name}: detected {} with {:.0}% confidence over {} lines ({}), open it with an \
+				 explicit OneShotBlockReader kind if this is wrong",
+				self.kind,
+				self.confidence * 100.0,
+				self.lines_examined,
+				self.reasons.join("; ")
+			);
+		}
+	}
+}
+
+impl BedKind
+{
+	// checks every data line against every kind rather than trusting the first line's
+	// column count
+	pub fn detect<S>(lines: &[S]) -> error::Result<Detection>
+	where
+		S: AsRef<str>,
+	{
+		Ok(Self::detect_report(lines)?.detection)
+	}
+
+	pub fn detect_report<S>(lines: &[S]) -> error::Result<DetectionReport>
+	where
+		S: AsRef<str>,
+	{
+		let mut parsed = [0usize; KINDS.len()];
+		let mut reasons = Vec::new();
+		let mut lines_examined = 0;
+		let mut first_line = None;
+		let mut numeric_names = 0;
+
+		for line in lines.iter().map(|line| line.as_ref().trim())
+		{
+			if !is_data_line(line)
+			{
+				continue;
+			}
+
+			lines_examined += 1;
+			first_line.get_or_insert(line);
+
+			let mut any = false;
+			for (count, kind) in parsed.iter_mut().zip(KINDS)
+			{
+				if line_parses_as(line, kind)
+				{
+					*count += 1;
+					any = true;
+				}
+			}
+
+			if !any
+			{
+				reasons.push(format!(
+					"line {lines_examined} ({} columns) does not parse as any BED kind",
+					line.split_whitespace().count()
+				));
+			}
+
+			if line_parses_as(line, BedKind::Bed4)
+				&& line
+					.split_whitespace()
+					.nth(3)
+					.is_some_and(|name| name.parse::<f64>().is_ok())
+			{
+				numeric_names += 1;
+			}
+		}
+
+		let Some(first_line) = first_line
+		else
+		{
+			return Err(error::Error::AutoDetect);
+		};
+
+		let mut candidates: Vec<(usize, BedKind)> = parsed
+			.into_iter()
+			.zip(KINDS)
+			.filter(|(count, _)| *count > 0)
+			.collect();
+
+		if candidates.is_empty()
+		{
+			return Err(error::Error::Parse(first_line.to_string()));
+		}
+
+		candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+		let complete = candidates
+			.iter()
+			.filter(|(count, _)| *count == lines_examined)
+			.count();
+
+		let (best_count, kind) = candidates[0];
+
+		let detection = match complete
+		{
+			1 => Detection::Kind(kind),
+			0 => Detection::Ambiguous(candidates.iter().map(|(_, kind)| *kind).collect()),
+			_ => Detection::Ambiguous(
+				candidates
+					.iter()
+					.take(complete)
+					.map(|(_, kind)| *kind)
+					.collect(),
+			),
+		};
+
+		if complete == 0
+		{
+			for (count, candidate) in &candidates
+			{
+				reasons.push(format!("{count} of {lines_examined} lines parse as {candidate}"));
+			}
+		}
+
+		if kind == BedKind::Bed4 && numeric_names == best_count
+		{
+			reasons.push("the fourth column is numeric, this may be bedGraph read as BED4".into());
+		}
+
+		if lines_examined < 2
+		{
+			reasons.push(format!("only {lines_examined} data line examined"));
+		}
+
+		Ok(DetectionReport {
+			kind,
+			detection,
+			confidence: best_count as f32 / lines_examined as f32,
+			lines_examined,
+			reasons,
+		})
+	}
+}
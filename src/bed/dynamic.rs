@@ -0,0 +1,317 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::error;
+use crate::bed::{BedFieldsSink, BedKind, BedSinkValue, ParseOptions, Strand, decode_name};
+use crate::bed::tokenizer::{next_line, tokenize};
+use crate::bed::utf8::utf8_field;
+use crate::filtering::ReadFilterContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType
+{
+	Text,
+	Int,
+	Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue
+{
+	Text(String),
+	Int(i64),
+	Float(f64),
+}
+
+impl FieldValue
+{
+	pub fn as_str(&self) -> Option<&str>
+	{
+		match self
+		{
+			Self::Text(text) => Some(text),
+			_ => None,
+		}
+	}
+
+	pub fn as_i64(&self) -> Option<i64>
+	{
+		match self
+		{
+			Self::Int(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	// ints widen, text does not
+	pub fn as_f64(&self) -> Option<f64>
+	{
+		match self
+		{
+			Self::Int(value) => Some(*value as f64),
+			Self::Float(value) => Some(*value),
+			Self::Text(_) => None,
+		}
+	}
+}
+
+// The layout of a file only known at runtime, such as "chrom,start,end,name,float:signal,
+// int:count". The first three columns are always chrom, start and end. name, score and
+// strand fill the usual BED fields, any other column is "type:name" with type text, int or
+// float, a bare name being text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSpec
+{
+	// the columns after end
+	columns: Vec<(String, ColumnType)>,
+}
+
+impl FromStr for ColumnSpec
+{
+	type Err = error::Error;
+
+	fn from_str(spec: &str) -> error::Result<Self>
+	{
+		let mut parts = spec.split(',').map(str::trim);
+
+		for expected in ["chrom", "start", "end"]
+		{
+			if parts.next() != Some(expected)
+			{
+				return Err(error::Error::Parse(format!(
+					"column spec {spec}: the first three columns must be chrom,start,end"
+				)));
+			}
+		}
+
+		let mut columns: Vec<(String, ColumnType)> = Vec::new();
+
+		for part in parts
+		{
+			let (column_type, name) = match part.split_once(':')
+			{
+				Some(("text" | "string", name)) => (ColumnType::Text, name),
+				Some(("int", name)) => (ColumnType::Int, name),
+				Some(("float", name)) => (ColumnType::Float, name),
+				Some((other, _)) =>
+				{
+					return Err(error::Error::Parse(format!(
+						"column spec {spec}: unknown column type {other}"
+					)));
+				}
+				None => (ColumnType::Text, part),
+			};
+
+			if name.is_empty() || columns.iter().any(|(existing, _)| existing == name)
+			{
+				return Err(error::Error::Parse(format!(
+					"column spec {spec}: empty or repeated column name {name}"
+				)));
+			}
+
+			columns.push((name.to_string(), column_type));
+		}
+
+		Ok(Self { columns })
+	}
+}
+
+impl ColumnSpec
+{
+	// the columns after end, in file order
+	pub fn columns(&self) -> impl Iterator<Item = (&str, ColumnType)>
+	{
+		self.columns
+			.iter()
+			.map(|(name, column_type)| (name.as_str(), *column_type))
+	}
+
+	// chrom, start and end included
+	pub fn column_count(&self) -> usize
+	{
+		self.columns.len() + 3
+	}
+
+	// Types the raw columns DynBedExtra kept, filling name, score and strand and leaving
+	// the rest in the value by name. Called by the reader for each record.
+	pub(crate) fn apply(
+		self: &Arc<Self>,
+		strand: &mut Strand,
+		value: &mut BedSinkValue,
+	) -> error::Result<()>
+	{
+		let raw = value.extra.take().unwrap_or_default();
+
+		if raw.len() != self.columns.len()
+		{
+			let expected = format!("{} column spec", self.column_count());
+			return Err(error::Error::BedMismatch(expected));
+		}
+
+		let mut values = Vec::with_capacity(raw.len());
+		// the widest plain BED kind the columns cover
+		let mut width = 3;
+
+		for ((name, column_type), text) in self.columns.iter().zip(raw.into_vec())
+		{
+			let invalid = || error::Error::Parse(format!("{name} column value {text}"));
+
+			match name.as_str()
+			{
+				"name" =>
+				{
+					value.name = Some(decode_name(text.as_bytes()).into_owned());
+					width = width.max(4);
+				}
+				"score" =>
+				{
+					value.score = Some(text.parse().map_err(|_| invalid())?);
+					width = width.max(5);
+				}
+				"strand" =>
+				{
+					*strand = Strand::from(text.as_str());
+					width = width.max(6);
+				}
+				_ =>
+				{
+					let field = match column_type
+					{
+						ColumnType::Text => FieldValue::Text(text),
+						ColumnType::Int => FieldValue::Int(text.parse().map_err(|_| invalid())?),
+						ColumnType::Float =>
+						{
+							FieldValue::Float(text.parse().map_err(|_| invalid())?)
+						}
+					};
+					values.push(field);
+				}
+			}
+		}
+
+		value.kind = match width
+		{
+			3 => BedKind::Bed3,
+			4 => BedKind::Bed4,
+			5 => BedKind::Bed5,
+			_ => BedKind::Bed6,
+		};
+		value.fields = Some(Box::new(DynFields {
+			spec: Arc::clone(self),
+			values,
+		}));
+
+		Ok(())
+	}
+
+	// position among the columns that are not name, score or strand
+	fn index_of(&self, name: &str) -> Option<usize>
+	{
+		self.columns
+			.iter()
+			.filter(|(column, _)| !matches!(column.as_str(), "name" | "score" | "strand"))
+			.position(|(column, _)| column == name)
+	}
+}
+
+// the typed columns of a record read with a ColumnSpec
+#[derive(Debug, Clone)]
+pub(crate) struct DynFields
+{
+	spec: Arc<ColumnSpec>,
+	values: Vec<FieldValue>,
+}
+
+impl DynFields
+{
+	pub(crate) fn get(&self, name: &str) -> Option<&FieldValue>
+	{
+		self.values.get(self.spec.index_of(name)?)
+	}
+
+	pub(crate) fn get_index(&self, index: usize) -> Option<&FieldValue>
+	{
+		self.values.get(index)
+	}
+}
+
+// BED3 followed by any number of columns typed at runtime, open the reader with
+// ReaderOptions::with_column_spec. Filters only see chrom, start and end since the other
+// columns are typed after parsing.
+#[derive(Debug, Clone, Default)]
+pub struct DynBedExtra;
+
+impl<Tid> BedFieldsSink<Tid> for DynBedExtra
+where
+	Tid: Debug + Clone + Send + Sync + PartialEq,
+{
+	const KIND: BedKind = BedKind::Bed3;
+
+	async fn parse_sink<'a>(
+		input: &'a [u8],
+		filter_ctx: Option<&ReadFilterContext>,
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
+	{
+		let (line, rest) = next_line(input);
+		let fields = tokenize(line, options.tokenizer_mode, false, "BED3+")?;
+
+		if fields.is_empty()
+		{
+			return Ok((rest, None));
+		}
+
+		if fields.len() < 3
+		{
+			return Err(error::Error::BedMismatch("BED3+".into()));
+		}
+
+		let tid = utf8_field(fields.get(0), "chrom", options.utf8_policy)?;
+		let start = lexical_core::parse::<u64>(fields.get(1))?;
+		let end = lexical_core::parse::<u64>(fields.get(2))?;
+
+		if let Some(ctx) = filter_ctx
+		{
+			if !ctx.passes(&tid, start, end, Strand::Both, None, None).await
+			{
+				return Ok((rest, None));
+			}
+		}
+
+		let extra = (3..fields.len())
+			.map(|i| {
+				Ok(utf8_field(fields.get(i), "extra column", options.utf8_policy)?.into_owned())
+			})
+			.collect::<error::Result<Box<[String]>>>()?;
+
+		Ok((
+			rest,
+			Some((
+				tid,
+				Strand::Both,
+				start,
+				end,
+				BedSinkValue {
+					name: None,
+					score: None,
+					n_valid_cov: None,
+					frac_mod: None,
+					n_mod: None,
+					n_canonical: None,
+					n_other_mod: None,
+					n_delete: None,
+					n_fail: None,
+					n_diff: None,
+					n_nocall: None,
+					kind: BedKind::Bed3,
+					bed12: None,
+					extra: Some(extra),
+					fields: None,
+					extra_names: &[],
+				},
+			)),
+		))
+	}
+}
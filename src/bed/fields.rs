@@ -1,12 +1,13 @@
 use crate::error;
 
+#[cfg(feature = "fs")]
 use std::path::Path;
 use std::str::FromStr;
 
 use serde::{Serialize, Deserialize};
 
-use crate::bed::detect_format;
-use crate::bed::BedKind;
+#[cfg(feature = "fs")]
+use crate::bed::{detect_format, BedKind};
 
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -33,6 +34,7 @@ impl ScoreField
 		self as usize
 	}
 
+	#[cfg(feature = "fs")]
 	pub async fn best_from_file(file: &Path) -> Self
 	{
 		let bed_format = detect_format(file).await;
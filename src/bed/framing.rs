@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+
+// the empty block bgzip writes last, from the SAM specification
+pub(crate) const BGZF_EOF: [u8; 28] = [
+	0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+	0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+const HEADER_LEN: usize = 18;
+const TRAILER_LEN: usize = 8;
+
+// the CRC32 and ISIZE a block ends with, checked against its decompressed bytes
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockTrailer
+{
+	pub(crate) offset: u64,
+	pub(crate) crc32: u32,
+	pub(crate) input_size: u32,
+}
+
+impl BlockTrailer
+{
+	pub(crate) fn matches(&self, decompressed: &[u8]) -> bool
+	{
+		decompressed.len() as u64 == self.input_size as u64
+			&& crc32fast::hash(decompressed) == self.crc32
+	}
+}
+
+// Follows BGZF block boundaries in the compressed bytes as they are read, by the BSIZE of
+// each block header, so the end of the source can be told apart from the end of a block.
+#[derive(Debug, Default)]
+pub(crate) struct BgzfFraming
+{
+	offset: u64,
+	block_start: u64,
+	header: [u8; HEADER_LEN],
+	header_len: usize,
+	// where the current block ends, once its header has been read
+	block_end: Option<u64>,
+	// the last block seen in full was the EOF marker
+	last_was_eof: bool,
+	// the source returned no more bytes
+	at_end: bool,
+	// a header without the BC subfield, the blocks cannot be followed
+	lost: bool,
+	// where a block too short to hold its own header and trailer starts
+	corrupt: Option<u64>,
+	// the last bytes of the current block, its trailer once the block is complete
+	tail: [u8; TRAILER_LEN],
+	// trailers of blocks read but not yet checked, only kept when checksums are verified
+	trailers: Option<VecDeque<BlockTrailer>>,
+}
+
+impl BgzfFraming
+{
+	// seeks land on block boundaries, as every offset handed to one comes from an index or
+	// the start of the file
+	pub(crate) fn reset(&mut self, position: u64)
+	{
+		let trailers = self.trailers.as_ref().map(|_| VecDeque::new());
+
+		*self = Self {
+			offset: position,
+			block_start: position,
+			trailers,
+			..Self::default()
+		};
+	}
+
+	pub(crate) fn keep_trailers(&mut self)
+	{
+		self.trailers.get_or_insert_with(VecDeque::new);
+	}
+
+	// the trailer of the oldest block not yet checked, blocks are handed out in read order
+	pub(crate) fn next_trailer(&mut self) -> Option<BlockTrailer>
+	{
+		self.trailers.as_mut()?.pop_front()
+	}
+
+	pub(crate) fn observe(&mut self, mut bytes: &[u8])
+	{
+		if bytes.is_empty()
+		{
+			self.at_end = true;
+			return;
+		}
+
+		while !bytes.is_empty() && !self.lost
+		{
+			let taken = match self.block_end
+			{
+				None =>
+				{
+					let taken = bytes.len().min(HEADER_LEN - self.header_len);
+					self.header[self.header_len..self.header_len + taken]
+						.copy_from_slice(&bytes[..taken]);
+					self.header_len += taken;
+
+					if self.header_len == HEADER_LEN
+					{
+						self.read_header();
+					}
+
+					taken
+				}
+				Some(block_end) =>
+				{
+					let taken = bytes.len().min((block_end - self.offset) as usize);
+					self.push_tail(&bytes[..taken]);
+
+					if self.offset + taken as u64 == block_end
+					{
+						self.last_was_eof = self.header == BGZF_EOF[..HEADER_LEN]
+							&& block_end - self.block_start == BGZF_EOF.len() as u64;
+
+						if let Some(trailers) = &mut self.trailers
+						{
+							let [c0, c1, c2, c3, s0, s1, s2, s3] = self.tail;
+							trailers.push_back(BlockTrailer {
+								offset: self.block_start,
+								crc32: u32::from_le_bytes([c0, c1, c2, c3]),
+								input_size: u32::from_le_bytes([s0, s1, s2, s3]),
+							});
+						}
+
+						self.block_start = block_end;
+						self.block_end = None;
+						self.header_len = 0;
+					}
+
+					taken
+				}
+			};
+
+			self.offset += taken as u64;
+			bytes = &bytes[taken..];
+		}
+	}
+
+	fn push_tail(&mut self, bytes: &[u8])
+	{
+		if bytes.len() >= TRAILER_LEN
+		{
+			self.tail.copy_from_slice(&bytes[bytes.len() - TRAILER_LEN..]);
+			return;
+		}
+
+		self.tail.rotate_left(bytes.len());
+		self.tail[TRAILER_LEN - bytes.len()..].copy_from_slice(bytes);
+	}
+
+	fn read_header(&mut self)
+	{
+		let header = &self.header;
+		let bgzf = header[..4] == [0x1f, 0x8b, 0x08, 0x04]
+			&& header[10..12] == [0x06, 0x00]
+			&& header[12..16] == [0x42, 0x43, 0x02, 0x00];
+
+		if !bgzf
+		{
+			self.lost = true;
+			return;
+		}
+
+		let block_size = u16::from_le_bytes([header[16], header[17]]) as u64 + 1;
+
+		// the block would end before the bytes already read, nothing after it can be followed
+		if block_size < (HEADER_LEN + TRAILER_LEN) as u64
+		{
+			self.lost = true;
+			self.corrupt = Some(self.block_start);
+			return;
+		}
+
+		self.block_end = Some(self.block_start + block_size);
+	}
+
+	// the offset of a block whose BSIZE cannot be right
+	pub(crate) fn corrupt(&self) -> Option<u64>
+	{
+		self.corrupt
+	}
+
+	// the source ended inside a block
+	pub(crate) fn truncated(&self) -> bool
+	{
+		self.at_end && !self.lost && (self.header_len > 0 || self.block_end.is_some())
+	}
+
+	// Some once the source has ended, true when it ended on a whole EOF marker block
+	pub(crate) fn complete(&self) -> Option<bool>
+	{
+		if !self.at_end || self.lost
+		{
+			return None;
+		}
+
+		Some(!self.truncated() && self.last_was_eof)
+	}
+
+	pub(crate) fn offset(&self) -> u64
+	{
+		self.offset
+	}
+}
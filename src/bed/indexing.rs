@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "indexing")]
+use std::hash::{DefaultHasher, Hash, Hasher};
+#[cfg(feature = "indexing")]
+use std::path::{Path, PathBuf};
+
+use crate::bed::bgzfwriter::BgzfWriter;
+use crate::bed::track::is_header_line;
+use crate::bed::tokenizer::next_line;
+use crate::error;
+use crate::region::Position;
+
+// the tabix preset for BED: generic format with 0-based starts, columns 1 to 3
+const TBX_UCSC: i32 = 0x10000;
+const METADATA_BIN: u32 = 37450;
+
+// Sorts, compresses and indexes the plain BED at path into dir, as a .bed.gz named after
+// it and a key of its full path, size and modification time, with a .tbi beside it. A
+// copy with the same key is reused, so an unchanged source is only indexed once. Returns
+// the path of the compressed copy.
+#[cfg(feature = "indexing")]
+pub(crate) async fn index_plain_bed(path: &Path, dir: &Path) -> error::Result<PathBuf>
+{
+	let metadata = tokio::fs::metadata(path).await?;
+
+	let mut hasher = DefaultHasher::new();
+	tokio::fs::canonicalize(path).await?.hash(&mut hasher);
+	metadata.len().hash(&mut hasher);
+	metadata.modified()?.hash(&mut hasher);
+
+	let stem = path.file_name().map_or("data".into(), |name| name.to_string_lossy());
+	let data = dir.join(format!("{stem}.{:016x}.bed.gz", hasher.finish()));
+	let index = PathBuf::from(format!("{}.tbi", data.display()));
+
+	if tokio::fs::try_exists(&index).await.unwrap_or(false)
+	{
+		return Ok(data);
+	}
+
+	let text = tokio::fs::read(path).await?;
+	let (compressed, tabix) = compress_and_index(&path.display().to_string(), &text)?;
+
+	tokio::fs::create_dir_all(dir).await?;
+	write_indexed(&data, compressed, &index, tabix).await?;
+
+	Ok(data)
+}
+
+// Sorts, compresses and indexes the plain BED at path as path.gz with path.gz.tbi beside
+// it, as bgzip and tabix would. Returns the path of the compressed copy.
+#[cfg(feature = "indexing")]
+pub async fn index_bed<P>(path: P) -> error::Result<PathBuf>
+where
+	P: AsRef<Path>,
+{
+	let path = path.as_ref();
+	let text = tokio::fs::read(path).await?;
+
+	// compressed input is left to bgzip and tabix, only plain text is sorted here
+	if text.starts_with(&[0x1f, 0x8b])
+	{
+		return Err(error::Error::BedMismatch("plain text".into()));
+	}
+
+	let (compressed, tabix) = compress_and_index(&path.display().to_string(), &text)?;
+
+	let data = PathBuf::from(format!("{}.gz", path.display()));
+	let index = PathBuf::from(format!("{}.tbi", data.display()));
+
+	write_indexed(&data, compressed, &index, tabix).await?;
+
+	Ok(data)
+}
+
+// written under temporary names and renamed, so a half written copy is never read
+#[cfg(feature = "indexing")]
+async fn write_indexed(
+	data: &Path,
+	compressed: Vec<u8>,
+	index: &Path,
+	tabix: Vec<u8>,
+) -> error::Result<()>
+{
+	let data_part = PathBuf::from(format!("{}.part", data.display()));
+	let index_part = PathBuf::from(format!("{}.part", index.display()));
+
+	tokio::fs::write(&data_part, compressed).await?;
+	tokio::fs::write(&index_part, tabix).await?;
+	tokio::fs::rename(&data_part, data).await?;
+	tokio::fs::rename(&index_part, index).await?;
+
+	Ok(())
+}
+
+// the sorted BGZF data and its tabix index. Sorting is in memory, as for a file that was
+// small enough to leave as plain text.
+pub(crate) fn compress_and_index(name: &str, text: &[u8]) -> error::Result<(Vec<u8>, Vec<u8>)>
+{
+	let mut headers = Vec::new();
+	let mut records = Vec::new();
+	// contigs stay in the order they first appear
+	let mut contigs: HashMap<&[u8], usize> = HashMap::new();
+
+	let mut rest = text;
+
+	while !rest.is_empty()
+	{
+		let (line, after_line) = next_line(rest);
+		rest = after_line;
+
+		let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+		if line.trim_ascii().is_empty()
+		{
+			continue;
+		}
+
+		if is_header_line(line) || line.starts_with(b"track")
+		{
+			// sorting would take a later track's header away from its records
+			if !records.is_empty() && !line.starts_with(b"#")
+			{
+				let line = String::from_utf8_lossy(line);
+				return Err(error::Error::Parse(format!("{name}: {line} follows records")));
+			}
+
+			headers.push(line);
+			continue;
+		}
+
+		let mut fields = line
+			.split(|byte| *byte == b'\t' || *byte == b' ')
+			.filter(|field| !field.is_empty());
+
+		let (Some(tid), Some(start), Some(end)) = (fields.next(), fields.next(), fields.next())
+		else
+		{
+			return Err(error::Error::BedFormat(name.to_string()));
+		};
+
+		let start = lexical_core::parse::<u64>(start)?;
+		let end = lexical_core::parse::<u64>(end)?;
+
+		let next_contig = contigs.len();
+		let contig = *contigs.entry(tid).or_insert(next_contig);
+
+		records.push((contig, start, end, line));
+	}
+
+	records.sort_by_key(|(contig, start, end, _)| (*contig, *start, *end));
+
+	let mut names = vec![&[][..]; contigs.len()];
+	for (tid, contig) in &contigs
+	{
+		names[*contig] = tid;
+	}
+
+	let mut writer = BgzfWriter::new(Vec::new());
+	// track and browser lines are skipped by count, # lines by the meta character
+	let skip = headers.iter().filter(|line| !line.starts_with(b"#")).count();
+
+	for line in headers
+	{
+		writer.write_all(line)?;
+		writer.write_all(b"\n")?;
+	}
+
+	let mut references: Vec<ReferenceBuilder> =
+		std::iter::repeat_with(ReferenceBuilder::default).take(names.len()).collect();
+
+	for (contig, start, end, line) in records
+	{
+		let record_start = writer.virtual_offset();
+		writer.write_all(line)?;
+		writer.write_all(b"\n")?;
+
+		references[contig].add(start, end, record_start, writer.virtual_offset())?;
+	}
+
+	let compressed = writer.finish()?;
+
+	let mut index = Vec::new();
+	index.extend_from_slice(b"TBI\x01");
+
+	let l_nm: usize = names.iter().map(|name| name.len() + 1).sum();
+
+	for value in [names.len() as i32, TBX_UCSC, 1, 2, 3, b'#' as i32, skip as i32, l_nm as i32]
+	{
+		index.extend_from_slice(&value.to_le_bytes());
+	}
+
+	for name in &names
+	{
+		index.extend_from_slice(name);
+		index.push(0);
+	}
+
+	for reference in references
+	{
+		reference.write(&mut index);
+	}
+
+	let mut tabix = BgzfWriter::new(Vec::new());
+	tabix.write_all(&index)?;
+
+	Ok((compressed, tabix.finish()?))
+}
+
+// one reference's bins, linear index and metadata as records are added in order
+#[derive(Default)]
+struct ReferenceBuilder
+{
+	bins: BTreeMap<u32, Vec<(u64, u64)>>,
+	linear: Vec<u64>,
+	offsets: Option<(u64, u64)>,
+	records: u64,
+}
+
+impl ReferenceBuilder
+{
+	fn add(&mut self, start: u64, end: u64, record_start: u64, record_end: u64) -> error::Result<()>
+	{
+		// zero length records still occupy their position
+		let end = end.max(start.saturating_add(1));
+
+		// past it the bins and linear index have nothing to hold the record
+		if end > Position::TABIX_MAX.get()
+		{
+			return Err(error::Error::PositionOutOfRange(end));
+		}
+
+		let chunks = self.bins.entry(reg2bin(start, end)).or_default();
+		match chunks.last_mut()
+		{
+			Some(last) if last.1 == record_start => last.1 = record_end,
+			_ => chunks.push((record_start, record_end)),
+		}
+
+		let last_window = ((end - 1) >> 14) as usize;
+		if self.linear.len() <= last_window
+		{
+			self.linear.resize(last_window + 1, u64::MAX);
+		}
+
+		for window in &mut self.linear[(start >> 14) as usize..=last_window]
+		{
+			*window = (*window).min(record_start);
+		}
+
+		let offsets = self.offsets.get_or_insert((record_start, record_end));
+		offsets.1 = record_end;
+		self.records += 1;
+
+		Ok(())
+	}
+
+	fn write(mut self, index: &mut Vec<u8>)
+	{
+		if let Some((first, last)) = self.offsets
+		{
+			self.bins.insert(METADATA_BIN, vec![(first, last), (self.records, 0)]);
+		}
+
+		index.extend_from_slice(&(self.bins.len() as i32).to_le_bytes());
+
+		for (bin, chunks) in &self.bins
+		{
+			index.extend_from_slice(&bin.to_le_bytes());
+			index.extend_from_slice(&(chunks.len() as i32).to_le_bytes());
+
+			for (start, end) in chunks
+			{
+				index.extend_from_slice(&start.to_le_bytes());
+				index.extend_from_slice(&end.to_le_bytes());
+			}
+		}
+
+		// windows no record starts in take the offset of the window before, as htslib does
+		let mut previous = 0;
+		for window in &mut self.linear
+		{
+			if *window == u64::MAX
+			{
+				*window = previous;
+			}
+			previous = *window;
+		}
+
+		index.extend_from_slice(&(self.linear.len() as i32).to_le_bytes());
+
+		for offset in &self.linear
+		{
+			index.extend_from_slice(&offset.to_le_bytes());
+		}
+	}
+}
+
+// the smallest bin holding start..end, from the SAM specification
+fn reg2bin(start: u64, end: u64) -> u32
+{
+	let end = end - 1;
+
+	for (shift, offset) in [(14, 4681), (17, 585), (20, 73), (23, 9), (26, 1)]
+	{
+		if start >> shift == end >> shift
+		{
+			return (offset + (start >> shift)) as u32;
+		}
+	}
+
+	0
+}
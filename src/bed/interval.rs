@@ -0,0 +1,108 @@
+use crate::bed::BedRecord;
+use crate::methyl::Window;
+
+// Coordinate helpers shared by records and windows. Intervals are taken as half-open
+// start..end, which is what the file holds when the reader is opened with
+// with_one_indexed(true). Contigs are compared by tid, so two interned intervals only match
+// when they come from the same resolver.
+pub trait GenomicInterval
+{
+	type Tid: PartialEq;
+
+	fn tid(&self) -> &Self::Tid;
+	fn start(&self) -> u64;
+	fn end(&self) -> u64;
+
+	fn len(&self) -> u64
+	{
+		self.end().saturating_sub(self.start())
+	}
+
+	fn is_empty(&self) -> bool
+	{
+		self.len() == 0
+	}
+
+	fn midpoint(&self) -> u64
+	{
+		self.start() + self.len() / 2
+	}
+
+	fn contains(&self, position: u64) -> bool
+	{
+		self.start() <= position && position < self.end()
+	}
+
+	fn overlaps<O>(&self, other: &O) -> bool
+	where
+		O: GenomicInterval<Tid = Self::Tid>,
+	{
+		self.tid() == other.tid() && self.start() < other.end() && other.start() < self.end()
+	}
+
+	fn intersect<O>(&self, other: &O) -> Option<(u64, u64)>
+	where
+		O: GenomicInterval<Tid = Self::Tid>,
+	{
+		self.overlaps(other)
+			.then(|| (self.start().max(other.start()), self.end().min(other.end())))
+	}
+
+	// bases between the two intervals, 0 when they overlap or touch and None across contigs
+	fn distance_to<O>(&self, other: &O) -> Option<u64>
+	where
+		O: GenomicInterval<Tid = Self::Tid>,
+	{
+		if self.tid() != other.tid()
+		{
+			return None;
+		}
+
+		Some(
+			other
+				.start()
+				.saturating_sub(self.end())
+				.max(self.start().saturating_sub(other.end())),
+		)
+	}
+}
+
+impl<Tid: PartialEq> GenomicInterval for BedRecord<Tid>
+{
+	type Tid = Tid;
+
+	fn tid(&self) -> &Tid
+	{
+		&self.tid
+	}
+
+	fn start(&self) -> u64
+	{
+		self.start
+	}
+
+	fn end(&self) -> u64
+	{
+		self.end
+	}
+}
+
+impl<Tid: PartialEq> GenomicInterval for Window<Tid>
+{
+	type Tid = Tid;
+
+	fn tid(&self) -> &Tid
+	{
+		&self.tid
+	}
+
+	fn start(&self) -> u64
+	{
+		self.start
+	}
+
+	fn end(&self) -> u64
+	{
+		self.end
+	}
+}
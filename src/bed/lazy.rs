@@ -0,0 +1,162 @@
+use std::sync::OnceLock;
+
+use futures::FutureExt;
+
+use crate::error;
+use crate::bed::{BedKind, BedRecord, BedSinkValue, GenomicInterval, ParseOptions, Strand};
+use crate::bed::parser::{parse_bed6_prefix_sink_simd, parse_kind_sink};
+use crate::bed::projection::leading_fields;
+use crate::bed::utf8::utf8_field;
+
+// A record whose coordinates are parsed up front and whose other columns wait until first
+// asked for, so position filters that reject most lines never pay for names, scores or
+// strands. Starts are shifted the way the reader that produced it shifts them.
+#[derive(Debug)]
+pub struct LazyBedRecord
+{
+	line: Box<[u8]>,
+	tid: String,
+	start: u64,
+	end: u64,
+	kind: BedKind,
+	options: ParseOptions,
+	parsed: OnceLock<(Strand, BedSinkValue)>,
+}
+
+impl LazyBedRecord
+{
+	// None for blank lines, the entire line is kept for the later parse
+	pub(crate) fn parse(
+		line: &[u8],
+		kind: BedKind,
+		options: ParseOptions,
+		one_indexed: bool,
+	) -> error::Result<Option<Self>>
+	{
+		if line.trim_ascii().is_empty()
+		{
+			return Ok(None);
+		}
+
+		let Some([tid, start, end]) = leading_fields::<3>(line, options.tokenizer_mode)
+		else
+		{
+			return Err(error::Error::BedMismatch(kind.to_string()));
+		};
+
+		let tid = utf8_field(tid, "chrom", options.utf8_policy)?.into_owned();
+		let start = lexical_core::parse::<u64>(start)?;
+		let end = lexical_core::parse::<u64>(end)?;
+
+		Ok(Some(Self {
+			line: line.into(),
+			tid,
+			start: if one_indexed { start } else { start.saturating_add(1) },
+			end,
+			kind,
+			options,
+			parsed: OnceLock::new(),
+		}))
+	}
+
+	pub fn tid(&self) -> &str
+	{
+		&self.tid
+	}
+
+	pub fn start(&self) -> u64
+	{
+		self.start
+	}
+
+	pub fn end(&self) -> u64
+	{
+		self.end
+	}
+
+	pub fn kind(&self) -> BedKind
+	{
+		self.kind
+	}
+
+	pub fn raw_line(&self) -> &[u8]
+	{
+		&self.line
+	}
+
+	pub fn is_parsed(&self) -> bool
+	{
+		self.parsed.get().is_some()
+	}
+
+	pub fn strand(&self) -> error::Result<Strand>
+	{
+		Ok(self.parsed()?.0)
+	}
+
+	// the columns after end, parsed on the first call. A parse error is returned on every
+	// call rather than cached.
+	pub fn value(&self) -> error::Result<&BedSinkValue>
+	{
+		Ok(&self.parsed()?.1)
+	}
+
+	pub fn into_record(self) -> error::Result<BedRecord<String>>
+	{
+		self.parsed()?;
+
+		let (strand, value) = self.parsed.into_inner().expect("parsed above");
+
+		Ok(BedRecord {
+			tid: self.tid,
+			strand,
+			start: self.start,
+			end: self.end,
+			value,
+		})
+	}
+
+	fn parsed(&self) -> error::Result<&(Strand, BedSinkValue)>
+	{
+		if let Some(parsed) = self.parsed.get()
+		{
+			return Ok(parsed);
+		}
+
+		// BED6+N types read by their BED6 prefix, the extra columns stay in the raw line
+		let parse = async {
+			match self.kind
+			{
+				BedKind::Bed6 => parse_bed6_prefix_sink_simd(&self.line, None, self.options).await,
+				kind => parse_kind_sink(kind, &self.line, None, self.options).await,
+			}
+		};
+
+		// with no read filter the parsers never wait
+		let (_, parsed) = parse.now_or_never().expect("unfiltered parse is ready")?;
+		let (_, strand, _, _, value) =
+			parsed.ok_or_else(|| error::Error::Parse(String::from_utf8_lossy(&self.line).into()))?;
+
+		Ok(self.parsed.get_or_init(|| (strand, value)))
+	}
+}
+
+impl GenomicInterval for LazyBedRecord
+{
+	type Tid = String;
+
+	fn tid(&self) -> &String
+	{
+		&self.tid
+	}
+
+	fn start(&self) -> u64
+	{
+		self.start
+	}
+
+	fn end(&self) -> u64
+	{
+		self.end
+	}
+}
@@ -1,26 +1,155 @@
 pub mod autooneshotreader;
+pub mod autowriter;
+mod batch;
 mod bed;
+mod bed12;
+mod bedpe;
+#[cfg(feature = "bgzf-write")]
+mod bgzfwriter;
+mod bins;
 mod blocks;
+mod collect;
+mod convert;
+mod describe;
+mod detect;
+mod dynamic;
 mod extra;
 mod fields;
+mod framing;
+#[cfg(any(feature = "indexing", feature = "testing"))]
+mod indexing;
+mod interval;
+mod lazy;
+mod modcode;
+mod name;
 pub mod oneshotreader;
 mod parser;
+mod peekable;
+mod plus;
+mod progress;
+mod projection;
+mod query;
 mod record;
+mod records;
 mod sink;
+mod sorted;
+mod stats;
+mod streaming;
+mod summary;
+mod tokenizer;
+mod track;
+mod typed;
+mod uri;
+mod utf8;
+mod warning;
+mod writer;
 
 pub use parser::*;
 pub use fields::*;
 pub use bed::*;
+pub use bed12::*;
+pub use bedpe::{BedpeWriter, PairedRecord};
+pub use writer::{BedWriter, WriterOptions};
 pub use sink::*;
+pub use sorted::DiscardSink;
+pub use blocks::BgzfBlock;
+pub use streaming::*;
+pub use uri::UriReader;
+pub use tokenizer::TokenizerMode;
+pub use track::TrackType;
+#[cfg(feature = "testing")]
+pub(crate) use track::{attributes as track_attributes, is_header_line};
+pub use collect::*;
+pub use batch::RecordBatch;
+pub use bins::BinnedCounts;
+pub use records::{RecordStreamExt, record_stream};
+pub(crate) use records::parsed_lines;
+#[cfg(feature = "testing")]
+pub(crate) use indexing::compress_and_index;
+#[cfg(feature = "testing")]
+pub(crate) use bgzfwriter::BgzfWriter;
+#[cfg(feature = "indexing")]
+pub use indexing::index_bed;
+pub use peekable::PeekableReader;
+pub use plus::{BedPlusColumns, parse_bed_plus_sink, parse_column};
+#[cfg(feature = "derive")]
+pub use sandman_derive::BedFields;
+pub use convert::Converted;
+pub use dynamic::{ColumnSpec, ColumnType, DynBedExtra, FieldValue};
+pub use typed::*;
+pub use utf8::{ParseOptions, Utf8Policy};
+pub use warning::{Warning, WarningCallback};
+pub use describe::ReaderDescription;
+pub use detect::{Detection, DetectionReport, line_parses_as};
+pub use modcode::*;
+pub use name::{decode_name, encode_name};
+pub use interval::GenomicInterval;
+pub use lazy::LazyBedRecord;
+pub use progress::ProgressCallback;
+pub use projection::Projection;
+pub(crate) use progress::CountingReader;
+pub use stats::ReaderStats;
+pub use summary::{ContigSummary, Histogram, StrandCounts, Summary};
+pub(crate) use stats::{Phase, StatsCounters};
 
-use crate::error;
-
-use tokio::fs::File as TokioFile;
-use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncSeekExt, BufReader as TokioBufReader};
-use std::path::Path;
-use pufferfish::prelude::*;
+#[cfg(feature = "fs")]
+use {
+	crate::error,
+	tokio::fs::File as TokioFile,
+	tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncSeekExt, BufReader as TokioBufReader},
+	std::path::Path,
+	pufferfish::prelude::*,
+};
 
+#[cfg(feature = "fs")]
+#[cfg_attr(
+	feature = "tracing",
+	tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+)]
 pub async fn detect_format<P>(path: P) -> error::Result<BedKind>
+where
+	P: AsRef<Path>,
+{
+	let lines = read_detection_lines(&path).await?;
+
+	let report = BedKind::detect_report(&lines).map_err(|_| {
+		error::Error::BedFormat(
+			path.as_ref()
+				.file_name()
+				.and_then(|s| s.to_str())
+				.unwrap_or("unknown")
+				.to_string(),
+		)
+	})?;
+	report.log_uncertain(&path.as_ref().display().to_string());
+
+	Ok(report.kind)
+}
+
+// like detect_format but leaves an ambiguous result for the caller to resolve
+#[cfg(feature = "fs")]
+pub async fn detect_kind<P>(path: P) -> error::Result<Detection>
+where
+	P: AsRef<Path>,
+{
+	let lines = read_detection_lines(&path).await?;
+
+	BedKind::detect(&lines)
+}
+
+// the line by line diagnostics behind detect_format's answer
+#[cfg(feature = "fs")]
+pub async fn detect_report<P>(path: P) -> error::Result<DetectionReport>
+where
+	P: AsRef<Path>,
+{
+	let lines = read_detection_lines(&path).await?;
+
+	BedKind::detect_report(&lines)
+}
+
+#[cfg(feature = "fs")]
+async fn read_detection_lines<P>(path: P) -> error::Result<Vec<String>>
 where
 	P: AsRef<Path>,
 {
@@ -30,7 +159,7 @@ where
 	let is_bgzf = reader.is_bgz().await;
 	reader.seek(std::io::SeekFrom::Start(0)).await?;
 
-	let lines = if is_bgzf
+	if is_bgzf
 	{
 		// Read first BGZF block
 		let block = reader
@@ -40,25 +169,16 @@ where
 			.ok_or_else(|| error::Error::BedFormat(path.as_ref().display().to_string()))?;
 
 		let mut block_reader = TokioBufReader::new(std::io::Cursor::new(&block));
-		read_lines(&mut block_reader, 10).await?
+		read_lines(&mut block_reader, 10).await
 	}
 	else
 	{
 		// Plain text
-		read_lines(&mut reader, 10).await?
-	};
-
-	BedKind::try_from(&lines).map_err(|_| {
-		error::Error::BedFormat(
-			path.as_ref()
-				.file_name()
-				.and_then(|s| s.to_str())
-				.unwrap_or("unknown")
-				.to_string(),
-		)
-	})
+		read_lines(&mut reader, 10).await
+	}
 }
 
+#[cfg(feature = "fs")]
 async fn read_lines<B>(reader: &mut B, max_lines: usize) -> error::Result<Vec<String>>
 where
 	B: AsyncBufRead + Unpin,
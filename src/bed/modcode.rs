@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::bed::BedRecord;
+
+// Modification code from the bedMethyl name column, following the SAM MM tag spec. modkit
+// writes either a single letter code or a ChEBI id, optionally followed by ",motif,offset".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ModCode
+{
+	Code(u8),
+	ChEBI(u32),
+}
+
+impl ModCode
+{
+	pub fn parse(name: &str) -> Option<Self>
+	{
+		let code = name.split(',').next()?.trim();
+
+		match code.as_bytes()
+		{
+			[c] if c.is_ascii_alphabetic() => Some(Self::Code(*c)),
+			_ => code.parse::<u32>().ok().map(Self::ChEBI),
+		}
+	}
+
+	// the unmodified base the code applies to, upper case codes are "any modification of"
+	pub fn canonical_base(&self) -> Option<u8>
+	{
+		match self
+		{
+			Self::Code(b'm' | b'h' | b'f' | b'c' | b'C') => Some(b'C'),
+			Self::Code(b'g' | b'e' | b'b' | b'T') => Some(b'T'),
+			Self::Code(b'a' | b'A') => Some(b'A'),
+			Self::Code(b'o' | b'G') => Some(b'G'),
+			Self::Code(b'n' | b'N') => Some(b'N'),
+			Self::Code(b'U') => Some(b'U'),
+			// N4-methylcytosine
+			Self::ChEBI(21839) => Some(b'C'),
+			// inosine
+			Self::ChEBI(17596) => Some(b'A'),
+			// pseudouridine
+			Self::ChEBI(17802) => Some(b'T'),
+			_ => None,
+		}
+	}
+
+	pub fn description(&self) -> Option<&'static str>
+	{
+		match self
+		{
+			Self::Code(b'm') => Some("5-methylcytosine"),
+			Self::Code(b'h') => Some("5-hydroxymethylcytosine"),
+			Self::Code(b'f') => Some("5-formylcytosine"),
+			Self::Code(b'c') => Some("5-carboxylcytosine"),
+			Self::Code(b'g') => Some("5-hydroxymethyluracil"),
+			Self::Code(b'e') => Some("5-formyluracil"),
+			Self::Code(b'b') => Some("5-carboxyluracil"),
+			Self::Code(b'a') => Some("6-methyladenine"),
+			Self::Code(b'o') => Some("8-oxoguanine"),
+			Self::Code(b'n') => Some("xanthosine"),
+			Self::Code(b'C' | b'T' | b'A' | b'G' | b'U' | b'N') => Some("any modification"),
+			Self::ChEBI(21839) => Some("N4-methylcytosine"),
+			Self::ChEBI(17596) => Some("inosine"),
+			Self::ChEBI(17802) => Some("pseudouridine"),
+			_ => None,
+		}
+	}
+}
+
+impl From<char> for ModCode
+{
+	fn from(code: char) -> Self
+	{
+		Self::Code(code as u8)
+	}
+}
+
+impl fmt::Display for ModCode
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Self::Code(c) => write!(f, "{}", *c as char),
+			Self::ChEBI(id) => write!(f, "{}", id),
+		}
+	}
+}
+
+impl<Tid> BedRecord<Tid>
+{
+	pub fn mod_code(&self) -> Option<ModCode>
+	{
+		self.value.mod_code()
+	}
+}
+
+pub fn filter_mod_code<'a, Tid, I>(
+	records: I,
+	code: impl Into<ModCode>,
+) -> impl Iterator<Item = &'a BedRecord<Tid>>
+where
+	Tid: 'a,
+	I: IntoIterator<Item = &'a BedRecord<Tid>>,
+{
+	let code = code.into();
+	records
+		.into_iter()
+		.filter(move |record| record.mod_code() == Some(code))
+}
@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+// UCSC allows percent-encoded bytes in the name column, %XX pairs are decoded and anything
+// else (including a stray '%') is kept as written
+pub fn decode_name(name: &[u8]) -> Cow<'_, str>
+{
+	if memchr::memchr(b'%', name).is_none()
+	{
+		return String::from_utf8_lossy(name);
+	}
+
+	let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+
+	let mut decoded = Vec::with_capacity(name.len());
+	let mut i = 0;
+
+	while i < name.len()
+	{
+		if name[i] == b'%' && i + 2 < name.len()
+		{
+			if let (Some(hi), Some(lo)) = (hex(name[i + 1]), hex(name[i + 2]))
+			{
+				decoded.push((hi << 4) | lo);
+				i += 3;
+				continue;
+			}
+		}
+
+		decoded.push(name[i]);
+		i += 1;
+	}
+
+	Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+// the inverse for writers, whitespace, quotes and '%' would otherwise split or change the
+// column when read back
+pub fn encode_name(name: &str) -> Cow<'_, str>
+{
+	let needs_encoding =
+		|c: char| c.is_ascii_whitespace() || c.is_ascii_control() || c == '%' || c == '"';
+
+	if !name.chars().any(needs_encoding)
+	{
+		return Cow::Borrowed(name);
+	}
+
+	let mut encoded = String::with_capacity(name.len() + 8);
+
+	for c in name.chars()
+	{
+		if needs_encoding(c)
+		{
+			let _ = write!(encoded, "%{:02X}", c as u32);
+		}
+		else
+		{
+			encoded.push(c);
+		}
+	}
+
+	Cow::Owned(encoded)
+}
@@ -1,46 +1,98 @@
 use tokio::io::{AsyncRead, AsyncSeek};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
+#[cfg(feature = "fs")]
 use tokio::fs::File;
+#[cfg(feature = "indexing")]
+use tokio::io::BufReader as TokioBufReader;
 
 use rayon::yield_now;
 
 use std::marker::PhantomData;
 use std::sync::Arc;
 use std::path::Path;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use memchr::memchr;
 
 use crate::error;
-use crate::store::TidResolver;
-use crate::bed::blocks::BgzfBlock;
+use crate::error::{Context, ResultExt};
+use crate::store::{TidLookup, TidResolver};
+use crate::bed::blocks::{BgzfBlock, BufferPool};
+use crate::bed::framing::{BgzfFraming, BlockTrailer};
+#[cfg(feature = "indexing")]
+use crate::bed::indexing::index_plain_bed;
 use crate::bed::{BedSink, BedFieldsSink};
+use crate::bed::{BedRecord, BinnedCounts, CollectSink};
+use crate::bed::records::record_stream;
+use crate::bed::query::{chunk_blocks, group_chunks, open_source, read_block_lines, read_chunk};
+use crate::bed::PeekableReader;
 use crate::bed::{ReaderId, SourceId};
 use crate::bed::NEXT_READER_ID;
 use crate::bed::Strand;
+use crate::bed::{BedKind, ColumnSpec, ParseOptions, TokenizerMode, TrackType, Utf8Policy};
+use crate::bed::{LazyBedRecord, Projection};
+use crate::bed::projection::{leading_fields, parse_projected_sink};
+use crate::bed::tokenizer::next_line;
+use crate::bed::track::{LineParser, is_header_line, type_name, unknown_attributes};
+use crate::bed::{Warning, WarningCallback};
+use crate::bed::warning::{PreviousRecord, check_record};
+use crate::bed::sorted::SortState;
+use crate::bed::DiscardSink;
+use crate::bed::parser::{parse_bed6_prefix_sink_simd, parse_kind_sink};
+use crate::bed::{Phase, ReaderStats, StatsCounters, Summary};
+use crate::bed::stats::Instant;
+use crate::bed::ReaderDescription;
+use crate::bed::{CountingReader, ProgressCallback};
 
 use crate::filtering::ReadFilterContext;
+use crate::retry::RetryPolicy;
+use crate::tabix;
+use crate::region::{CoordinateSystem, Region};
 
 use rayon::prelude::*;
 use rayon::{ThreadPoolBuilder, ThreadPool};
 
 use futures::stream::Buffered;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 
-#[cfg(feature = "interning")]
-use crate::store::TidStore;
+use crate::store::DefaultResolver;
 
 use pufferfish::prelude::*;
 
 const DEFAULT_BUFFER_SIZE: usize = 200;
 
+// enough for a batch in flight on each side of a bounded channel
+const DEFAULT_BUFFER_POOL_SIZE: usize = 4;
+
+// upper bound of a decompressed BGZF block
+const MAX_BLOCK_SIZE: usize = 64 * 1024;
+
+// opens another source over the same data, from its start
+pub type Reopen<R> = Arc<dyn Fn() -> error::Result<R> + Send + Sync>;
+
+type StreamItem<R> = <Buffered<BgzfBlockStream<CountingReader<R>>> as futures::Stream>::Item;
+
 pub struct ReaderOptions<Interner>
 {
 	pub buffer_size: Option<usize>,
-	pub interner: Option<Arc<Mutex<Interner>>>,
+	pub interner: Option<Arc<RwLock<Interner>>>,
 	pub read_filter: Option<Arc<Mutex<ReadFilterContext>>>,
 	pub one_indexed: Option<bool>,
 	pub n_threads: Option<usize>,
+	pub progress: Option<ProgressCallback>,
+	pub total_bytes: Option<u64>,
+	pub retry_policy: RetryPolicy,
+	pub max_result_bytes: Option<usize>,
+	pub max_records: Option<u64>,
+	pub tokenizer_mode: TokenizerMode,
+	pub utf8_policy: Utf8Policy,
+	pub warnings: Option<WarningCallback>,
+	pub require_sorted: bool,
+	pub column_spec: Option<Arc<ColumnSpec>>,
+	pub projection: Projection,
+	pub buffer_pool_size: usize,
+	pub verify_checksums: bool,
+	pub index: Option<Arc<tabix::Index>>,
 }
 
 impl<Interner> Default for ReaderOptions<Interner>
@@ -53,6 +105,48 @@ impl<Interner> Default for ReaderOptions<Interner>
 			read_filter: None,
 			one_indexed: None,
 			n_threads: None,
+			progress: None,
+			total_bytes: None,
+			retry_policy: RetryPolicy::default(),
+			max_result_bytes: None,
+			max_records: None,
+			tokenizer_mode: TokenizerMode::default(),
+			utf8_policy: Utf8Policy::default(),
+			warnings: None,
+			require_sorted: false,
+			column_spec: None,
+			projection: Projection::All,
+			buffer_pool_size: DEFAULT_BUFFER_POOL_SIZE,
+			verify_checksums: false,
+			index: None,
+		}
+	}
+}
+
+impl<Interner> Clone for ReaderOptions<Interner>
+{
+	fn clone(&self) -> Self
+	{
+		Self {
+			buffer_size: self.buffer_size,
+			interner: self.interner.clone(),
+			read_filter: self.read_filter.clone(),
+			one_indexed: self.one_indexed,
+			n_threads: self.n_threads,
+			progress: self.progress.clone(),
+			total_bytes: self.total_bytes,
+			retry_policy: self.retry_policy,
+			max_result_bytes: self.max_result_bytes,
+			max_records: self.max_records,
+			tokenizer_mode: self.tokenizer_mode,
+			utf8_policy: self.utf8_policy,
+			warnings: self.warnings.clone(),
+			require_sorted: self.require_sorted,
+			column_spec: self.column_spec.clone(),
+			projection: self.projection,
+			buffer_pool_size: self.buffer_pool_size,
+			verify_checksums: self.verify_checksums,
+			index: self.index.clone(),
 		}
 	}
 }
@@ -65,7 +159,7 @@ impl<Interner> ReaderOptions<Interner>
 		self
 	}
 
-	pub fn with_interner(mut self, interner: Arc<Mutex<Interner>>) -> Self
+	pub fn with_interner(mut self, interner: Arc<RwLock<Interner>>) -> Self
 	{
 		self.interner = Some(interner);
 		self
@@ -88,6 +182,119 @@ impl<Interner> ReaderOptions<Interner>
 		self.one_indexed = Some(one_based);
 		self
 	}
+
+	// coordinates handed to sinks, the same switch as with_one_indexed spelled out
+	pub fn with_coordinate_system(self, system: CoordinateSystem) -> Self
+	{
+		self.with_one_indexed(system == CoordinateSystem::ZeroBasedHalfOpen)
+	}
+
+	// called after each batch of blocks with the compressed offset reached
+	pub fn with_progress<P>(mut self, progress: P) -> Self
+	where
+		P: Fn(u64, Option<u64>) + Send + Sync + 'static,
+	{
+		self.progress = Some(Arc::new(progress));
+		self
+	}
+
+	// bounds each pull from the block stream, retrying a stalled read before giving up
+	pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self
+	{
+		self.retry_policy = retry_policy;
+		self
+	}
+
+	// caps how much a single next_bgzf_blocks call decompresses, larger requests are served
+	// over several calls. A region query fetching more than this fails with
+	// Error::ResultTooLarge.
+	pub fn with_max_result_bytes(mut self, max_result_bytes: usize) -> Self
+	{
+		self.max_result_bytes = Some(max_result_bytes);
+		self
+	}
+
+	// records handed to sinks before the scan fails with Error::QueryTooLarge, counted
+	// until the reader is reset, or per call for region queries and samples
+	pub fn with_max_records(mut self, max_records: u64) -> Self
+	{
+		self.max_records = Some(max_records);
+		self
+	}
+
+	// StrictTabs turns whitespace the tolerant default would skip over into parse errors
+	pub fn with_tokenizer_mode(mut self, tokenizer_mode: TokenizerMode) -> Self
+	{
+		self.tokenizer_mode = tokenizer_mode;
+		self
+	}
+
+	// what to do with name or chrom columns that are not valid UTF-8
+	pub fn with_utf8_policy(mut self, utf8_policy: Utf8Policy) -> Self
+	{
+		self.utf8_policy = utf8_policy;
+		self
+	}
+
+	// fail with Error::Unsorted at the first record out of contig/start order
+	pub fn with_require_sorted(mut self, require_sorted: bool) -> Self
+	{
+		self.require_sorted = require_sorted;
+		self
+	}
+
+	// decompressed batch buffers kept for reuse once their blocks are dropped, 0 allocates
+	// a fresh buffer for every batch
+	pub fn with_buffer_pool_size(mut self, buffer_pool_size: usize) -> Self
+	{
+		self.buffer_pool_size = buffer_pool_size;
+		self
+	}
+
+	// check each block's CRC32 and ISIZE against what it decompressed to, failing with
+	// Error::CorruptBlock rather than skipping a bad block
+	pub fn with_verify_checksums(mut self, verify_checksums: bool) -> Self
+	{
+		self.verify_checksums = verify_checksums;
+		self
+	}
+
+	// an index parsed once and shared by every reader of the same file, see index()
+	pub fn with_index(mut self, index: Arc<tabix::Index>) -> Self
+	{
+		self.index = Some(index);
+		self
+	}
+
+	// skip parsing the columns a scan does not need, Projection::Coordinates for intervals
+	pub fn with_projection(mut self, projection: Projection) -> Self
+	{
+		self.projection = projection;
+		self
+	}
+
+	// types the trailing columns of a DynBedExtra reader, other readers ignore it
+	pub fn with_column_spec(mut self, spec: ColumnSpec) -> Self
+	{
+		self.column_spec = Some(Arc::new(spec));
+		self
+	}
+
+	// called for non-fatal anomalies such as unsorted or duplicate records, the read carries on
+	pub fn with_warnings<W>(mut self, warnings: W) -> Self
+	where
+		W: Fn(Warning) + Send + Sync + 'static,
+	{
+		self.warnings = Some(Arc::new(warnings));
+		self
+	}
+
+	// files fill this in from their metadata
+	pub fn with_total_bytes(mut self, total_bytes: u64) -> Self
+	{
+		self.total_bytes = Some(total_bytes);
+		self
+	}
 }
 
 pub struct OneShotBlockReader<R, T, F>
@@ -97,243 +304,266 @@ where
 	F: BedFieldsSink<T::Tid> + std::fmt::Debug,
 {
 	pub(crate) name: String,
-	pub(crate) stream: Buffered<BgzfBlockStream<R>>,
-	pub(crate) resolver: Arc<Mutex<T>>,
+	pub(crate) stream: Buffered<BgzfBlockStream<CountingReader<R>>>,
+	pub(crate) resolver: Arc<RwLock<T>>,
 	pub(crate) filter_ctx: Option<Arc<Mutex<ReadFilterContext>>>,
 	pub(crate) reader_id: ReaderId,
 	pub(crate) one_indexed: bool,
 	pub(crate) source_id: Option<SourceId>,
 	pub(crate) pending_tail: Option<Vec<u8>>,
-	pub(crate) thread_pool: ThreadPool,
+	// blocks pulled from the stream but not yet handed out, kept here so a dropped
+	// next_bgzf_blocks future does not lose them
+	pub(crate) pending_blocks: Vec<StreamItem<R>>,
+	pub(crate) thread_pool: Option<ThreadPool>,
+	pub(crate) stats: StatsCounters,
+	pub(crate) bytes_read: Arc<AtomicU64>,
+	pub(crate) framing: Arc<std::sync::Mutex<BgzfFraming>>,
+	pub(crate) total_bytes: Option<u64>,
+	pub(crate) progress: Option<ProgressCallback>,
+	pub(crate) retry_policy: RetryPolicy,
+	pub(crate) max_result_bytes: Option<usize>,
+	pub(crate) max_records: Option<u64>,
+	pub(crate) parse_options: ParseOptions,
+	// the type of the last track line seen
+	pub(crate) track: std::sync::Mutex<Option<TrackType>>,
+	pub(crate) warnings: Option<WarningCallback>,
+	// the last record, for the sortedness and duplicate warnings
+	pub(crate) last_record: std::sync::Mutex<Option<PreviousRecord>>,
+	pub(crate) require_sorted: bool,
+	pub(crate) column_spec: Option<Arc<ColumnSpec>>,
+	pub(crate) projection: Projection,
+	pub(crate) buffers: Arc<BufferPool>,
+	pub(crate) sort_state: std::sync::Mutex<SortState<T::Tid>>,
+	pub(crate) records_emitted: AtomicU64,
+	// what try_clone needs to open the same data again, with the interner this reader uses
+	pub(crate) pool: Arc<pool::BgzfBlockPool>,
+	pub(crate) options: ReaderOptions<T>,
+	pub(crate) reopen: Option<Reopen<R>>,
 
 	_phantom: PhantomData<(R, F)>,
 }
 
-#[cfg(not(feature = "interning"))]
-impl<F> OneShotBlockReader<File, (), F>
+#[cfg(feature = "fs")]
+impl<F> OneShotBlockReader<File, DefaultResolver, F>
 where
-	F: BedFieldsSink<String> + std::fmt::Debug + 'static,
+	F: BedFieldsSink<<DefaultResolver as TidLookup>::Tid> + std::fmt::Debug,
 {
 	pub async fn from_path<P>(
 		path: P,
-		source_id: impl Into<Option<SourceId>> + 'static,
+		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
 	) -> error::Result<Self>
 	where
 		P: AsRef<Path> + std::marker::Copy,
 	{
-		let name = path
-			.as_ref()
-			.file_name()
-			.and_then(|s| s.to_str())
-			.unwrap_or("unknown")
-			.to_string();
-
-		let gzip_file = Self::open_bed_file(path).await?;
-		Ok(Self::from_reader(name, gzip_file, source_id, pool).await)
+		Self::from_path_with(path, source_id, pool, ReaderOptions::default()).await
 	}
 
 	pub async fn from_path_with_options<P>(
 		path: P,
 		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
-		options: ReaderOptions<()>,
+		options: ReaderOptions<DefaultResolver>,
 	) -> error::Result<Self>
 	where
 		P: AsRef<Path> + std::marker::Copy,
 	{
-		let name = path
-			.as_ref()
-			.file_name()
-			.and_then(|s| s.to_str())
-			.unwrap_or("unknown")
-			.to_string();
-
-		let gzip_file = Self::open_bed_file(path).await?;
-		Ok(Self::from_reader_with_options(name, gzip_file, source_id, pool, options).await)
+		Self::from_path_with(path, source_id, pool, options).await
 	}
 }
 
-#[cfg(feature = "interning")]
-impl<F> OneShotBlockReader<File, TidStore, F>
+impl<R, F> OneShotBlockReader<R, DefaultResolver, F>
 where
-	F: BedFieldsSink<<TidStore as TidResolver>::Tid> + std::fmt::Debug,
+	R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin + 'static,
+	F: BedFieldsSink<<DefaultResolver as TidLookup>::Tid> + std::fmt::Debug,
 {
-	pub async fn from_path<P>(
-		path: P,
-		source_id: impl Into<Option<SourceId>> + 'static,
+	pub async fn from_reader(
+		name: String,
+		reader: R,
+		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
 	) -> error::Result<Self>
-	where
-		P: AsRef<Path> + std::marker::Copy,
 	{
-		let name = path
-			.as_ref()
-			// .file_name()
-			// .and_then(|s| s.to_str())
-			// .unwrap_or("unknown")
-			.to_string_lossy()
-			.into_owned();
-
-		let gzip_file = Self::open_bed_file(path).await?;
-		let reader = Self::from_reader(name, gzip_file, source_id, pool).await?;
-
-		Ok(reader)
+		Self::from_reader_with(name, reader, source_id, pool, ReaderOptions::default()).await
 	}
 
-	pub async fn from_path_with_options<P>(
-		path: P,
-		source_id: impl Into<Option<SourceId>> + 'static,
+	pub async fn from_reader_with_options(
+		name: String,
+		reader: R,
+		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
-		options: ReaderOptions<TidStore>,
+		options: ReaderOptions<DefaultResolver>,
 	) -> error::Result<Self>
-	where
-		P: AsRef<Path> + std::marker::Copy,
 	{
-		let name = path
-			.as_ref()
-			// .file_name()
-			// .and_then(|s| s.to_str())
-			// .unwrap_or("unknown")
-			.to_string_lossy()
-			.into_owned();
-
-		let gzip_file = Self::open_bed_file(path).await?;
-		let reader =
-			Self::from_reader_with_options(name, gzip_file, source_id, pool, options).await?;
-
-		Ok(reader)
+		Self::from_reader_with(name, reader, source_id, pool, options).await
 	}
 }
 
-#[cfg(not(feature = "interning"))]
-impl<R, F> OneShotBlockReader<R, (), F>
+#[cfg(feature = "fs")]
+impl<T, F> OneShotBlockReader<File, T, F>
 where
-	R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin + 'static,
-	F: BedFieldsSink<String> + std::fmt::Debug,
+	T: TidResolver + Default + std::clone::Clone + std::fmt::Debug + Send + Sync + 'static,
+	F: BedFieldsSink<T::Tid> + std::fmt::Debug,
 {
-	pub async fn from_reader(
-		name: String,
-		reader: R,
-		source_id: impl Into<Option<SourceId>>,
-		pool: Arc<pool::BgzfBlockPool>,
-	) -> Self
+	async fn open_bed_file<P>(path: P) -> error::Result<File>
+	where
+		P: AsRef<Path> + Copy,
 	{
-		let reader_id = NEXT_READER_ID.fetch_add(1, Ordering::SeqCst);
-		// let resolver = Arc::new(Mutex::new(TidStore::default()));
-
-		let stream = BgzfBlockStream::new(reader, pool.clone(), Some(is_bgzf_eof))
-			.buffered(DEFAULT_BUFFER_SIZE);
+		let path = path.as_ref();
 
-		let builder = ThreadPoolBuilder::new();
-		let thread_pool = builder.build().expect("Unable to setup thread pool");
+		let gzip_file = File::open(path).await?;
 
-		OneShotBlockReader {
-			name,
-			stream,
-			thread_pool,
-			resolver: Arc::new(Mutex::new(())),
-			filter_ctx: None,
-			reader_id: ReaderId(reader_id),
-			source_id: source_id.into(),
-			pending_tail: None,
-			one_indexed: false,
-			_phantom: PhantomData,
-		}
+		Ok(gzip_file)
 	}
 
-	pub async fn from_reader_with_options(
-		name: String,
-		reader: R,
+	pub async fn from_path_with<P>(
+		path: P,
 		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
-		options: ReaderOptions<()>,
-	) -> Self
+		options: ReaderOptions<T>,
+	) -> error::Result<Self>
+	where
+		P: AsRef<Path> + std::marker::Copy,
 	{
-		let reader_id = NEXT_READER_ID.fetch_add(1, Ordering::SeqCst);
-		// let resolver = Arc::new(Mutex::new(TidStore::default()));
+		// The full path as given, whatever the resolver, so errors and describe() point at
+		// the file and ensure_indexed can open it again. Readers without interning were
+		// named by the file name alone before this constructor.
+		let name = path.as_ref().to_string_lossy().into_owned();
 
-		let stream = BgzfBlockStream::new(reader, pool.clone(), Some(is_bgzf_eof))
-			.buffered(options.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE));
+		let gzip_file = Self::open_bed_file(path).await?;
 
-		let builder = ThreadPoolBuilder::new();
-		let builder = if let Some(n) = options.n_threads
+		let options = match options.total_bytes
 		{
-			builder.num_threads(n)
+			Some(_) => options,
+			None =>
+			{
+				let total_bytes = gzip_file.metadata().await?.len();
+				options.with_total_bytes(total_bytes)
+			}
+		};
+
+		let path = path.as_ref().to_path_buf();
+		let reader = Self::from_reader_with(name, gzip_file, source_id, pool, options).await?;
+
+		Ok(reader.with_reopen(move || Ok(File::from_std(std::fs::File::open(&path)?))))
+	}
+
+	// A reader ready for region queries. Plain text is sorted, compressed and indexed into
+	// temp_dir and read from there, reusing the copy while the source is unchanged. BGZF
+	// data is read as is with the index found beside it.
+	#[cfg(feature = "indexing")]
+	pub async fn ensure_indexed<D>(self, temp_dir: D) -> error::Result<Self>
+	where
+		D: AsRef<Path>,
+	{
+		if self.index().is_some()
+		{
+			return Ok(self);
 		}
+
+		let path = std::path::PathBuf::from(&self.name);
+		let context = || Context::new("indexing").with_file(self.name.clone());
+
+		let is_bgzf = TokioBufReader::new(File::open(&path).await.context(context)?)
+			.is_bgz()
+			.await;
+
+		let data = match is_bgzf
+		{
+			true => path,
+			false => index_plain_bed(&path, temp_dir.as_ref()).await.context(context)?,
+		};
+
+		let Some(index) =
+			tabix::Reader::for_data_path(&data, &tabix::IndexSearch::default()).await?
 		else
 		{
-			builder
+			return Err(error::Error::NoIndex(self.name.clone()));
 		};
 
-		let thread_pool = builder.build().expect("Unable to setup thread pool");
+		let options = self.options.clone().with_index(Arc::clone(index.index()));
 
-		OneShotBlockReader {
-			name,
-			stream,
-			thread_pool,
-			resolver: Arc::new(Mutex::new(())),
-			filter_ctx: options.read_filter.into(),
-			reader_id: ReaderId(reader_id),
-			source_id: source_id.into(),
-			one_indexed: options.one_indexed.unwrap_or(false),
-			pending_tail: None,
-			_phantom: PhantomData,
-		}
+		Self::from_path_with(&data, self.source_id.clone(), Arc::clone(&self.pool), options).await
 	}
 }
 
-#[cfg(feature = "interning")]
-impl<R, F> OneShotBlockReader<R, TidStore, F>
+impl<R, T, F> OneShotBlockReader<R, T, F>
 where
 	R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin + 'static,
-	F: BedFieldsSink<<TidStore as TidResolver>::Tid> + std::fmt::Debug,
+	T: TidResolver + Default + std::clone::Clone + std::fmt::Debug + Send + Sync + 'static,
+	F: BedFieldsSink<T::Tid> + std::fmt::Debug,
 {
-	pub async fn from_reader(
+	pub async fn from_reader_with(
 		name: String,
 		reader: R,
-		source_id: impl Into<Option<SourceId>> + 'static,
+		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
+		options: ReaderOptions<T>,
 	) -> error::Result<Self>
 	{
-		let stream = BgzfBlockStream::new(reader, pool.clone(), Some(is_bgzf_eof))
-			.buffered(DEFAULT_BUFFER_SIZE);
+		Ok(Self::new_with_options(name, reader, source_id, pool, options))
+	}
 
-		let resolver = Arc::new(Mutex::new(TidStore::default()));
+	// how try_clone opens the data again, set by from_path_with for files. For an object
+	// store pass a closure calling ObjectStoreReader::try_clone, in a browser
+	// FetchReader::try_clone. Region queries call it on the blocking pool, so it may block.
+	pub fn with_reopen<G>(mut self, reopen: G) -> Self
+	where
+		G: Fn() -> error::Result<R> + Send + Sync + 'static,
+	{
+		self.reopen = Some(Arc::new(reopen));
+		self
+	}
 
-		let reader_id = NEXT_READER_ID.fetch_add(1, Ordering::SeqCst);
+	// Another reader over the same data from its start, sharing this one's tid store, pool
+	// and options, for tasks querying the data concurrently.
+	pub fn try_clone(&self) -> error::Result<Self>
+	{
+		let Some(reopen) = &self.reopen
+		else
+		{
+			return Err(error::Error::NotReopenable(self.name.clone()));
+		};
 
-		let builder = ThreadPoolBuilder::new();
-		let thread_pool = builder.build().expect("Unable to setup thread pool");
+		let mut reader = Self::new_with_options(
+			self.name.clone(),
+			reopen()?,
+			self.source_id.clone(),
+			Arc::clone(&self.pool),
+			self.options.clone(),
+		);
+		reader.reopen = Some(Arc::clone(reopen));
 
-		Ok(Self {
-			name,
-			thread_pool,
-			stream,
-			resolver,
-			filter_ctx: None,
-			reader_id: ReaderId(reader_id),
-			source_id: source_id.into(),
-			one_indexed: false,
-			pending_tail: None,
-			_phantom: PhantomData,
-		})
+		Ok(reader)
 	}
 
-	pub async fn from_reader_with_options(
+	fn new_with_options(
 		name: String,
 		reader: R,
-		source_id: impl Into<Option<SourceId>> + 'static,
+		source_id: impl Into<Option<SourceId>>,
 		pool: Arc<pool::BgzfBlockPool>,
-		options: ReaderOptions<TidStore>,
-	) -> error::Result<Self>
+		options: ReaderOptions<T>,
+	) -> Self
 	{
+		let reopen_options = options.clone();
+
+		let reader = CountingReader::new(reader);
+		let bytes_read = reader.position();
+		let framing = reader.framing();
+
+		if options.verify_checksums
+		{
+			if let Ok(mut framing) = framing.lock()
+			{
+				framing.keep_trailers();
+			}
+		}
+
 		let stream = BgzfBlockStream::new(reader, pool.clone(), Some(is_bgzf_eof))
 			.buffered(options.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE));
 
-		let resolver = options
-			.interner
-			.unwrap_or(Arc::new(Mutex::new(TidStore::default())));
+		// a missing interner falls back to a fresh store for this reader only
+		let resolver = options.interner.unwrap_or_default();
 
 		let reader_id = NEXT_READER_ID.fetch_add(1, Ordering::SeqCst);
 
@@ -347,9 +577,15 @@ where
 			builder
 		};
 
-		let thread_pool = builder.build().expect("Unable to setup thread pool");
+		// targets without threads (wasm32) fall back to decompressing in place
+		let thread_pool = builder.build().ok();
+
+		let reopen_options = ReaderOptions {
+			interner: Some(Arc::clone(&resolver)),
+			..reopen_options
+		};
 
-		Ok(Self {
+		Self {
 			name,
 			thread_pool,
 			stream,
@@ -359,8 +595,33 @@ where
 			source_id: source_id.into(),
 			one_indexed: options.one_indexed.unwrap_or(false),
 			pending_tail: None,
+			pending_blocks: Vec::new(),
+			stats: StatsCounters::default(),
+			bytes_read,
+			framing,
+			total_bytes: options.total_bytes,
+			progress: options.progress,
+			retry_policy: options.retry_policy,
+			max_result_bytes: options.max_result_bytes,
+			max_records: options.max_records,
+			parse_options: ParseOptions {
+				tokenizer_mode: options.tokenizer_mode,
+				utf8_policy: options.utf8_policy,
+			},
+			track: std::sync::Mutex::new(None),
+			warnings: options.warnings,
+			last_record: std::sync::Mutex::new(None),
+			require_sorted: options.require_sorted,
+			column_spec: options.column_spec,
+			projection: options.projection,
+			buffers: Arc::new(BufferPool::new(options.buffer_pool_size)),
+			sort_state: std::sync::Mutex::new(SortState::default()),
+			records_emitted: AtomicU64::new(0),
+			pool,
+			options: reopen_options,
+			reopen: None,
 			_phantom: PhantomData,
-		})
+		}
 	}
 }
 
@@ -370,106 +631,374 @@ where
 	T: TidResolver + std::clone::Clone + std::fmt::Debug + Send + Sync + 'static,
 	F: BedFieldsSink<T::Tid> + std::fmt::Debug,
 {
-	async fn open_bed_file<P>(path: P) -> error::Result<File>
-	where
-		P: AsRef<Path> + Copy,
-	{
-		let path = path.as_ref();
-
-		let gzip_file = File::open(path).await?;
-
-		Ok(gzip_file)
-	}
-
 	pub async fn reset(&mut self) -> error::Result<()>
 	{
 		self.stream.get_mut().reset().await?;
 		self.pending_tail = None;
+		self.records_emitted.store(0, Ordering::Relaxed);
+		self.pending_blocks.clear();
 		Ok(())
 	}
 
-	pub async fn store(&mut self) -> Arc<Mutex<T>>
+	pub async fn store(&mut self) -> Arc<RwLock<T>>
 	{
 		self.resolver.clone()
 	}
 
-	pub async fn next_bgzf_blocks(&mut self, n: usize) -> error::Result<Option<BgzfBlock>>
+	async fn resolve_tid(&self, tid: &str) -> T::Tid
 	{
-		let batch: Vec<_> = self.stream.by_ref().take(n).collect().await;
-
-		let batch: Vec<_> = batch
-			.into_iter()
-			.filter_map(|res| match res
-			{
-				Ok(Some(block)) => Some(block), // keep the valid block
-				_ => None,                      // skip empty blocks
-			})
-			.collect();
-
-		if batch.is_empty()
+		// already interned names only need the shared lock
+		if let Some(found) = self.resolver.read().await.find(tid)
 		{
-			return match self.pending_tail.take()
-			{
-				Some(tail) if !tail.is_empty() => Ok(Some(BgzfBlock {
-					// block_offset,
-					bytes: tail,
-					filter_ctx: self.filter_ctx.clone(),
-					source_id: self.source_id.clone(),
-					reader_id: self.reader_id.clone(),
-				})),
-				_ => Ok(None),
-			};
+			return found;
 		}
 
-		let decompressed: Vec<Vec<u8>> = self.thread_pool.install(|| {
-			batch
-				.into_par_iter()
-				.map(|block| match decompress_bgzf_block(&block)
-				{
-					Ok(bytes) =>
-					{
-						yield_now();
-						bytes
-					}
-					Err(e) =>
-					{
-						eprintln!("Decompression error: {:?}", e);
-						Vec::new()
-					}
-				})
-				.collect()
-		});
-
-		let total_size: usize = decompressed.iter().map(|b| b.len()).sum();
+		self.resolver.write().await.to_symbol_id(tid)
+	}
 
-		let mut blocks = Vec::with_capacity(total_size);
+	pub async fn pretty_tid(&self, tid: &T::Tid) -> Option<String>
+	{
+		self.resolver
+			.read()
+			.await
+			.from_symbol_id(tid)
+			.map(str::to_owned)
+	}
 
-		for bytes in decompressed
+	pub fn stats(&self) -> ReaderStats
+	{
+		self.stats.snapshot()
+	}
+
+	// reads the rest of the input without keeping any records and reports whether every
+	// record was in contig/start order
+	pub async fn check_sorted(&mut self) -> error::Result<bool>
+	{
+		while let Some(block) = self.next_bgzf_blocks(DEFAULT_BUFFER_SIZE).await?
 		{
-			blocks.extend_from_slice(&bytes);
+			self.read_tids_in_block_sink(block, &mut DiscardSink).await?;
+		}
+
+		Ok(self.stats().is_sorted())
+	}
+
+	// per-contig counts, coverage, length and score distributions and strands of the rest
+	// of the input, in one pass
+	pub async fn summarize(&mut self) -> error::Result<Summary<T::Tid>>
+	{
+		let mut summary = Summary::new();
+
+		while let Some(block) = self.next_bgzf_blocks(DEFAULT_BUFFER_SIZE).await?
+		{
+			let filtered = self.read_tids_in_block_sink(block, &mut summary).await?;
+			summary.filtered += filtered.unwrap_or(0) as u64;
+		}
+
+		Ok(summary)
+	}
+
+	// compressed bytes consumed from the source, including read-ahead
+	pub fn bytes_read(&self) -> u64
+	{
+		self.bytes_read.load(Ordering::Relaxed)
+	}
+
+	pub fn total_bytes(&self) -> Option<u64>
+	{
+		self.total_bytes
+	}
+
+	// the index handed to ReaderOptions::with_index, shared with the reader's clones
+	pub fn index(&self) -> Option<&Arc<tabix::Index>>
+	{
+		self.options.index.as_ref()
+	}
+
+	// whether the source ended with the BGZF EOF marker, None until the end has been read
+	// or when the blocks could not be followed
+	pub fn is_complete(&self) -> Option<bool>
+	{
+		self.framing.lock().ok()?.complete()
+	}
+
+	// None unless checksums are verified
+	fn next_trailer(&self) -> Option<BlockTrailer>
+	{
+		self.framing.lock().ok()?.next_trailer()
+	}
+
+	// a block header with an impossible size, or the source ending inside a block
+	fn framing_error(&self) -> Option<error::Error>
+	{
+		let framing = self.framing.lock().ok()?;
+
+		if let Some(offset) = framing.corrupt()
+		{
+			return Some(error::Error::CorruptBlock { offset });
+		}
+
+		framing
+			.truncated()
+			.then(|| error::Error::TruncatedBgzf(self.name.clone(), framing.offset()))
+	}
+
+	// Releases the source and decompression threads now rather than whenever the reader
+	// happens to be dropped. Pending partial lines are discarded.
+	pub async fn close(self) -> error::Result<()>
+	{
+		let Self {
+			stream,
+			thread_pool,
+			..
+		} = self;
+
+		drop(stream);
+		drop(thread_pool);
+
+		Ok(())
+	}
+
+	// A timed out pull is retried on the same stream, the in-flight read is kept rather
+	// than restarted since block framing cannot resume mid-block.
+	async fn next_stream_item(&mut self) -> error::Result<Option<StreamItem<R>>>
+	{
+		let Some(timeout) = self.retry_policy.timeout
+		else
+		{
+			return Ok(self.stream.next().await);
+		};
+
+		let mut attempt = 0;
+
+		loop
+		{
+			match tokio::time::timeout(timeout, self.stream.next()).await
+			{
+				Ok(item) => return Ok(item),
+				Err(_) if attempt < self.retry_policy.retries =>
+				{
+					attempt += 1;
+					log::debug!("{}: read timed out, attempt {}", self.name, attempt);
+					tokio::time::sleep(self.retry_policy.backoff_for(attempt)).await;
+				}
+				Err(_) => return Err(error::Error::Timeout(self.name.clone())),
+			}
+		}
+	}
+
+	fn report_progress(&self)
+	{
+		if let Some(progress) = &self.progress
+		{
+			progress(self.bytes_read(), self.total_bytes);
+		}
+	}
+
+	pub fn coordinate_system(&self) -> CoordinateSystem
+	{
+		CoordinateSystem::of_reader(self.one_indexed)
+	}
+
+	pub fn describe(&self) -> ReaderDescription
+	{
+		ReaderDescription {
+			name: self.name.clone(),
+			kind: <F as BedFieldsSink<T::Tid>>::KIND,
+			reader_id: self.reader_id.clone(),
+			source_id: self.source_id.clone(),
+			one_indexed: self.one_indexed,
+			decompression_threads: self
+				.thread_pool
+				.as_ref()
+				.map(|pool| pool.current_num_threads()),
+			filtered: self.filter_ctx.is_some(),
+		}
+	}
+
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(level = "debug", skip_all, fields(reader = %self.name, n))
+	)]
+	pub async fn next_bgzf_blocks(&mut self, n: usize) -> error::Result<Option<BgzfBlock>>
+	{
+		let started = Instant::now();
+
+		let n = match self.max_result_bytes
+		{
+			Some(max_result_bytes) => n.min((max_result_bytes / MAX_BLOCK_SIZE).max(1)),
+			None => n,
+		};
+
+		// cancel safe: everything pulled so far stays on self until the batch is complete
+		while self.pending_blocks.len() < n
+		{
+			match self.next_stream_item().await?
+			{
+				Some(item) => self.pending_blocks.push(item),
+				None => break,
+			}
 		}
 
-		if let Some(tail) = self.pending_tail.as_mut()
+		let batch = std::mem::take(&mut self.pending_blocks);
+		self.stats.add_elapsed(Phase::Read, started);
+		self.report_progress();
+
+		let mut blocks_read = Vec::with_capacity(batch.len());
+		let mut trailers = Vec::with_capacity(batch.len());
+		for res in batch
 		{
-			if !tail.is_empty()
+			// every stream item is one block, empty ones included, so trailers stay in step
+			let trailer = self.next_trailer();
+
+			match res
 			{
-				let mut merged = Vec::with_capacity(tail.len() + blocks.len());
-				merged.extend_from_slice(tail);
-				merged.extend_from_slice(&blocks);
-				blocks = merged;
-				tail.clear();
+				// keep the valid block
+				Ok(Some(block)) =>
+				{
+					blocks_read.push(block);
+					trailers.push(trailer);
+				}
+				Ok(None) => (), // skip empty blocks
+				// read failures, such as a timed out remote fetch, end the scan rather than
+				// looking like the end of the file
+				Err(e) =>
+				{
+					let e = self.framing_error().unwrap_or_else(|| error::Error::from(e));
+					return Err(e.with_context(
+						Context::new("block read")
+							.with_file(self.name.clone())
+							.with_byte_offset(self.bytes_read()),
+					));
+				}
 			}
 		}
+		let batch = blocks_read;
 
-		if let Some(idx) = memchr::memrchr(b'\n', &blocks)
+		if let Some(offset) = self.framing.lock().ok().and_then(|framing| framing.corrupt())
 		{
-			let remainder = blocks.split_off(idx + 1);
-			*self.pending_tail.get_or_insert(Vec::new()) = remainder;
+			return Err(error::Error::CorruptBlock { offset }
+				.with_context(Context::new("block read").with_file(self.name.clone())));
 		}
-		else
+
+		if batch.is_empty()
+		{
+			if let Some(e) = self.framing_error()
+			{
+				return Err(e);
+			}
+
+			return match self.pending_tail.take()
+			{
+				Some(tail) if !tail.is_empty() => Ok(Some(BgzfBlock {
+					// block_offset,
+					bytes: tail,
+					filter_ctx: self.filter_ctx.clone(),
+					source_id: self.source_id.clone(),
+					reader_id: self.reader_id.clone(),
+					buffers: None,
+				})),
+				_ => Ok(None),
+			};
+		}
+
+		let started = Instant::now();
+		let n_blocks = batch.len() as u64;
+
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!("decompress", blocks = n_blocks).entered();
+
+		// None where a block failed to decompress
+		let decompressed: Vec<Option<Vec<u8>>> = match &self.thread_pool
+		{
+			Some(thread_pool) => thread_pool.install(|| {
+				batch
+					.into_par_iter()
+					.map(|block| match decompress_bgzf_block(&block)
+					{
+						Ok(bytes) =>
+						{
+							yield_now();
+							Some(bytes)
+						}
+						Err(e) =>
+						{
+							log::warn!("{}: decompression error: {:?}", self.name, e);
+							None
+						}
+					})
+					.collect()
+			}),
+			None => batch
+				.into_iter()
+				.map(|block| match decompress_bgzf_block(&block)
+				{
+					Ok(bytes) => Some(bytes),
+					Err(e) =>
+					{
+						log::warn!("{}: decompression error: {:?}", self.name, e);
+						None
+					}
+				})
+				.collect(),
+		};
+
+		for (bytes, trailer) in decompressed.iter().zip(&trailers)
+		{
+			let Some(trailer) = trailer
+			else
+			{
+				continue;
+			};
+
+			if !bytes.as_ref().is_some_and(|bytes| trailer.matches(bytes))
+			{
+				return Err(error::Error::CorruptBlock {
+					offset: trailer.offset,
+				}
+				.with_context(Context::new("block read").with_file(self.name.clone())));
+			}
+		}
+
+		// without verification a bad block is counted and skipped
+		let failed = decompressed.iter().filter(|bytes| bytes.is_none()).count() as u64;
+		self.stats.add_blocks(0, 0, failed);
+		let decompressed: Vec<Vec<u8>> = decompressed
+			.into_iter()
+			.map(Option::unwrap_or_default)
+			.collect();
+
+		let total_size: usize = decompressed.iter().map(|b| b.len()).sum();
+
+		self.stats.add_elapsed(Phase::Decompress, started);
+		self.stats.add_blocks(n_blocks, total_size as u64, 0);
+
+		#[cfg(feature = "tracing")]
+		tracing::debug!(blocks = n_blocks, bytes = total_size, "decompressed batch");
+
+		// the partial line left by the previous batch goes first, then this batch, into a
+		// buffer handed back by an earlier block where there is one
+		let tail = self.pending_tail.get_or_insert(Vec::new());
+		let mut blocks = self.buffers.take(tail.len() + total_size);
+		blocks.extend_from_slice(tail);
+		tail.clear();
+
+		for bytes in decompressed
+		{
+			blocks.extend_from_slice(&bytes);
+		}
+
+		match memchr::memrchr(b'\n', &blocks)
 		{
-			*self.pending_tail.get_or_insert(Vec::new()) = blocks;
-			blocks = Vec::new();
+			Some(idx) =>
+			{
+				tail.extend_from_slice(&blocks[idx + 1..]);
+				blocks.truncate(idx + 1);
+			}
+			None =>
+			{
+				tail.extend_from_slice(&blocks);
+				blocks.clear();
+			}
 		}
 
 		Ok(Some(BgzfBlock {
@@ -478,14 +1007,437 @@ where
 			filter_ctx: self.filter_ctx.clone(),
 			source_id: self.source_id.clone(),
 			reader_id: self.reader_id.clone(),
+			buffers: Some(Arc::clone(&self.buffers)),
 		}))
 	}
 
+	// Splits the block into records that parse only their coordinates until asked for more.
+	// Track and header lines are handled as read_tids_in_block_sink handles them, read
+	// filters and column specs are not applied.
+	pub fn read_lazy_in_block(&self, block: BgzfBlock) -> error::Result<Vec<LazyBedRecord>>
+	{
+		let mut records = Vec::new();
+
+		self.scan_data_lines(&block, |line, kind| {
+			records.extend(LazyBedRecord::parse(line, kind, self.parse_options, self.one_indexed)?);
+			Ok(())
+		})?;
+
+		Ok(records)
+	}
+
+	// Records in the rest of the input overlapping region, from their coordinates alone.
+	// Like check_sorted this reads the input to its end.
+	pub async fn count_in_region(&mut self, region: &Region) -> error::Result<u64>
+	{
+		let mut count = 0;
+		self.for_each_interval_in(region, |_, _| count += 1).await?;
+
+		Ok(count)
+	}
+
+	// bases of region covered by at least one record, overlapping records counted once
+	pub async fn bases_covered_in_region(&mut self, region: &Region) -> error::Result<u64>
+	{
+		let mut intervals = Vec::new();
+		self.for_each_interval_in(region, |start, end| {
+			intervals.push((start.max(region.start), end.min(region.end)));
+		})
+		.await?;
+
+		intervals.sort_unstable();
+
+		let mut covered = 0;
+		let mut current: Option<(u64, u64)> = None;
+
+		for (start, end) in intervals
+		{
+			match current.as_mut()
+			{
+				Some((_, current_end)) if start <= *current_end =>
+				{
+					*current_end = (*current_end).max(end);
+				}
+				_ =>
+				{
+					if let Some((current_start, current_end)) = current.replace((start, end))
+					{
+						covered += current_end - current_start;
+					}
+				}
+			}
+		}
+
+		Ok(covered + current.map_or(0, |(start, end)| end - start))
+	}
+
+	// smallest start and largest end of the records on tid in the rest of the input.
+	// tabix::Index::extent_bounds gives bounds from an index without reading the data
+	pub async fn extent(&mut self, tid: &str) -> error::Result<Option<(u64, u64)>>
+	{
+		let mut extent: Option<(u64, u64)> = None;
+
+		self.for_each_interval_in(&Region::contig(tid), |start, end| {
+			let (min_start, max_end) = extent.get_or_insert((start, end));
+			*min_start = (*min_start).min(start);
+			*max_end = (*max_end).max(end);
+		})
+		.await?;
+
+		Ok(extent)
+	}
+
+	// Counts, score sums and fractions modified of the records on tid in n_bins equal bins
+	// of start..end (0-based half-open), for density tracks. Reads the rest of the input
+	// without keeping any records.
+	pub async fn binned_counts(
+		&mut self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		n_bins: usize,
+	) -> error::Result<BinnedCounts<T::Tid>>
+	{
+		let tid = self.resolve_tid(tid).await;
+		let mut bins = BinnedCounts::new(tid, start, end, n_bins, self.coordinate_system());
+
+		while let Some(block) = self.next_bgzf_blocks(DEFAULT_BUFFER_SIZE).await?
+		{
+			self.read_tids_in_block_sink(block, &mut bins).await?;
+		}
+
+		Ok(bins)
+	}
+
+	// Records overlapping region through the index from ReaderOptions::with_index instead of
+	// a scan. The chunks are fetched and decompressed on up to parallelism sources opened by
+	// the reopen closure at once, then parsed in file order, which for sorted data is
+	// coordinate order. The reader's own position is left alone.
+	pub async fn query_region_concurrent(
+		&self,
+		region: &Region,
+		parallelism: usize,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		let (index, reopen) = self.indexed_source()?;
+		let extract = tabix::ExtractOptions::default();
+
+		let Some(chunks) = index.chunks_for_region(region, &extract)?
+		else
+		{
+			return Ok(Vec::new());
+		};
+
+		// decompressed bytes held so far over every group, checked as each chunk arrives
+		let held = AtomicU64::new(0);
+
+		let fetched: Vec<error::Result<Vec<u8>>> =
+			futures::stream::iter(group_chunks(chunks, parallelism))
+				.map(|group| {
+					let reopen = Arc::clone(reopen);
+					let held = &held;
+					async move {
+						let mut source = open_source(&reopen).await?;
+						let mut bytes = Vec::new();
+
+						for chunk in &group
+						{
+							let chunk_bytes = read_chunk(&mut source, chunk, &extract).await?;
+							let total = held.fetch_add(chunk_bytes.len() as u64, Ordering::Relaxed)
+								+ chunk_bytes.len() as u64;
+
+							if let Some(max_result_bytes) =
+								self.max_result_bytes.filter(|max| total > *max as u64)
+							{
+								return Err(error::Error::ResultTooLarge(
+									self.name.clone(),
+									max_result_bytes,
+								));
+							}
+
+							bytes.extend(chunk_bytes);
+						}
+
+						Ok(bytes)
+					}
+				})
+				.buffered(parallelism.max(1))
+				.collect()
+				.await;
+
+		let fetched = fetched.into_iter().collect::<error::Result<Vec<_>>>().context(|| {
+			Context::new("region query")
+				.with_file(self.name.clone())
+				.with_region(region)
+		})?;
+
+		self.records_in(region, fetched).await
+	}
+
+	// About max_records records spread over tid:start-end (0-based half-open), for overview
+	// rendering. Blocks are skipped in proportion to the records the middle one holds and
+	// what is read is thinned evenly, so dense parts of the region are sampled as sparsely
+	// as the rest.
+	pub async fn sample_region(
+		&self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		max_records: usize,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		let region = Region::zero_based(tid, start, end);
+		let (index, reopen) = self.indexed_source()?;
+
+		let Some(chunks) = index.chunks_for_region(&region, &tabix::ExtractOptions::default())?
+		else
+		{
+			return Ok(Vec::new());
+		};
+
+		if max_records == 0
+		{
+			return Ok(Vec::new());
+		}
+
+		let context = || {
+			Context::new("region sample")
+				.with_file(self.name.clone())
+				.with_region(&region)
+		};
+
+		let mut source = open_source(reopen).await.context(context)?;
+		let mut blocks = Vec::new();
+
+		for chunk in &chunks
+		{
+			let offsets = chunk_blocks(&mut source, chunk).await.context(context)?;
+			blocks.extend(offsets.into_iter().map(|offset| (offset, chunk)));
+		}
+
+		if blocks.is_empty()
+		{
+			return Ok(Vec::new());
+		}
+
+		// lines in the middle block stand in for the density of the rest
+		let (offset, chunk) = blocks[blocks.len() / 2];
+		let probe = read_block_lines(&mut source, offset, chunk).await.context(context)?;
+		let per_block = memchr::memchr_iter(b'\n', &probe).count().max(1);
+		let wanted = max_records.div_ceil(per_block).min(blocks.len());
+
+		let mut parts = Vec::with_capacity(wanted);
+
+		for i in 0..wanted
+		{
+			let (offset, chunk) = blocks[i * blocks.len() / wanted];
+			parts.push(read_block_lines(&mut source, offset, chunk).await.context(context)?);
+		}
+
+		let records = self.records_in(&region, parts).await?;
+		let n = records.len();
+
+		if n <= max_records
+		{
+			return Ok(records);
+		}
+
+		// keeps the records where i * max_records / n steps up, exactly max_records of them
+		Ok(records
+			.into_iter()
+			.enumerate()
+			.filter(|(i, _)| i * max_records / n != (i + 1) * max_records / n)
+			.map(|(_, record)| record)
+			.collect())
+	}
+
+	fn indexed_source(&self) -> error::Result<(&Arc<tabix::Index>, &Reopen<R>)>
+	{
+		let Some(index) = self.index()
+		else
+		{
+			return Err(error::Error::NoIndex(self.name.clone()));
+		};
+
+		let Some(reopen) = &self.reopen
+		else
+		{
+			return Err(error::Error::NotReopenable(self.name.clone()));
+		};
+
+		Ok((index, reopen))
+	}
+
+	// parses lines read through the index, keeping the records that overlap region since
+	// chunks and blocks hold whole records on either side of it
+	async fn records_in(
+		&self,
+		region: &Region,
+		parts: Vec<Vec<u8>>,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		let mut sink = CollectSink::new();
+		let emitted = AtomicU64::new(0);
+
+		for bytes in parts
+		{
+			let block = BgzfBlock {
+				bytes,
+				filter_ctx: self.filter_ctx.clone(),
+				source_id: self.source_id.clone(),
+				reader_id: self.reader_id.clone(),
+				buffers: None,
+			};
+
+			self.read_block_counted(block, &mut sink, &emitted).await?;
+		}
+
+		let Some(tid) = self.resolver.read().await.find(&region.tid)
+		else
+		{
+			return Ok(Vec::new());
+		};
+
+		let system = self.coordinate_system();
+
+		Ok(sink
+			.drain()
+			.filter(|record| {
+				let (start, end) =
+					system.convert(record.start, record.end, CoordinateSystem::ZeroBasedHalfOpen);
+				record.tid == tid && start < region.end && region.start < end
+			})
+			.collect())
+	}
+
+	// file coordinates of every record overlapping region
+	async fn for_each_interval_in(
+		&mut self,
+		region: &Region,
+		mut f: impl FnMut(u64, u64),
+	) -> error::Result<()>
+	{
+		let mode = self.parse_options.tokenizer_mode;
+
+		while let Some(block) = self.next_bgzf_blocks(DEFAULT_BUFFER_SIZE).await?
+		{
+			self.scan_data_lines(&block, |line, kind| {
+				if line.trim_ascii().is_empty()
+				{
+					return Ok(());
+				}
+
+				let Some([tid, start, end]) = leading_fields::<3>(line, mode)
+				else
+				{
+					return Err(error::Error::BedMismatch(kind.to_string()));
+				};
+
+				if tid != region.tid.as_bytes()
+				{
+					return Ok(());
+				}
+
+				let start = lexical_core::parse::<u64>(start)?;
+				let end = lexical_core::parse::<u64>(end)?;
+
+				if start < region.end && region.start < end
+				{
+					f(start, end);
+				}
+
+				Ok(())
+			})?;
+		}
+
+		Ok(())
+	}
+
+	// calls f with each line that holds a record and the kind it is read as, following
+	// track lines and skipping headers
+	fn scan_data_lines<G>(&self, block: &BgzfBlock, mut f: G) -> error::Result<()>
+	where
+		G: FnMut(&[u8], BedKind) -> error::Result<()>,
+	{
+		let mut cursor = &block.bytes as &[u8];
+		let mut track = self.track.lock().map(|track| *track).unwrap_or_default();
+		let mut line_number = 0;
+
+		while !cursor.is_empty()
+		{
+			let (line, after_line) = next_line(cursor);
+			line_number += 1;
+
+			let kind = if let Some(track_type) = TrackType::from_line(line)
+			{
+				track = Some(track_type);
+				None
+			}
+			else if is_header_line(line)
+			{
+				None
+			}
+			else
+			{
+				match track.map_or(LineParser::Own, |track| track.line_parser(F::KIND))
+				{
+					LineParser::Own => Some(F::KIND),
+					LineParser::Kind(kind) => Some(kind),
+					LineParser::Bed6Prefix => Some(BedKind::Bed6),
+					LineParser::Skip => None,
+				}
+			};
+
+			if let Some(kind) = kind
+			{
+				f(line, kind).context(|| {
+					Context::new("record parse")
+						.with_file(self.name.clone())
+						.with_line(line_number)
+						.with_byte_offset((block.bytes.len() - cursor.len()) as u64)
+				})?;
+			}
+
+			cursor = after_line;
+		}
+
+		if let Ok(mut current) = self.track.lock()
+		{
+			*current = track;
+		}
+
+		Ok(())
+	}
+
+	// Dropping this future mid-way leaves the reader untouched, but the sink may have seen
+	// part of the block.
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(
+			level = "debug",
+			skip_all,
+			fields(reader = %self.name, bytes = block.bytes.len())
+		)
+	)]
 	pub async fn read_tids_in_block_sink<V>(
 		&self,
 		block: BgzfBlock,
 		sink: &mut V,
 	) -> error::Result<Option<usize>>
+	where
+		V: BedSink<T::Tid> + Send + Sync + ?Sized,
+	{
+		self.read_block_counted(block, sink, &self.records_emitted).await
+	}
+
+	// as read_tids_in_block_sink, with max_records checked against emitted, which region
+	// queries keep for themselves so one query's records do not count against the next
+	async fn read_block_counted<V>(
+		&self,
+		block: BgzfBlock,
+		sink: &mut V,
+		emitted: &AtomicU64,
+	) -> error::Result<Option<usize>>
 	where
 		V: BedSink<T::Tid> + Send + Sync + ?Sized,
 	{
@@ -493,27 +1445,110 @@ where
 		let mut current_tid: Option<T::Tid> = None;
 		let mut current_start: Option<u64> = None;
 		let mut current_end: Option<u64> = None;
-		let mut last_strand = Strand::Both;
+		let mut current_strand = Strand::Both;
 
 		let mut filtered_out: Option<usize> = None;
+		let mut parsed_count = 0;
+		let mut malformed = 0;
+		let started = Instant::now();
+
+		// blocks must be read in file order for a track line to apply to the blocks after it
+		let mut track = self.track.lock().map(|track| *track).unwrap_or_default();
+		let mut previous = match &self.warnings
+		{
+			Some(_) => self.last_record.lock().map(|last| last.clone()).unwrap_or_default(),
+			None => None,
+		};
+
+		let mut sort_state = self.sort_state.lock().map(|state| state.clone()).unwrap_or_default();
+		let mut unsorted = 0;
+		let mut line_number = 0;
 
 		while !cursor.is_empty()
 		{
-			let (rest, parsed) = if let Some(filter_arc) = &self.filter_ctx
+			let (line, after_line) = next_line(cursor);
+			line_number += 1;
+
+			if let Some(track_type) = TrackType::from_line(line)
 			{
-				let locked = filter_arc.lock().await;
-				let filter_ref: &ReadFilterContext = &*locked;
-				F::parse_sink(cursor, Some(filter_ref)).await?
+				if let Some(warn) = &self.warnings
+				{
+					unknown_attributes(line)
+						.into_iter()
+						.for_each(|attribute| warn(Warning::UnknownTrackAttribute(attribute)));
+
+					if track_type == TrackType::Other
+					{
+						warn(Warning::UnsupportedTrackType(type_name(line).unwrap_or_default()));
+					}
+				}
+
+				track = Some(track_type);
+				cursor = after_line;
+				continue;
 			}
-			else
+
+			if is_header_line(line)
 			{
-				F::parse_sink(cursor, None).await?
+				cursor = after_line;
+				continue;
+			}
+
+			let parser = track.map_or(LineParser::Own, |track| track.line_parser(F::KIND));
+
+			if parser == LineParser::Skip
+			{
+				*filtered_out.get_or_insert(0) += 1;
+				cursor = after_line;
+				continue;
+			}
+
+			let (rest, parsed) = {
+				let locked = match &self.filter_ctx
+				{
+					Some(filter_arc) => Some(filter_arc.lock().await),
+					None => None,
+				};
+				let filter_ref: Option<&ReadFilterContext> = locked.as_deref();
+
+				let line_kind = match parser
+				{
+					LineParser::Kind(kind) => kind,
+					LineParser::Bed6Prefix => BedKind::Bed6,
+					_ => F::KIND,
+				};
+				let projected = filter_ref.is_none() && self.projection.applies_to(line_kind);
+
+				let parsed = match parser
+				{
+					_ if projected =>
+					{
+						parse_projected_sink(self.projection, cursor, self.parse_options).await
+					}
+					LineParser::Kind(kind) =>
+					{
+						parse_kind_sink(kind, cursor, filter_ref, self.parse_options).await
+					}
+					LineParser::Bed6Prefix =>
+					{
+						parse_bed6_prefix_sink_simd(cursor, filter_ref, self.parse_options).await
+					}
+					_ => F::parse_sink(cursor, filter_ref, self.parse_options).await,
+				};
+
+				parsed.context(|| {
+					Context::new("record parse")
+						.with_file(self.name.clone())
+						.with_line(line_number)
+						.with_byte_offset((block.bytes.len() - cursor.len()) as u64)
+				})?
 			};
 
 			if rest.len() == cursor.len()
 			{
 				let skip = memchr(b'\n', rest).map(|p| p + 1).unwrap_or(1);
 				cursor = &rest[skip..];
+				malformed += 1;
 				continue;
 			}
 			else
@@ -521,22 +1556,65 @@ where
 				cursor = rest;
 			}
 
-			if let Some((tid, strand, start, end, value)) = parsed
+			if let Some((tid, mut strand, start, end, mut value)) = parsed
 			{
+				// only DynBedExtra leaves untyped columns on a BED3 value
+				let untyped = value.kind == BedKind::Bed3 && value.extra.is_some();
+
+				if let Some(spec) = self.column_spec.as_ref().filter(|_| untyped)
+				{
+					spec.apply(&mut strand, &mut value).context(|| {
+						Context::new("column spec")
+							.with_file(self.name.clone())
+							.with_line(line_number)
+					})?;
+				}
+
+				if let Some(warn) = &self.warnings
+				{
+					check_record(warn, &mut previous, &tid, start, end, strand, &value);
+				}
+
 				let (start, end) = if self.one_indexed
 				{
 					(start, end)
 				}
 				else
 				{
-					(start + 1, end)
+					(start.saturating_add(1), end)
 				};
 
-				last_strand = strand;
+				parsed_count += 1;
+
+				if let Some(max_records) = self.max_records
+				{
+					if emitted.fetch_add(1, Ordering::Relaxed) >= max_records
+					{
+						return Err(error::Error::QueryTooLarge(self.name.clone(), max_records));
+					}
+				}
+
+				let tid = self.resolve_tid(&tid).await;
+
+				if !sort_state.advance(&tid, start)
+				{
+					unsorted += 1;
+
+					if self.require_sorted
+					{
+						self.stats.add_unsorted(unsorted);
 
-				let tid = self.resolver.lock().await.to_symbol_id(tid);
+						let pretty = self
+							.pretty_tid(&tid)
+							.await
+							.unwrap_or_else(|| format!("{tid:?}"));
+						return Err(error::Error::Unsorted(self.name.clone(), pretty, start));
+					}
+				}
 
-				if current_tid.as_ref() != Some(&tid)
+				// a change of strand starts a new run on the same tid, so sinks see each
+				// record's own strand
+				if current_tid.as_ref() != Some(&tid) || current_strand != strand
 				{
 					if let Some(prev_end) = current_end
 					{
@@ -545,12 +1623,13 @@ where
 
 					if let Some(prev_tid) = current_tid
 					{
-						sink.end_tid(&prev_tid, &strand);
+						sink.end_tid(&prev_tid, &current_strand);
 					}
 
 					sink.begin_tid(&tid, &strand);
 
 					current_tid = Some(tid);
+					current_strand = strand;
 					current_start = None;
 					current_end = None;
 				}
@@ -584,9 +1663,64 @@ where
 
 		if let Some(tid) = current_tid
 		{
-			sink.end_tid(&tid, &last_strand);
+			sink.end_tid(&tid, &current_strand);
+		}
+
+		if let Ok(mut current) = self.track.lock()
+		{
+			*current = track;
+		}
+
+		if let Ok(mut state) = self.sort_state.lock()
+		{
+			*state = sort_state;
+		}
+		self.stats.add_unsorted(unsorted);
+
+		if self.warnings.is_some()
+		{
+			if let Ok(mut last) = self.last_record.lock()
+			{
+				*last = previous;
+			}
 		}
 
+		self.stats.add_lines(
+			parsed_count,
+			filtered_out.unwrap_or(0) as u64,
+			malformed,
+		);
+		self.stats.add_elapsed(Phase::Parse, started);
+
+		#[cfg(feature = "tracing")]
+		tracing::debug!(
+			parsed = parsed_count,
+			filtered = filtered_out.unwrap_or(0),
+			malformed,
+			"parsed block"
+		);
+
 		Ok(filtered_out)
 	}
 }
+
+impl<R, T, F> OneShotBlockReader<R, T, F>
+where
+	R: AsyncRead + AsyncSeek + Send + Unpin + Sync + 'static,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+	F: BedFieldsSink<T::Tid> + std::fmt::Debug,
+{
+	// consumes the reader into a stream of owned records, for RecordStreamExt
+	pub fn records(
+		self,
+		blocks_per_batch: usize,
+	) -> impl Stream<Item = error::Result<BedRecord<T::Tid>>>
+	{
+		record_stream(self, blocks_per_batch)
+	}
+
+	pub fn peekable(self, blocks_per_batch: usize) -> PeekableReader<Self, T>
+	{
+		PeekableReader::new(self, blocks_per_batch)
+	}
+}
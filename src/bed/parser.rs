@@ -1,7 +1,12 @@
+use std::borrow::Cow;
 use std::fmt::Debug;
 
 use crate::error;
 use crate::bed::{Strand, BedKind, BedSinkValue, Bed3Fields};
+use crate::bed::{Bed12Columns, ItemRgb};
+use crate::bed::tokenizer::{next_line, tokenize};
+use crate::bed::{ParseOptions, decode_name};
+use crate::bed::utf8::utf8_field;
 use crate::bed::{Bed4Extra, Bed5Extra, Bed6Extra, Bed12Extra, BedMethylExtra};
 use crate::filtering::ReadFilterContext;
 
@@ -29,6 +34,12 @@ mod bed6_fields
 }
 mod bed12_fields
 {
+	pub const THICK_START: usize = 6;
+	pub const THICK_END: usize = 7;
+	pub const ITEM_RGB: usize = 8;
+	pub const BLOCK_COUNT: usize = 9;
+	pub const BLOCK_SIZES: usize = 10;
+	pub const BLOCK_STARTS: usize = 11;
 	pub const N_FIELDS: usize = 12;
 }
 mod bedmethyl_fields
@@ -44,6 +55,7 @@ mod bedmethyl_fields
 	pub const N_NOCALL: usize = 17;
 
 	pub const N_FIELDS: usize = 18;
+	pub const N_LEGACY_FIELDS: usize = 11;
 }
 
 // #[async_trait::async_trait]
@@ -57,8 +69,9 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
+		options: ParseOptions,
 	) -> impl std::future::Future<
-		Output = error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>,
+		Output = error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>,
 	> + Send
 	where
 		Self: Sized;
@@ -75,9 +88,10 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
-	) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 	{
-		let (rest, parsed) = parse_bed3_sink_simd(input, filter_ctx).await?;
+		let (rest, parsed) = parse_bed3_sink_simd(input, filter_ctx, options).await?;
 
 		Ok((rest, parsed))
 	}
@@ -94,9 +108,10 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
-	) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 	{
-		let (rest, parsed) = parse_bed4_sink_simd(input, filter_ctx).await?;
+		let (rest, parsed) = parse_bed4_sink_simd(input, filter_ctx, options).await?;
 
 		Ok((rest, parsed))
 	}
@@ -113,9 +128,10 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
-	) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 	{
-		let (rest, parsed) = parse_bed5_sink_simd(input, filter_ctx).await?;
+		let (rest, parsed) = parse_bed5_sink_simd(input, filter_ctx, options).await?;
 
 		Ok((rest, parsed))
 	}
@@ -132,9 +148,10 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
-	) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 	{
-		let (rest, parsed) = parse_bed6_sink_simd(input, filter_ctx).await?;
+		let (rest, parsed) = parse_bed6_sink_simd(input, filter_ctx, options).await?;
 
 		Ok((rest, parsed))
 	}
@@ -151,9 +168,10 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
-	) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 	{
-		let (rest, parsed) = parse_bed12_sink_simd(input, filter_ctx).await?;
+		let (rest, parsed) = parse_bed12_sink_simd(input, filter_ctx, options).await?;
 
 		Ok((rest, parsed))
 	}
@@ -170,9 +188,10 @@ where
 		input: &'a [u8],
 		// _ctx: Option<ParseContext<'b>>,
 		filter_ctx: Option<&ReadFilterContext>,
-	) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+		options: ParseOptions,
+	) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 	{
-		let (rest, parsed) = parse_bedmethyl_sink_simd(input, filter_ctx).await?;
+		let (rest, parsed) = parse_bedmethyl_sink_simd(input, filter_ctx, options).await?;
 
 		Ok((rest, parsed))
 	}
@@ -181,61 +200,28 @@ where
 pub async fn parse_bed3_sink_simd<'a>(
 	input: &'a [u8],
 	_filter_ctx: Option<&ReadFilterContext>,
-) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 {
-	if input.is_empty() || input[0] == b'\n'
-	{
-		let rest = memchr::memchr(b'\n', input)
-			.map(|p| p + 1)
-			.unwrap_or(input.len());
-		return Ok((&input[rest..], None));
-	}
-
-	let line_end = memchr::memchr(b'\n', input).unwrap_or(input.len());
-	let line = &input[..line_end];
-
-	let mut fields = [0usize; 32];
-	let mut n = 0;
-	let mut start_idx = 0;
+	let (line, rest) = next_line(input);
+	let fields = tokenize(line, options.tokenizer_mode, false, "BED3")?;
 
-	for (i, &b) in line.iter().enumerate()
+	if fields.is_empty()
 	{
-		if b == b' ' || b == b'\t'
-		{
-			fields[n] = start_idx;
-			n += 1;
-			start_idx = i + 1;
-		}
+		return Ok((rest, None));
 	}
 
-	if (n + 1) != bed3_fields::N_FIELDS
+	if fields.len() != bed3_fields::N_FIELDS
 	{
 		return Err(error::Error::BedMismatch("BED3".into()));
 	}
 
-	fields[n] = start_idx;
-
-	let tid = unsafe {
-		std::str::from_utf8_unchecked(
-			&line[fields[bed3_fields::TID]..fields[bed3_fields::START] - 1],
-		)
-	};
-	let start_val = lexical_core::parse::<u64>(
-		&line[fields[bed3_fields::START]..fields[bed3_fields::END] - 1],
-	)?;
-	let end_val = lexical_core::parse::<u64>(&line[fields[bed3_fields::END]..fields[line.len()]])?;
-
-	let rest = if line_end < input.len()
-	{
-		&input[line_end + 1..]
-	}
-	else
-	{
-		&input[line_end..]
-	};
+	let tid = utf8_field(fields.get(bed3_fields::TID), "chrom", options.utf8_policy)?;
+	let start_val = lexical_core::parse::<u64>(fields.get(bed3_fields::START))?;
+	let end_val = lexical_core::parse::<u64>(fields.get(bed3_fields::END))?;
 
 	Ok((
-		&rest,
+		rest,
 		Some((
 			tid,
 			Strand::Both,
@@ -253,6 +239,11 @@ pub async fn parse_bed3_sink_simd<'a>(
 				n_fail: None,
 				n_diff: None,
 				n_nocall: None,
+				kind: BedKind::Bed3,
+				bed12: None,
+				extra: None,
+				fields: None,
+				extra_names: &[],
 			},
 		)),
 	))
@@ -261,75 +252,41 @@ pub async fn parse_bed3_sink_simd<'a>(
 pub async fn parse_bed4_sink_simd<'a>(
 	input: &'a [u8],
 	filter_ctx: Option<&ReadFilterContext>,
-) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 {
-	if input.is_empty() || input[0] == b'\n'
-	{
-		let rest = memchr::memchr(b'\n', input)
-			.map(|p| p + 1)
-			.unwrap_or(input.len());
-		return Ok((&input[rest..], None));
-	}
-
-	let line_end = memchr::memchr(b'\n', input).unwrap_or(input.len());
-	let line = &input[..line_end];
+	let (line, rest) = next_line(input);
+	let fields = tokenize(line, options.tokenizer_mode, false, "BED4")?;
 
-	let mut fields = [0usize; 32];
-	let mut n = 0;
-	let mut start_idx = 0;
-
-	for (i, &b) in line.iter().enumerate()
+	if fields.is_empty()
 	{
-		if b == b' ' || b == b'\t'
-		{
-			fields[n] = start_idx;
-			n += 1;
-			start_idx = i + 1;
-		}
+		return Ok((rest, None));
 	}
 
-	if (n + 1) != bed4_fields::N_FIELDS
+	if fields.len() != bed4_fields::N_FIELDS
 	{
 		return Err(error::Error::BedMismatch("BED4".into()));
 	}
 
-	fields[n] = start_idx;
-
-	let tid = unsafe {
-		std::str::from_utf8_unchecked(
-			&line[fields[bed3_fields::TID]..fields[bed3_fields::START] - 1],
-		)
-	};
-	let start_val = lexical_core::parse::<u64>(
-		&line[fields[bed3_fields::START]..fields[bed3_fields::END] - 1],
-	)?;
-	let end_val =
-		lexical_core::parse::<u64>(&line[fields[bed3_fields::END]..fields[bed4_fields::NAME] - 1])?;
-	let name = &line[fields[bed4_fields::NAME]..fields[line.len()]];
-
-	let rest = if line_end < input.len()
-	{
-		&input[line_end + 1..]
-	}
-	else
-	{
-		&input[line_end..]
-	};
+	let tid = utf8_field(fields.get(bed3_fields::TID), "chrom", options.utf8_policy)?;
+	let start_val = lexical_core::parse::<u64>(fields.get(bed3_fields::START))?;
+	let end_val = lexical_core::parse::<u64>(fields.get(bed3_fields::END))?;
+	let name = fields.get(bed4_fields::NAME);
 
 	if let Some(ctx) = filter_ctx
 	{
 		if !ctx
-			.passes(tid, start_val, end_val, Strand::Both, Some(&name), None)
+			.passes(&tid, start_val, end_val, Strand::Both, Some(&name), None)
 			.await
 		{
-			return Ok((&rest, None));
+			return Ok((rest, None));
 		}
 	}
 
-	let name = unsafe { std::str::from_utf8_unchecked(name) }.to_owned();
+	let name = decode_name(&utf8_field(name, "name", options.utf8_policy)?).into_owned();
 
 	Ok((
-		&rest,
+		rest,
 		Some((
 			tid,
 			Strand::Both,
@@ -347,6 +304,11 @@ pub async fn parse_bed4_sink_simd<'a>(
 				n_fail: None,
 				n_diff: None,
 				n_nocall: None,
+				kind: BedKind::Bed4,
+				bed12: None,
+				extra: None,
+				fields: None,
+				extra_names: &[],
 			},
 		)),
 	))
@@ -355,67 +317,33 @@ pub async fn parse_bed4_sink_simd<'a>(
 pub async fn parse_bed5_sink_simd<'a>(
 	input: &'a [u8],
 	filter_ctx: Option<&ReadFilterContext>,
-) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 {
-	if input.is_empty() || input[0] == b'\n'
-	{
-		let rest = memchr::memchr(b'\n', input)
-			.map(|p| p + 1)
-			.unwrap_or(input.len());
-		return Ok((&input[rest..], None));
-	}
-
-	let line_end = memchr::memchr(b'\n', input).unwrap_or(input.len());
-	let line = &input[..line_end];
+	let (line, rest) = next_line(input);
+	let fields = tokenize(line, options.tokenizer_mode, false, "BED5")?;
 
-	let mut fields = [0usize; 32];
-	let mut n = 0;
-	let mut start_idx = 0;
-
-	for (i, &b) in line.iter().enumerate()
+	if fields.is_empty()
 	{
-		if b == b' ' || b == b'\t'
-		{
-			fields[n] = start_idx;
-			n += 1;
-			start_idx = i + 1;
-		}
+		return Ok((rest, None));
 	}
 
-	if (n + 1) != bed5_fields::N_FIELDS
+	if fields.len() != bed5_fields::N_FIELDS
 	{
 		return Err(error::Error::BedMismatch("BED5".into()));
 	}
 
-	fields[n] = start_idx;
-
-	let tid = unsafe {
-		std::str::from_utf8_unchecked(
-			&line[fields[bed3_fields::TID]..fields[bed3_fields::START] - 1],
-		)
-	};
-	let start_val = lexical_core::parse::<u64>(
-		&line[fields[bed3_fields::START]..fields[bed3_fields::END] - 1],
-	)?;
-	let end_val =
-		lexical_core::parse::<u64>(&line[fields[bed3_fields::END]..fields[bed4_fields::NAME] - 1])?;
-	let name = &line[fields[bed4_fields::NAME]..fields[bed5_fields::SCORE] - 1];
-	let score = lexical_core::parse::<u32>(&line[fields[bed5_fields::SCORE]..fields[line.len()]])?;
-
-	let rest = if line_end < input.len()
-	{
-		&input[line_end + 1..]
-	}
-	else
-	{
-		&input[line_end..]
-	};
+	let tid = utf8_field(fields.get(bed3_fields::TID), "chrom", options.utf8_policy)?;
+	let start_val = lexical_core::parse::<u64>(fields.get(bed3_fields::START))?;
+	let end_val = lexical_core::parse::<u64>(fields.get(bed3_fields::END))?;
+	let name = fields.get(bed4_fields::NAME);
+	let score = lexical_core::parse::<u32>(fields.get(bed5_fields::SCORE))?;
 
 	if let Some(ctx) = filter_ctx
 	{
 		if !ctx
 			.passes(
-				tid,
+				&tid,
 				start_val,
 				end_val,
 				Strand::Both,
@@ -424,14 +352,14 @@ pub async fn parse_bed5_sink_simd<'a>(
 			)
 			.await
 		{
-			return Ok((&rest, None));
+			return Ok((rest, None));
 		}
 	}
 
-	let name = unsafe { std::str::from_utf8_unchecked(name) }.to_owned();
+	let name = decode_name(&utf8_field(name, "name", options.utf8_policy)?).into_owned();
 
 	Ok((
-		&rest,
+		rest,
 		Some((
 			tid,
 			Strand::Both,
@@ -449,6 +377,11 @@ pub async fn parse_bed5_sink_simd<'a>(
 				n_fail: None,
 				n_diff: None,
 				n_nocall: None,
+				kind: BedKind::Bed5,
+				bed12: None,
+				extra: None,
+				fields: None,
+				extra_names: &[],
 			},
 		)),
 	))
@@ -457,70 +390,71 @@ pub async fn parse_bed5_sink_simd<'a>(
 pub async fn parse_bed6_sink_simd<'a>(
 	input: &'a [u8],
 	filter_ctx: Option<&ReadFilterContext>,
-) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 {
-	if input.is_empty() || input[0] == b'\n'
-	{
-		let rest = memchr::memchr(b'\n', input)
-			.map(|p| p + 1)
-			.unwrap_or(input.len());
-		return Ok((&input[rest..], None));
-	}
+	parse_bed6_columns(input, filter_ctx, options, Trailing::None).await
+}
 
-	let line_end = memchr::memchr(b'\n', input).unwrap_or(input.len());
-	let line = &input[..line_end];
+// BED6+N formats such as narrowPeak, the columns after the sixth are ignored
+pub async fn parse_bed6_prefix_sink_simd<'a>(
+	input: &'a [u8],
+	filter_ctx: Option<&ReadFilterContext>,
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
+{
+	parse_bed6_columns(input, filter_ctx, options, Trailing::Ignore).await
+}
 
-	let mut fields = [0usize; 32];
-	let mut n = 0;
-	let mut start_idx = 0;
+// what to do with columns after the sixth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trailing
+{
+	None,
+	Ignore,
+	// exactly this many, kept as text in the value
+	Keep(usize),
+}
 
-	for (i, &b) in line.iter().enumerate()
-	{
-		if b == b' ' || b == b'\t'
-		{
-			fields[n] = start_idx;
-			n += 1;
-			start_idx = i + 1;
-		}
-	}
+pub(crate) async fn parse_bed6_columns<'a>(
+	input: &'a [u8],
+	filter_ctx: Option<&ReadFilterContext>,
+	options: ParseOptions,
+	trailing: Trailing,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
+{
+	let (line, rest) = next_line(input);
+	let fields = tokenize(line, options.tokenizer_mode, false, "BED6")?;
 
-	if (n + 1) != bed6_fields::N_FIELDS
+	if fields.is_empty()
 	{
-		return Err(error::Error::BedMismatch("BED6".into()));
+		return Ok((rest, None));
 	}
 
-	fields[n] = start_idx;
-
-	let tid = unsafe {
-		std::str::from_utf8_unchecked(
-			&line[fields[bed3_fields::TID]..fields[bed3_fields::START] - 1],
-		)
+	let expected = match trailing
+	{
+		Trailing::None => fields.len() == bed6_fields::N_FIELDS,
+		Trailing::Ignore => fields.len() >= bed6_fields::N_FIELDS,
+		Trailing::Keep(n) => fields.len() == bed6_fields::N_FIELDS + n,
 	};
-	let start_val = lexical_core::parse::<u64>(
-		&line[fields[bed3_fields::START]..fields[bed3_fields::END] - 1],
-	)?;
-	let end_val =
-		lexical_core::parse::<u64>(&line[fields[bed3_fields::END]..fields[bed4_fields::NAME] - 1])?;
-	let name = &line[fields[bed4_fields::NAME]..fields[bed5_fields::SCORE] - 1];
-	let score = lexical_core::parse::<u32>(
-		&line[fields[bed5_fields::SCORE]..fields[bed6_fields::STRAND] - 1],
-	)?;
-	let strand = Strand::from(line[fields[bed6_fields::STRAND]]);
-
-	let rest = if line_end < input.len()
+
+	if !expected
 	{
-		&input[line_end + 1..]
+		return Err(error::Error::BedMismatch("BED6".into()));
 	}
-	else
-	{
-		&input[line_end..]
-	};
+
+	let tid = utf8_field(fields.get(bed3_fields::TID), "chrom", options.utf8_policy)?;
+	let start_val = lexical_core::parse::<u64>(fields.get(bed3_fields::START))?;
+	let end_val = lexical_core::parse::<u64>(fields.get(bed3_fields::END))?;
+	let name = fields.get(bed4_fields::NAME);
+	let score = lexical_core::parse::<u32>(fields.get(bed5_fields::SCORE))?;
+	let strand = strand_field(fields.get(bed6_fields::STRAND));
 
 	if let Some(ctx) = filter_ctx
 	{
 		if !ctx
 			.passes(
-				tid,
+				&tid,
 				start_val,
 				end_val,
 				strand,
@@ -529,14 +463,26 @@ pub async fn parse_bed6_sink_simd<'a>(
 			)
 			.await
 		{
-			return Ok((&rest, None));
+			return Ok((rest, None));
 		}
 	}
 
-	let name = unsafe { std::str::from_utf8_unchecked(name) }.to_owned();
+	let name = decode_name(&utf8_field(name, "name", options.utf8_policy)?).into_owned();
+
+	let extra = match trailing
+	{
+		Trailing::Keep(_) => Some(
+			(bed6_fields::N_FIELDS..fields.len())
+				.map(|i| {
+					Ok(utf8_field(fields.get(i), "extra column", options.utf8_policy)?.into_owned())
+				})
+				.collect::<error::Result<Box<[String]>>>()?,
+		),
+		_ => None,
+	};
 
 	Ok((
-		&rest,
+		rest,
 		Some((
 			tid,
 			strand,
@@ -554,78 +500,90 @@ pub async fn parse_bed6_sink_simd<'a>(
 				n_fail: None,
 				n_diff: None,
 				n_nocall: None,
+				kind: BedKind::Bed6,
+				bed12: None,
+				extra,
+				fields: None,
+				extra_names: &[],
 			},
 		)),
 	))
 }
 
-pub async fn parse_bed12_sink_simd<'a>(
+// the parser for kind, used when a track line switches away from the reader's own
+pub(crate) async fn parse_kind_sink<'a>(
+	kind: BedKind,
 	input: &'a [u8],
 	filter_ctx: Option<&ReadFilterContext>,
-) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 {
-	if input.is_empty() || input[0] == b'\n'
+	match kind
 	{
-		let rest = memchr::memchr(b'\n', input)
-			.map(|p| p + 1)
-			.unwrap_or(input.len());
-		return Ok((&input[rest..], None));
+		BedKind::Bed3 => parse_bed3_sink_simd(input, filter_ctx, options).await,
+		BedKind::Bed4 => parse_bed4_sink_simd(input, filter_ctx, options).await,
+		BedKind::Bed5 => parse_bed5_sink_simd(input, filter_ctx, options).await,
+		BedKind::Bed6 => parse_bed6_sink_simd(input, filter_ctx, options).await,
+		BedKind::Bed12 => parse_bed12_sink_simd(input, filter_ctx, options).await,
+		BedKind::BedMethyl => parse_bedmethyl_sink_simd(input, filter_ctx, options).await,
 	}
+}
+
+// doubled separators leave the column empty, read as unstranded rather than indexed into
+fn strand_field(field: &[u8]) -> Strand
+{
+	Strand::from(field.first().copied().unwrap_or(b'.'))
+}
 
-	let line_end = memchr::memchr(b'\n', input).unwrap_or(input.len());
-	let line = &input[..line_end];
+// comma separated, with or without the trailing comma UCSC writes
+fn parse_u32_list(field: &[u8]) -> error::Result<Vec<u32>>
+{
+	field
+		.split(|&b| b == b',')
+		.map(|value| value.trim_ascii())
+		.filter(|value| !value.is_empty())
+		.map(|value| Ok(lexical_core::parse::<u32>(value)?))
+		.collect()
+}
 
-	let mut fields = [0usize; 32];
-	let mut n = 0;
-	let mut start_idx = 0;
+pub async fn parse_bed12_sink_simd<'a>(
+	input: &'a [u8],
+	filter_ctx: Option<&ReadFilterContext>,
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
+{
+	let (line, rest) = next_line(input);
+	let fields = tokenize(line, options.tokenizer_mode, false, "BED12")?;
 
-	for (i, &b) in line.iter().enumerate()
+	if fields.is_empty()
 	{
-		if b == b' ' || b == b'\t'
-		{
-			fields[n] = start_idx;
-			n += 1;
-			start_idx = i + 1;
-		}
+		return Ok((rest, None));
 	}
 
-	if (n + 1) != bed12_fields::N_FIELDS
+	if fields.len() != bed12_fields::N_FIELDS
 	{
 		return Err(error::Error::BedMismatch("BED12".into()));
 	}
 
-	fields[n] = start_idx;
-
-	let tid = unsafe {
-		std::str::from_utf8_unchecked(
-			&line[fields[bed3_fields::TID]..fields[bed3_fields::START] - 1],
-		)
-	};
-	let start_val = lexical_core::parse::<u64>(
-		&line[fields[bed3_fields::START]..fields[bed3_fields::END] - 1],
-	)?;
-	let end_val =
-		lexical_core::parse::<u64>(&line[fields[bed3_fields::END]..fields[bed4_fields::NAME] - 1])?;
-	let name = &line[fields[bed4_fields::NAME]..fields[bed5_fields::SCORE] - 1];
-	let score = lexical_core::parse::<u32>(
-		&line[fields[bed5_fields::SCORE]..fields[bed6_fields::STRAND] - 1],
-	)?;
-	let strand = Strand::from(line[fields[bed6_fields::STRAND]]);
-
-	let rest = if line_end < input.len()
-	{
-		&input[line_end + 1..]
-	}
-	else
-	{
-		&input[line_end..]
-	};
+	let tid = utf8_field(fields.get(bed3_fields::TID), "chrom", options.utf8_policy)?;
+	let start_val = lexical_core::parse::<u64>(fields.get(bed3_fields::START))?;
+	let end_val = lexical_core::parse::<u64>(fields.get(bed3_fields::END))?;
+	let name = fields.get(bed4_fields::NAME);
+	let score = lexical_core::parse::<u32>(fields.get(bed5_fields::SCORE))?;
+	let strand = strand_field(fields.get(bed6_fields::STRAND));
+	let thick_start = lexical_core::parse::<u64>(fields.get(bed12_fields::THICK_START))?;
+	let thick_end = lexical_core::parse::<u64>(fields.get(bed12_fields::THICK_END))?;
+	let item_rgb_raw =
+		utf8_field(fields.get(bed12_fields::ITEM_RGB), "itemRgb", options.utf8_policy)?;
+	let block_count = lexical_core::parse::<u32>(fields.get(bed12_fields::BLOCK_COUNT))?;
+	let block_sizes = parse_u32_list(fields.get(bed12_fields::BLOCK_SIZES))?;
+	let block_starts = parse_u32_list(fields.get(bed12_fields::BLOCK_STARTS))?;
 
 	if let Some(ctx) = filter_ctx
 	{
 		if !ctx
 			.passes(
-				tid,
+				&tid,
 				start_val,
 				end_val,
 				strand,
@@ -634,14 +592,14 @@ pub async fn parse_bed12_sink_simd<'a>(
 			)
 			.await
 		{
-			return Ok((&rest, None));
+			return Ok((rest, None));
 		}
 	}
 
-	let name = unsafe { std::str::from_utf8_unchecked(name) }.to_owned();
+	let name = decode_name(&utf8_field(name, "name", options.utf8_policy)?).into_owned();
 
 	Ok((
-		&rest,
+		rest,
 		Some((
 			tid,
 			strand,
@@ -659,6 +617,21 @@ pub async fn parse_bed12_sink_simd<'a>(
 				n_fail: None,
 				n_diff: None,
 				n_nocall: None,
+				kind: BedKind::Bed12,
+				bed12: Some(Box::new(Bed12Columns {
+					chrom_start: start_val,
+					chrom_end: end_val,
+					thick_start,
+					thick_end,
+					item_rgb: ItemRgb::parse(&item_rgb_raw),
+					item_rgb_raw: item_rgb_raw.into_owned(),
+					block_count,
+					block_sizes,
+					block_starts,
+				})),
+				extra: None,
+				fields: None,
+				extra_names: &[],
 			},
 		)),
 	))
@@ -667,102 +640,64 @@ pub async fn parse_bed12_sink_simd<'a>(
 pub async fn parse_bedmethyl_sink_simd<'a>(
 	input: &'a [u8],
 	filter_ctx: Option<&ReadFilterContext>,
-) -> error::Result<(&'a [u8], Option<(&'a str, Strand, u64, u64, BedSinkValue)>)>
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
 {
-	if input.is_empty() || input[0] == b'\n'
-	{
-		let rest = memchr::memchr(b'\n', input)
-			.map(|p| p + 1)
-			.unwrap_or(input.len());
-		return Ok((&input[rest..], None));
-	}
+	let (line, rest) = next_line(input);
 
-	let line_end = memchr::memchr(b'\n', input).unwrap_or(input.len());
-	let mut line = &input[..line_end];
+	// modkit writes columns 10-18 space separated inside the last tab delimited field, both
+	// modkit layouts tokenize into the same 18 fields
+	let fields = tokenize(line, options.tokenizer_mode, true, "BEDMethyl")?;
 
-	if line.ends_with(b"\r")
+	let legacy = match fields.len()
 	{
-		line = &line[..line.len() - 1];
-	}
-
-	let mut fields = [0usize; 32];
-	let mut n = 0;
-	let mut start_idx = 0;
-
-	for (i, &b) in line.iter().enumerate()
-	{
-		if b == b' ' || b == b'\t' || b == b'\r'
-		{
-			fields[n] = start_idx;
-			n += 1;
-			start_idx = i + 1;
-		}
-	}
-
-	if (n + 1) != bedmethyl_fields::N_FIELDS
-	{
-		return Err(error::Error::BedMismatch("BEDMethyl".into()));
-	}
-
-	fields[n] = start_idx;
+		0 => return Ok((rest, None)),
+		bedmethyl_fields::N_FIELDS => false,
+		bedmethyl_fields::N_LEGACY_FIELDS => true,
+		_ => return Err(error::Error::BedMismatch("BEDMethyl".into())),
+	};
 
-	let tid = unsafe {
-		std::str::from_utf8_unchecked(
-			&line[fields[bed3_fields::TID]..fields[bed3_fields::START] - 1],
+	let field = |i: usize| fields.get(i);
+
+	let tid = utf8_field(field(bed3_fields::TID), "chrom", options.utf8_policy)?;
+	let start_val = lexical_core::parse::<u64>(field(bed3_fields::START))?;
+	let end_val = lexical_core::parse::<u64>(field(bed3_fields::END))?;
+	let name = field(bed4_fields::NAME);
+	let score = lexical_core::parse::<u32>(field(bed5_fields::SCORE))?;
+	let strand = strand_field(field(bed6_fields::STRAND));
+
+	let n_valid_cov = lexical_core::parse::<u32>(field(bedmethyl_fields::N_VALID_COV))?;
+	let frac_mod = lexical_core::parse::<f32>(field(bedmethyl_fields::FRAC_MOD))?;
+
+	let (n_mod, counts) = if !legacy
+	{
+		(
+			lexical_core::parse::<u32>(field(bedmethyl_fields::N_MOD))?,
+			Some([
+				lexical_core::parse::<u32>(field(bedmethyl_fields::N_CANONICAL))?,
+				lexical_core::parse::<u32>(field(bedmethyl_fields::N_OTHER_MOD))?,
+				lexical_core::parse::<u32>(field(bedmethyl_fields::N_DELETE))?,
+				lexical_core::parse::<u32>(field(bedmethyl_fields::N_FAIL))?,
+				lexical_core::parse::<u32>(field(bedmethyl_fields::N_DIFF))?,
+				lexical_core::parse::<u32>(field(bedmethyl_fields::N_NOCALL))?,
+			]),
 		)
-	};
-	let start_val = lexical_core::parse::<u64>(
-		&line[fields[bed3_fields::START]..fields[bed3_fields::END] - 1],
-	)?;
-	let end_val =
-		lexical_core::parse::<u64>(&line[fields[bed3_fields::END]..fields[bed4_fields::NAME] - 1])?;
-	let name = &line[fields[bed4_fields::NAME]..fields[bed5_fields::SCORE] - 1];
-	let score = lexical_core::parse::<u32>(
-		&line[fields[bed5_fields::SCORE]..fields[bed6_fields::STRAND] - 1],
-	)?;
-	let strand = Strand::from(line[fields[bed6_fields::STRAND]]);
-
-	let n_valid_cov = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_VALID_COV]..fields[bedmethyl_fields::FRAC_MOD] - 1],
-	)?;
-	let frac_mod = lexical_core::parse::<f32>(
-		&line[fields[bedmethyl_fields::FRAC_MOD]..fields[bedmethyl_fields::N_MOD] - 1],
-	)?;
-	let n_mod = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_MOD]..fields[bedmethyl_fields::N_CANONICAL] - 1],
-	)?;
-	let n_canonical = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_CANONICAL]..fields[bedmethyl_fields::N_OTHER_MOD] - 1],
-	)?;
-	let n_other_mod = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_OTHER_MOD]..fields[bedmethyl_fields::N_DELETE] - 1],
-	)?;
-	let n_delete = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_DELETE]..fields[bedmethyl_fields::N_FAIL] - 1],
-	)?;
-	let n_fail = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_FAIL]..fields[bedmethyl_fields::N_DIFF] - 1],
-	)?;
-	let n_diff = lexical_core::parse::<u32>(
-		&line[fields[bedmethyl_fields::N_DIFF]..fields[bedmethyl_fields::N_NOCALL] - 1],
-	)?;
-	let n_nocall =
-		lexical_core::parse::<u32>(&line[fields[bedmethyl_fields::N_NOCALL]..line.len()])?;
-
-	let rest = if line_end < input.len()
-	{
-		&input[line_end + 1..]
 	}
 	else
 	{
-		&input[line_end..]
+		// ENCODE bedMethyl only has coverage and percent methylated, the modified count is
+		// recovered from the two
+		((n_valid_cov as f32 * frac_mod / 100.0).round() as u32, None)
 	};
 
 	if let Some(ctx) = filter_ctx
 	{
+		let [n_canonical, n_other_mod, n_delete, n_fail, n_diff, n_nocall] =
+			counts.unwrap_or_default();
+
 		if !ctx
 			.passes(
-				tid,
+				&tid,
 				start_val,
 				end_val,
 				strand,
@@ -782,14 +717,15 @@ pub async fn parse_bedmethyl_sink_simd<'a>(
 			)
 			.await
 		{
-			return Ok((&rest, None));
+			return Ok((rest, None));
 		}
 	}
 
-	let name = unsafe { std::str::from_utf8_unchecked(name) }.to_owned();
+	let name = decode_name(&utf8_field(name, "name", options.utf8_policy)?).into_owned();
+	let count = |i: usize| counts.map(|counts| counts[i]);
 
 	Ok((
-		&rest,
+		rest,
 		Some((
 			tid,
 			strand,
@@ -801,12 +737,17 @@ pub async fn parse_bedmethyl_sink_simd<'a>(
 				n_valid_cov: Some(n_valid_cov),
 				frac_mod: Some(frac_mod),
 				n_mod: Some(n_mod),
-				n_canonical: Some(n_canonical),
-				n_other_mod: Some(n_other_mod),
-				n_delete: Some(n_delete),
-				n_fail: Some(n_fail),
-				n_diff: Some(n_diff),
-				n_nocall: Some(n_nocall),
+				n_canonical: count(0),
+				n_other_mod: count(1),
+				n_delete: count(2),
+				n_fail: count(3),
+				n_diff: count(4),
+				n_nocall: count(5),
+				kind: BedKind::BedMethyl,
+				bed12: None,
+				extra: None,
+				fields: None,
+				extra_names: &[],
 			},
 		)),
 	))
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+use crate::bed::autooneshotreader::AutoOneShotBlockReaderTrait;
+use crate::bed::{BedRecord, CollectSink};
+use crate::error;
+use crate::store::TidResolver;
+
+// Pulls records one at a time with a lookahead, for merge joins across readers and for
+// wrappers that skip ahead to the first record of interest. Blocks are decoded
+// blocks_per_batch at a time and buffered until taken.
+pub struct PeekableReader<Rd, T>
+where
+	Rd: AutoOneShotBlockReaderTrait<T>,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	reader: Rd,
+	blocks_per_batch: usize,
+	buffered: VecDeque<BedRecord<T::Tid>>,
+	finished: bool,
+	_resolver: PhantomData<T>,
+}
+
+impl<Rd, T> PeekableReader<Rd, T>
+where
+	Rd: AutoOneShotBlockReaderTrait<T>,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	pub fn new(reader: Rd, blocks_per_batch: usize) -> Self
+	{
+		Self {
+			reader,
+			blocks_per_batch: blocks_per_batch.max(1),
+			buffered: VecDeque::new(),
+			finished: false,
+			_resolver: PhantomData,
+		}
+	}
+
+	// the next record without consuming it, None at the end of the input
+	pub async fn peek_line(&mut self) -> error::Result<Option<&BedRecord<T::Tid>>>
+	{
+		self.fill().await?;
+
+		Ok(self.buffered.front())
+	}
+
+	pub async fn next_line(&mut self) -> error::Result<Option<BedRecord<T::Tid>>>
+	{
+		self.fill().await?;
+
+		Ok(self.buffered.pop_front())
+	}
+
+	// takes records up to the first one the predicate rejects, which is left to peek at
+	pub async fn skip_while<P>(&mut self, mut predicate: P) -> error::Result<usize>
+	where
+		P: FnMut(&BedRecord<T::Tid>) -> bool,
+	{
+		let mut skipped = 0;
+
+		while let Some(record) = self.peek_line().await?
+		{
+			if !predicate(record)
+			{
+				break;
+			}
+
+			self.buffered.pop_front();
+			skipped += 1;
+		}
+
+		Ok(skipped)
+	}
+
+	pub fn get_ref(&self) -> &Rd
+	{
+		&self.reader
+	}
+
+	// records already decoded but not yet taken are dropped
+	pub fn into_inner(self) -> Rd
+	{
+		self.reader
+	}
+
+	async fn fill(&mut self) -> error::Result<()>
+	{
+		while self.buffered.is_empty() && !self.finished
+		{
+			let Some(block) = self.reader.next_bgzf_blocks(self.blocks_per_batch).await?
+			else
+			{
+				self.finished = true;
+				break;
+			};
+
+			let mut sink = CollectSink::new();
+			self.reader.read_tids_in_block_sink(block, &mut sink).await?;
+			self.buffered.extend(sink.drain());
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use crate::error;
+use crate::bed::{BedSinkValue, ParseOptions, Strand};
+use crate::bed::parser::{Trailing, parse_bed6_columns};
+use crate::filtering::ReadFilterContext;
+
+// The columns a BED6+N type adds after strand, usually derived with #[derive(BedFields)]
+// from the sandman-derive crate. The derive also implements BedFieldsSink so the type can
+// be used as the F of a OneShotBlockReader.
+pub trait BedPlusColumns: Sized
+{
+	const COLUMNS: &'static [&'static str];
+
+	fn parse_columns(columns: &[&str]) -> error::Result<Self>;
+
+	// each column preceded by a tab, to follow the BED6 prefix
+	fn write_columns(&self, out: &mut String);
+
+	// the typed columns of a record read with this type
+	fn from_value(value: &BedSinkValue) -> error::Result<Self>
+	{
+		let columns: Vec<&str> = value.extra_columns().iter().map(String::as_str).collect();
+
+		if columns.len() != Self::COLUMNS.len()
+		{
+			return Err(error::Error::VariantMismatch);
+		}
+
+		Self::parse_columns(&columns)
+	}
+}
+
+pub fn parse_column<V>(text: &str, column: &str) -> error::Result<V>
+where
+	V: FromStr,
+{
+	text.parse()
+		.map_err(|_| error::Error::Parse(format!("{column} column value {text}")))
+}
+
+// BED6 followed by exactly C::COLUMNS, which are checked here so a bad line fails where
+// it was read rather than when the record is used
+pub async fn parse_bed_plus_sink<'a, C>(
+	input: &'a [u8],
+	filter_ctx: Option<&ReadFilterContext>,
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
+where
+	C: BedPlusColumns,
+{
+	let (rest, mut parsed) =
+		parse_bed6_columns(input, filter_ctx, options, Trailing::Keep(C::COLUMNS.len())).await?;
+
+	if let Some((_, _, _, _, value)) = &mut parsed
+	{
+		value.extra_names = C::COLUMNS;
+		C::from_value(value)?;
+	}
+
+	Ok((rest, parsed))
+}
@@ -0,0 +1,101 @@
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+use crate::bed::framing::BgzfFraming;
+
+// called with (compressed bytes read, total compressed size when known)
+pub type ProgressCallback = Arc<dyn Fn(u64, Option<u64>) + Send + Sync>;
+
+// Tracks the compressed offset of the underlying source so progress can be reported
+// without knowing anything about the blocks read from it, and the block framing so a source
+// that stops partway through a block can be reported as truncated.
+#[derive(Debug)]
+pub(crate) struct CountingReader<R>
+{
+	inner: R,
+	position: Arc<AtomicU64>,
+	framing: Arc<Mutex<BgzfFraming>>,
+}
+
+impl<R> CountingReader<R>
+{
+	pub(crate) fn new(inner: R) -> Self
+	{
+		Self {
+			inner,
+			position: Arc::new(AtomicU64::new(0)),
+			framing: Arc::new(Mutex::new(BgzfFraming::default())),
+		}
+	}
+
+	pub(crate) fn position(&self) -> Arc<AtomicU64>
+	{
+		self.position.clone()
+	}
+
+	pub(crate) fn framing(&self) -> Arc<Mutex<BgzfFraming>>
+	{
+		self.framing.clone()
+	}
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R>
+{
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<std::io::Result<()>>
+	{
+		let before = buf.filled().len();
+		let had_room = buf.remaining() > 0;
+		let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+		if let Poll::Ready(Ok(())) = result
+		{
+			let read = &buf.filled()[before..];
+			self.position.fetch_add(read.len() as u64, Ordering::Relaxed);
+
+			// a read into a full buffer returns nothing without meaning the source ended
+			if had_room || !read.is_empty()
+			{
+				if let Ok(mut framing) = self.framing.lock()
+				{
+					framing.observe(read);
+				}
+			}
+		}
+
+		result
+	}
+}
+
+impl<R: AsyncSeek + Unpin> AsyncSeek for CountingReader<R>
+{
+	fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()>
+	{
+		Pin::new(&mut self.inner).start_seek(position)
+	}
+
+	fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<u64>>
+	{
+		let result = Pin::new(&mut self.inner).poll_complete(cx);
+
+		if let Poll::Ready(Ok(position)) = result
+		{
+			self.position.store(position, Ordering::Relaxed);
+
+			if let Ok(mut framing) = self.framing.lock()
+			{
+				framing.reset(position);
+			}
+		}
+
+		result
+	}
+}
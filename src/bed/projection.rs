@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+
+use crate::error;
+use crate::bed::{BedKind, BedSinkValue, ParseOptions, Strand, TokenizerMode};
+use crate::bed::parser::parse_bed6_prefix_sink_simd;
+use crate::bed::tokenizer::next_line;
+use crate::bed::utf8::utf8_field;
+
+// The columns a reader needs, the rest of each line is skipped without being tokenized.
+// Records from a projected reader carry the BED kind of what was kept. Readers with a
+// read filter parse whole lines so the filter sees every column it tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Projection
+{
+	#[default]
+	All,
+	// chrom, start and end
+	Coordinates,
+	// the BED6 columns of BED12 and bedMethyl lines
+	Bed6,
+}
+
+impl Projection
+{
+	pub(crate) fn applies_to(self, kind: BedKind) -> bool
+	{
+		match self
+		{
+			Self::All => false,
+			Self::Coordinates => true,
+			Self::Bed6 => matches!(kind, BedKind::Bed6 | BedKind::Bed12 | BedKind::BedMethyl),
+		}
+	}
+}
+
+// the first N fields of a line, found with memchr and leaving the rest of it untouched
+pub(crate) fn leading_fields<const N: usize>(
+	line: &[u8],
+	mode: TokenizerMode,
+) -> Option<[&[u8]; N]>
+{
+	let mut fields = [&line[..0]; N];
+	let mut rest = match mode
+	{
+		TokenizerMode::Tolerant => line.trim_ascii(),
+		TokenizerMode::StrictTabs => line,
+	};
+
+	for field in fields.iter_mut()
+	{
+		if mode == TokenizerMode::Tolerant
+		{
+			rest = rest.trim_ascii_start();
+		}
+
+		if rest.is_empty()
+		{
+			return None;
+		}
+
+		let end = match mode
+		{
+			TokenizerMode::Tolerant => memchr::memchr2(b'\t', b' ', rest),
+			TokenizerMode::StrictTabs => memchr::memchr(b'\t', rest),
+		}
+		.unwrap_or(rest.len());
+
+		*field = &rest[..end];
+		rest = &rest[(end + 1).min(rest.len())..];
+	}
+
+	Some(fields)
+}
+
+pub(crate) async fn parse_projected_sink<'a>(
+	projection: Projection,
+	input: &'a [u8],
+	options: ParseOptions,
+) -> error::Result<(&'a [u8], Option<(Cow<'a, str>, Strand, u64, u64, BedSinkValue)>)>
+{
+	if projection == Projection::Bed6
+	{
+		return parse_bed6_prefix_sink_simd(input, None, options).await;
+	}
+
+	let (line, rest) = next_line(input);
+
+	if line.trim_ascii().is_empty()
+	{
+		return Ok((rest, None));
+	}
+
+	let Some([tid, start, end]) = leading_fields::<3>(line, options.tokenizer_mode)
+	else
+	{
+		return Err(error::Error::BedMismatch("BED3".into()));
+	};
+
+	let tid = utf8_field(tid, "chrom", options.utf8_policy)?;
+	let start = lexical_core::parse::<u64>(start)?;
+	let end = lexical_core::parse::<u64>(end)?;
+
+	Ok((
+		rest,
+		Some((
+			tid,
+			Strand::Both,
+			start,
+			end,
+			BedSinkValue {
+				name: None,
+				score: None,
+				n_valid_cov: None,
+				frac_mod: None,
+				n_mod: None,
+				n_canonical: None,
+				n_other_mod: None,
+				n_delete: None,
+				n_fail: None,
+				n_diff: None,
+				n_nocall: None,
+				kind: BedKind::Bed3,
+				bed12: None,
+				extra: None,
+				fields: None,
+				extra_names: &[],
+			},
+		)),
+	))
+}
@@ -0,0 +1,216 @@
+use std::io::SeekFrom;
+use std::ops::Range;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, BufReader as TokioBufReader};
+
+use pufferfish::prelude::*;
+
+use crate::bed::oneshotreader::Reopen;
+use crate::error;
+use crate::tabix::{ExtractOptions, extract_subblock};
+
+const HEADER_LEN: usize = 18;
+
+// Splits sorted chunks into at most n runs of neighbours, so each source fetches one run.
+pub(crate) fn group_chunks(chunks: Vec<Range<u64>>, n: usize) -> Vec<Vec<Range<u64>>>
+{
+	let per_group = chunks.len().div_ceil(n.max(1)).max(1);
+
+	chunks
+		.chunks(per_group)
+		.map(<[Range<u64>]>::to_vec)
+		.collect()
+}
+
+// Another source from reopen, opened on the blocking pool as opening a file blocks
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) async fn open_source<R>(reopen: &Reopen<R>) -> error::Result<R>
+where
+	R: std::marker::Send + 'static,
+{
+	let reopen = std::sync::Arc::clone(reopen);
+	tokio::task::spawn_blocking(move || reopen()).await.map_err(std::io::Error::other)?
+}
+
+// wasm32 has no threads to hand it to, and its sources open without blocking
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) async fn open_source<R>(reopen: &Reopen<R>) -> error::Result<R>
+where
+	R: std::marker::Send + 'static,
+{
+	reopen()
+}
+
+// The decompressed bytes a chunk of virtual offsets covers. The compressed range is read
+// in one go, the last block's length coming from its header.
+pub(crate) async fn read_chunk<R>(
+	reader: &mut R,
+	chunk: &Range<u64>,
+	options: &ExtractOptions,
+) -> error::Result<Vec<u8>>
+where
+	R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
+{
+	let (first_block, last_block) = (chunk.start >> 16, chunk.end >> 16);
+
+	// the span comes from the index, so it is checked against the data before allocating
+	let data_len = reader.seek(SeekFrom::End(0)).await?;
+
+	if last_block < first_block || last_block > data_len
+	{
+		return Err(error::Error::IndexOffset(format!(
+			"chunk {}..{} is outside the {data_len} bytes of data",
+			chunk.start, chunk.end
+		)));
+	}
+
+	reader.seek(SeekFrom::Start(first_block)).await?;
+
+	let mut compressed = vec![0u8; (last_block - first_block) as usize];
+	reader.read_exact(&mut compressed).await?;
+
+	// a chunk ending at the start of a block reads nothing from it, and that block may be
+	// past the end of the file
+	if chunk.end & 0xffff != 0
+	{
+		let mut header = [0u8; HEADER_LEN];
+		reader.read_exact(&mut header).await?;
+
+		let mut rest = vec![0u8; block_size(&header).saturating_sub(HEADER_LEN)];
+		reader.read_exact(&mut rest).await?;
+
+		compressed.extend_from_slice(&header);
+		compressed.extend_from_slice(&rest);
+	}
+
+	let offsets = block_offsets(&compressed, first_block)?;
+
+	let mut blocks = TokioBufReader::new(std::io::Cursor::new(&compressed));
+	let mut bytes = Vec::new();
+
+	for offset in offsets
+	{
+		let Some(block) = blocks
+			.read_and_decompress_bgzf_block(Some(is_bgzf_eof))
+			.await?
+		else
+		{
+			break;
+		};
+
+		bytes.extend_from_slice(extract_subblock(&block, offset, chunk, options)?);
+	}
+
+	Ok(bytes)
+}
+
+// compressed offsets of the whole blocks in bytes, which start at first_block
+fn block_offsets(bytes: &[u8], first_block: u64) -> error::Result<Vec<u64>>
+{
+	let mut offsets = Vec::new();
+	let mut position = 0;
+
+	while position < bytes.len()
+	{
+		let offset = first_block + position as u64;
+
+		let Some(header) = bytes.get(position..position + HEADER_LEN)
+		else
+		{
+			return Err(error::Error::IndexOffset(format!("no BGZF block at {offset:#x}")));
+		};
+
+		if header[..4] != [0x1f, 0x8b, 0x08, 0x04]
+		{
+			return Err(error::Error::IndexOffset(format!("no BGZF block at {offset:#x}")));
+		}
+
+		offsets.push(offset);
+		position += block_size(header);
+	}
+
+	Ok(offsets)
+}
+
+// Compressed offsets of the blocks a chunk touches, found by hopping from header to header
+// rather than reading the blocks.
+pub(crate) async fn chunk_blocks<R>(reader: &mut R, chunk: &Range<u64>) -> error::Result<Vec<u64>>
+where
+	R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
+{
+	let (first_block, last_block) = (chunk.start >> 16, chunk.end >> 16);
+	let through_last = chunk.end & 0xffff != 0;
+
+	let mut offsets = Vec::new();
+	let mut offset = first_block;
+
+	while offset < last_block || (offset == last_block && through_last)
+	{
+		reader.seek(SeekFrom::Start(offset)).await?;
+
+		let mut header = [0u8; HEADER_LEN];
+		reader.read_exact(&mut header).await?;
+
+		offsets.push(offset);
+		offset += block_size(&header) as u64;
+	}
+
+	Ok(offsets)
+}
+
+// The whole lines of the block at offset that lie within chunk. Lines running in from the
+// block before or out to the block after are dropped, as they belong to the blocks either
+// side.
+pub(crate) async fn read_block_lines<R>(
+	reader: &mut R,
+	offset: u64,
+	chunk: &Range<u64>,
+) -> error::Result<Vec<u8>>
+where
+	R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
+{
+	reader.seek(SeekFrom::Start(offset)).await?;
+
+	let mut compressed = vec![0u8; HEADER_LEN];
+	reader.read_exact(&mut compressed).await?;
+
+	let mut rest = vec![0u8; block_size(&compressed).saturating_sub(HEADER_LEN)];
+	reader.read_exact(&mut rest).await?;
+	compressed.extend_from_slice(&rest);
+
+	let Some(block) = TokioBufReader::new(std::io::Cursor::new(&compressed))
+		.read_and_decompress_bgzf_block(Some(is_bgzf_eof))
+		.await?
+	else
+	{
+		return Ok(Vec::new());
+	};
+
+	let start = if offset == chunk.start >> 16
+	{
+		(chunk.start & 0xffff) as usize
+	}
+	else
+	{
+		memchr::memchr(b'\n', &block).map_or(block.len(), |idx| idx + 1)
+	};
+
+	let end = if offset == chunk.end >> 16
+	{
+		(chunk.end & 0xffff) as usize
+	}
+	else
+	{
+		memchr::memrchr(b'\n', &block).map_or(0, |idx| idx + 1)
+	};
+
+	let end = end.min(block.len());
+
+	Ok(block.get(start..end).map(<[u8]>::to_vec).unwrap_or_default())
+}
+
+// the whole length of a block, from the BSIZE its header holds
+fn block_size(header: &[u8]) -> usize
+{
+	u16::from_le_bytes([header[16], header[17]]) as usize + 1
+}
@@ -0,0 +1,216 @@
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use std::future;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::bed::autooneshotreader::AutoOneShotBlockReaderTrait;
+use crate::bed::{BedRecord, CollectSink, GenomicInterval, Strand};
+use crate::error;
+use crate::region::CoordinateSystem;
+use crate::store::TidResolver;
+
+// every record of the reader in file order, blocks_per_batch BGZF blocks are decoded at a
+// time. The stream ends after the first error.
+pub fn record_stream<Rd, T>(
+	reader: Rd,
+	blocks_per_batch: usize,
+) -> impl Stream<Item = error::Result<BedRecord<T::Tid>>>
+where
+	Rd: AutoOneShotBlockReaderTrait<T>,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	stream::try_unfold(reader, move |mut reader| async move {
+		let Some(block) = reader.next_bgzf_blocks(blocks_per_batch).await?
+		else
+		{
+			return Ok(None);
+		};
+
+		let mut sink = CollectSink::new();
+		reader.read_tids_in_block_sink(block, &mut sink).await?;
+
+		let records: Vec<_> = sink.drain().collect();
+		Ok(Some((records, reader)))
+	})
+	.map_ok(|records| stream::iter(records.into_iter().map(Ok)))
+	.try_flatten()
+}
+
+// Lines of a plain text file turned into items by parse, which gives None for lines to
+// skip such as headers. The stream ends after the first error.
+pub(crate) fn parsed_lines<R, T, F>(reader: R, parse: F) -> impl Stream<Item = error::Result<T>>
+where
+	R: AsyncBufRead + std::marker::Unpin,
+	F: FnMut(&[u8]) -> error::Result<Option<T>>,
+{
+	stream::unfold(
+		(reader, parse, Vec::new(), false),
+		|(mut reader, mut parse, mut line, done)| async move {
+			if done
+			{
+				return None;
+			}
+
+			loop
+			{
+				line.clear();
+
+				let item = match reader.read_until(b'\n', &mut line).await
+				{
+					Ok(0) => return None,
+					Ok(_) => match parse(&line)
+					{
+						Ok(Some(item)) => Ok(item),
+						Ok(None) => continue,
+						Err(e) => Err(e),
+					},
+					Err(e) => Err(e.into()),
+				};
+
+				let done = item.is_err();
+				return Some((item, (reader, parse, line, done)));
+			}
+		},
+	)
+}
+
+// combinators over record streams, errors pass through untouched
+pub trait RecordStreamExt<Tid>: Stream<Item = error::Result<BedRecord<Tid>>> + Sized
+where
+	Tid: PartialEq + Clone,
+{
+	fn filter_region<I>(self, region: I) -> impl Stream<Item = error::Result<BedRecord<Tid>>>
+	where
+		I: GenomicInterval<Tid = Tid>,
+	{
+		self.try_filter(move |record| future::ready(record.overlaps(&region)))
+	}
+
+	// exact match, Strand::Both keeps only unstranded records
+	fn filter_strand(self, strand: Strand) -> impl Stream<Item = error::Result<BedRecord<Tid>>>
+	{
+		self.try_filter(move |record| future::ready(record.strand == strand))
+	}
+
+	fn map_coordinates(
+		self,
+		from: CoordinateSystem,
+		to: CoordinateSystem,
+	) -> impl Stream<Item = error::Result<BedRecord<Tid>>>
+	{
+		self.map_ok(move |mut record| {
+			(record.start, record.end) = from.convert(record.start, record.end, to);
+			record
+		})
+	}
+
+	// Sorted input only. Overlapping or touching records on the same tid become the first
+	// of them with its end extended, the other records' columns are dropped.
+	fn merge_overlapping(self) -> impl Stream<Item = error::Result<BedRecord<Tid>>>
+	{
+		stream::unfold(
+			(Box::pin(self), None::<BedRecord<Tid>>, false),
+			|(mut inner, mut pending, done)| async move {
+				if done
+				{
+					return None;
+				}
+
+				loop
+				{
+					match inner.next().await
+					{
+						Some(Ok(record)) =>
+						{
+							if let Some(current) = pending
+								.as_mut()
+								.filter(|current| {
+									current.tid == record.tid && record.start <= current.end
+								})
+							{
+								current.end = current.end.max(record.end);
+								continue;
+							}
+
+							if let Some(merged) = pending.replace(record)
+							{
+								return Some((Ok(merged), (inner, pending, false)));
+							}
+						}
+						Some(Err(e)) => return Some((Err(e), (inner, pending, false))),
+						None =>
+						{
+							return pending.take().map(|merged| (Ok(merged), (inner, None, true)));
+						}
+					}
+				}
+			},
+		)
+	}
+
+	// Sorted input only. Groups consecutive records on the same tid, so contigs can be handed
+	// to try_for_each_concurrent without buffering the whole file. A contig seen again after
+	// another one starts a new batch.
+	fn chunk_by_tid(self) -> impl Stream<Item = error::Result<(Tid, Vec<BedRecord<Tid>>)>>
+	{
+		stream::unfold(
+			(Box::pin(self), Vec::<BedRecord<Tid>>::new(), false),
+			|(mut inner, mut batch, done)| async move {
+				if done
+				{
+					return None;
+				}
+
+				loop
+				{
+					match inner.next().await
+					{
+						Some(Ok(record)) =>
+						{
+							if batch.first().is_none_or(|first| first.tid == record.tid)
+							{
+								batch.push(record);
+								continue;
+							}
+
+							let full = std::mem::replace(&mut batch, vec![record]);
+							let tid = full[0].tid.clone();
+							return Some((Ok((tid, full)), (inner, batch, false)));
+						}
+						Some(Err(e)) => return Some((Err(e), (inner, batch, false))),
+						None if batch.is_empty() => return None,
+						None =>
+						{
+							let tid = batch[0].tid.clone();
+							return Some((Ok((tid, batch)), (inner, Vec::new(), true)));
+						}
+					}
+				}
+			},
+		)
+	}
+
+	// Sorted input only. Ends the stream at the first record on tid starting at or past
+	// position, records on the contigs before it are all kept.
+	fn take_until_position(
+		self,
+		tid: Tid,
+		position: u64,
+	) -> impl Stream<Item = error::Result<BedRecord<Tid>>>
+	{
+		self.take_while(move |record| {
+			future::ready(match record
+			{
+				Ok(record) => record.tid != tid || record.start < position,
+				Err(_) => true,
+			})
+		})
+	}
+}
+
+impl<S, Tid> RecordStreamExt<Tid> for S
+where
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+	Tid: PartialEq + Clone,
+{
+}
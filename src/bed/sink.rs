@@ -1,5 +1,10 @@
 use std::fmt::Debug;
 
+use crate::bed::Bed12Columns;
+use crate::bed::BedKind;
+use crate::bed::FieldValue;
+use crate::bed::dynamic::DynFields;
+use crate::bed::ModCode;
 use crate::bed::ScoreField;
 use crate::bed::ReaderId;
 use crate::bed::SourceId;
@@ -22,6 +27,45 @@ pub struct BedSinkValue
 	pub(crate) n_fail: Option<u32>,
 	pub(crate) n_diff: Option<u32>,
 	pub(crate) n_nocall: Option<u32>,
+
+	// the format the record was parsed from
+	pub(crate) kind: BedKind,
+
+	// BED12 only
+	pub(crate) bed12: Option<Box<Bed12Columns>>,
+
+	// the columns after the sixth for BED6+N types, as written
+	pub(crate) extra: Option<Box<[String]>>,
+
+	// typed columns, for DynBedExtra readers opened with a ColumnSpec
+	pub(crate) fields: Option<Box<DynFields>>,
+
+	// the names of the extra columns when the type reading them declares them
+	pub(crate) extra_names: &'static [&'static str],
+}
+
+// a column past the standard BED ones, by name or by position among the extra columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomColumn<'a>
+{
+	Name(&'a str),
+	Index(usize),
+}
+
+impl<'a> From<&'a str> for CustomColumn<'a>
+{
+	fn from(name: &'a str) -> Self
+	{
+		Self::Name(name)
+	}
+}
+
+impl From<usize> for CustomColumn<'_>
+{
+	fn from(index: usize) -> Self
+	{
+		Self::Index(index)
+	}
 }
 
 impl BedSinkValue
@@ -64,8 +108,63 @@ impl BedSinkValue
 	{
 		self.name.as_deref()
 	}
+
+	pub fn kind(&self) -> BedKind
+	{
+		self.kind
+	}
+
+	pub fn bed12(&self) -> Option<&Bed12Columns>
+	{
+		self.bed12.as_deref()
+	}
+
+	pub fn extra_columns(&self) -> &[String]
+	{
+		self.extra.as_deref().unwrap_or_default()
+	}
+
+	// a column of a ColumnSpec other than name, score and strand
+	pub fn field(&self, name: &str) -> Option<&FieldValue>
+	{
+		self.fields.as_deref()?.get(name)
+	}
+
+	// Extra columns whichever way they were read. Columns typed by a ColumnSpec keep their
+	// type, BED6+N columns come back as text.
+	pub fn get_custom_value<'a>(&self, column: impl Into<CustomColumn<'a>>) -> Option<FieldValue>
+	{
+		let column = column.into();
+
+		if let Some(fields) = self.fields.as_deref()
+		{
+			return match column
+			{
+				CustomColumn::Name(name) => fields.get(name),
+				CustomColumn::Index(index) => fields.get_index(index),
+			}
+			.cloned();
+		}
+
+		let index = match column
+		{
+			CustomColumn::Name(name) => self.extra_names.iter().position(|column| *column == name)?,
+			CustomColumn::Index(index) => index,
+		};
+
+		self.extra_columns()
+			.get(index)
+			.map(|text| FieldValue::Text(text.clone()))
+	}
+
+	pub fn mod_code(&self) -> Option<ModCode>
+	{
+		self.name.as_deref().and_then(ModCode::parse)
+	}
 }
 
+// begin_tid and end_tid bracket a run of records on one tid and strand, so a tid is begun
+// again whenever the strand changes
 pub trait BedSink<Tid>: Send + Sync
 {
 	fn begin_tid(&mut self, tid: &Tid, strand: &Strand);
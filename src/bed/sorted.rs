@@ -0,0 +1,83 @@
+use crate::bed::{BedSink, BedSinkValue, ReaderId, SourceId, Strand};
+
+// where the last record was and which contigs are finished, carried between blocks
+#[derive(Debug, Clone)]
+pub(crate) struct SortState<Tid>
+{
+	last: Option<(Tid, u64)>,
+	finished: Vec<Tid>,
+}
+
+impl<Tid> Default for SortState<Tid>
+{
+	fn default() -> Self
+	{
+		Self {
+			last: None,
+			finished: Vec::new(),
+		}
+	}
+}
+
+impl<Tid> SortState<Tid>
+where
+	Tid: PartialEq + Clone,
+{
+	// false when the record starts before the one before it or returns to a finished contig
+	pub(crate) fn advance(&mut self, tid: &Tid, start: u64) -> bool
+	{
+		match &mut self.last
+		{
+			Some((last_tid, last_start)) if last_tid == tid =>
+			{
+				let in_order = start >= *last_start;
+				*last_start = (*last_start).max(start);
+				in_order
+			}
+			Some((last_tid, _)) =>
+			{
+				let returning = self.finished.contains(tid);
+				self.finished.push(last_tid.clone());
+				self.last = Some((tid.clone(), start));
+				!returning
+			}
+			None =>
+			{
+				self.last = Some((tid.clone(), start));
+				true
+			}
+		}
+	}
+}
+
+// a sink that drops everything, for passes that only want the reader's stats
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiscardSink;
+
+impl<Tid> BedSink<Tid> for DiscardSink
+{
+	fn begin_tid(&mut self, _tid: &Tid, _strand: &Strand)
+	{
+	}
+
+	fn end_tid(&mut self, _tid: &Tid, _strand: &Strand)
+	{
+	}
+
+	fn begin_position(&mut self, _start: u64)
+	{
+	}
+
+	fn end_position(&mut self, _end: u64)
+	{
+	}
+
+	fn push_value(
+		&mut self,
+		_source_id: &Option<SourceId>,
+		_reader_id: &ReaderId,
+		_value: BedSinkValue,
+	)
+	{
+	}
+}
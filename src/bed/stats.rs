@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) use std::time::Instant;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReaderStats
+{
+	pub blocks_read: u64,
+	pub bytes_decompressed: u64,
+	pub decompression_errors: u64,
+	pub lines_read: u64,
+	pub records_parsed: u64,
+	pub records_filtered: u64,
+	pub malformed_lines: u64,
+	// records out of order so far, see is_sorted
+	pub unsorted_records: u64,
+
+	// time spent per phase, summed over calls
+	pub read_time: Duration,
+	pub decompress_time: Duration,
+	pub parse_time: Duration,
+}
+
+impl ReaderStats
+{
+	// after a full pass, whether the input was sorted by contig and start
+	pub fn is_sorted(&self) -> bool
+	{
+		self.unsorted_records == 0
+	}
+}
+
+// Instant::now panics on wasm32-unknown-unknown, which has no clock without JavaScript, so
+// there the phase times stay at zero
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl Instant
+{
+	pub(crate) fn now() -> Self
+	{
+		Instant
+	}
+
+	pub(crate) fn elapsed(&self) -> Duration
+	{
+		Duration::ZERO
+	}
+}
+
+// counters are updated from &self in read_tids_in_block_sink, so they live in atomics
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters
+{
+	blocks_read: AtomicU64,
+	bytes_decompressed: AtomicU64,
+	decompression_errors: AtomicU64,
+	lines_read: AtomicU64,
+	records_parsed: AtomicU64,
+	records_filtered: AtomicU64,
+	malformed_lines: AtomicU64,
+	unsorted_records: AtomicU64,
+	read_nanos: AtomicU64,
+	decompress_nanos: AtomicU64,
+	parse_nanos: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase
+{
+	Read,
+	Decompress,
+	Parse,
+}
+
+impl StatsCounters
+{
+	pub(crate) fn add_blocks(&self, blocks: u64, bytes: u64, errors: u64)
+	{
+		self.blocks_read.fetch_add(blocks, Ordering::Relaxed);
+		self.bytes_decompressed.fetch_add(bytes, Ordering::Relaxed);
+		self.decompression_errors.fetch_add(errors, Ordering::Relaxed);
+	}
+
+	pub(crate) fn add_lines(&self, parsed: u64, filtered: u64, malformed: u64)
+	{
+		self.lines_read
+			.fetch_add(parsed + filtered + malformed, Ordering::Relaxed);
+		self.records_parsed.fetch_add(parsed, Ordering::Relaxed);
+		self.records_filtered.fetch_add(filtered, Ordering::Relaxed);
+		self.malformed_lines.fetch_add(malformed, Ordering::Relaxed);
+	}
+
+	pub(crate) fn add_unsorted(&self, unsorted: u64)
+	{
+		self.unsorted_records.fetch_add(unsorted, Ordering::Relaxed);
+	}
+
+	pub(crate) fn add_elapsed(&self, phase: Phase, since: Instant)
+	{
+		let nanos = since.elapsed().as_nanos().min(u64::MAX as u128) as u64;
+		let counter = match phase
+		{
+			Phase::Read => &self.read_nanos,
+			Phase::Decompress => &self.decompress_nanos,
+			Phase::Parse => &self.parse_nanos,
+		};
+
+		counter.fetch_add(nanos, Ordering::Relaxed);
+	}
+
+	pub(crate) fn snapshot(&self) -> ReaderStats
+	{
+		let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+		ReaderStats {
+			blocks_read: load(&self.blocks_read),
+			bytes_decompressed: load(&self.bytes_decompressed),
+			decompression_errors: load(&self.decompression_errors),
+			lines_read: load(&self.lines_read),
+			records_parsed: load(&self.records_parsed),
+			records_filtered: load(&self.records_filtered),
+			malformed_lines: load(&self.malformed_lines),
+			unsorted_records: load(&self.unsorted_records),
+			read_time: Duration::from_nanos(load(&self.read_nanos)),
+			decompress_time: Duration::from_nanos(load(&self.decompress_nanos)),
+			parse_time: Duration::from_nanos(load(&self.parse_nanos)),
+		}
+	}
+}
@@ -0,0 +1,129 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf, SeekFrom};
+
+const DEFAULT_PEEK_LIMIT: usize = 1024 * 1024;
+
+// Makes a plain AsyncRead (stdin, pipes, sockets) usable by the readers. Everything read is
+// kept until peek_limit bytes have gone past, so format detection can rewind to the start;
+// after that any seek other than to the current position fails with Unsupported.
+pub struct StreamingReader<R>
+{
+	inner: R,
+	prefix: Vec<u8>,
+	peek_limit: usize,
+	position: u64,
+	consumed: u64,
+	seekable: bool,
+}
+
+impl<R> StreamingReader<R>
+where
+	R: AsyncRead + Unpin,
+{
+	pub fn new(inner: R) -> Self
+	{
+		Self::with_peek_limit(inner, DEFAULT_PEEK_LIMIT)
+	}
+
+	pub fn with_peek_limit(inner: R, peek_limit: usize) -> Self
+	{
+		Self {
+			inner,
+			prefix: Vec::new(),
+			peek_limit,
+			position: 0,
+			consumed: 0,
+			seekable: true,
+		}
+	}
+
+	pub fn into_inner(self) -> R
+	{
+		self.inner
+	}
+}
+
+impl<R> AsyncRead for StreamingReader<R>
+where
+	R: AsyncRead + Unpin,
+{
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>>
+	{
+		let this = &mut *self;
+
+		// replaying the recorded prefix after a rewind
+		if this.position < this.consumed
+		{
+			let available = &this.prefix[this.position as usize..];
+			let n = available.len().min(buf.remaining());
+			buf.put_slice(&available[..n]);
+			this.position += n as u64;
+			return Poll::Ready(Ok(()));
+		}
+
+		let filled_before = buf.filled().len();
+		ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+		let fresh = &buf.filled()[filled_before..];
+
+		if this.seekable
+		{
+			if this.prefix.len() + fresh.len() <= this.peek_limit
+			{
+				this.prefix.extend_from_slice(fresh);
+			}
+			else
+			{
+				this.seekable = false;
+				this.prefix = Vec::new();
+			}
+		}
+
+		this.consumed += fresh.len() as u64;
+		this.position = this.consumed;
+
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl<R> AsyncSeek for StreamingReader<R>
+where
+	R: AsyncRead + Unpin,
+{
+	fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()>
+	{
+		let this = &mut *self;
+
+		let target = match position
+		{
+			SeekFrom::Start(n) => Some(n),
+			SeekFrom::Current(n) => this.position.checked_add_signed(n),
+			SeekFrom::End(_) => None,
+		};
+
+		match target
+		{
+			Some(target) if target == this.position => Ok(()),
+			Some(target) if this.seekable && target <= this.consumed =>
+			{
+				this.position = target;
+				Ok(())
+			}
+			_ => Err(io::Error::new(
+				io::ErrorKind::Unsupported,
+				"streaming input can only rewind within its peek buffer",
+			)),
+		}
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>>
+	{
+		Poll::Ready(Ok(self.position))
+	}
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use crate::bed::{BedSink, BedSinkValue, ReaderId, SourceId, Strand};
+
+// Counts in power of two buckets, bucket i holding values in [2^(i-1), 2^i) and bucket 0
+// holding zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram
+{
+	counts: Vec<u64>,
+}
+
+impl Histogram
+{
+	pub fn add(&mut self, value: u64)
+	{
+		let bucket = (u64::BITS - value.leading_zeros()) as usize;
+
+		if self.counts.len() <= bucket
+		{
+			self.counts.resize(bucket + 1, 0);
+		}
+
+		self.counts[bucket] += 1;
+	}
+
+	// (lowest, highest, count) of every bucket up to the last non-empty one
+	pub fn buckets(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_
+	{
+		self.counts.iter().enumerate().map(|(bucket, count)| match bucket
+		{
+			0 => (0, 0, *count),
+			_ =>
+			{
+				let lowest = 1u64 << (bucket - 1);
+				(lowest, lowest + (lowest - 1), *count)
+			}
+		})
+	}
+
+	pub fn total(&self) -> u64
+	{
+		self.counts.iter().sum()
+	}
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StrandCounts
+{
+	pub plus: u64,
+	pub minus: u64,
+	pub unstranded: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContigSummary
+{
+	pub records: u64,
+	// sum of record lengths, overlaps counted each time
+	pub bases: u64,
+	// bases under at least one record
+	pub bases_covered: u64,
+	// merged intervals, kept sorted so unsorted input is still counted once per base
+	covered: Vec<(u64, u64)>,
+}
+
+impl ContigSummary
+{
+	fn add(&mut self, start: u64, end: u64)
+	{
+		self.records += 1;
+		self.bases += end.saturating_sub(start);
+
+		if end <= start
+		{
+			return;
+		}
+
+		// sorted input only ever touches the last interval
+		let first = self.covered.partition_point(|(_, e)| *e < start);
+		let last = self.covered.partition_point(|(s, _)| *s <= end);
+
+		if first == last
+		{
+			self.covered.insert(first, (start, end));
+			self.bases_covered += end - start;
+			return;
+		}
+
+		let merged_start = start.min(self.covered[first].0);
+		let merged_end = end.max(self.covered[last - 1].1);
+		let replaced: u64 = self.covered.drain(first..last).map(|(s, e)| e - s).sum();
+
+		self.covered.insert(first, (merged_start, merged_end));
+		self.bases_covered += (merged_end - merged_start) - replaced;
+	}
+}
+
+// A whole file QC pass in one read, filled as a sink through Reader::summarize. Starts are
+// as the reader reports them, so lengths of a reader that shifts starts are one short.
+#[derive(Debug, Clone)]
+pub struct Summary<Tid>
+{
+	pub records: u64,
+	// lines the reader's filter skipped
+	pub filtered: u64,
+	pub contigs: BTreeMap<Tid, ContigSummary>,
+	pub lengths: Histogram,
+	// records that have a score column
+	pub scores: Histogram,
+	pub min_score: Option<u32>,
+	pub max_score: Option<u32>,
+	pub strands: StrandCounts,
+
+	// sink state, the tid and strand of the current run and the record whose end has not
+	// been seen yet
+	current: Option<(Tid, Strand)>,
+	start: u64,
+	pending: Vec<Option<u32>>,
+}
+
+impl<Tid> Default for Summary<Tid>
+{
+	fn default() -> Self
+	{
+		Self {
+			records: 0,
+			filtered: 0,
+			contigs: BTreeMap::new(),
+			lengths: Histogram::default(),
+			scores: Histogram::default(),
+			min_score: None,
+			max_score: None,
+			strands: StrandCounts::default(),
+			current: None,
+			start: 0,
+			pending: Vec::new(),
+		}
+	}
+}
+
+impl<Tid> Summary<Tid>
+where
+	Tid: Ord + Clone,
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	pub fn bases(&self) -> u64
+	{
+		self.contigs.values().map(|contig| contig.bases).sum()
+	}
+
+	pub fn bases_covered(&self) -> u64
+	{
+		self.contigs.values().map(|contig| contig.bases_covered).sum()
+	}
+
+	pub fn add(&mut self, tid: &Tid, strand: Strand, start: u64, end: u64, score: Option<u32>)
+	{
+		self.records += 1;
+		self.lengths.add(end.saturating_sub(start));
+
+		match strand
+		{
+			Strand::Plus => self.strands.plus += 1,
+			Strand::Minus => self.strands.minus += 1,
+			Strand::Both => self.strands.unstranded += 1,
+		}
+
+		if let Some(score) = score
+		{
+			self.scores.add(score.into());
+			self.min_score = Some(self.min_score.map_or(score, |min| min.min(score)));
+			self.max_score = Some(self.max_score.map_or(score, |max| max.max(score)));
+		}
+
+		match self.contigs.get_mut(tid)
+		{
+			Some(contig) => contig.add(start, end),
+			None => self.contigs.entry(tid.clone()).or_default().add(start, end),
+		}
+	}
+}
+
+impl<Tid> BedSink<Tid> for Summary<Tid>
+where
+	Tid: Ord + Clone + Send + Sync,
+{
+	fn begin_tid(&mut self, tid: &Tid, strand: &Strand)
+	{
+		self.current = Some((tid.clone(), *strand));
+	}
+
+	fn end_tid(&mut self, _tid: &Tid, _strand: &Strand)
+	{
+		self.current = None;
+	}
+
+	fn begin_position(&mut self, start: u64)
+	{
+		self.start = start;
+	}
+
+	fn end_position(&mut self, end: u64)
+	{
+		let Some((tid, strand)) = self.current.take()
+		else
+		{
+			self.pending.clear();
+			return;
+		};
+
+		for score in std::mem::take(&mut self.pending)
+		{
+			self.add(&tid, strand, self.start, end, score);
+		}
+
+		self.current = Some((tid, strand));
+	}
+
+	fn push_value(
+		&mut self,
+		_source_id: &Option<SourceId>,
+		_reader_id: &ReaderId,
+		value: BedSinkValue,
+	)
+	{
+		self.pending.push(value.score);
+	}
+}
@@ -0,0 +1,229 @@
+use crate::error;
+
+const MAX_FIELDS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenizerMode
+{
+	// CRLF endings, trailing whitespace and runs of spaces/tabs are all accepted
+	#[default]
+	Tolerant,
+	// one tab between fields and nothing else, for validating files before handing them on
+	StrictTabs,
+}
+
+// the separator sets the modes split on, searched with memchr rather than byte by byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Separators
+{
+	Whitespace,
+	Tabs,
+	TabsAndSpaces,
+}
+
+impl Separators
+{
+	fn contains(self, b: u8) -> bool
+	{
+		match self
+		{
+			Self::Whitespace => b.is_ascii_whitespace(),
+			Self::Tabs => b == b'\t',
+			Self::TabsAndSpaces => b == b'\t' || b == b' ',
+		}
+	}
+
+	fn find(self, haystack: &[u8]) -> Option<usize>
+	{
+		match self
+		{
+			// newlines never reach the tokenizer, form feeds are rare enough to search for
+			// separately
+			Self::Whitespace =>
+			{
+				let end = memchr::memchr3(b'\t', b' ', b'\r', haystack);
+				memchr::memchr(b'\x0c', &haystack[..end.unwrap_or(haystack.len())]).or(end)
+			}
+			Self::Tabs => memchr::memchr(b'\t', haystack),
+			Self::TabsAndSpaces => memchr::memchr2(b'\t', b' ', haystack),
+		}
+	}
+}
+
+pub(crate) struct Fields<'a>
+{
+	line: &'a [u8],
+	ranges: [(usize, usize); MAX_FIELDS],
+	n: usize,
+}
+
+impl<'a> Fields<'a>
+{
+	pub(crate) fn len(&self) -> usize
+	{
+		self.n
+	}
+
+	pub(crate) fn is_empty(&self) -> bool
+	{
+		self.n == 0
+	}
+
+	pub(crate) fn get(&self, i: usize) -> &'a [u8]
+	{
+		let (start, end) = self.ranges[i];
+		&self.line[start..end]
+	}
+}
+
+// splits off the next line, a missing final newline is the end of the input
+pub(crate) fn next_line(input: &[u8]) -> (&[u8], &[u8])
+{
+	match memchr::memchr(b'\n', input)
+	{
+		Some(line_end) => (&input[..line_end], &input[line_end + 1..]),
+		None => (input, &input[input.len()..]),
+	}
+}
+
+// spaces are only accepted as separators by strict mode when allow_spaces is set, modkit
+// writes the bedMethyl count columns space separated
+pub(crate) fn tokenize<'a>(
+	line: &'a [u8],
+	mode: TokenizerMode,
+	allow_spaces: bool,
+	format: &str,
+) -> error::Result<Fields<'a>>
+{
+	let mut fields = Fields {
+		line,
+		ranges: [(0, 0); MAX_FIELDS],
+		n: 0,
+	};
+
+	match mode
+	{
+		TokenizerMode::Tolerant =>
+		{
+			let separators = Separators::Whitespace;
+			let mut i = 0;
+
+			while i < line.len()
+			{
+				if separators.contains(line[i])
+				{
+					i += 1;
+					continue;
+				}
+
+				let (start, end, next) = field_at(line, i, separators);
+				push_field(&mut fields, start, end, format)?;
+				i = next;
+			}
+		}
+		TokenizerMode::StrictTabs =>
+		{
+			if line.is_empty()
+			{
+				return Ok(fields);
+			}
+
+			if line.ends_with(b"\r")
+			{
+				return Err(error::Error::Parse(format!("{format} line has a CRLF ending")));
+			}
+
+			let separators = if allow_spaces
+			{
+				Separators::TabsAndSpaces
+			}
+			else
+			{
+				Separators::Tabs
+			};
+			let mut i = 0;
+
+			loop
+			{
+				let (start, end, next) = field_at(line, i, separators);
+
+				// quoted fields may hold spaces, anything else must be tab separated
+				let quoted = start != i;
+				if !quoted && line[start..end].iter().any(|b| b.is_ascii_whitespace())
+				{
+					return Err(error::Error::Parse(format!(
+						"{format} line has whitespace other than tabs between fields"
+					)));
+				}
+
+				push_strict_field(&mut fields, start, end, quoted, format)?;
+
+				if next == line.len()
+				{
+					break;
+				}
+
+				i = next + 1;
+			}
+		}
+	}
+
+	Ok(fields)
+}
+
+// (start, end, next) of the field at i, next being the separator after it or the end of the
+// line. A field wrapped in double quotes may contain separators and is returned without its
+// quotes, an unterminated quote is read as an ordinary character.
+fn field_at(line: &[u8], i: usize, separators: Separators) -> (usize, usize, usize)
+{
+	if line.get(i) == Some(&b'"')
+	{
+		if let Some(close) = memchr::memchr(b'"', &line[i + 1..]).map(|p| i + 1 + p)
+		{
+			let next = close + 1;
+			if next == line.len() || separators.contains(line[next])
+			{
+				return (i + 1, close, next);
+			}
+		}
+	}
+
+	let i = i.min(line.len());
+	let end = separators
+		.find(&line[i..])
+		.map(|p| i + p)
+		.unwrap_or(line.len());
+
+	(i, end, end)
+}
+
+fn push_field(fields: &mut Fields, start: usize, end: usize, format: &str) -> error::Result<()>
+{
+	if fields.n == MAX_FIELDS
+	{
+		return Err(error::Error::BedMismatch(format.into()));
+	}
+
+	fields.ranges[fields.n] = (start, end);
+	fields.n += 1;
+
+	Ok(())
+}
+
+fn push_strict_field(
+	fields: &mut Fields,
+	start: usize,
+	end: usize,
+	quoted: bool,
+	format: &str,
+) -> error::Result<()>
+{
+	if start == end && !quoted
+	{
+		return Err(error::Error::Parse(format!(
+			"{format} line has an empty field or a repeated/trailing separator"
+		)));
+	}
+
+	push_field(fields, start, end, format)
+}
@@ -0,0 +1,141 @@
+use crate::bed::BedKind;
+
+// the `type=` attribute of a UCSC track line, deciding how the lines after it are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackType
+{
+	// no type or type=bed, read with the reader's own kind
+	Bed,
+	BedGraph,
+	// narrowPeak, broadPeak and gappedPeak, BED6 followed by peak columns
+	Peak,
+	BedMethyl,
+	// types with no BED parser (wiggle_0, bigBed, ...), their lines are skipped
+	Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineParser
+{
+	Own,
+	Kind(BedKind),
+	// the BED6 prefix, trailing columns ignored
+	Bed6Prefix,
+	Skip,
+}
+
+impl TrackType
+{
+	// None when the line is not a track line
+	pub fn from_line(line: &[u8]) -> Option<Self>
+	{
+		let track_type = attributes(line)?
+			.find(|(key, _)| *key == "type")
+			.map(|(_, value)| value);
+
+		Some(match track_type
+		{
+			None | Some("bed") => Self::Bed,
+			Some(value) if value.eq_ignore_ascii_case("bedGraph") => Self::BedGraph,
+			Some("narrowPeak" | "broadPeak" | "gappedPeak") => Self::Peak,
+			Some(value) if value.eq_ignore_ascii_case("bedMethyl") => Self::BedMethyl,
+			Some(_) => Self::Other,
+		})
+	}
+
+	pub(crate) fn line_parser(self, own: BedKind) -> LineParser
+	{
+		let kind = match self
+		{
+			Self::Bed => return LineParser::Own,
+			Self::Peak => return LineParser::Bed6Prefix,
+			Self::Other => return LineParser::Skip,
+			// the value column lands in the name
+			Self::BedGraph => BedKind::Bed4,
+			Self::BedMethyl => BedKind::BedMethyl,
+		};
+
+		if kind == own
+		{
+			LineParser::Own
+		}
+		else
+		{
+			LineParser::Kind(kind)
+		}
+	}
+}
+
+const KNOWN_ATTRIBUTES: [&str; 27] = [
+	"type",
+	"name",
+	"description",
+	"visibility",
+	"color",
+	"altColor",
+	"itemRgb",
+	"colorByStrand",
+	"useScore",
+	"group",
+	"priority",
+	"db",
+	"offset",
+	"maxItems",
+	"url",
+	"htmlUrl",
+	"bigDataUrl",
+	"graphType",
+	"viewLimits",
+	"autoScale",
+	"alwaysZero",
+	"yLineMark",
+	"yLineOnOff",
+	"windowingFunction",
+	"smoothingWindow",
+	"gridDefault",
+	"maxHeightPixels",
+];
+
+// key=value pairs of a track line, None when the line is not one. Words of quoted values
+// with spaces have no '=' and are passed over.
+pub(crate) fn attributes(line: &[u8]) -> Option<impl Iterator<Item = (&str, &str)>>
+{
+	let line = std::str::from_utf8(line).ok()?.trim();
+
+	if line != "track" && !line.starts_with("track ") && !line.starts_with("track\t")
+	{
+		return None;
+	}
+
+	Some(
+		line.split_whitespace()
+			.skip(1)
+			.filter_map(|attribute| attribute.split_once('='))
+			.map(|(key, value)| (key, value.trim_matches('"'))),
+	)
+}
+
+pub(crate) fn unknown_attributes(line: &[u8]) -> Vec<String>
+{
+	attributes(line)
+		.into_iter()
+		.flatten()
+		.filter(|(key, _)| !KNOWN_ATTRIBUTES.contains(key))
+		.map(|(key, _)| key.to_string())
+		.collect()
+}
+
+pub(crate) fn type_name(line: &[u8]) -> Option<String>
+{
+	attributes(line)?
+		.find(|(key, _)| *key == "type")
+		.map(|(_, value)| value.to_string())
+}
+
+// browser lines and comments carry no records whichever track they sit in
+pub(crate) fn is_header_line(line: &[u8]) -> bool
+{
+	let line = line.trim_ascii_start();
+
+	line.starts_with(b"#") || line.starts_with(b"browser ") || line.starts_with(b"browser\t")
+}
@@ -0,0 +1,207 @@
+use crate::bed::{Bed12Columns, BedKind, BedRecord, Strand};
+use crate::error;
+
+// Typed views of a collected record for code that knows which format it is reading.
+// Conversions fail with Error::VariantMismatch when the record was parsed as another kind,
+// use BedRecord::try_into_kind first to normalise mixed input.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed3Record<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed4Record<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed5Record<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub name: String,
+	pub score: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed6Record<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub name: String,
+	pub score: u32,
+	pub strand: Strand,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bed12Record<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub name: String,
+	pub score: u32,
+	pub strand: Strand,
+	pub columns: Bed12Columns,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BedMethylRecord<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub name: String,
+	pub score: u32,
+	pub strand: Strand,
+	pub n_valid_cov: u32,
+	pub frac_mod: f32,
+	pub n_mod: u32,
+	// None in the 11 column ENCODE layout
+	pub n_canonical: Option<u32>,
+	pub n_other_mod: Option<u32>,
+	pub n_delete: Option<u32>,
+	pub n_fail: Option<u32>,
+	pub n_diff: Option<u32>,
+	pub n_nocall: Option<u32>,
+}
+
+fn expect_kind<Tid>(record: &BedRecord<Tid>, kind: BedKind) -> error::Result<()>
+{
+	if record.value.kind() != kind
+	{
+		return Err(error::Error::VariantMismatch);
+	}
+
+	Ok(())
+}
+
+impl<Tid> TryFrom<BedRecord<Tid>> for Bed3Record<Tid>
+{
+	type Error = error::Error;
+
+	fn try_from(record: BedRecord<Tid>) -> error::Result<Self>
+	{
+		expect_kind(&record, BedKind::Bed3)?;
+
+		Ok(Self {
+			tid: record.tid,
+			start: record.start,
+			end: record.end,
+		})
+	}
+}
+
+impl<Tid> TryFrom<BedRecord<Tid>> for Bed4Record<Tid>
+{
+	type Error = error::Error;
+
+	fn try_from(record: BedRecord<Tid>) -> error::Result<Self>
+	{
+		expect_kind(&record, BedKind::Bed4)?;
+
+		Ok(Self {
+			tid: record.tid,
+			start: record.start,
+			end: record.end,
+			name: record.value.name.ok_or(error::Error::VariantMismatch)?,
+		})
+	}
+}
+
+impl<Tid> TryFrom<BedRecord<Tid>> for Bed5Record<Tid>
+{
+	type Error = error::Error;
+
+	fn try_from(record: BedRecord<Tid>) -> error::Result<Self>
+	{
+		expect_kind(&record, BedKind::Bed5)?;
+
+		Ok(Self {
+			tid: record.tid,
+			start: record.start,
+			end: record.end,
+			name: record.value.name.ok_or(error::Error::VariantMismatch)?,
+			score: record.value.score.ok_or(error::Error::VariantMismatch)?,
+		})
+	}
+}
+
+impl<Tid> TryFrom<BedRecord<Tid>> for Bed6Record<Tid>
+{
+	type Error = error::Error;
+
+	fn try_from(record: BedRecord<Tid>) -> error::Result<Self>
+	{
+		expect_kind(&record, BedKind::Bed6)?;
+
+		Ok(Self {
+			tid: record.tid,
+			start: record.start,
+			end: record.end,
+			name: record.value.name.ok_or(error::Error::VariantMismatch)?,
+			score: record.value.score.ok_or(error::Error::VariantMismatch)?,
+			strand: record.strand,
+		})
+	}
+}
+
+impl<Tid> TryFrom<BedRecord<Tid>> for Bed12Record<Tid>
+{
+	type Error = error::Error;
+
+	fn try_from(record: BedRecord<Tid>) -> error::Result<Self>
+	{
+		expect_kind(&record, BedKind::Bed12)?;
+
+		Ok(Self {
+			tid: record.tid,
+			start: record.start,
+			end: record.end,
+			name: record.value.name.ok_or(error::Error::VariantMismatch)?,
+			score: record.value.score.ok_or(error::Error::VariantMismatch)?,
+			strand: record.strand,
+			columns: *record.value.bed12.ok_or(error::Error::VariantMismatch)?,
+		})
+	}
+}
+
+impl<Tid> TryFrom<BedRecord<Tid>> for BedMethylRecord<Tid>
+{
+	type Error = error::Error;
+
+	fn try_from(record: BedRecord<Tid>) -> error::Result<Self>
+	{
+		expect_kind(&record, BedKind::BedMethyl)?;
+
+		let value = record.value;
+
+		Ok(Self {
+			tid: record.tid,
+			start: record.start,
+			end: record.end,
+			name: value.name.ok_or(error::Error::VariantMismatch)?,
+			score: value.score.ok_or(error::Error::VariantMismatch)?,
+			strand: record.strand,
+			n_valid_cov: value.n_valid_cov.ok_or(error::Error::VariantMismatch)?,
+			frac_mod: value.frac_mod.ok_or(error::Error::VariantMismatch)?,
+			n_mod: value.n_mod.ok_or(error::Error::VariantMismatch)?,
+			n_canonical: value.n_canonical,
+			n_other_mod: value.n_other_mod,
+			n_delete: value.n_delete,
+			n_fail: value.n_fail,
+			n_diff: value.n_diff,
+			n_nocall: value.n_nocall,
+		})
+	}
+}
@@ -0,0 +1,155 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(feature = "fs")]
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf, SeekFrom};
+
+use crate::error;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use crate::bed::StreamingReader;
+#[cfg(feature = "object-store")]
+use crate::remote::{ObjectStoreOptions, ObjectStoreReader};
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use crate::remote::FetchReader;
+
+// The source a location string names: "-" for stdin, a path or file:// URL for a local
+// file, anything else with a scheme (http(s)://, s3://, gs://) for an object store. In a
+// browser there is no stdin, and http(s):// is read through fetch.
+pub enum UriReader
+{
+	#[cfg(feature = "fs")]
+	File(File),
+	#[cfg(feature = "object-store")]
+	ObjectStore(ObjectStoreReader),
+	#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+	Fetch(FetchReader),
+	#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+	Stdin(StreamingReader<tokio::io::Stdin>),
+}
+
+impl UriReader
+{
+	// with the compressed size when the source knows it
+	pub async fn open(uri: &str) -> error::Result<(Self, Option<u64>)>
+	{
+		#[cfg(feature = "object-store")]
+		return Self::open_with(uri, ObjectStoreOptions::default()).await;
+
+		#[cfg(not(feature = "object-store"))]
+		Self::open_local(uri).await
+	}
+
+	#[cfg(feature = "object-store")]
+	pub async fn open_with(
+		uri: &str,
+		remote: ObjectStoreOptions,
+	) -> error::Result<(Self, Option<u64>)>
+	{
+		match uri.split_once("://")
+		{
+			#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+			Some(("http" | "https", _)) => Self::open_fetch(uri).await,
+			Some((scheme, _)) if scheme != "file" =>
+			{
+				let reader = ObjectStoreReader::open(uri, remote).await?;
+				let size = reader.size();
+				Ok((Self::ObjectStore(reader), Some(size)))
+			}
+			_ => Self::open_local(uri).await,
+		}
+	}
+
+	#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+	async fn open_fetch(uri: &str) -> error::Result<(Self, Option<u64>)>
+	{
+		let reader = FetchReader::open(uri).await?;
+		let size = reader.size();
+		Ok((Self::Fetch(reader), Some(size)))
+	}
+
+	async fn open_local(uri: &str) -> error::Result<(Self, Option<u64>)>
+	{
+		#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+		if uri == "-"
+		{
+			return Ok((Self::Stdin(StreamingReader::new(tokio::io::stdin())), None));
+		}
+
+		let path = match uri.split_once("://")
+		{
+			#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+			Some(("http" | "https", _)) => return Self::open_fetch(uri).await,
+			Some(("file", path)) => path,
+			Some(_) => return Err(error::Error::InvalidUrl(uri.to_string())),
+			None => uri,
+		};
+
+		#[cfg(feature = "fs")]
+		{
+			let file = File::open(path).await?;
+			let size = file.metadata().await?.len();
+
+			Ok((Self::File(file), Some(size)))
+		}
+
+		#[cfg(not(feature = "fs"))]
+		Err(error::Error::InvalidUrl(path.to_string()))
+	}
+}
+
+impl AsyncRead for UriReader
+{
+	fn poll_read(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>>
+	{
+		match self.get_mut()
+		{
+			#[cfg(feature = "fs")]
+			Self::File(file) => Pin::new(file).poll_read(cx, buf),
+			#[cfg(feature = "object-store")]
+			Self::ObjectStore(reader) => Pin::new(reader).poll_read(cx, buf),
+			#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+			Self::Fetch(reader) => Pin::new(reader).poll_read(cx, buf),
+			#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+			Self::Stdin(reader) => Pin::new(reader).poll_read(cx, buf),
+		}
+	}
+}
+
+impl AsyncSeek for UriReader
+{
+	fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()>
+	{
+		match self.get_mut()
+		{
+			#[cfg(feature = "fs")]
+			Self::File(file) => Pin::new(file).start_seek(position),
+			#[cfg(feature = "object-store")]
+			Self::ObjectStore(reader) => Pin::new(reader).start_seek(position),
+			#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+			Self::Fetch(reader) => Pin::new(reader).start_seek(position),
+			#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+			Self::Stdin(reader) => Pin::new(reader).start_seek(position),
+		}
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>>
+	{
+		match self.get_mut()
+		{
+			#[cfg(feature = "fs")]
+			Self::File(file) => Pin::new(file).poll_complete(cx),
+			#[cfg(feature = "object-store")]
+			Self::ObjectStore(reader) => Pin::new(reader).poll_complete(cx),
+			#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+			Self::Fetch(reader) => Pin::new(reader).poll_complete(cx),
+			#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+			Self::Stdin(reader) => Pin::new(reader).poll_complete(cx),
+		}
+	}
+}
@@ -0,0 +1,36 @@
+use std::borrow::Cow;
+
+use crate::error;
+use crate::bed::TokenizerMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Utf8Policy
+{
+	// invalid bytes fail the record with the field and byte offset they were found at
+	#[default]
+	Error,
+	// invalid bytes become U+FFFD, for files with stray Latin-1 in their name columns
+	Lossy,
+}
+
+// everything the record parsers take besides the input and the filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions
+{
+	pub tokenizer_mode: TokenizerMode,
+	pub utf8_policy: Utf8Policy,
+}
+
+pub(crate) fn utf8_field<'a>(
+	bytes: &'a [u8],
+	field: &str,
+	policy: Utf8Policy,
+) -> error::Result<Cow<'a, str>>
+{
+	match std::str::from_utf8(bytes)
+	{
+		Ok(text) => Ok(Cow::Borrowed(text)),
+		Err(_) if policy == Utf8Policy::Lossy => Ok(String::from_utf8_lossy(bytes)),
+		Err(e) => Err(error::Error::InvalidUtf8(field.into(), e.valid_up_to())),
+	}
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::bed::{BedSinkValue, Strand};
+
+// anomalies worth telling a pipeline about that do not stop the read. Positions are as
+// written in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning
+{
+	DuplicateRecord
+	{
+		tid: String,
+		start: u64,
+		end: u64,
+	},
+	Unsorted
+	{
+		tid: String,
+		start: u64,
+		previous_start: u64,
+	},
+	ZeroLength
+	{
+		tid: String,
+		start: u64,
+	},
+	// the BED spec limits scores to 0-1000
+	ScoreOutOfRange
+	{
+		tid: String,
+		start: u64,
+		score: u32,
+	},
+	UnknownTrackAttribute(String),
+	// the lines of such a track are skipped
+	UnsupportedTrackType(String),
+}
+
+pub type WarningCallback = Arc<dyn Fn(Warning) + Send + Sync>;
+
+impl fmt::Display for Warning
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Warning::DuplicateRecord { tid, start, end } =>
+			{
+				write!(f, "{tid}:{start}-{end}: duplicate record")
+			}
+			Warning::Unsorted {
+				tid,
+				start,
+				previous_start,
+			} => write!(f, "{tid}:{start}: starts before the previous record at {previous_start}"),
+			Warning::ZeroLength { tid, start } => write!(f, "{tid}:{start}: zero length interval"),
+			Warning::ScoreOutOfRange { tid, start, score } =>
+			{
+				write!(f, "{tid}:{start}: score {score} is outside 0-1000")
+			}
+			Warning::UnknownTrackAttribute(attribute) =>
+			{
+				write!(f, "unknown track attribute {attribute}")
+			}
+			Warning::UnsupportedTrackType(track_type) =>
+			{
+				write!(f, "unsupported track type {track_type}, its records are skipped")
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PreviousRecord
+{
+	tid: String,
+	start: u64,
+	end: u64,
+	strand: Strand,
+	name: Option<String>,
+}
+
+// compares a record against the one before it, previous carrying over between blocks. Only
+// records that also share strand and name are duplicates, bedMethyl writes one line per
+// modification code at the same site.
+pub(crate) fn check_record(
+	warn: &WarningCallback,
+	previous: &mut Option<PreviousRecord>,
+	tid: &str,
+	start: u64,
+	end: u64,
+	strand: Strand,
+	value: &BedSinkValue,
+)
+{
+	if start == end
+	{
+		warn(Warning::ZeroLength {
+			tid: tid.to_string(),
+			start,
+		});
+	}
+
+	if let Some(score) = value.score.filter(|score| *score > 1000)
+	{
+		warn(Warning::ScoreOutOfRange {
+			tid: tid.to_string(),
+			start,
+			score,
+		});
+	}
+
+	let name = value.get_name();
+
+	if let Some(previous) = previous.as_ref().filter(|previous| previous.tid == tid)
+	{
+		if start == previous.start
+			&& end == previous.end
+			&& strand == previous.strand
+			&& name == previous.name.as_deref()
+		{
+			warn(Warning::DuplicateRecord {
+				tid: tid.to_string(),
+				start,
+				end,
+			});
+		}
+		else if start < previous.start
+		{
+			warn(Warning::Unsorted {
+				tid: tid.to_string(),
+				start,
+				previous_start: previous.start,
+			});
+		}
+	}
+
+	*previous = Some(PreviousRecord {
+		tid: tid.to_string(),
+		start,
+		end,
+		strand,
+		name: name.map(str::to_string),
+	});
+}
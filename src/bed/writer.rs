@@ -0,0 +1,236 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use crate::bed::{BedKind, BedRecord, encode_name};
+
+// How numbers are written, so output can match another tool's byte for byte. Integers are
+// always written plainly, without separators or exponents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriterOptions
+{
+	float_precision: Option<usize>,
+	trailing_zero: bool,
+	format_extras: bool,
+}
+
+impl WriterOptions
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	// Digits after the point for frac_mod and formatted extras, modkit writes 2. None writes
+	// the shortest form that reads back as the same value.
+	pub fn with_float_precision(mut self, float_precision: Option<usize>) -> Self
+	{
+		self.float_precision = float_precision;
+		self
+	}
+
+	// whole floats as 50.0 rather than 50 when no precision is set
+	pub fn with_trailing_zero(mut self, trailing_zero: bool) -> Self
+	{
+		self.trailing_zero = trailing_zero;
+		self
+	}
+
+	// BED6+N columns holding decimals, such as narrowPeak's signalValue, written as floats
+	// are rather than as they were read. Other columns are always written as read.
+	pub fn with_format_extras(mut self, format_extras: bool) -> Self
+	{
+		self.format_extras = format_extras;
+		self
+	}
+
+	fn write_float<F>(&self, out: &mut impl Write, value: F) -> std::io::Result<()>
+	where
+		F: Display + Copy + Into<f64>,
+	{
+		if let Some(precision) = self.float_precision
+		{
+			return write!(out, "{value:.precision$}");
+		}
+
+		let whole = value.into().is_finite() && value.into().fract() == 0.0;
+
+		if self.trailing_zero && whole
+		{
+			return write!(out, "{value}.0");
+		}
+
+		write!(out, "{value}")
+	}
+
+	fn write_extra(&self, out: &mut impl Write, extra: &str) -> std::io::Result<()>
+	{
+		if self.format_extras && extra.contains('.')
+		{
+			if let Ok(value) = extra.parse::<f64>()
+			{
+				return self.write_float(out, value);
+			}
+		}
+
+		write!(out, "{extra}")
+	}
+}
+
+// Writes records as BED lines of the kind they were parsed from, coordinates as the records
+// hold them, so readers opened with_one_indexed(true) write back what they read. Names are
+// encoded as encode_name does and bedMethyl display columns are written as modkit does.
+pub struct BedWriter<W>
+{
+	inner: W,
+	options: WriterOptions,
+}
+
+impl<W> BedWriter<W>
+where
+	W: Write,
+{
+	pub fn new(inner: W) -> Self
+	{
+		Self {
+			inner,
+			options: WriterOptions::default(),
+		}
+	}
+
+	pub fn with_options(mut self, options: WriterOptions) -> Self
+	{
+		self.options = options;
+		self
+	}
+
+	// a track line of key=value attributes, values with whitespace quoted
+	pub fn write_track_line(&mut self, attributes: &[(&str, &str)]) -> std::io::Result<()>
+	{
+		write!(self.inner, "track")?;
+
+		for (key, value) in attributes
+		{
+			if value.contains(char::is_whitespace)
+			{
+				write!(self.inner, " {key}=\"{value}\"")?;
+			}
+			else
+			{
+				write!(self.inner, " {key}={value}")?;
+			}
+		}
+
+		writeln!(self.inner)
+	}
+
+	// a browser line, such as "position chr1:1-1000"
+	pub fn write_browser_line(&mut self, line: &str) -> std::io::Result<()>
+	{
+		writeln!(self.inner, "browser {}", line.trim())
+	}
+
+	pub fn write<Tid>(&mut self, record: &BedRecord<Tid>) -> std::io::Result<()>
+	where
+		Tid: AsRef<str>,
+	{
+		self.write_named(record.tid.as_ref(), record)
+	}
+
+	// record under the contig name tid, for records holding interned tids
+	pub(crate) fn write_named<Tid>(
+		&mut self,
+		tid: &str,
+		record: &BedRecord<Tid>,
+	) -> std::io::Result<()>
+	{
+		let out = &mut self.inner;
+		let options = self.options;
+		let value = &record.value;
+
+		write!(out, "{tid}\t{}\t{}", record.start, record.end)?;
+
+		if value.kind == BedKind::Bed3
+		{
+			return writeln!(out);
+		}
+
+		write!(out, "\t{}", encode_name(value.name.as_deref().unwrap_or(".")))?;
+
+		if value.kind == BedKind::Bed4
+		{
+			return writeln!(out);
+		}
+
+		write!(out, "\t{}", value.score.unwrap_or(0))?;
+
+		if value.kind == BedKind::Bed5
+		{
+			return writeln!(out);
+		}
+
+		write!(out, "\t{}", record.strand)?;
+
+		match value.kind
+		{
+			BedKind::Bed12 =>
+			{
+				if let Some(bed12) = &value.bed12
+				{
+					let join = |values: &[u32]| {
+						values.iter().map(|value| format!("{value},")).collect::<String>()
+					};
+
+					write!(
+						out,
+						"\t{}\t{}\t{}\t{}",
+						bed12.thick_start, bed12.thick_end, bed12.item_rgb_raw, bed12.block_count
+					)?;
+					write!(out, "\t{}\t{}", join(&bed12.block_sizes), join(&bed12.block_starts))?;
+				}
+			}
+			BedKind::BedMethyl =>
+			{
+				// modkit writes the record's own bounds as the thick ones and red as the colour
+				write!(out, "\t{}\t{}\t255,0,0", record.start, record.end)?;
+				write!(out, "\t{}\t", value.n_valid_cov.unwrap_or(0))?;
+				options.write_float(out, value.frac_mod.unwrap_or(0.0))?;
+
+				// ENCODE's layout ends at the percentage, modkit's carries the counts on
+				if value.n_canonical.is_some()
+				{
+					for count in [
+						value.n_mod,
+						value.n_canonical,
+						value.n_other_mod,
+						value.n_delete,
+						value.n_fail,
+						value.n_diff,
+						value.n_nocall,
+					]
+					{
+						write!(out, "\t{}", count.unwrap_or(0))?;
+					}
+				}
+			}
+			_ => (),
+		}
+
+		for extra in value.extra.iter().flatten()
+		{
+			write!(out, "\t")?;
+			options.write_extra(out, extra)?;
+		}
+
+		writeln!(out)
+	}
+
+	pub fn flush(&mut self) -> std::io::Result<()>
+	{
+		self.inner.flush()
+	}
+
+	pub fn into_inner(self) -> W
+	{
+		self.inner
+	}
+}
@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use tokio::fs::File;
+
+use sandman::bed::{BedRecord, BedWriter, CollectSink, GenomicInterval};
+use sandman::blocking::{self, AutoOneShotBlockReader};
+use sandman::error;
+use sandman::prelude::{ReaderOptions, TokenizerMode};
+use sandman::region::Region;
+use sandman::pufferfish::pool::BgzfBlockPool;
+
+const BLOCKS_PER_BATCH: usize = 200;
+// chunks of a region query fetched at once
+const QUERY_PARALLELISM: usize = 4;
+
+const USAGE: &str = "usage: sandman <command> [args]
+
+commands:
+  view <file> [chrom[:start-end]]   print records, optionally limited to a 1-based region,
+                                    read through the .tbi beside the file when there is one
+  sort <file>                       print records sorted by chrom, start and end
+  merge <file>                      print merged overlapping intervals as BED3
+  intersect <a> <b>                 print records of a overlapping any record of b
+  stats <file>                      print record, contig, base, length and score counts
+  validate <file>                   read the whole file and report problems
+  index <file>                      sort and compress a plain BED as <file>.gz, indexed
+                                    by <file>.gz.tbi";
+
+type Record = BedRecord<String>;
+
+fn pool() -> Arc<BgzfBlockPool>
+{
+	Arc::new(BgzfBlockPool::new(10000, 64 * 1024))
+}
+
+// keep coordinates as written so records print back unchanged
+fn options(tokenizer_mode: TokenizerMode) -> ReaderOptions<()>
+{
+	ReaderOptions::<()>::default()
+		.with_one_indexed(true)
+		.with_tokenizer_mode(tokenizer_mode)
+}
+
+fn open(
+	path: &str,
+	tokenizer_mode: TokenizerMode,
+) -> error::Result<AutoOneShotBlockReader<File, ()>>
+{
+	AutoOneShotBlockReader::from_path_with(path, None, pool(), options(tokenizer_mode))
+}
+
+// as open, with the .tbi beside the file when there is one so regions are read through it
+fn open_indexed(path: &str) -> error::Result<AutoOneShotBlockReader<File, ()>>
+{
+	AutoOneShotBlockReader::from_path_indexed(path, None, pool(), options(TokenizerMode::Tolerant))
+}
+
+fn for_each_record<F>(path: &str, f: F) -> error::Result<usize>
+where
+	F: FnMut(Record),
+{
+	for_each_record_with(path, TokenizerMode::Tolerant, f)
+}
+
+fn for_each_record_with<F>(
+	path: &str,
+	tokenizer_mode: TokenizerMode,
+	mut f: F,
+) -> error::Result<usize>
+where
+	F: FnMut(Record),
+{
+	let mut reader = open(path, tokenizer_mode)?;
+	let mut sink = CollectSink::new();
+	let mut filtered_out = 0;
+
+	while let Some(block) = reader.next_bgzf_blocks(BLOCKS_PER_BATCH)?
+	{
+		filtered_out += reader.read_tids_in_block_sink(block, &mut sink)?.unwrap_or(0);
+		sink.drain().for_each(&mut f);
+	}
+
+	Ok(filtered_out)
+}
+
+fn read_records(path: &str) -> error::Result<Vec<Record>>
+{
+	let mut records = Vec::new();
+	for_each_record(path, |record| records.push(record))?;
+	Ok(records)
+}
+
+fn sort_records(records: &mut [Record])
+{
+	records.sort_by(|a, b| {
+		a.tid
+			.cmp(&b.tid)
+			.then(a.start.cmp(&b.start))
+			.then(a.end.cmp(&b.end))
+	});
+}
+
+// merged (start, end) intervals per chrom
+fn merged_intervals(mut records: Vec<Record>) -> BTreeMap<String, Vec<(u64, u64)>>
+{
+	sort_records(&mut records);
+
+	let mut merged: BTreeMap<String, Vec<(u64, u64)>> = BTreeMap::new();
+	for record in records
+	{
+		let intervals = merged.entry(record.tid).or_default();
+		match intervals.last_mut()
+		{
+			Some((_, end)) if record.start <= *end => *end = (*end).max(record.end),
+			_ => intervals.push((record.start, record.end)),
+		}
+	}
+
+	merged
+}
+
+fn run(args: &[String]) -> Result<(), String>
+{
+	let stdout = std::io::stdout();
+	let mut out = BufWriter::new(stdout.lock());
+	let io = |e: std::io::Error| e.to_string();
+	let err = |e: error::Error| e.to_string();
+
+	match args
+	{
+		[command, path] if command == "view" =>
+		{
+			let mut writer = BedWriter::new(&mut out);
+			let mut result = Ok(());
+			for_each_record(path, |record| {
+				if result.is_ok()
+				{
+					result = writer.write(&record);
+				}
+			})
+			.map_err(err)?;
+			result.map_err(io)?;
+		}
+		[command, path, region] if command == "view" =>
+		{
+			let region = Region::parse(region).map_err(err)?;
+			let mut writer = BedWriter::new(&mut out);
+			let reader = open_indexed(path).map_err(err)?;
+
+			match reader.query_region_concurrent(&region, QUERY_PARALLELISM)
+			{
+				Ok(records) =>
+				{
+					for record in &records
+					{
+						writer.write(record).map_err(io)?;
+					}
+				}
+				// plain text, or BGZF without a .tbi, is scanned from start to end
+				Err(error::Error::NoIndex(_)) =>
+				{
+					let mut result = Ok(());
+					for_each_record(path, |record| {
+						if result.is_ok() && region.overlaps(&record)
+						{
+							result = writer.write(&record);
+						}
+					})
+					.map_err(err)?;
+					result.map_err(io)?;
+				}
+				Err(e) => return Err(err(e)),
+			}
+		}
+		[command, path] if command == "sort" =>
+		{
+			let mut records = read_records(path).map_err(err)?;
+			sort_records(&mut records);
+			let mut writer = BedWriter::new(&mut out);
+			for record in &records
+			{
+				writer.write(record).map_err(io)?;
+			}
+		}
+		[command, path] if command == "merge" =>
+		{
+			let records = read_records(path).map_err(err)?;
+			for (chrom, intervals) in merged_intervals(records)
+			{
+				for (start, end) in intervals
+				{
+					writeln!(out, "{chrom}\t{start}\t{end}").map_err(io)?;
+				}
+			}
+		}
+		[command, a, b] if command == "intersect" =>
+		{
+			let targets = merged_intervals(read_records(b).map_err(err)?);
+			let mut writer = BedWriter::new(&mut out);
+			let mut result = Ok(());
+			for_each_record(a, |record| {
+				let Some(intervals) = targets.get(&record.tid)
+				else
+				{
+					return;
+				};
+
+				// first merged interval ending after the record starts
+				let i = intervals.partition_point(|(_, end)| *end <= record.start);
+				let overlaps = intervals
+					.get(i)
+					.is_some_and(|(start, _)| *start < record.end);
+
+				if result.is_ok() && overlaps
+				{
+					result = writer.write(&record);
+				}
+			})
+			.map_err(err)?;
+			result.map_err(io)?;
+		}
+		[command, path] if command == "stats" =>
+		{
+			let report = blocking::detect_report(path).map_err(err)?;
+			let summary = open(path, TokenizerMode::Tolerant)
+				.and_then(|mut reader| reader.summarize())
+				.map_err(err)?;
+
+			writeln!(out, "format\t{:?}", report.kind).map_err(io)?;
+			writeln!(out, "format_confidence\t{:.2}", report.confidence).map_err(io)?;
+			for reason in &report.reasons
+			{
+				writeln!(out, "format_note\t{reason}").map_err(io)?;
+			}
+			writeln!(out, "records\t{}", summary.records).map_err(io)?;
+			writeln!(out, "filtered\t{}", summary.filtered).map_err(io)?;
+			writeln!(out, "bases\t{}", summary.bases()).map_err(io)?;
+			writeln!(out, "bases_covered\t{}", summary.bases_covered()).map_err(io)?;
+			writeln!(out, "strand\t+\t{}", summary.strands.plus).map_err(io)?;
+			writeln!(out, "strand\t-\t{}", summary.strands.minus).map_err(io)?;
+			writeln!(out, "strand\t.\t{}", summary.strands.unstranded).map_err(io)?;
+			for (lowest, highest, count) in summary.lengths.buckets()
+			{
+				writeln!(out, "length\t{lowest}-{highest}\t{count}").map_err(io)?;
+			}
+			if let (Some(min), Some(max)) = (summary.min_score, summary.max_score)
+			{
+				writeln!(out, "score_range\t{min}\t{max}").map_err(io)?;
+			}
+			for (lowest, highest, count) in summary.scores.buckets()
+			{
+				writeln!(out, "score\t{lowest}-{highest}\t{count}").map_err(io)?;
+			}
+			writeln!(out, "contigs\t{}", summary.contigs.len()).map_err(io)?;
+			for (contig, contig_summary) in &summary.contigs
+			{
+				writeln!(out, "contig\t{contig}\t{}", contig_summary.records).map_err(io)?;
+			}
+		}
+		[command, path] if command == "validate" =>
+		{
+			let mut problems = 0usize;
+			let mut previous: Option<(String, u64)> = None;
+			let mut seen = std::collections::HashSet::new();
+
+			// stricter than the other commands so stray spaces and CRLF endings are reported
+			let filtered_out = for_each_record_with(path, TokenizerMode::StrictTabs, |record| {
+				if record.end < record.start
+				{
+					problems += 1;
+					let _ = writeln!(
+						out,
+						"{}:{}-{}: end before start",
+						record.tid, record.start, record.end
+					);
+				}
+
+				match &previous
+				{
+					Some((tid, start)) if *tid == record.tid && record.start < *start =>
+					{
+						problems += 1;
+						let _ = writeln!(out, "{}:{}: not sorted by start", record.tid, record.start);
+					}
+					Some((tid, _)) if *tid != record.tid && !seen.insert(record.tid.clone()) =>
+					{
+						problems += 1;
+						let _ = writeln!(out, "{}: contig is not contiguous", record.tid);
+					}
+					None =>
+					{
+						seen.insert(record.tid.clone());
+					}
+					_ => (),
+				}
+
+				previous = Some((record.tid, record.start));
+			})
+			.map_err(err)?;
+
+			if filtered_out > 0
+			{
+				writeln!(out, "{filtered_out} lines skipped by the reader").map_err(io)?;
+			}
+
+			out.flush().map_err(io)?;
+			if problems > 0
+			{
+				return Err(format!("{problems} problems found"));
+			}
+
+			writeln!(out, "ok").map_err(io)?;
+		}
+		[command, path] if command == "index" =>
+		{
+			let data = blocking::index_bed(path).map_err(err)?;
+			writeln!(out, "{}", data.display()).map_err(io)?;
+		}
+		_ => return Err(USAGE.into()),
+	}
+
+	out.flush().map_err(io)
+}
+
+fn main() -> ExitCode
+{
+	let args: Vec<String> = std::env::args().skip(1).collect();
+
+	match run(&args)
+	{
+		Ok(()) => ExitCode::SUCCESS,
+		Err(e) =>
+		{
+			eprintln!("{e}");
+			ExitCode::FAILURE
+		}
+	}
+}
@@ -0,0 +1,299 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncSeek, BufReader as TokioBufReader};
+use tokio::runtime::{Builder, Runtime};
+
+use pufferfish::prelude::*;
+
+use crate::bed::autooneshotreader;
+use crate::bed::autooneshotreader::AutoOneShotBlockReaderTrait;
+use crate::bed::oneshotreader::ReaderOptions;
+use crate::bed::{BedKind, BedRecord, BedSink, BgzfBlock, BinnedCounts, SourceId, UriReader};
+use crate::bed::{Detection, DetectionReport, ReaderDescription, ReaderStats, Summary};
+use crate::region::Region;
+use crate::store::{DefaultResolver, TidResolver};
+use crate::tabix;
+
+use crate::error;
+
+// Each reader drives its own current-thread runtime, so these must not be called from
+// inside an async context (tokio panics on nested block_on).
+fn runtime() -> error::Result<Runtime>
+{
+	Ok(Builder::new_current_thread().enable_all().build()?)
+}
+
+pub fn detect_format<P>(path: P) -> error::Result<BedKind>
+where
+	P: AsRef<Path>,
+{
+	runtime()?.block_on(crate::bed::detect_format(path))
+}
+
+pub fn detect_kind<P>(path: P) -> error::Result<Detection>
+where
+	P: AsRef<Path>,
+{
+	runtime()?.block_on(crate::bed::detect_kind(path))
+}
+
+pub fn detect_report<P>(path: P) -> error::Result<DetectionReport>
+where
+	P: AsRef<Path>,
+{
+	runtime()?.block_on(crate::bed::detect_report(path))
+}
+
+#[cfg(feature = "indexing")]
+pub fn index_bed<P>(path: P) -> error::Result<std::path::PathBuf>
+where
+	P: AsRef<Path>,
+{
+	runtime()?.block_on(crate::bed::index_bed(path))
+}
+
+pub struct AutoOneShotBlockReader<R, T>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	runtime: Runtime,
+	inner: autooneshotreader::AutoOneShotBlockReader<R, T>,
+}
+
+impl AutoOneShotBlockReader<File, DefaultResolver>
+{
+	pub fn from_path<P>(
+		path: P,
+		source_id: impl Into<Option<SourceId>> + 'static,
+		pool: Arc<pool::BgzfBlockPool>,
+	) -> error::Result<Self>
+	where
+		P: AsRef<Path> + Copy,
+	{
+		Self::from_path_with(path, source_id, pool, ReaderOptions::default())
+	}
+}
+
+impl<T> AutoOneShotBlockReader<File, T>
+where
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	pub fn from_path_with<P>(
+		path: P,
+		source_id: impl Into<Option<SourceId>> + 'static,
+		pool: Arc<pool::BgzfBlockPool>,
+		options: ReaderOptions<T>,
+	) -> error::Result<Self>
+	where
+		P: AsRef<Path> + Copy,
+	{
+		let runtime = runtime()?;
+		let inner = runtime.block_on(autooneshotreader::from_path_with(
+			path, source_id, pool, options,
+		))?;
+
+		Ok(Self { runtime, inner })
+	}
+
+	// as from_path_with, with the index IndexSearch finds beside the file when there is one,
+	// so region queries work without ensure_indexed
+	pub fn from_path_indexed<P>(
+		path: P,
+		source_id: impl Into<Option<SourceId>> + 'static,
+		pool: Arc<pool::BgzfBlockPool>,
+		options: ReaderOptions<T>,
+	) -> error::Result<Self>
+	where
+		P: AsRef<Path> + Copy,
+	{
+		let runtime = runtime()?;
+
+		let options = match runtime.block_on(tabix::Reader::for_data_path(
+			path,
+			&tabix::IndexSearch::default(),
+		))?
+		{
+			Some(index) => options.with_index(Arc::clone(index.index())),
+			None => options,
+		};
+
+		let inner = runtime.block_on(autooneshotreader::from_path_with(
+			path, source_id, pool, options,
+		))?;
+
+		Ok(Self { runtime, inner })
+	}
+
+	#[cfg(feature = "indexing")]
+	pub fn ensure_indexed<D>(self, temp_dir: D) -> error::Result<Self>
+	where
+		D: AsRef<Path>,
+	{
+		let Self { runtime, inner } = self;
+		let inner = runtime.block_on(inner.ensure_indexed(temp_dir))?;
+
+		Ok(Self { runtime, inner })
+	}
+}
+
+impl<T> AutoOneShotBlockReader<TokioBufReader<UriReader>, T>
+where
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	pub fn from_uri_with(
+		uri: &str,
+		source_id: impl Into<Option<SourceId>> + 'static,
+		pool: Arc<pool::BgzfBlockPool>,
+		options: ReaderOptions<T>,
+	) -> error::Result<Self>
+	{
+		let runtime = runtime()?;
+		let inner = runtime.block_on(autooneshotreader::from_uri_with(
+			uri, source_id, pool, options,
+		))?;
+
+		Ok(Self { runtime, inner })
+	}
+}
+
+impl<R, T> AutoOneShotBlockReader<R, T>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	// a clone gets its own runtime, so it can be moved to another thread
+	pub fn try_clone(&self) -> error::Result<Self>
+	{
+		Ok(Self {
+			runtime: runtime()?,
+			inner: self.inner.try_clone()?,
+		})
+	}
+}
+
+impl<R, T> AutoOneShotBlockReader<R, T>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	pub fn name(&self) -> String
+	{
+		self.inner.name()
+	}
+
+	pub fn stats(&self) -> ReaderStats
+	{
+		self.inner.stats()
+	}
+
+	pub fn describe(&self) -> ReaderDescription
+	{
+		self.inner.describe()
+	}
+
+	pub fn next_bgzf_blocks(&mut self, n: usize) -> error::Result<Option<BgzfBlock>>
+	{
+		self.runtime.block_on(self.inner.next_bgzf_blocks(n))
+	}
+
+	pub fn read_tids_in_block_sink<S>(
+		&self,
+		block: BgzfBlock,
+		sink: &mut S,
+	) -> error::Result<Option<usize>>
+	where
+		S: BedSink<T::Tid> + ?Sized,
+	{
+		self.runtime
+			.block_on(self.inner.read_tids_in_block_sink(block, sink))
+	}
+
+	// reads the whole file into the sink, returning how many records the filter dropped
+	pub fn read_all_sink<S>(&mut self, blocks_per_batch: usize, sink: &mut S) -> error::Result<usize>
+	where
+		S: BedSink<T::Tid> + ?Sized,
+	{
+		let mut filtered_out = 0;
+
+		while let Some(block) = self.next_bgzf_blocks(blocks_per_batch)?
+		{
+			filtered_out += self.read_tids_in_block_sink(block, sink)?.unwrap_or(0);
+		}
+
+		Ok(filtered_out)
+	}
+
+	pub fn check_sorted(&mut self) -> error::Result<bool>
+	{
+		self.runtime.block_on(self.inner.check_sorted())
+	}
+
+	pub fn is_complete(&self) -> Option<bool>
+	{
+		self.inner.is_complete()
+	}
+
+	pub fn summarize(&mut self) -> error::Result<Summary<T::Tid>>
+	{
+		self.runtime.block_on(self.inner.summarize())
+	}
+
+	pub fn count_in_region(&mut self, region: &Region) -> error::Result<u64>
+	{
+		self.runtime.block_on(self.inner.count_in_region(region))
+	}
+
+	pub fn bases_covered_in_region(&mut self, region: &Region) -> error::Result<u64>
+	{
+		self.runtime.block_on(self.inner.bases_covered_in_region(region))
+	}
+
+	pub fn extent(&mut self, tid: &str) -> error::Result<Option<(u64, u64)>>
+	{
+		self.runtime.block_on(self.inner.extent(tid))
+	}
+
+	pub fn binned_counts(
+		&mut self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		n_bins: usize,
+	) -> error::Result<BinnedCounts<T::Tid>>
+	{
+		self.runtime.block_on(self.inner.binned_counts(tid, start, end, n_bins))
+	}
+
+	pub fn query_region_concurrent(
+		&self,
+		region: &Region,
+		parallelism: usize,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		self.runtime.block_on(self.inner.query_region_concurrent(region, parallelism))
+	}
+
+	pub fn sample_region(
+		&self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		max_records: usize,
+	) -> error::Result<Vec<BedRecord<T::Tid>>>
+	{
+		self.runtime.block_on(self.inner.sample_region(tid, start, end, max_records))
+	}
+
+	pub fn close(self) -> error::Result<()>
+	{
+		self.runtime.block_on(self.inner.close())
+	}
+
+	pub fn into_inner(self) -> autooneshotreader::AutoOneShotBlockReader<R, T>
+	{
+		self.inner
+	}
+}
@@ -0,0 +1,271 @@
+#![cfg(feature = "capi")]
+
+// Build a C library with `cargo rustc --release --features capi --crate-type cdylib` (or
+// staticlib) and include include/sandman.h, which declares everything below.
+// Records handed out by sandman_reader_next stay valid until the next call on the same reader.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::sync::Arc;
+
+use tokio::fs::File;
+
+use pufferfish::prelude::*;
+
+use crate::bed::oneshotreader::ReaderOptions;
+use crate::bed::{BedRecord, CollectSink, ScoreField, Strand};
+use crate::blocking::AutoOneShotBlockReader;
+use crate::region::Region;
+
+const BLOCKS_PER_BATCH: usize = 64;
+const QUERY_PARALLELISM: usize = 4;
+
+thread_local! {
+	static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String)
+{
+	let message = CString::new(message).unwrap_or_else(|_| c"error message contained NUL".into());
+	LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+#[repr(C)]
+pub struct SandmanRecord
+{
+	pub tid: *const c_char,
+	pub start: u64,
+	pub end: u64,
+	// '+', '-' or '.'
+	pub strand: c_char,
+	// NULL when the format has no name column
+	pub name: *const c_char,
+	pub has_score: bool,
+	pub score: f32,
+}
+
+struct OwnedRecord
+{
+	tid: CString,
+	start: u64,
+	end: u64,
+	strand: Strand,
+	name: Option<CString>,
+	score: Option<f32>,
+}
+
+impl From<BedRecord<String>> for OwnedRecord
+{
+	fn from(record: BedRecord<String>) -> Self
+	{
+		Self {
+			tid: CString::new(record.tid).unwrap_or_default(),
+			start: record.start,
+			end: record.end,
+			strand: record.strand,
+			name: record
+				.value
+				.get_name()
+				.and_then(|name| CString::new(name).ok()),
+			score: record.value.get_f32(ScoreField::Score),
+		}
+	}
+}
+
+pub struct SandmanReader
+{
+	reader: AutoOneShotBlockReader<File, ()>,
+	sink: CollectSink<String>,
+	// the records of the last sandman_reader_query not handed out yet
+	query: Option<VecDeque<BedRecord<String>>>,
+	current: Option<OwnedRecord>,
+}
+
+/// # Safety
+/// `path` must be a valid NUL terminated string. An index beside the file is loaded for
+/// `sandman_reader_query`. Returns NULL on failure, see `sandman_last_error`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sandman_reader_open(path: *const c_char) -> *mut SandmanReader
+{
+	if path.is_null()
+	{
+		set_last_error("path is NULL".into());
+		return std::ptr::null_mut();
+	}
+
+	let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str()
+	else
+	{
+		set_last_error("path is not valid UTF-8".into());
+		return std::ptr::null_mut();
+	};
+
+	let pool = Arc::new(pool::BgzfBlockPool::new(1024, 64 * 1024));
+
+	let options = ReaderOptions::<()>::default();
+
+	match AutoOneShotBlockReader::from_path_indexed(path, None, pool, options)
+	{
+		Ok(reader) => Box::into_raw(Box::new(SandmanReader {
+			reader,
+			sink: CollectSink::default(),
+			query: None,
+			current: None,
+		})),
+		Err(e) =>
+		{
+			set_last_error(e.to_string());
+			std::ptr::null_mut()
+		}
+	}
+}
+
+/// # Safety
+/// `reader` must come from `sandman_reader_open` and `record` must point to writable memory.
+/// Returns 1 when a record was written, 0 at end of file and -1 on error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sandman_reader_next(
+	reader: *mut SandmanReader,
+	record: *mut SandmanRecord,
+) -> c_int
+{
+	let (Some(reader), Some(record)) = (unsafe { reader.as_mut() }, unsafe { record.as_mut() })
+	else
+	{
+		set_last_error("reader or record is NULL".into());
+		return -1;
+	};
+
+	let next = match &mut reader.query
+	{
+		Some(records) => match records.pop_front()
+		{
+			Some(next) => next,
+			None => return 0,
+		},
+		None =>
+		{
+			while reader.sink.is_empty()
+			{
+				match reader.reader.next_bgzf_blocks(BLOCKS_PER_BATCH)
+				{
+					Ok(Some(block)) =>
+					{
+						if let Err(e) =
+							reader.reader.read_tids_in_block_sink(block, &mut reader.sink)
+						{
+							set_last_error(e.to_string());
+							return -1;
+						}
+					}
+					Ok(None) => return 0,
+					Err(e) =>
+					{
+						set_last_error(e.to_string());
+						return -1;
+					}
+				}
+			}
+
+			reader.sink.pop().expect("record queued")
+		}
+	};
+
+	let current = reader.current.insert(next.into());
+
+	*record = SandmanRecord {
+		tid: current.tid.as_ptr(),
+		start: current.start,
+		end: current.end,
+		strand: match current.strand
+		{
+			Strand::Plus => b'+',
+			Strand::Minus => b'-',
+			Strand::Both => b'.',
+		} as c_char,
+		name: current
+			.name
+			.as_ref()
+			.map_or(std::ptr::null(), |name| name.as_ptr()),
+		has_score: current.score.is_some(),
+		score: current.score.unwrap_or(0.0),
+	};
+
+	1
+}
+
+/// # Safety
+/// `reader` must come from `sandman_reader_open` and `chrom` must be a valid NUL terminated
+/// string or NULL. Looks up the records overlapping chrom:start-end, 0-based half-open, through the
+/// index, after which `sandman_reader_next` hands out those records and then returns 0 until
+/// the next query. A NULL `chrom` leaves query mode, `sandman_reader_next` carrying on through
+/// the file from where it was. Returns the number of records found, or -1 on error, such as
+/// when the file has no index.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sandman_reader_query(
+	reader: *mut SandmanReader,
+	chrom: *const c_char,
+	start: u64,
+	end: u64,
+) -> i64
+{
+	let Some(reader) = (unsafe { reader.as_mut() })
+	else
+	{
+		set_last_error("reader is NULL".into());
+		return -1;
+	};
+
+	if chrom.is_null()
+	{
+		reader.query = None;
+		return 0;
+	}
+
+	let Ok(chrom) = unsafe { CStr::from_ptr(chrom) }.to_str()
+	else
+	{
+		set_last_error("chrom is not valid UTF-8".into());
+		return -1;
+	};
+
+	let region = Region::zero_based(chrom, start, end);
+
+	match reader.reader.query_region_concurrent(&region, QUERY_PARALLELISM)
+	{
+		Ok(records) =>
+		{
+			let found = records.len() as i64;
+			reader.query = Some(records.into());
+			found
+		}
+		Err(e) =>
+		{
+			set_last_error(e.to_string());
+			-1
+		}
+	}
+}
+
+/// # Safety
+/// `reader` must come from `sandman_reader_open` (or be NULL) and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sandman_reader_free(reader: *mut SandmanReader)
+{
+	if !reader.is_null()
+	{
+		drop(unsafe { Box::from_raw(reader) });
+	}
+}
+
+// Message for the last failed call on this thread, NULL if none. Valid until the next failure.
+#[unsafe(no_mangle)]
+pub extern "C" fn sandman_last_error() -> *const c_char
+{
+	LAST_ERROR.with(|last| {
+		last.borrow()
+			.as_ref()
+			.map_or(std::ptr::null(), |message| message.as_ptr())
+	})
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "futures-io")]
+
+use std::sync::Arc;
+
+use futures::io::{AsyncRead as FuturesAsyncRead, AsyncSeek as FuturesAsyncSeek};
+
+use tokio::io::BufReader as TokioBufReader;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+pub use tokio_util::compat::Compat;
+
+use pufferfish::prelude::*;
+
+use crate::bed::autooneshotreader::{self, AutoOneShotBlockReader};
+use crate::bed::oneshotreader::ReaderOptions;
+use crate::bed::SourceId;
+use crate::store::{DefaultResolver, TidResolver};
+
+use crate::error;
+
+// Only the IO traits are tokio's here: the readers themselves need no tokio runtime as long
+// as the source is opened through another runtime (async-std, smol, ...) and handed in.
+
+pub fn from_futures_io<R>(reader: R) -> Compat<R>
+where
+	R: FuturesAsyncRead + FuturesAsyncSeek + Unpin,
+{
+	reader.compat()
+}
+
+pub async fn from_futures_reader<R>(
+	name: String,
+	reader: R,
+	source_id: impl Into<Option<SourceId>> + 'static,
+	pool: Arc<pool::BgzfBlockPool>,
+) -> error::Result<AutoOneShotBlockReader<TokioBufReader<Compat<R>>, DefaultResolver>>
+where
+	R: FuturesAsyncRead + FuturesAsyncSeek + Unpin + Send + Sync + 'static,
+{
+	from_futures_reader_with(name, reader, source_id, pool, ReaderOptions::default()).await
+}
+
+pub async fn from_futures_reader_with<R, T>(
+	name: String,
+	reader: R,
+	source_id: impl Into<Option<SourceId>> + 'static,
+	pool: Arc<pool::BgzfBlockPool>,
+	options: ReaderOptions<T>,
+) -> error::Result<AutoOneShotBlockReader<TokioBufReader<Compat<R>>, T>>
+where
+	R: FuturesAsyncRead + FuturesAsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	let reader = TokioBufReader::new(from_futures_io(reader));
+	autooneshotreader::from_reader_with(name, reader, source_id, pool, options).await
+}
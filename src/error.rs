@@ -5,6 +5,85 @@ use lexical_core::Error as LexicalCoreError;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+// where an error happened, attached on the way up so failures deep in a pipeline still say
+// which file and stage they came from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context
+{
+	pub file: Option<String>,
+	// e.g. "tabix header", "region query", "record parse"
+	pub phase: &'static str,
+	pub region: Option<String>,
+	// record parses count these from the start of the batch of blocks being parsed
+	pub byte_offset: Option<u64>,
+	pub line: Option<u64>,
+}
+
+impl Context
+{
+	pub fn new(phase: &'static str) -> Self
+	{
+		Self {
+			phase,
+			..Default::default()
+		}
+	}
+
+	pub fn with_file(mut self, file: impl Into<String>) -> Self
+	{
+		self.file = Some(file.into());
+		self
+	}
+
+	pub fn with_region(mut self, region: impl ToString) -> Self
+	{
+		self.region = Some(region.to_string());
+		self
+	}
+
+	pub fn with_byte_offset(mut self, byte_offset: u64) -> Self
+	{
+		self.byte_offset = Some(byte_offset);
+		self
+	}
+
+	pub fn with_line(mut self, line: u64) -> Self
+	{
+		self.line = Some(line);
+		self
+	}
+}
+
+impl std::fmt::Display for Context
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		f.write_str(self.phase)?;
+
+		if let Some(file) = &self.file
+		{
+			write!(f, " in {file}")?;
+		}
+
+		if let Some(region) = &self.region
+		{
+			write!(f, " for {region}")?;
+		}
+
+		if let Some(line) = self.line
+		{
+			write!(f, " at line {line}")?;
+		}
+
+		if let Some(byte_offset) = self.byte_offset
+		{
+			write!(f, " (byte {byte_offset})")?;
+		}
+
+		Ok(())
+	}
+}
+
 #[derive(Error, Debug)]
 pub enum Error
 {
@@ -56,6 +135,58 @@ pub enum Error
 	NoIndex(String),
 	#[error("Associated Tabix file for BED {0} not open")]
 	TabixNotOpen(String),
+	#[error("Invalid url {0}")]
+	InvalidUrl(String),
+	#[error("Timed out reading {0}")]
+	Timeout(String),
+	#[error("Position {0} is beyond the 512 Mb a tabix index can address")]
+	PositionOutOfRange(u64),
+	#[error("Invalid region {0}")]
+	InvalidRegion(String),
+	#[error("Invalid BED12 record: {0}")]
+	InvalidBed12(String),
+	#[error("Query on {0} exceeded the limit of {1} records")]
+	QueryTooLarge(String, u64),
+	#[error("Query on {0} exceeded the limit of {1} bytes")]
+	ResultTooLarge(String, usize),
+	#[error("{0} is not sorted, {1}:{2} is out of order")]
+	Unsorted(String, String, u64),
+	#[error("Invalid UTF-8 in {0} at byte {1}")]
+	InvalidUtf8(String, usize),
+	#[error("{0} cannot be reopened, it has no way to open its source again")]
+	NotReopenable(String),
+	#[error("{0} indexes are not supported")]
+	UnsupportedIndex(String),
+	#[error("Invalid line in chain file {0}: {1}")]
+	ChainFormat(String, String),
+	#[error("Invalid line in PSL file {0}: {1}")]
+	PslFormat(String, String),
+	#[error("Invalid line in SAF file {0}: {1}")]
+	SafFormat(String, String),
+	#[error("Invalid line in GTF file {0}: {1}")]
+	GtfFormat(String, String),
+	#[error("Invalid line in alias file {0}: {1}")]
+	AliasFormat(String, String),
+	#[error("Round trip through BedWriter changed {0}")]
+	RoundTrip(String),
+	#[error("Inconsistent tabix index: {0}")]
+	IndexOffset(String),
+	#[error("{0} ends partway through a BGZF block at byte {1}")]
+	TruncatedBgzf(String, u64),
+	#[error("BGZF block at byte {offset} does not match its CRC32 or ISIZE")]
+	CorruptBlock
+	{
+		offset: u64,
+	},
+	#[error("{context}: {source}")]
+	Context
+	{
+		context: Context,
+		source: Box<Error>,
+	},
+	#[cfg(feature = "object-store")]
+	#[error(transparent)]
+	ObjectStore(#[from] object_store::Error),
 	#[error(transparent)]
 	Pufferfish(#[from] PufferfishError),
 	#[error(transparent)]
@@ -63,3 +194,55 @@ pub enum Error
 	#[error("IO error: {0}")]
 	Io(#[from] std::io::Error),
 }
+
+impl Error
+{
+	pub fn with_context(self, context: Context) -> Self
+	{
+		Error::Context {
+			context,
+			source: Box::new(self),
+		}
+	}
+
+	// the contexts from outermost to innermost
+	pub fn contexts(&self) -> impl Iterator<Item = &Context>
+	{
+		let mut current = Some(self);
+
+		std::iter::from_fn(move || match current.take()?
+		{
+			Error::Context { context, source } =>
+			{
+				current = Some(source.as_ref());
+				Some(context)
+			}
+			_ => None,
+		})
+	}
+
+	// the underlying error with any context stripped
+	pub fn root(&self) -> &Error
+	{
+		match self
+		{
+			Error::Context { source, .. } => source.root(),
+			e => e,
+		}
+	}
+}
+
+pub trait ResultExt<T>
+{
+	fn context(self, context: impl FnOnce() -> Context) -> Result<T>;
+}
+
+impl<T, E> ResultExt<T> for std::result::Result<T, E>
+where
+	E: Into<Error>,
+{
+	fn context(self, context: impl FnOnce() -> Context) -> Result<T>
+	{
+		self.map_err(|e| e.into().with_context(context()))
+	}
+}
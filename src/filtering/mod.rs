@@ -8,12 +8,14 @@ use std::collections::HashMap;
 use crate::bed::Strand;
 use crate::filtering::basechecker::BaseChecker;
 use crate::bed::ScoreField;
+use crate::bed::ModCode;
 
 #[derive(Default)]
 pub struct ReadFilterContext
 {
 	minimum_scores: Option<Vec<(ScoreField, f32)>>,
 	basechecker: Option<BaseChecker>,
+	mod_codes: Option<Vec<ModCode>>,
 }
 
 impl ReadFilterContext
@@ -25,6 +27,14 @@ impl ReadFilterContext
 			.push((ix, score));
 	}
 
+	// keeps only records whose name column is one of the added modification codes
+	pub fn filter_mod_code(&mut self, code: impl Into<ModCode>)
+	{
+		self.mod_codes
+			.get_or_insert_with(Vec::new)
+			.push(code.into());
+	}
+
 	pub fn set_basechecker(&mut self, contigs: Contigs, checker_map: HashMap<Cow<'static, str>, u8>)
 	{
 		self.basechecker = Some(BaseChecker(contigs, checker_map));
@@ -99,6 +109,18 @@ impl ReadFilterContext
 			}
 		}
 
+		if let Some(mod_codes) = &self.mod_codes
+		{
+			let code = name
+				.and_then(|name| std::str::from_utf8(name).ok())
+				.and_then(ModCode::parse);
+
+			if !code.is_some_and(|code| mod_codes.contains(&code))
+			{
+				return false;
+			}
+		}
+
 		if let Some(basechecker) = &self.basechecker
 		{
 			let Some(name) = name
@@ -107,10 +129,10 @@ impl ReadFilterContext
 				return false; // base checker requires name but missing
 			};
 
-			let name = unsafe { std::str::from_utf8_unchecked(name) };
+			let name = String::from_utf8_lossy(name);
 
 			if basechecker
-				.check_base(&tid, start, end, &strand, name)
+				.check_base(&tid, start, end, &strand, &name)
 				.await
 				.is_err()
 			{
@@ -1,21 +1,75 @@
+pub mod alignment;
+pub mod annotation;
 pub mod bed;
+#[cfg(feature = "fs")]
+pub mod blocking;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "futures-io")]
+pub mod compat;
 pub mod error;
 pub mod filtering;
+pub mod liftover;
+pub mod methyl;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod ops;
+pub mod pipeline;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod region;
+#[cfg(any(feature = "object-store", all(target_arch = "wasm32", target_os = "unknown")))]
+pub mod remote;
+pub mod retry;
 pub mod store;
 pub mod tabix;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
 
 pub use pufferfish::prelude as pufferfish;
 
 pub mod prelude
 {
-	pub use crate::bed::autooneshotreader::AutoOneShotBlockReaderTrait;
+	pub use crate::alignment::{Psl, Side};
+	pub use crate::annotation::{GtfRecord, SafRecord};
+	pub use crate::bed::autooneshotreader;
+	pub use crate::bed::autooneshotreader::{AutoOneShotBlockReader, AutoOneShotBlockReaderTrait};
+	pub use crate::bed::autowriter::{AutoWriter, PlainBedWriter};
+	#[cfg(feature = "bgzf-write")]
+	pub use crate::bed::autowriter::BgzfBedWriter;
+	pub use crate::bed::oneshotreader::{OneShotBlockReader, ReaderOptions};
+	#[cfg(feature = "fs")]
+	pub use crate::bed::{detect_format, detect_kind, detect_report};
 
 	pub use crate::bed::ScoreField;
 
-	pub use crate::store::DefaultTid;
+	pub use crate::store::{DefaultTid, DefaultResolver};
+	pub use crate::store::{AliasResolver, ChromAliases};
+	pub use crate::store::{TidLookup, TidResolver};
 
-	pub use crate::bed::{BedSinkValue, BedSink};
+	pub use crate::bed::{BedSinkValue, BedSink, BedFieldsSink};
 	pub use crate::bed::{SourceId, ReaderId};
-	pub use crate::bed::Strand;
+	pub use crate::bed::{BedKind, BedMethylLayout, ModCode, Strand, TokenizerMode, Utf8Policy};
+	pub use crate::bed::{BedRecord, CollectSink, GenomicInterval, RecordBatch, RecordStreamExt};
+	pub use crate::bed::{BedPlusColumns, LazyBedRecord, PeekableReader};
+	pub use crate::bed::{ColumnSpec, DynBedExtra, FieldValue, Projection};
+	#[cfg(feature = "derive")]
+	pub use crate::bed::BedFields;
+	pub use crate::bed::{Detection, DetectionReport, ReaderDescription, ReaderStats};
+	pub use crate::bed::{BinnedCounts, Summary};
+	pub use crate::bed::{BedWriter, BedpeWriter, PairedRecord, WriterOptions};
+	pub use crate::bed::Warning;
 	pub use crate::bed::{Bed3Fields, Bed4Extra, Bed5Extra, Bed6Extra, Bed12Extra, BedMethylExtra};
+
+	pub use crate::filtering::ReadFilterContext;
+	pub use crate::liftover::{ChainMap, LiftOptions, Lifted};
+	pub use crate::ops::{Annotated, JoinKind, JoinPair, NucContent, OverlapOptions, RecordSequence};
+	pub use crate::ops::{BedGraphRecord, CoverageOptions, RollingStat, ScoreColumn};
+	pub use crate::ops::{Metaprofile, ScoreMatrix, Segment, SegmentOptions};
+	pub use crate::pipeline::{Corrections, CopySummary, Sanitizer, Transforms};
+	pub use crate::pipeline::{Scaling, ScoreScale};
+	pub use crate::region::{CoordinateSystem, Position, Region};
+	pub use crate::retry::RetryPolicy;
 }
@@ -0,0 +1,205 @@
+use crate::alignment::{Side, bed12_record};
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+
+// One alignment chain from a UCSC chain file. Target positions are on the assembly being
+// lifted from, query positions on the one lifted to, both 0-based half-open. Query
+// positions on the minus strand count from the end of the query contig, as the file holds
+// them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain
+{
+	pub score: f64,
+	pub t_name: String,
+	pub t_size: u64,
+	pub t_start: u64,
+	pub t_end: u64,
+	pub q_name: String,
+	pub q_size: u64,
+	pub q_strand: Strand,
+	pub q_start: u64,
+	pub q_end: u64,
+	pub id: Option<u64>,
+	pub blocks: Vec<ChainBlock>,
+}
+
+// an ungapped stretch aligned in both assemblies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainBlock
+{
+	pub t_start: u64,
+	pub q_start: u64,
+	pub size: u64,
+}
+
+impl ChainBlock
+{
+	pub fn t_end(&self) -> u64
+	{
+		self.t_start + self.size
+	}
+
+	pub fn q_end(&self) -> u64
+	{
+		self.q_start + self.size
+	}
+}
+
+impl Chain
+{
+	// a query interval in forward strand positions
+	pub fn forward_query(&self, start: u64, end: u64) -> (u64, u64)
+	{
+		if let Strand::Minus = self.q_strand
+		{
+			(self.q_size - end, self.q_size - start)
+		}
+		else
+		{
+			(start, end)
+		}
+	}
+
+	// The chain as a BED12 record on one side, its blocks the aligned stretches and its name
+	// the contig on the other side, with score 0 as chain scores do not fit the BED range.
+	pub fn to_bed_record(&self, side: Side) -> BedRecord<String>
+	{
+		let (tid, other) = match side
+		{
+			Side::Target => (&self.t_name, &self.q_name),
+			Side::Query => (&self.q_name, &self.t_name),
+		};
+
+		let blocks = self
+			.blocks
+			.iter()
+			.map(|block| match side
+			{
+				Side::Target => (block.t_start, block.t_end()),
+				Side::Query => self.forward_query(block.q_start, block.q_end()),
+			})
+			.collect();
+
+		bed12_record(tid.clone(), self.q_strand, other.clone(), blocks)
+	}
+}
+
+// every chain in the text of a chain file, in the order they appear
+pub(crate) fn parse_chains(name: &str, text: &[u8]) -> error::Result<Vec<Chain>>
+{
+	let mut parser = ChainParser::new(name);
+	let mut chains = Vec::new();
+
+	for line in text.split(|byte| *byte == b'\n')
+	{
+		chains.extend(parser.line(line)?);
+	}
+
+	chains.extend(parser.finish());
+
+	Ok(chains)
+}
+
+// Builds chains a line at a time, handing each back once the next header or the end of
+// the file closes it.
+pub(crate) struct ChainParser
+{
+	name: String,
+	// the chain being read, with the target and query positions of its next block
+	current: Option<(Chain, u64, u64)>,
+}
+
+impl ChainParser
+{
+	pub(crate) fn new(name: &str) -> Self
+	{
+		Self {
+			name: name.to_string(),
+			current: None,
+		}
+	}
+
+	pub(crate) fn line(&mut self, line: &[u8]) -> error::Result<Option<Chain>>
+	{
+		let line = line.trim_ascii();
+
+		if line.is_empty() || line.starts_with(b"#")
+		{
+			return Ok(None);
+		}
+
+		let invalid = || {
+			error::Error::ChainFormat(self.name.clone(), String::from_utf8_lossy(line).into_owned())
+		};
+
+		let fields: Vec<&str> = std::str::from_utf8(line)
+			.map_err(|_| invalid())?
+			.split_ascii_whitespace()
+			.collect();
+
+		if fields[0] == "chain"
+		{
+			let chain = parse_header(&fields).ok_or_else(invalid)?;
+			let (t, q) = (chain.t_start, chain.q_start);
+
+			let finished = self.current.replace((chain, t, q));
+			return Ok(finished.map(|(chain, _, _)| chain));
+		}
+
+		let numbers: Option<Vec<u64>> = fields.iter().map(|field| field.parse().ok()).collect();
+
+		// the last line of a chain has only the size of its final block
+		let (size, dt, dq) = match numbers.as_deref()
+		{
+			Some([size, dt, dq]) => (*size, *dt, *dq),
+			Some([size]) => (*size, 0, 0),
+			_ => return Err(invalid()),
+		};
+
+		let Some((chain, t, q)) = self.current.as_mut()
+		else
+		{
+			return Err(invalid());
+		};
+
+		chain.blocks.push(ChainBlock {
+			t_start: *t,
+			q_start: *q,
+			size,
+		});
+
+		*t += size + dt;
+		*q += size + dq;
+
+		Ok(None)
+	}
+
+	pub(crate) fn finish(self) -> Option<Chain>
+	{
+		self.current.map(|(chain, _, _)| chain)
+	}
+}
+
+// chain score tName tSize tStrand tStart tEnd qName qSize qStrand qStart qEnd [id]
+fn parse_header(fields: &[&str]) -> Option<Chain>
+{
+	if fields.len() < 12 || fields[4] != "+"
+	{
+		return None;
+	}
+
+	Some(Chain {
+		score: fields[1].parse().ok()?,
+		t_name: fields[2].to_string(),
+		t_size: fields[3].parse().ok()?,
+		t_start: fields[5].parse().ok()?,
+		t_end: fields[6].parse().ok()?,
+		q_name: fields[7].to_string(),
+		q_size: fields[8].parse().ok()?,
+		q_strand: Strand::from(fields[9]),
+		q_start: fields[10].parse().ok()?,
+		q_end: fields[11].parse().ok()?,
+		id: fields.get(12).and_then(|id| id.parse().ok()),
+		blocks: Vec::new(),
+	})
+}
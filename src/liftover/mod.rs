@@ -0,0 +1,324 @@
+mod chain;
+
+pub use chain::{Chain, ChainBlock};
+pub(crate) use chain::ChainParser;
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::collections::HashMap;
+
+use futures::stream::{Stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiftOptions
+{
+	// share of an interval's bases a chain has to map, liftOver -minMatch
+	pub min_match: f64,
+	// every chain passing min_match gives an interval, rather than the interval being
+	// unmapped as duplicated, liftOver -multiple
+	pub multiple: bool,
+	// one interval per aligned block rather than one spanning the gaps between them
+	pub split: bool,
+}
+
+impl Default for LiftOptions
+{
+	fn default() -> Self
+	{
+		Self {
+			min_match: 0.95,
+			multiple: false,
+			split: false,
+		}
+	}
+}
+
+impl LiftOptions
+{
+	pub fn with_min_match(mut self, min_match: f64) -> Self
+	{
+		self.min_match = min_match;
+		self
+	}
+
+	pub fn with_multiple(mut self, multiple: bool) -> Self
+	{
+		self.multiple = multiple;
+		self
+	}
+
+	pub fn with_split(mut self, split: bool) -> Self
+	{
+		self.split = split;
+		self
+	}
+}
+
+// why an interval did not lift, as liftOver writes them to its unmapped file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unmapped
+{
+	// no chain maps any of it
+	Deleted,
+	// no chain maps min_match of it
+	PartiallyDeleted,
+	// more than one chain maps it and multiple is not set
+	Duplicated,
+}
+
+impl std::fmt::Display for Unmapped
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		let reason = match self
+		{
+			Unmapped::Deleted => "Deleted in new",
+			Unmapped::PartiallyDeleted => "Partially deleted in new",
+			Unmapped::Duplicated => "Duplicated in new",
+		};
+
+		write!(f, "{reason}")
+	}
+}
+
+// one interval on the new assembly, 0-based half-open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiftedInterval
+{
+	pub tid: String,
+	pub start: u64,
+	pub end: u64,
+	pub strand: Strand,
+}
+
+#[derive(Debug, Clone)]
+pub enum Lifted<Tid>
+{
+	// more than one for split or multiple lifts
+	Mapped(Vec<BedRecord<String>>),
+	Unmapped(BedRecord<Tid>, Unmapped),
+}
+
+// The chains of a chain file by the contig they lift from, each contig's sorted by start.
+#[derive(Debug, Clone, Default)]
+pub struct ChainMap
+{
+	chains: HashMap<String, Vec<Chain>>,
+	// the longest chain on each contig, so a lookup knows how far back to search
+	longest: HashMap<String, u64>,
+}
+
+impl ChainMap
+{
+	pub fn new(chains: Vec<Chain>) -> Self
+	{
+		let mut map = Self::default();
+
+		for chain in chains
+		{
+			let longest = map.longest.entry(chain.t_name.clone()).or_default();
+			*longest = (*longest).max(chain.t_end - chain.t_start);
+
+			map.chains.entry(chain.t_name.clone()).or_default().push(chain);
+		}
+
+		for chains in map.chains.values_mut()
+		{
+			chains.sort_by_key(|chain| (chain.t_start, chain.t_end));
+		}
+
+		map
+	}
+
+	// the text of an uncompressed chain file
+	pub fn parse(name: &str, text: &[u8]) -> error::Result<Self>
+	{
+		Ok(Self::new(chain::parse_chains(name, text)?))
+	}
+
+	pub async fn from_reader<R>(name: &str, mut reader: R) -> error::Result<Self>
+	where
+		R: AsyncRead + std::marker::Unpin,
+	{
+		let mut text = Vec::new();
+		reader.read_to_end(&mut text).await?;
+
+		Self::parse(name, &text)
+	}
+
+	#[cfg(feature = "fs")]
+	pub async fn from_path<P>(path: P) -> error::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
+		let text = tokio::fs::read(path.as_ref()).await?;
+
+		Self::parse(&path.as_ref().display().to_string(), &text)
+	}
+
+	pub fn chains(&self, tid: &str) -> &[Chain]
+	{
+		self.chains.get(tid).map_or(&[], Vec::as_slice)
+	}
+
+	// chains whose target span overlaps start..end
+	fn overlapping(&self, tid: &str, start: u64, end: u64) -> impl Iterator<Item = &Chain>
+	{
+		let chains = self.chains(tid);
+		let longest = self.longest.get(tid).copied().unwrap_or_default();
+
+		let first = chains.partition_point(|chain| chain.t_start + longest <= start);
+		let last = chains.partition_point(|chain| chain.t_start < end);
+
+		chains[first..last.max(first)]
+			.iter()
+			.filter(move |chain| chain.t_end > start)
+	}
+
+	// Lifts start..end, 0-based half-open, with liftOver's rules. A zero length interval is
+	// lifted as the base after it.
+	pub fn lift(
+		&self,
+		tid: &str,
+		start: u64,
+		end: u64,
+		strand: Strand,
+		options: &LiftOptions,
+	) -> Result<Vec<LiftedInterval>, Unmapped>
+	{
+		let end = end.max(start + 1);
+		let length = end - start;
+
+		let mut passing = Vec::new();
+		let mut any_mapped = false;
+
+		for chain in self.overlapping(tid, start, end)
+		{
+			let pieces = map_through(chain, start, end);
+			let mapped: u64 = pieces.iter().map(|(start, end)| end - start).sum();
+
+			if mapped == 0
+			{
+				continue;
+			}
+
+			any_mapped = true;
+
+			if mapped as f64 >= options.min_match * length as f64
+			{
+				passing.push((chain, pieces));
+			}
+		}
+
+		if passing.is_empty()
+		{
+			let reason = if any_mapped
+			{
+				Unmapped::PartiallyDeleted
+			}
+			else
+			{
+				Unmapped::Deleted
+			};
+
+			return Err(reason);
+		}
+
+		if passing.len() > 1 && !options.multiple
+		{
+			return Err(Unmapped::Duplicated);
+		}
+
+		let mut lifted = Vec::new();
+
+		for (chain, pieces) in passing
+		{
+			let strand = match (chain.q_strand, strand)
+			{
+				(Strand::Minus, Strand::Plus) => Strand::Minus,
+				(Strand::Minus, Strand::Minus) => Strand::Plus,
+				(_, strand) => strand,
+			};
+
+			let mut intervals: Vec<(u64, u64)> = pieces
+				.into_iter()
+				.map(|(start, end)| chain.forward_query(start, end))
+				.collect();
+
+			intervals.sort_unstable();
+
+			if !options.split
+			{
+				let spanned = (intervals[0].0, intervals[intervals.len() - 1].1);
+				intervals = vec![spanned];
+			}
+
+			lifted.extend(intervals.into_iter().map(|(start, end)| LiftedInterval {
+				tid: chain.q_name.clone(),
+				start,
+				end,
+				strand,
+			}));
+		}
+
+		Ok(lifted)
+	}
+
+	// A record moved to the new assembly, its value left as it was. BED12 blocks are not
+	// remapped, so lift their blocks as records of their own where that matters.
+	pub fn lift_record<Tid>(&self, record: BedRecord<Tid>, options: &LiftOptions) -> Lifted<Tid>
+	where
+		Tid: AsRef<str>,
+	{
+		match self.lift(record.tid.as_ref(), record.start, record.end, record.strand, options)
+		{
+			Ok(intervals) => Lifted::Mapped(
+				intervals
+					.into_iter()
+					.map(|interval| BedRecord {
+						tid: interval.tid,
+						strand: interval.strand,
+						start: interval.start,
+						end: interval.end,
+						value: record.value.clone(),
+					})
+					.collect(),
+			),
+			Err(reason) => Lifted::Unmapped(record, reason),
+		}
+	}
+}
+
+// the query pieces, in the chain's own strand, of the blocks overlapping start..end
+fn map_through(chain: &Chain, start: u64, end: u64) -> Vec<(u64, u64)>
+{
+	let first = chain.blocks.partition_point(|block| block.t_end() <= start);
+
+	chain.blocks[first..]
+		.iter()
+		.take_while(|block| block.t_start < end)
+		.map(|block| {
+			let (overlap_start, overlap_end) = (start.max(block.t_start), end.min(block.t_end()));
+			let q_start = block.q_start + (overlap_start - block.t_start);
+			(q_start, q_start + (overlap_end - overlap_start))
+		})
+		.collect()
+}
+
+// Every record lifted through chains, the unmapped ones kept with the reason. The output
+// follows the input order, so it is no longer sorted by the new positions.
+pub fn liftover<Tid, S>(
+	records: S,
+	chains: &ChainMap,
+	options: LiftOptions,
+) -> impl Stream<Item = error::Result<Lifted<Tid>>>
+where
+	Tid: AsRef<str>,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	records.map(move |record| Ok(chains.lift_record(record?, &options)))
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::bed::{BedRecord, ScoreField};
+
+// Records and windows have to share a coordinate convention, sites are placed by their start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Windows<Tid>
+{
+	// consecutive windows of this many bases from the start of each contig
+	Fixed(u64),
+	Regions(Vec<Window<Tid>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethylSummary<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	// sites passing the coverage threshold, CpGs for a CpG pileup
+	pub n_sites: u64,
+	pub n_mod: u64,
+	pub n_valid_cov: u64,
+	// summed over all reads, so high coverage sites weigh more
+	pub weighted_frac_mod: f32,
+	// average of the per site fractions
+	pub mean_frac_mod: f32,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Accumulator
+{
+	n_sites: u64,
+	n_mod: u64,
+	n_valid_cov: u64,
+	frac_sum: f64,
+}
+
+impl Accumulator
+{
+	fn add<Tid>(&mut self, record: &BedRecord<Tid>)
+	{
+		let n_valid_cov = record.value.get_u32(ScoreField::NValidCov).unwrap_or(0) as u64;
+
+		self.n_sites += 1;
+		self.n_valid_cov += n_valid_cov;
+		self.n_mod += record.value.get_u32(ScoreField::NMod).unwrap_or(0) as u64;
+		self.frac_sum += record.value.get_f32(ScoreField::FracMod).unwrap_or(0.0) as f64;
+	}
+
+	fn summary<Tid>(&self, tid: Tid, start: u64, end: u64) -> MethylSummary<Tid>
+	{
+		let weighted_frac_mod = if self.n_valid_cov > 0
+		{
+			(self.n_mod as f64 / self.n_valid_cov as f64 * 100.0) as f32
+		}
+		else
+		{
+			0.0
+		};
+
+		let mean_frac_mod = if self.n_sites > 0
+		{
+			(self.frac_sum / self.n_sites as f64) as f32
+		}
+		else
+		{
+			0.0
+		};
+
+		MethylSummary {
+			tid,
+			start,
+			end,
+			n_sites: self.n_sites,
+			n_mod: self.n_mod,
+			n_valid_cov: self.n_valid_cov,
+			weighted_frac_mod,
+			mean_frac_mod,
+		}
+	}
+}
+
+fn has_coverage<Tid>(record: &BedRecord<Tid>, min_coverage: u32) -> bool
+{
+	record
+		.value
+		.get_u32(ScoreField::NValidCov)
+		.is_some_and(|n_valid_cov| n_valid_cov >= min_coverage)
+}
+
+// Fixed windows only report windows with at least one site, supplied regions are always
+// reported in the order given.
+pub fn aggregate<'a, Tid, I>(
+	records: I,
+	windows: &Windows<Tid>,
+	min_coverage: u32,
+) -> Vec<MethylSummary<Tid>>
+where
+	Tid: Clone + Ord + Debug + 'a,
+	I: IntoIterator<Item = &'a BedRecord<Tid>>,
+{
+	let records = records
+		.into_iter()
+		.filter(|record| has_coverage(record, min_coverage));
+
+	match windows
+	{
+		Windows::Fixed(size) =>
+		{
+			let size = (*size).max(1);
+			let mut bins: BTreeMap<(Tid, u64), Accumulator> = BTreeMap::new();
+
+			for record in records
+			{
+				bins.entry((record.tid.clone(), record.start / size))
+					.or_default()
+					.add(record);
+			}
+
+			bins.into_iter()
+				.map(|((tid, bin), accumulator)| {
+					accumulator.summary(tid, bin * size, (bin + 1) * size)
+				})
+				.collect()
+		}
+		Windows::Regions(regions) =>
+		{
+			let mut by_tid: BTreeMap<&Tid, Vec<&BedRecord<Tid>>> = BTreeMap::new();
+			for record in records
+			{
+				by_tid.entry(&record.tid).or_default().push(record);
+			}
+
+			for sites in by_tid.values_mut()
+			{
+				sites.sort_by_key(|record| record.start);
+			}
+
+			regions
+				.iter()
+				.map(|region| {
+					let mut accumulator = Accumulator::default();
+
+					if let Some(sites) = by_tid.get(&region.tid)
+					{
+						let first = sites.partition_point(|record| record.start < region.start);
+						sites[first..]
+							.iter()
+							.take_while(|record| record.start < region.end)
+							.for_each(|record| accumulator.add(record));
+					}
+
+					accumulator.summary(region.tid.clone(), region.start, region.end)
+				})
+				.collect()
+		}
+	}
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::bed::{BedRecord, ModCode, ScoreField, Strand};
+
+pub fn filter_coverage<'a, Tid, I>(records: I, min_coverage: u32) -> impl Iterator<Item = &'a BedRecord<Tid>>
+where
+	Tid: 'a,
+	I: IntoIterator<Item = &'a BedRecord<Tid>>,
+{
+	records.into_iter().filter(move |record| {
+		record
+			.value
+			.get_u32(ScoreField::NValidCov)
+			.is_some_and(|n_valid_cov| n_valid_cov >= min_coverage)
+	})
+}
+
+fn add(a: Option<u32>, b: Option<u32>) -> Option<u32>
+{
+	match (a, b)
+	{
+		(Some(a), Some(b)) => Some(a + b),
+		(a, b) => a.or(b),
+	}
+}
+
+fn merge<Tid>(plus: &mut BedRecord<Tid>, minus: &BedRecord<Tid>)
+{
+	let (a, b) = (&mut plus.value, &minus.value);
+
+	a.n_valid_cov = add(a.n_valid_cov, b.n_valid_cov);
+	a.n_mod = add(a.n_mod, b.n_mod);
+	a.n_canonical = add(a.n_canonical, b.n_canonical);
+	a.n_other_mod = add(a.n_other_mod, b.n_other_mod);
+	a.n_delete = add(a.n_delete, b.n_delete);
+	a.n_fail = add(a.n_fail, b.n_fail);
+	a.n_diff = add(a.n_diff, b.n_diff);
+	a.n_nocall = add(a.n_nocall, b.n_nocall);
+}
+
+fn finish<Tid>(record: &mut BedRecord<Tid>)
+{
+	let value = &mut record.value;
+
+	// modkit scores a site by its valid coverage
+	value.score = value.n_valid_cov.or(value.score);
+	value.frac_mod = match (value.n_mod, value.n_valid_cov)
+	{
+		(Some(n_mod), Some(n_valid_cov)) if n_valid_cov > 0 =>
+		{
+			Some(n_mod as f32 / n_valid_cov as f32 * 100.0)
+		}
+		(Some(_), Some(_)) => Some(0.0),
+		_ => value.frac_mod,
+	};
+
+	record.strand = Strand::Both;
+}
+
+// Merges the - strand call of each CpG (on the G, one base after the C) into the + strand
+// call, like modkit's --combine-strands. Records are matched on tid, position and
+// modification code and come back sorted, with the strand unset. A - strand call without a
+// partner is shifted onto the C.
+pub fn collapse_strands<Tid, I>(records: I) -> Vec<BedRecord<Tid>>
+where
+	Tid: Clone + Ord + Debug,
+	I: IntoIterator<Item = BedRecord<Tid>>,
+{
+	let mut sites: BTreeMap<(Tid, u64, Option<ModCode>), BedRecord<Tid>> = BTreeMap::new();
+	let mut minus = Vec::new();
+
+	for record in records
+	{
+		match record.strand
+		{
+			Strand::Minus => minus.push(record),
+			_ =>
+			{
+				let key = (record.tid.clone(), record.start, record.mod_code());
+				match sites.get_mut(&key)
+				{
+					Some(site) => merge(site, &record),
+					None =>
+					{
+						sites.insert(key, record);
+					}
+				}
+			}
+		}
+	}
+
+	for mut record in minus
+	{
+		let start = record.start.saturating_sub(1);
+		let key = (record.tid.clone(), start, record.mod_code());
+
+		match sites.get_mut(&key)
+		{
+			Some(site) => merge(site, &record),
+			None =>
+			{
+				record.start = start;
+				record.end = record.end.saturating_sub(1).max(start + 1);
+				sites.insert(key, record);
+			}
+		}
+	}
+
+	sites
+		.into_values()
+		.map(|mut record| {
+			finish(&mut record);
+			record
+		})
+		.collect()
+}
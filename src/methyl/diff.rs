@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::bed::{BedRecord, ModCode, ScoreField};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions
+{
+	pub min_coverage: u32,
+	// two proportion z-test on the modified counts of each site
+	pub test: bool,
+	// also report sites present in only one input
+	pub keep_unmatched: bool,
+}
+
+impl Default for DiffOptions
+{
+	fn default() -> Self
+	{
+		Self {
+			min_coverage: 1,
+			test: false,
+			keep_unmatched: false,
+		}
+	}
+}
+
+impl DiffOptions
+{
+	pub fn with_min_coverage(mut self, min_coverage: u32) -> Self
+	{
+		self.min_coverage = min_coverage;
+		self
+	}
+
+	pub fn with_test(mut self, test: bool) -> Self
+	{
+		self.test = test;
+		self
+	}
+
+	pub fn with_keep_unmatched(mut self, keep_unmatched: bool) -> Self
+	{
+		self.keep_unmatched = keep_unmatched;
+		self
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SiteCounts
+{
+	pub n_mod: u32,
+	pub n_valid_cov: u32,
+	pub frac_mod: f32,
+}
+
+impl SiteCounts
+{
+	fn from_record<Tid>(record: &BedRecord<Tid>) -> Option<Self>
+	{
+		let n_valid_cov = record.value.get_u32(ScoreField::NValidCov)?;
+		let n_mod = record.value.get_u32(ScoreField::NMod)?;
+		let frac_mod = match record.value.get_f32(ScoreField::FracMod)
+		{
+			Some(frac_mod) => frac_mod,
+			None if n_valid_cov > 0 => n_mod as f32 / n_valid_cov as f32 * 100.0,
+			None => 0.0,
+		};
+
+		Some(Self {
+			n_mod,
+			n_valid_cov,
+			frac_mod,
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethylDiff<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub mod_code: Option<ModCode>,
+	pub a: Option<SiteCounts>,
+	pub b: Option<SiteCounts>,
+	// b minus a in percentage points, None unless both sides are present
+	pub delta: Option<f32>,
+	pub p_value: Option<f64>,
+}
+
+// Joins the two inputs on tid, start and modification code and reports per site
+// differences, sorted by position.
+pub fn diff<'a, Tid, A, B>(a: A, b: B, options: DiffOptions) -> Vec<MethylDiff<Tid>>
+where
+	Tid: Clone + Ord + Debug + 'a,
+	A: IntoIterator<Item = &'a BedRecord<Tid>>,
+	B: IntoIterator<Item = &'a BedRecord<Tid>>,
+{
+	type Key<Tid> = (Tid, u64, Option<ModCode>);
+
+	let mut sites: BTreeMap<Key<Tid>, (u64, Option<SiteCounts>, Option<SiteCounts>)> =
+		BTreeMap::new();
+
+	let passes = |counts: &SiteCounts| counts.n_valid_cov >= options.min_coverage;
+
+	for record in a
+	{
+		if let Some(counts) = SiteCounts::from_record(record).filter(passes)
+		{
+			let key = (record.tid.clone(), record.start, record.mod_code());
+			sites.entry(key).or_insert((record.end, None, None)).1 = Some(counts);
+		}
+	}
+
+	for record in b
+	{
+		if let Some(counts) = SiteCounts::from_record(record).filter(passes)
+		{
+			let key = (record.tid.clone(), record.start, record.mod_code());
+			sites.entry(key).or_insert((record.end, None, None)).2 = Some(counts);
+		}
+	}
+
+	sites
+		.into_iter()
+		.filter(|(_, (_, a, b))| options.keep_unmatched || (a.is_some() && b.is_some()))
+		.map(|((tid, start, mod_code), (end, a, b))| {
+			let (delta, p_value) = match (a, b)
+			{
+				(Some(a), Some(b)) => (
+					Some(b.frac_mod - a.frac_mod),
+					options.test.then(|| two_proportion_test(&a, &b)).flatten(),
+				),
+				_ => (None, None),
+			};
+
+			MethylDiff {
+				tid,
+				start,
+				end,
+				mod_code,
+				a,
+				b,
+				delta,
+				p_value,
+			}
+		})
+		.collect()
+}
+
+// two sided p-value, None when the pooled proportion gives no variance
+fn two_proportion_test(a: &SiteCounts, b: &SiteCounts) -> Option<f64>
+{
+	let (n1, n2) = (a.n_valid_cov as f64, b.n_valid_cov as f64);
+	if n1 == 0.0 || n2 == 0.0
+	{
+		return None;
+	}
+
+	let (p1, p2) = (a.n_mod as f64 / n1, b.n_mod as f64 / n2);
+	let pooled = (a.n_mod as f64 + b.n_mod as f64) / (n1 + n2);
+	let variance = pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2);
+
+	if variance <= 0.0
+	{
+		return None;
+	}
+
+	let z = (p1 - p2).abs() / variance.sqrt();
+	Some(erfc(z / std::f64::consts::SQRT_2).clamp(0.0, 1.0))
+}
+
+// Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7
+fn erfc(x: f64) -> f64
+{
+	let t = 1.0 / (1.0 + 0.3275911 * x);
+	let poly = t
+		* (0.254829592
+			+ t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+
+	poly * (-x * x).exp()
+}
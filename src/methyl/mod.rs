@@ -0,0 +1,7 @@
+mod aggregate;
+mod collapse;
+mod diff;
+
+pub use aggregate::*;
+pub use collapse::*;
+pub use diff::*;
@@ -0,0 +1,116 @@
+#![cfg(feature = "mmap")]
+
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use memmap2::Mmap;
+
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncSeek, ReadBuf, SeekFrom};
+
+use crate::error;
+
+// Reads are plain memcpys out of the mapping and never return Pending, so the async traits
+// cost nothing here. Cloning shares the mapping with an independent position.
+#[derive(Clone)]
+pub struct MmapFile
+{
+	map: Arc<Mmap>,
+	position: usize,
+}
+
+impl MmapFile
+{
+	pub fn open<P>(path: P) -> error::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
+		let file = std::fs::File::open(path)?;
+
+		// the mapping is read only; truncating the file underneath it is on the caller
+		let map = unsafe { Mmap::map(&file)? };
+
+		Ok(Self {
+			map: Arc::new(map),
+			position: 0,
+		})
+	}
+
+	pub fn len(&self) -> usize
+	{
+		self.map.len()
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.map.is_empty()
+	}
+
+	pub fn as_slice(&self) -> &[u8]
+	{
+		&self.map
+	}
+
+	fn remaining(&self) -> &[u8]
+	{
+		&self.map[self.position.min(self.map.len())..]
+	}
+}
+
+impl AsyncRead for MmapFile
+{
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		_cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>>
+	{
+		let remaining = self.remaining();
+		let n = remaining.len().min(buf.remaining());
+		buf.put_slice(&remaining[..n]);
+		self.position += n;
+
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl AsyncBufRead for MmapFile
+{
+	fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>>
+	{
+		Poll::Ready(Ok(self.get_mut().remaining()))
+	}
+
+	fn consume(mut self: Pin<&mut Self>, amt: usize)
+	{
+		self.position += amt;
+	}
+}
+
+impl AsyncSeek for MmapFile
+{
+	fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()>
+	{
+		let len = self.map.len() as u64;
+
+		let target = match position
+		{
+			SeekFrom::Start(n) => Some(n),
+			SeekFrom::End(n) => len.checked_add_signed(n),
+			SeekFrom::Current(n) => (self.position as u64).checked_add_signed(n),
+		}
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+
+		self.position = usize::try_from(target)
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "seek past addressable memory"))?;
+
+		Ok(())
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>>
+	{
+		Poll::Ready(Ok(self.position as u64))
+	}
+}
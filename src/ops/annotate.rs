@@ -0,0 +1,63 @@
+use futures::stream::{Stream, StreamExt};
+
+use crate::bed::BedRecord;
+use crate::error;
+use crate::ops::join::{OverlapOptions, Row, rows};
+
+// a record with the annotation records overlapping it
+#[derive(Debug, Clone)]
+pub struct Annotated<Tid>
+{
+	pub record: BedRecord<Tid>,
+	pub hits: Vec<BedRecord<Tid>>,
+}
+
+impl<Tid> Annotated<Tid>
+{
+	// names of the hits that have one, e.g. gene names
+	pub fn names(&self) -> impl Iterator<Item = &str>
+	{
+		self.hits.iter().filter_map(|hit| hit.value.get_name())
+	}
+}
+
+// Every record with the features of annotations overlapping it, both sorted as join
+// expects. Records with no hits are passed through with none.
+pub fn annotate<Tid, A, B>(
+	records: A,
+	annotations: B,
+) -> impl Stream<Item = error::Result<Annotated<Tid>>>
+where
+	Tid: Ord + Clone,
+	A: Stream<Item = error::Result<BedRecord<Tid>>>,
+	B: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	annotate_with(records, annotations, OverlapOptions::default(), |record, hits| {
+		Annotated { record, hits }
+	})
+}
+
+// as annotate, with f building the output from each record and its hits
+pub fn annotate_with<Tid, A, B, U, F>(
+	records: A,
+	annotations: B,
+	options: OverlapOptions,
+	mut f: F,
+) -> impl Stream<Item = error::Result<U>>
+where
+	Tid: Ord + Clone,
+	A: Stream<Item = error::Result<BedRecord<Tid>>>,
+	B: Stream<Item = error::Result<BedRecord<Tid>>>,
+	F: FnMut(BedRecord<Tid>, Vec<BedRecord<Tid>>) -> U,
+{
+	rows(records, annotations, false, options).filter_map(move |row| {
+		let output = match row
+		{
+			Ok(Row::Left(record, hits)) => Some(Ok(f(record, hits))),
+			Ok(Row::Right(_)) => None,
+			Err(e) => Some(Err(e)),
+		};
+
+		std::future::ready(output)
+	})
+}
@@ -0,0 +1,58 @@
+use std::io::Write;
+
+// one bedGraph line, 0-based half-open
+#[derive(Debug, Clone, PartialEq)]
+pub struct BedGraphRecord<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub value: f64,
+}
+
+// Writes bedGraph lines, values in their shortest form so whole counts print without a
+// fraction.
+pub struct BedGraphWriter<W>
+{
+	inner: W,
+}
+
+impl<W> BedGraphWriter<W>
+where
+	W: Write,
+{
+	pub fn new(inner: W) -> Self
+	{
+		Self { inner }
+	}
+
+	// a track line, written before any records
+	pub fn write_track_line(&mut self, name: &str) -> std::io::Result<()>
+	{
+		writeln!(self.inner, "track type=bedGraph name=\"{name}\"")
+	}
+
+	pub fn write<Tid>(&mut self, record: &BedGraphRecord<Tid>) -> std::io::Result<()>
+	where
+		Tid: AsRef<str>,
+	{
+		writeln!(
+			self.inner,
+			"{}\t{}\t{}\t{}",
+			record.tid.as_ref(),
+			record.start,
+			record.end,
+			record.value
+		)
+	}
+
+	pub fn flush(&mut self) -> std::io::Result<()>
+	{
+		self.inner.flush()
+	}
+
+	pub fn into_inner(self) -> W
+	{
+		self.inner
+	}
+}
@@ -0,0 +1,115 @@
+use faisync::Contigs;
+use futures::stream::{Stream, StreamExt};
+
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+use crate::region::CoordinateSystem;
+
+// a record with the reference bases it covers
+#[derive(Debug, Clone)]
+pub struct RecordSequence<Tid>
+{
+	pub record: BedRecord<Tid>,
+	pub sequence: Vec<u8>,
+}
+
+impl<Tid> BedRecord<Tid>
+where
+	Tid: AsRef<str>,
+{
+	// The forward strand bases under the record, whose start and end are in system,
+	// CoordinateSystem::of_reader for records as a reader gives them. BED12 records give their
+	// blocks joined, the spliced sequence, without the introns.
+	pub async fn sequence(
+		&self,
+		fasta: &Contigs,
+		system: CoordinateSystem,
+	) -> error::Result<Vec<u8>>
+	{
+		let tid = self.tid.as_ref();
+
+		let contig = fasta
+			.get(tid)
+			.ok_or(error::Error::TidNotFound(tid.to_string()))?;
+
+		// BED12 blocks come from the columns as written, 0-based whatever the reader shifted
+		let blocks = match self.value.bed12()
+		{
+			Some(bed12) => bed12.blocks().collect(),
+			None => vec![system.convert(self.start, self.end, CoordinateSystem::ZeroBasedHalfOpen)],
+		};
+
+		let mut sequence = Vec::new();
+
+		for (start, end) in blocks
+		{
+			for pos in start..end
+			{
+				// past the end of the contig
+				let base = contig.base_at(pos.into()).await.ok_or_else(|| {
+					error::Error::InvalidRegion(format!("{tid}:{start}-{end}"))
+				})?;
+
+				sequence.push(base);
+			}
+		}
+
+		Ok(sequence)
+	}
+
+	// as sequence, reverse complemented for records on the minus strand
+	pub async fn stranded_sequence(
+		&self,
+		fasta: &Contigs,
+		system: CoordinateSystem,
+	) -> error::Result<Vec<u8>>
+	{
+		let mut sequence = self.sequence(fasta, system).await?;
+
+		if let Strand::Minus = self.strand
+		{
+			sequence.reverse();
+			sequence.iter_mut().for_each(|base| *base = faisync::reverse_complement(*base));
+		}
+
+		Ok(sequence)
+	}
+}
+
+// Every record with its bases, as bedtools getfasta gives them, reverse complemented on the
+// minus strand when strand_aware is set. Records are in system, as for BedRecord::sequence.
+// The stream ends after the first error.
+pub fn get_fasta<Tid, S>(
+	records: S,
+	fasta: &Contigs,
+	system: CoordinateSystem,
+	strand_aware: bool,
+) -> impl Stream<Item = error::Result<RecordSequence<Tid>>>
+where
+	Tid: AsRef<str>,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	records
+		.then(move |record| async move {
+			let record = record?;
+
+			let sequence = if strand_aware
+			{
+				record.stranded_sequence(fasta, system).await?
+			}
+			else
+			{
+				record.sequence(fasta, system).await?
+			};
+
+			Ok(RecordSequence { record, sequence })
+		})
+		.scan(false, |failed, result: error::Result<RecordSequence<Tid>>| {
+			let output = (!*failed).then(|| {
+				*failed = result.is_err();
+				result
+			});
+
+			std::future::ready(output)
+		})
+}
@@ -0,0 +1,221 @@
+use std::pin::Pin;
+
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::bed::{BedRecord, RecordStreamExt};
+use crate::error;
+use crate::ops::BedGraphRecord;
+use crate::region::CoordinateSystem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoverageOptions
+{
+	// runs no record covers are written with depth 0, bedtools genomecov -bga
+	pub include_zero: bool,
+	// BED12 records count only their blocks, -split
+	pub split: bool,
+	// what the records' coordinates are in, the runs are always 0-based half-open
+	pub system: CoordinateSystem,
+}
+
+impl Default for CoverageOptions
+{
+	fn default() -> Self
+	{
+		Self {
+			include_zero: true,
+			split: false,
+			system: CoordinateSystem::ZeroBasedHalfOpen,
+		}
+	}
+}
+
+impl CoverageOptions
+{
+	pub fn with_include_zero(mut self, include_zero: bool) -> Self
+	{
+		self.include_zero = include_zero;
+		self
+	}
+
+	pub fn with_split(mut self, split: bool) -> Self
+	{
+		self.split = split;
+		self
+	}
+
+	// CoordinateSystem::of_reader for records as a reader gives them
+	pub fn with_coordinate_system(mut self, system: CoordinateSystem) -> Self
+	{
+		self.system = system;
+		self
+	}
+}
+
+// Run length depth over every contig of chrom_sizes, as bedGraph records in its order.
+pub fn genomecov<Tid, S>(
+	records: S,
+	chrom_sizes: Vec<(Tid, u64)>,
+) -> impl Stream<Item = error::Result<BedGraphRecord<Tid>>>
+where
+	Tid: AsRef<str> + PartialEq + Clone,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	genomecov_with(records, chrom_sizes, CoverageOptions::default())
+}
+
+// As genomecov. Records have to be sorted with their contigs in the order of chrom_sizes,
+// each contig's records are held while its depth is worked out.
+pub fn genomecov_with<Tid, S>(
+	records: S,
+	chrom_sizes: Vec<(Tid, u64)>,
+	options: CoverageOptions,
+) -> impl Stream<Item = error::Result<BedGraphRecord<Tid>>>
+where
+	Tid: AsRef<str> + PartialEq + Clone,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	let contigs: Pin<Box<_>> = Box::pin(records.chunk_by_tid());
+
+	stream::unfold((contigs, chrom_sizes, 0, false), move |state| async move {
+		let (mut contigs, sizes, next, done) = state;
+
+		if done
+		{
+			return None;
+		}
+
+		// contigs of chrom_sizes with no records only show up with include_zero
+		let empty = |range: std::ops::Range<usize>| -> Vec<error::Result<BedGraphRecord<Tid>>> {
+			sizes[range]
+				.iter()
+				.flat_map(|(tid, size)| coverage_runs(tid, *size, &[], &options))
+				.map(Ok)
+				.collect()
+		};
+
+		let (runs, next, done) = match contigs.next().await
+		{
+			Some(Ok((tid, batch))) =>
+			{
+				match sizes[next..].iter().position(|(name, _)| *name == tid)
+				{
+					Some(skipped) =>
+					{
+						let mut runs = empty(next..next + skipped);
+						let size = sizes[next + skipped].1;
+
+						let covered = coverage_runs(&tid, size, &batch, &options);
+						runs.extend(covered.into_iter().map(Ok));
+						(runs, next + skipped + 1, false)
+					}
+					None =>
+					{
+						let name = tid.as_ref().to_string();
+
+						let e = if sizes[..next].iter().any(|(seen, _)| *seen == tid)
+						{
+							error::Error::Unsorted("records".into(), name, batch[0].start)
+						}
+						else
+						{
+							error::Error::TidNotFound(name)
+						};
+
+						(vec![Err(e)], next, true)
+					}
+				}
+			}
+			Some(Err(e)) => (vec![Err(e)], next, true),
+			None => (empty(next..sizes.len()), sizes.len(), true),
+		};
+
+		Some((stream::iter(runs), (contigs, sizes, next, done)))
+	})
+	.flatten()
+}
+
+// the depth runs of one contig, neighbouring runs of the same depth joined
+fn coverage_runs<Tid>(
+	tid: &Tid,
+	size: u64,
+	records: &[BedRecord<Tid>],
+	options: &CoverageOptions,
+) -> Vec<BedGraphRecord<Tid>>
+where
+	Tid: Clone,
+{
+	let mut events = Vec::with_capacity(records.len() * 2);
+
+	for record in records
+	{
+		let mut push = |start: u64, end: u64| {
+			let (start, end) = (start.min(size), end.min(size));
+
+			if start < end
+			{
+				events.push((start, 1i64));
+				events.push((end, -1i64));
+			}
+		};
+
+		match record.value.bed12().filter(|_| options.split)
+		{
+			Some(bed12) => bed12.blocks().for_each(|(start, end)| push(start, end)),
+			None =>
+			{
+				let (start, end) = options.system.convert(
+					record.start,
+					record.end,
+					CoordinateSystem::ZeroBasedHalfOpen,
+				);
+				push(start, end)
+			}
+		}
+	}
+
+	events.sort_unstable_by_key(|(position, _)| *position);
+
+	let mut runs: Vec<BedGraphRecord<Tid>> = Vec::new();
+	let mut run = |start: u64, end: u64, depth: i64| {
+		if depth == 0 && !options.include_zero
+		{
+			return;
+		}
+
+		let value = depth as f64;
+
+		if let Some(last) = runs.last_mut().filter(|last| last.end == start && last.value == value)
+		{
+			last.end = end;
+			return;
+		}
+
+		runs.push(BedGraphRecord {
+			tid: tid.clone(),
+			start,
+			end,
+			value,
+		});
+	};
+
+	let (mut position, mut depth) = (0, 0);
+
+	for (at, change) in events
+	{
+		if at > position
+		{
+			run(position, at, depth);
+			position = at;
+		}
+
+		depth += change;
+	}
+
+	if size > position
+	{
+		run(position, size, depth);
+	}
+
+	runs
+}
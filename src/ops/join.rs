@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::bed::{BedRecord, PairedRecord};
+use crate::error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind
+{
+	// pairs of overlapping records only
+	Inner,
+	// every left record, with None on the right when nothing overlaps it
+	Left,
+	// as Left, plus every right record that overlapped nothing with None on the left
+	Outer,
+}
+
+// Fractions are of each record's length, as bedtools -f and -F take them, and need the
+// records to overlap whatever max_gap is.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OverlapOptions
+{
+	// records this many bases apart still match, 0 for overlap only
+	pub max_gap: u64,
+	// bases two records have to share, ignored when max_gap is set
+	pub min_overlap: u64,
+	pub same_strand: bool,
+	// share of the left record covered, -f
+	pub min_left_fraction: f64,
+	// share of the right record covered, -F
+	pub min_right_fraction: f64,
+	// min_left_fraction applies to the right record too, -r
+	pub reciprocal: bool,
+	// either fraction is enough rather than both, -e
+	pub either: bool,
+}
+
+impl OverlapOptions
+{
+	pub fn with_max_gap(mut self, max_gap: u64) -> Self
+	{
+		self.max_gap = max_gap;
+		self
+	}
+
+	pub fn with_min_overlap(mut self, min_overlap: u64) -> Self
+	{
+		self.min_overlap = min_overlap;
+		self
+	}
+
+	pub fn with_same_strand(mut self, same_strand: bool) -> Self
+	{
+		self.same_strand = same_strand;
+		self
+	}
+
+	pub fn with_min_left_fraction(mut self, min_left_fraction: f64) -> Self
+	{
+		self.min_left_fraction = min_left_fraction;
+		self
+	}
+
+	pub fn with_min_right_fraction(mut self, min_right_fraction: f64) -> Self
+	{
+		self.min_right_fraction = min_right_fraction;
+		self
+	}
+
+	pub fn with_reciprocal(mut self, reciprocal: bool) -> Self
+	{
+		self.reciprocal = reciprocal;
+		self
+	}
+
+	pub fn with_either(mut self, either: bool) -> Self
+	{
+		self.either = either;
+		self
+	}
+
+	// whether join would pair the two records
+	pub fn matches<Tid>(&self, left: &BedRecord<Tid>, right: &BedRecord<Tid>) -> bool
+	where
+		Tid: PartialEq,
+	{
+		if left.tid != right.tid || (self.same_strand && left.strand != right.strand)
+		{
+			return false;
+		}
+
+		let shared = left.end.min(right.end).saturating_sub(left.start.max(right.start));
+
+		let near = match self.max_gap
+		{
+			0 => shared > 0 && shared >= self.min_overlap,
+			gap => right.start < left.end + gap && left.start < right.end + gap,
+		};
+
+		if !near
+		{
+			return false;
+		}
+
+		let min_right_fraction = if self.reciprocal
+		{
+			self.min_left_fraction
+		}
+		else
+		{
+			self.min_right_fraction
+		};
+
+		if self.min_left_fraction <= 0.0 && min_right_fraction <= 0.0
+		{
+			return true;
+		}
+
+		let covers =
+			|length: u64, fraction: f64| shared > 0 && shared as f64 >= fraction * length as f64;
+
+		let left_covered = covers(left.end.saturating_sub(left.start), self.min_left_fraction);
+		let right_covered = covers(right.end.saturating_sub(right.start), min_right_fraction);
+
+		if self.either
+		{
+			left_covered || right_covered
+		}
+		else
+		{
+			left_covered && right_covered
+		}
+	}
+}
+
+// one output row, both sides set for an inner match
+#[derive(Debug, Clone)]
+pub struct JoinPair<Tid>
+{
+	pub left: Option<BedRecord<Tid>>,
+	pub right: Option<BedRecord<Tid>>,
+}
+
+impl<Tid> From<JoinPair<Tid>> for PairedRecord<Tid>
+{
+	fn from(pair: JoinPair<Tid>) -> Self
+	{
+		PairedRecord::new(pair.left, pair.right)
+	}
+}
+
+// Streams the overlaps of two inputs sorted by tid then start, holding only the right
+// records that can still overlap the current left one. Tids are compared by their order,
+// so both inputs need the same contig order, as readers sharing one interner give for
+// files listing their contigs alike. Rows follow the left input, right records that
+// overlapped nothing come out as they fall behind it. The stream ends after the first
+// error from either side.
+pub fn join<Tid, A, B>(
+	left: A,
+	right: B,
+	kind: JoinKind,
+	options: OverlapOptions,
+) -> impl Stream<Item = error::Result<JoinPair<Tid>>>
+where
+	Tid: Ord + Clone,
+	A: Stream<Item = error::Result<BedRecord<Tid>>>,
+	B: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	rows(left, right, kind == JoinKind::Outer, options).flat_map(move |row| {
+		let pairs = match row
+		{
+			Ok(Row::Left(record, hits)) if hits.is_empty() => match kind
+			{
+				JoinKind::Inner => Vec::new(),
+				_ => vec![Ok(JoinPair {
+					left: Some(record),
+					right: None,
+				})],
+			},
+			Ok(Row::Left(record, hits)) => hits
+				.into_iter()
+				.map(|hit| {
+					Ok(JoinPair {
+						left: Some(record.clone()),
+						right: Some(hit),
+					})
+				})
+				.collect(),
+			Ok(Row::Right(record)) => vec![Ok(JoinPair {
+				left: None,
+				right: Some(record),
+			})],
+			Err(e) => vec![Err(e)],
+		};
+
+		stream::iter(pairs)
+	})
+}
+
+// each left record with the right records it matched, or a right record that matched
+// nothing when outer is set
+pub(crate) enum Row<Tid>
+{
+	Left(BedRecord<Tid>, Vec<BedRecord<Tid>>),
+	Right(BedRecord<Tid>),
+}
+
+// the sweep behind join and annotate
+pub(crate) fn rows<Tid, A, B>(
+	left: A,
+	right: B,
+	outer: bool,
+	options: OverlapOptions,
+) -> impl Stream<Item = error::Result<Row<Tid>>>
+where
+	Tid: Ord + Clone,
+	A: Stream<Item = error::Result<BedRecord<Tid>>>,
+	B: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	let state = JoinState {
+		left: Box::pin(left),
+		right: Box::pin(right),
+		peeked: None,
+		right_done: false,
+		window: Vec::new(),
+		ready: VecDeque::new(),
+		done: false,
+		outer,
+		options,
+	};
+
+	stream::unfold(state, |mut state| async move {
+		loop
+		{
+			if let Some(row) = state.ready.pop_front()
+			{
+				return Some((row, state));
+			}
+
+			if state.done
+			{
+				return None;
+			}
+
+			state.advance().await;
+		}
+	})
+}
+
+struct JoinState<Tid, A, B>
+{
+	left: Pin<Box<A>>,
+	right: Pin<Box<B>>,
+	// the next right record, not yet reached by the left input
+	peeked: Option<BedRecord<Tid>>,
+	right_done: bool,
+	// right records that may overlap the current left record, with whether any did
+	window: Vec<(BedRecord<Tid>, bool)>,
+	ready: VecDeque<error::Result<Row<Tid>>>,
+	done: bool,
+	outer: bool,
+	options: OverlapOptions,
+}
+
+impl<Tid, A, B> JoinState<Tid, A, B>
+where
+	Tid: Ord + Clone,
+	A: Stream<Item = error::Result<BedRecord<Tid>>>,
+	B: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	// joins the next left record, or flushes the right side once the left has ended
+	async fn advance(&mut self)
+	{
+		let record = match self.left.next().await
+		{
+			Some(Ok(record)) => record,
+			Some(Err(e)) => return self.fail(e),
+			None =>
+			{
+				self.finish().await;
+				return;
+			}
+		};
+
+		let gap = self.options.max_gap;
+
+		// right records wholly before this one cannot reach any later left record either
+		let (behind, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.window)
+			.into_iter()
+			.partition(|(right, _)| match right.tid.cmp(&record.tid)
+			{
+				std::cmp::Ordering::Less => true,
+				std::cmp::Ordering::Equal => right.end + gap <= record.start,
+				std::cmp::Ordering::Greater => false,
+			});
+		self.window = kept;
+		self.release(behind);
+
+		loop
+		{
+			if self.peeked.is_none() && !self.right_done
+			{
+				match self.right.next().await
+				{
+					Some(Ok(right)) => self.peeked = Some(right),
+					Some(Err(e)) => return self.fail(e),
+					None => self.right_done = true,
+				}
+			}
+
+			let Some(right) = self.peeked.take()
+			else
+			{
+				break;
+			};
+
+			if right.tid < record.tid
+			{
+				self.release(vec![(right, false)]);
+			}
+			else if right.tid == record.tid && right.start < record.end + gap
+			{
+				self.window.push((right, false));
+			}
+			else
+			{
+				self.peeked = Some(right);
+				break;
+			}
+		}
+
+		let mut hits = Vec::new();
+
+		for (right, matched) in &mut self.window
+		{
+			if self.options.matches(&record, right)
+			{
+				*matched = true;
+				hits.push(right.clone());
+			}
+		}
+
+		self.ready.push_back(Ok(Row::Left(record, hits)));
+	}
+
+	// right records the left input has passed, kept for an outer join if nothing matched
+	fn release(&mut self, records: Vec<(BedRecord<Tid>, bool)>)
+	{
+		if !self.outer
+		{
+			return;
+		}
+
+		for (right, matched) in records
+		{
+			if !matched
+			{
+				self.ready.push_back(Ok(Row::Right(right)));
+			}
+		}
+	}
+
+	async fn finish(&mut self)
+	{
+		self.done = true;
+
+		if !self.outer
+		{
+			return;
+		}
+
+		let window = std::mem::take(&mut self.window);
+		self.release(window);
+
+		if let Some(right) = self.peeked.take()
+		{
+			self.release(vec![(right, false)]);
+		}
+
+		while !self.right_done
+		{
+			match self.right.next().await
+			{
+				Some(Ok(right)) => self.release(vec![(right, false)]),
+				Some(Err(e)) => return self.fail(e),
+				None => self.right_done = true,
+			}
+		}
+	}
+
+	fn fail(&mut self, e: error::Error)
+	{
+		self.ready.push_back(Err(e));
+		self.done = true;
+	}
+}
@@ -0,0 +1,74 @@
+use tokio::io::{AsyncRead, AsyncSeek};
+
+use crate::bed::autooneshotreader::{AutoOneShotBlockReader, AutoOneShotBlockReaderTrait};
+use crate::error;
+use crate::ops::{RollingStat, ScoreColumn};
+use crate::region::Region;
+use crate::store::TidResolver;
+
+// Aggregated scores with a row per region and a column per sample, NaN where a sample has
+// no scored record in a region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreMatrix
+{
+	pub regions: Vec<Region>,
+	// the readers' names, in the order the columns are
+	pub samples: Vec<String>,
+	pub values: Vec<Vec<f32>>,
+}
+
+impl ScoreMatrix
+{
+	pub fn get(&self, region: usize, sample: usize) -> Option<f32>
+	{
+		self.values.get(region)?.get(sample).copied()
+	}
+}
+
+// Scores of column over the records overlapping each region, agg over each sample's
+// records. Every sample needs an index and a way to reopen its source, as for
+// query_region_concurrent, and is queried for a region alongside the others.
+pub async fn score_matrix<R, T>(
+	samples: &[AutoOneShotBlockReader<R, T>],
+	regions: &[Region],
+	column: impl Into<ScoreColumn>,
+	agg: RollingStat,
+) -> error::Result<ScoreMatrix>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	let column = column.into();
+	let mut values = Vec::with_capacity(regions.len());
+
+	for region in regions
+	{
+		let queries = samples.iter().map(|sample| sample.query_region_concurrent(region, 1));
+		let per_sample = futures::future::try_join_all(queries).await?;
+
+		let row = per_sample
+			.into_iter()
+			.map(|records| {
+				let mut scores: Vec<f64> =
+					records.iter().filter_map(|record| column.value(record)).collect();
+
+				if scores.is_empty()
+				{
+					f32::NAN
+				}
+				else
+				{
+					agg.apply(&mut scores) as f32
+				}
+			})
+			.collect();
+
+		values.push(row);
+	}
+
+	Ok(ScoreMatrix {
+		regions: regions.to_vec(),
+		samples: samples.iter().map(|sample| sample.name()).collect(),
+		values,
+	})
+}
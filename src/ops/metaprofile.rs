@@ -0,0 +1,125 @@
+use tokio::io::{AsyncRead, AsyncSeek};
+
+use crate::bed::autooneshotreader::{AutoOneShotBlockReader, AutoOneShotBlockReaderTrait};
+use crate::bed::{BedRecord, Strand};
+use crate::error;
+use crate::ops::ScoreColumn;
+use crate::region::{CoordinateSystem, Region};
+use crate::store::TidResolver;
+
+// Binned scores around a set of anchors, as deeptools computeMatrix gives in scale-regions
+// mode. Rows and bins run 5' to 3', minus strand anchors flipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metaprofile
+{
+	// a row per anchor, NaN for bins without a scored record
+	pub matrix: Vec<Vec<f32>>,
+	// the mean of each bin over the anchors scored there, None where none are
+	pub profile: Vec<Option<f64>>,
+	// how many anchors had a score in each bin
+	pub anchors_scored: Vec<u64>,
+}
+
+// Averages column over n_bins bins spanning each anchor and flank_bp either side of it,
+// so anchors of any length share the bins. A bin's score is the mean of the records
+// overlapping it. The signal reader needs an index and a way to reopen its source, as for
+// query_region_concurrent. Anchors are 0-based half-open.
+pub async fn metaprofile<R, T, Tid>(
+	signal: &AutoOneShotBlockReader<R, T>,
+	anchors: &[BedRecord<Tid>],
+	flank_bp: u64,
+	n_bins: usize,
+	column: impl Into<ScoreColumn>,
+) -> error::Result<Metaprofile>
+where
+	R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+	Tid: AsRef<str>,
+{
+	let column = column.into();
+	let system = CoordinateSystem::of_reader(signal.describe().one_indexed);
+
+	let mut matrix = Vec::with_capacity(anchors.len());
+
+	for anchor in anchors
+	{
+		let (start, end) = (anchor.start.saturating_sub(flank_bp), anchor.end + flank_bp);
+		let region = Region::zero_based(anchor.tid.as_ref(), start, end);
+
+		let mut sums = vec![0.0; n_bins];
+		let mut counts = vec![0u64; n_bins];
+
+		let width = (end - start).max(1) as u128;
+		let bin_of = |position: u64| ((position - start) as u128 * n_bins as u128 / width) as usize;
+
+		for record in signal.query_region_concurrent(&region, 1).await?
+		{
+			let Some(value) = column.value(&record)
+			else
+			{
+				continue;
+			};
+
+			let (record_start, record_end) =
+				system.convert(record.start, record.end, CoordinateSystem::ZeroBasedHalfOpen);
+			let (record_start, record_end) = (record_start.max(start), record_end.min(end));
+
+			if record_start >= record_end || n_bins == 0
+			{
+				continue;
+			}
+
+			for bin in bin_of(record_start)..=bin_of(record_end - 1).min(n_bins - 1)
+			{
+				sums[bin] += value;
+				counts[bin] += 1;
+			}
+		}
+
+		let mut row: Vec<f32> = sums
+			.iter()
+			.zip(&counts)
+			.map(|(sum, count)| {
+				if *count > 0
+				{
+					(sum / *count as f64) as f32
+				}
+				else
+				{
+					f32::NAN
+				}
+			})
+			.collect();
+
+		if let Strand::Minus = anchor.strand
+		{
+			row.reverse();
+		}
+
+		matrix.push(row);
+	}
+
+	let mut totals = vec![0.0; n_bins];
+	let mut anchors_scored = vec![0u64; n_bins];
+
+	for row in &matrix
+	{
+		for (bin, value) in row.iter().enumerate().filter(|(_, value)| !value.is_nan())
+		{
+			totals[bin] += *value as f64;
+			anchors_scored[bin] += 1;
+		}
+	}
+
+	let profile = totals
+		.iter()
+		.zip(&anchors_scored)
+		.map(|(total, count)| (*count > 0).then(|| total / *count as f64))
+		.collect();
+
+	Ok(Metaprofile {
+		matrix,
+		profile,
+		anchors_scored,
+	})
+}
@@ -0,0 +1,23 @@
+mod annotate;
+mod bedgraph;
+mod fasta;
+mod genomecov;
+mod join;
+mod matrix;
+mod metaprofile;
+mod nuc;
+mod rolling;
+mod score;
+mod segments;
+
+pub use annotate::{Annotated, annotate, annotate_with};
+pub use bedgraph::{BedGraphRecord, BedGraphWriter};
+pub use fasta::{RecordSequence, get_fasta};
+pub use genomecov::{CoverageOptions, genomecov, genomecov_with};
+pub use join::{JoinKind, JoinPair, OverlapOptions, join};
+pub use matrix::{ScoreMatrix, score_matrix};
+pub use metaprofile::{Metaprofile, metaprofile};
+pub use nuc::{NucContent, nuc_content};
+pub use rolling::{RollingStat, rolling};
+pub use score::ScoreColumn;
+pub use segments::{Segment, SegmentOptions, call_segments, call_segments_with};
@@ -0,0 +1,94 @@
+use faisync::Contigs;
+use futures::stream::{Stream, StreamExt};
+
+use crate::bed::BedRecord;
+use crate::error;
+use crate::ops::get_fasta;
+use crate::region::CoordinateSystem;
+
+// base composition of one sequence, case insensitive as soft masked references need
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NucContent
+{
+	pub a: u64,
+	pub c: u64,
+	pub g: u64,
+	pub t: u64,
+	pub n: u64,
+	// IUPAC codes besides N, and anything else
+	pub other: u64,
+	// CG dinucleotides on the forward strand, the same count as on the reverse
+	pub cpg: u64,
+}
+
+impl NucContent
+{
+	pub fn from_sequence(sequence: &[u8]) -> Self
+	{
+		let mut content = Self::default();
+
+		for base in sequence
+		{
+			match base.to_ascii_uppercase()
+			{
+				b'A' => content.a += 1,
+				b'C' => content.c += 1,
+				b'G' => content.g += 1,
+				b'T' => content.t += 1,
+				b'N' => content.n += 1,
+				_ => content.other += 1,
+			}
+		}
+
+		content.cpg = sequence
+			.windows(2)
+			.filter(|pair| pair.eq_ignore_ascii_case(b"CG"))
+			.count() as u64;
+
+		content
+	}
+
+	pub fn len(&self) -> u64
+	{
+		self.a + self.c + self.g + self.t + self.n + self.other
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.len() == 0
+	}
+
+	// share of A, C, G and T that are G or C, None when there are none
+	pub fn gc_fraction(&self) -> Option<f64>
+	{
+		let called = self.a + self.c + self.g + self.t;
+		(called > 0).then(|| (self.c + self.g) as f64 / called as f64)
+	}
+
+	// CpG observed over expected, (CpG * length) / (C * G) as Gardiner-Garden and Frommer
+	// define it, None without both C and G
+	pub fn cpg_observed_expected(&self) -> Option<f64>
+	{
+		(self.c > 0 && self.g > 0)
+			.then(|| (self.cpg * self.len()) as f64 / (self.c * self.g) as f64)
+	}
+}
+
+// Every record with the composition of its bases, the spliced sequence for BED12 records.
+// Records are in system, as for get_fasta. The stream ends after the first error.
+pub fn nuc_content<Tid, S>(
+	records: S,
+	fasta: &Contigs,
+	system: CoordinateSystem,
+) -> impl Stream<Item = error::Result<(BedRecord<Tid>, NucContent)>>
+where
+	Tid: AsRef<str>,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	get_fasta(records, fasta, system, false).map(|result| {
+		result.map(|fetched| {
+			let content = NucContent::from_sequence(&fetched.sequence);
+			(fetched.record, content)
+		})
+	})
+}
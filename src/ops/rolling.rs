@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::bed::{BedRecord, RecordStreamExt};
+use crate::error;
+use crate::ops::{BedGraphRecord, ScoreColumn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollingStat
+{
+	Mean,
+	Median,
+	Sum,
+}
+
+impl RollingStat
+{
+	// values is non-empty, median sorts it
+	pub(crate) fn apply(&self, values: &mut [f64]) -> f64
+	{
+		match self
+		{
+			RollingStat::Sum => values.iter().sum(),
+			RollingStat::Mean => values.iter().sum::<f64>() / values.len() as f64,
+			RollingStat::Median =>
+			{
+				values.sort_unstable_by(f64::total_cmp);
+
+				let middle = values.len() / 2;
+
+				if values.len() % 2 == 0
+				{
+					(values[middle - 1] + values[middle]) / 2.0
+				}
+				else
+				{
+					values[middle]
+				}
+			}
+		}
+	}
+}
+
+// Windows of window_bp every step bases along each contig, as bedGraph records of stat
+// over the scores of the records overlapping them. Windows start at multiples of step and
+// those without a score are left out. Records have to be sorted, each contig's held while
+// its windows are worked out.
+pub fn rolling<Tid, S>(
+	records: S,
+	window_bp: u64,
+	step: u64,
+	stat: RollingStat,
+	column: impl Into<ScoreColumn>,
+) -> impl Stream<Item = error::Result<BedGraphRecord<Tid>>>
+where
+	Tid: PartialEq + Clone,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	let column = column.into();
+	let (window_bp, step) = (window_bp.max(1), step.max(1));
+
+	records.chunk_by_tid().flat_map(move |contig| {
+		let windows = match contig
+		{
+			Ok((tid, batch)) => windows(&tid, &batch, window_bp, step, stat, &column)
+				.into_iter()
+				.map(Ok)
+				.collect(),
+			Err(e) => vec![Err(e)],
+		};
+
+		stream::iter(windows)
+	})
+}
+
+fn windows<Tid>(
+	tid: &Tid,
+	records: &[BedRecord<Tid>],
+	window_bp: u64,
+	step: u64,
+	stat: RollingStat,
+	column: &ScoreColumn,
+) -> Vec<BedGraphRecord<Tid>>
+where
+	Tid: Clone,
+{
+	let scored: Vec<(u64, u64, f64)> = records
+		.iter()
+		.filter_map(|record| Some((record.start, record.end, column.value(record)?)))
+		.collect();
+
+	// the first window reaching past start
+	let first_window = |start: u64| {
+		if start >= window_bp
+		{
+			(start - window_bp) / step + 1
+		}
+		else
+		{
+			0
+		}
+	};
+
+	let mut output = Vec::new();
+	let mut active: VecDeque<(u64, u64, f64)> = VecDeque::new();
+	let mut values = Vec::new();
+	let mut next = 0;
+
+	let Some(&(start, _, _)) = scored.first()
+	else
+	{
+		return output;
+	};
+
+	let mut window = first_window(start);
+
+	loop
+	{
+		let (window_start, window_end) = (window * step, window * step + window_bp);
+
+		while let Some(record) = scored.get(next).filter(|record| record.0 < window_end)
+		{
+			active.push_back(*record);
+			next += 1;
+		}
+
+		active.retain(|(_, end, _)| *end > window_start);
+
+		if active.is_empty()
+		{
+			// skip the windows between here and the next record
+			let Some(&(start, _, _)) = scored.get(next)
+			else
+			{
+				break;
+			};
+
+			window = first_window(start).max(window + 1);
+			continue;
+		}
+
+		values.clear();
+		values.extend(active.iter().map(|(_, _, value)| *value));
+
+		output.push(BedGraphRecord {
+			tid: tid.clone(),
+			start: window_start,
+			end: window_end,
+			value: stat.apply(&mut values),
+		});
+
+		window += 1;
+	}
+
+	output
+}
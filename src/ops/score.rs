@@ -0,0 +1,38 @@
+use crate::bed::{BedRecord, FieldValue, ScoreField};
+
+// the number ops read from each record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScoreColumn
+{
+	Field(ScoreField),
+	// the name column, where bedGraph read as BED4 keeps its value
+	Name,
+	// an extra column by name, such as narrowPeak's signalValue
+	Column(String),
+}
+
+impl From<ScoreField> for ScoreColumn
+{
+	fn from(field: ScoreField) -> Self
+	{
+		ScoreColumn::Field(field)
+	}
+}
+
+impl ScoreColumn
+{
+	// None when the record lacks the column or it is not a number
+	pub fn value<Tid>(&self, record: &BedRecord<Tid>) -> Option<f64>
+	{
+		match self
+		{
+			ScoreColumn::Field(field) => record.value.get_f32(*field).map(f64::from),
+			ScoreColumn::Name => record.value.get_name()?.trim().parse().ok(),
+			ScoreColumn::Column(name) => match record.value.get_custom_value(name.as_str())?
+			{
+				FieldValue::Text(text) => text.trim().parse().ok(),
+				value => value.as_f64(),
+			},
+		}
+	}
+}
@@ -0,0 +1,196 @@
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::bed::BedRecord;
+use crate::error;
+use crate::ops::ScoreColumn;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentOptions
+{
+	pub threshold: f64,
+	// segments shorter than this are dropped
+	pub min_len: u64,
+	// passing records this many bases apart still join one segment
+	pub max_gap: u64,
+	// segments of scores below the threshold rather than above, hypomethylated regions
+	pub below: bool,
+	// segments made of fewer records are dropped
+	pub min_records: u64,
+}
+
+impl SegmentOptions
+{
+	pub fn new(threshold: f64) -> Self
+	{
+		Self {
+			threshold,
+			min_len: 0,
+			max_gap: 0,
+			below: false,
+			min_records: 1,
+		}
+	}
+
+	pub fn with_min_len(mut self, min_len: u64) -> Self
+	{
+		self.min_len = min_len;
+		self
+	}
+
+	pub fn with_max_gap(mut self, max_gap: u64) -> Self
+	{
+		self.max_gap = max_gap;
+		self
+	}
+
+	pub fn with_below(mut self, below: bool) -> Self
+	{
+		self.below = below;
+		self
+	}
+
+	pub fn with_min_records(mut self, min_records: u64) -> Self
+	{
+		self.min_records = min_records;
+		self
+	}
+
+	fn passes(&self, value: f64) -> bool
+	{
+		if self.below
+		{
+			value < self.threshold
+		}
+		else
+		{
+			value > self.threshold
+		}
+	}
+
+	// the finished segment when it is long enough
+	fn keep<Tid>(&self, (mut segment, sum): (Segment<Tid>, f64)) -> Option<Segment<Tid>>
+	{
+		if segment.end - segment.start < self.min_len || segment.records < self.min_records
+		{
+			return None;
+		}
+
+		segment.mean = sum / segment.records as f64;
+		Some(segment)
+	}
+}
+
+// a run of records past the threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment<Tid>
+{
+	pub tid: Tid,
+	pub start: u64,
+	pub end: u64,
+	pub records: u64,
+	pub mean: f64,
+}
+
+// Segments where the score of column exceeds threshold, neighbours at most max_gap apart
+// merged and those under min_len dropped.
+pub fn call_segments<Tid, S>(
+	records: S,
+	column: impl Into<ScoreColumn>,
+	threshold: f64,
+	min_len: u64,
+	max_gap: u64,
+) -> impl Stream<Item = error::Result<Segment<Tid>>>
+where
+	Tid: PartialEq,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	let options = SegmentOptions::new(threshold)
+		.with_min_len(min_len)
+		.with_max_gap(max_gap);
+
+	call_segments_with(records, column, options)
+}
+
+// As call_segments. Records have to be sorted. One that fails the threshold ends the
+// segment it falls in, records without the column are passed over. The stream ends after
+// the first error.
+pub fn call_segments_with<Tid, S>(
+	records: S,
+	column: impl Into<ScoreColumn>,
+	options: SegmentOptions,
+) -> impl Stream<Item = error::Result<Segment<Tid>>>
+where
+	Tid: PartialEq,
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+{
+	let column = column.into();
+
+	// the segment being grown, with the sum of its scores
+	let state = (Box::pin(records), None::<(Segment<Tid>, f64)>, false);
+
+	stream::unfold(state, move |(mut inner, mut current, done)| {
+		let column = column.clone();
+
+		async move {
+			if done
+			{
+				return None;
+			}
+
+			loop
+			{
+				let record = match inner.next().await
+				{
+					Some(Ok(record)) => record,
+					Some(Err(e)) => return Some((Err(e), (inner, None, true))),
+					None =>
+					{
+						let finished = current.take().and_then(|segment| options.keep(segment));
+						return finished.map(|segment| (Ok(segment), (inner, None, true)));
+					}
+				};
+
+				let Some(value) = column.value(&record)
+				else
+				{
+					continue;
+				};
+
+				if !options.passes(value)
+				{
+					if let Some(segment) = current.take().and_then(|segment| options.keep(segment))
+					{
+						return Some((Ok(segment), (inner, None, false)));
+					}
+
+					continue;
+				}
+
+				if let Some((segment, sum)) = current.as_mut().filter(|(segment, _)| {
+					segment.tid == record.tid && record.start <= segment.end + options.max_gap
+				})
+				{
+					segment.end = segment.end.max(record.end);
+					segment.records += 1;
+					*sum += value;
+					continue;
+				}
+
+				let started = Segment {
+					tid: record.tid,
+					start: record.start,
+					end: record.end,
+					records: 1,
+					mean: 0.0,
+				};
+
+				if let Some(segment) = current
+					.replace((started, value))
+					.and_then(|segment| options.keep(segment))
+				{
+					return Some((Ok(segment), (inner, current, false)));
+				}
+			}
+		}
+	})
+}
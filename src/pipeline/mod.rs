@@ -0,0 +1,77 @@
+mod rescale;
+mod sanitize;
+mod transform;
+
+pub use rescale::{MAX_SCORE, Scaling, ScoreScale};
+pub use sanitize::{Corrections, Sanitizer};
+pub use transform::Transforms;
+
+use futures::stream::{Stream, StreamExt};
+
+use crate::bed::autooneshotreader::AutoOneShotBlockReaderTrait;
+use crate::bed::autowriter::AutoWriter;
+use crate::bed::{BedRecord, record_stream};
+use crate::error;
+use crate::store::{TidLookup, TidResolver};
+
+// the batch copy reads at a time, as blocks_per_batch for record_stream
+const BLOCKS_PER_BATCH: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopySummary
+{
+	pub read: u64,
+	pub written: u64,
+	// records a transform returned None for
+	pub dropped: u64,
+}
+
+// Every record of reader through transforms into writer, a batch of blocks held at a time.
+// The writer is flushed, not closed, so track lines can come before and more records after.
+pub async fn copy<Rd, T, W, L>(
+	reader: Rd,
+	writer: &mut W,
+	transforms: &mut Transforms<T::Tid>,
+) -> error::Result<CopySummary>
+where
+	Rd: AutoOneShotBlockReaderTrait<T>,
+	T: TidResolver + Clone + std::fmt::Debug + Send + Sync + 'static,
+	W: AutoWriter<L>,
+	L: TidLookup<Tid = T::Tid>,
+{
+	copy_records(record_stream(reader, BLOCKS_PER_BATCH), writer, transforms).await
+}
+
+// as copy, from any stream of records such as a region query's
+pub async fn copy_records<S, Tid, W, L>(
+	records: S,
+	writer: &mut W,
+	transforms: &mut Transforms<Tid>,
+) -> error::Result<CopySummary>
+where
+	S: Stream<Item = error::Result<BedRecord<Tid>>>,
+	W: AutoWriter<L>,
+	L: TidLookup<Tid = Tid>,
+{
+	let mut records = std::pin::pin!(records);
+	let mut summary = CopySummary::default();
+
+	while let Some(record) = records.next().await
+	{
+		summary.read += 1;
+
+		let Some(record) = transforms.apply(record?)
+		else
+		{
+			summary.dropped += 1;
+			continue;
+		};
+
+		writer.write_record(&record).await?;
+		summary.written += 1;
+	}
+
+	writer.flush().await?;
+
+	Ok(summary)
+}
@@ -0,0 +1,138 @@
+use crate::bed::{BedKind, BedRecord};
+use crate::ops::ScoreColumn;
+
+// the highest score BED allows, what browsers shade the darkest
+pub const MAX_SCORE: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling
+{
+	Linear,
+	// ln(1 + value - min), for signals spanning orders of magnitude
+	Log,
+	// the share of the fitted values below, so scores spread evenly whatever the signal's shape
+	Quantile,
+}
+
+// Maps a numeric column into 0..=1000. Values outside the range it was built or fitted with
+// are held to its ends, values that are not finite score 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreScale
+{
+	scaling: Scaling,
+	min: f64,
+	max: f64,
+	// sorted, Quantile only
+	points: Vec<f64>,
+}
+
+impl ScoreScale
+{
+	pub fn linear(min: f64, max: f64) -> Self
+	{
+		Self {
+			scaling: Scaling::Linear,
+			min,
+			max,
+			points: Vec::new(),
+		}
+	}
+
+	pub fn log(min: f64, max: f64) -> Self
+	{
+		Self {
+			scaling: Scaling::Log,
+			min,
+			max,
+			points: Vec::new(),
+		}
+	}
+
+	// The range or distribution of values, such as a first pass over the column. Linear and
+	// Log only keep its bounds, Quantile keeps every finite value.
+	pub fn fit<I>(scaling: Scaling, values: I) -> Self
+	where
+		I: IntoIterator<Item = f64>,
+	{
+		let mut points: Vec<f64> = values.into_iter().filter(|value| value.is_finite()).collect();
+		points.sort_by(f64::total_cmp);
+
+		let min = points.first().copied().unwrap_or_default();
+		let max = points.last().copied().unwrap_or_default();
+
+		if scaling != Scaling::Quantile
+		{
+			points = Vec::new();
+		}
+
+		Self {
+			scaling,
+			min,
+			max,
+			points,
+		}
+	}
+
+	// as fit, over column in records, those lacking it skipped
+	pub fn fit_records<'a, Tid, I>(scaling: Scaling, column: &ScoreColumn, records: I) -> Self
+	where
+		Tid: 'a,
+		I: IntoIterator<Item = &'a BedRecord<Tid>>,
+	{
+		Self::fit(scaling, records.into_iter().filter_map(|record| column.value(record)))
+	}
+
+	pub fn score(&self, value: f64) -> u32
+	{
+		if !value.is_finite()
+		{
+			return 0;
+		}
+
+		let fraction = match self.scaling
+		{
+			Scaling::Linear => (value - self.min) / (self.max - self.min),
+			Scaling::Log =>
+			{
+				let value = value.clamp(self.min, self.max);
+				(value - self.min).ln_1p() / (self.max - self.min).ln_1p()
+			}
+			Scaling::Quantile if self.points.len() < 2 => 1.0,
+			Scaling::Quantile =>
+			{
+				let below = self.points.partition_point(|&point| point < value);
+				below as f64 / (self.points.len() - 1) as f64
+			}
+		};
+
+		// a range without width puts everything at the top
+		let fraction = if fraction.is_nan()
+		{
+			1.0
+		}
+		else
+		{
+			fraction.clamp(0.0, 1.0)
+		};
+
+		(fraction * MAX_SCORE as f64).round() as u32
+	}
+
+	// The score of record's column, records lacking it left as they are. BED3 and BED4
+	// records become BED5 so the score is written.
+	pub fn rescale<Tid>(&self, column: &ScoreColumn, record: &mut BedRecord<Tid>)
+	{
+		let Some(value) = column.value(record)
+		else
+		{
+			return;
+		};
+
+		record.value.score = Some(self.score(value));
+
+		if matches!(record.value.kind, BedKind::Bed3 | BedKind::Bed4)
+		{
+			record.value.kind = BedKind::Bed5;
+		}
+	}
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::bed::{BedRecord, BedSinkValue};
+use crate::region::CoordinateSystem;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Corrections
+{
+	// ends past their contig's length brought back to it
+	pub truncated: u64,
+	// start and end the wrong way round, put right with with_swap
+	pub swapped: u64,
+	// records left without any length, or swapped ones when with_swap is off
+	pub dropped: u64,
+}
+
+impl Corrections
+{
+	pub fn total(&self) -> u64
+	{
+		self.truncated + self.swapped + self.dropped
+	}
+}
+
+// shared by every clone, so the count can be read back after the transforms are used up
+#[derive(Debug, Default)]
+struct Counters
+{
+	truncated: AtomicU64,
+	swapped: AtomicU64,
+	dropped: AtomicU64,
+}
+
+// Fixes records a strict tool would refuse, given the length of each contig as genomecov
+// takes them. Coordinates are taken as 0-based half-open, as readers opened with_one_indexed
+// give them, unless with_coordinate_system says otherwise. Records on contigs without a length
+// are only checked for their own length.
+#[derive(Debug, Clone)]
+pub struct Sanitizer<Tid>
+{
+	sizes: Arc<HashMap<Tid, u64>>,
+	swap: bool,
+	system: CoordinateSystem,
+	counters: Arc<Counters>,
+}
+
+impl<Tid> Sanitizer<Tid>
+where
+	Tid: Hash + Eq,
+{
+	pub fn new<I>(chrom_sizes: I) -> Self
+	where
+		I: IntoIterator<Item = (Tid, u64)>,
+	{
+		Self {
+			sizes: Arc::new(chrom_sizes.into_iter().collect()),
+			swap: false,
+			system: CoordinateSystem::ZeroBasedHalfOpen,
+			counters: Arc::default(),
+		}
+	}
+
+	// records with start after end swapped round rather than dropped
+	pub fn with_swap(mut self, swap: bool) -> Self
+	{
+		self.swap = swap;
+		self
+	}
+
+	// the system records come in and go out in, CoordinateSystem::of_reader for a reader's own
+	pub fn with_coordinate_system(mut self, system: CoordinateSystem) -> Self
+	{
+		self.system = system;
+		self
+	}
+
+	// the corrections made so far by this sanitizer and its clones
+	pub fn corrections(&self) -> Corrections
+	{
+		let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+		Corrections {
+			truncated: load(&self.counters.truncated),
+			swapped: load(&self.counters.swapped),
+			dropped: load(&self.counters.dropped),
+		}
+	}
+
+	// the record as a strict tool would take it, None when nothing of it is left
+	pub fn sanitize(&self, mut record: BedRecord<Tid>) -> Option<BedRecord<Tid>>
+	{
+		let count = |counter: &AtomicU64| counter.fetch_add(1, Ordering::Relaxed);
+
+		if record.start > record.end
+		{
+			if !self.swap
+			{
+				count(&self.counters.dropped);
+				return None;
+			}
+
+			std::mem::swap(&mut record.start, &mut record.end);
+			count(&self.counters.swapped);
+		}
+
+		(record.start, record.end) =
+			self.system.convert(record.start, record.end, CoordinateSystem::ZeroBasedHalfOpen);
+
+		if let Some(&size) = self.sizes.get(&record.tid).filter(|&&size| record.end > size)
+		{
+			let cut = record.end - size;
+			record.end = size;
+			record.end -= truncate_bed12(&mut record.value, cut).min(record.end);
+			count(&self.counters.truncated);
+		}
+
+		if record.start >= record.end
+		{
+			count(&self.counters.dropped);
+			return None;
+		}
+
+		(record.start, record.end) =
+			CoordinateSystem::ZeroBasedHalfOpen.convert(record.start, record.end, self.system);
+
+		Some(record)
+	}
+}
+
+// Takes cut off the end of the BED12 columns, dropping the blocks past it and shortening the
+// one across it. Gives how much more has to come off the record to end with its last block.
+fn truncate_bed12(value: &mut BedSinkValue, cut: u64) -> u64
+{
+	let Some(bed12) = value.bed12.as_deref_mut()
+	else
+	{
+		return 0;
+	};
+
+	let end = bed12.chrom_end.saturating_sub(cut).max(bed12.chrom_start);
+	let length = end - bed12.chrom_start;
+
+	let mut kept = 0;
+	let mut last_end = 0;
+
+	for (&start, size) in bed12.block_starts.iter().zip(bed12.block_sizes.iter_mut())
+	{
+		let start = start as u64;
+
+		if start >= length
+		{
+			break;
+		}
+
+		*size = (*size as u64).min(length - start) as u32;
+		last_end = start + *size as u64;
+		kept += 1;
+	}
+
+	bed12.block_starts.truncate(kept);
+	bed12.block_sizes.truncate(kept);
+	bed12.block_count = kept as u32;
+
+	let end = bed12.chrom_start + last_end;
+	let shortfall = bed12.chrom_end.saturating_sub(cut).saturating_sub(end);
+
+	bed12.chrom_end = end;
+	bed12.thick_start = bed12.thick_start.min(end);
+	bed12.thick_end = bed12.thick_end.min(end);
+
+	shortfall
+}
@@ -0,0 +1,191 @@
+use std::hash::Hash;
+
+use crate::bed::{BedKind, BedRecord, Strand};
+use crate::ops::ScoreColumn;
+use crate::pipeline::{Sanitizer, ScoreScale};
+use crate::store::ChromAliases;
+
+type Step<Tid> = Box<dyn FnMut(BedRecord<Tid>) -> Option<BedRecord<Tid>> + Send>;
+
+// Record-level steps run in the order they were added, a step returning None drops the
+// record before the steps after it see it.
+pub struct Transforms<Tid>
+{
+	steps: Vec<Step<Tid>>,
+}
+
+impl<Tid> Default for Transforms<Tid>
+{
+	fn default() -> Self
+	{
+		Self { steps: Vec::new() }
+	}
+}
+
+impl<Tid> Transforms<Tid>
+where
+	Tid: 'static,
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	// any change to a record, or None to drop it
+	pub fn with_step<F>(mut self, step: F) -> Self
+	where
+		F: FnMut(BedRecord<Tid>) -> Option<BedRecord<Tid>> + Send + 'static,
+	{
+		self.steps.push(Box::new(step));
+		self
+	}
+
+	pub fn with_map<F>(self, mut map: F) -> Self
+	where
+		F: FnMut(BedRecord<Tid>) -> BedRecord<Tid> + Send + 'static,
+	{
+		self.with_step(move |record| Some(map(record)))
+	}
+
+	// keeps the records keep returns true for
+	pub fn with_filter<F>(self, mut keep: F) -> Self
+	where
+		F: FnMut(&BedRecord<Tid>) -> bool + Send + 'static,
+	{
+		self.with_step(move |record| keep(&record).then_some(record))
+	}
+
+	// contig names through rename, records it gives None for are dropped
+	pub fn with_rename<F>(self, mut rename: F) -> Self
+	where
+		F: FnMut(&Tid) -> Option<Tid> + Send + 'static,
+	{
+		self.with_step(move |mut record| {
+			record.tid = rename(&record.tid)?;
+			Some(record)
+		})
+	}
+
+	// the score column held within min..=max, records without one pass unchanged
+	pub fn with_clamp_scores(self, min: u32, max: u32) -> Self
+	{
+		self.with_map(move |mut record| {
+			record.value.score = record.value.score.map(|score| score.clamp(min, max));
+			record
+		})
+	}
+
+	// The score column set from column through scale, such as narrowPeak's signalValue into
+	// a score a browser can shade by. Records without column keep their score.
+	pub fn with_rescale(self, column: impl Into<ScoreColumn>, scale: ScoreScale) -> Self
+	{
+		let column = column.into();
+
+		self.with_map(move |mut record| {
+			scale.rescale(&column, &mut record);
+			record
+		})
+	}
+
+	// Keeps the columns of kind and drops the rest, so BED12, bedMethyl or BED6+N records
+	// are written as BED6 or fewer columns. Kinds with columns a record does not hold leave
+	// it as it is.
+	pub fn with_columns(self, kind: BedKind) -> Self
+	{
+		self.with_map(move |mut record| {
+			narrow(&mut record, kind);
+			record
+		})
+	}
+}
+
+impl<Tid> Transforms<Tid>
+where
+	Tid: Hash + Eq + Send + Sync + 'static,
+{
+	// Ends held to contig lengths and records without length dropped, the count of each
+	// correction kept in sanitizer for after the copy.
+	pub fn with_sanitizer(self, sanitizer: Sanitizer<Tid>) -> Self
+	{
+		self.with_step(move |record| sanitizer.sanitize(record))
+	}
+}
+
+impl Transforms<String>
+{
+	// Contigs renamed through aliases, such as with_strip_chr for Ensembl names, unless
+	// aliases drops unmapped names. Interned tids are renamed on reading by AliasResolver.
+	pub fn with_aliases(self, aliases: ChromAliases) -> Self
+	{
+		self.with_rename(move |tid| aliases.rename(tid).map(|tid| tid.into_owned()))
+	}
+}
+
+impl<Tid> Transforms<Tid>
+{
+	// the record after every step, None when one dropped it
+	pub fn apply(&mut self, record: BedRecord<Tid>) -> Option<BedRecord<Tid>>
+	{
+		self.steps.iter_mut().try_fold(record, |record, step| step(record))
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.steps.is_empty()
+	}
+}
+
+fn narrow<Tid>(record: &mut BedRecord<Tid>, kind: BedKind)
+{
+	let columns = |kind: BedKind| match kind
+	{
+		BedKind::Bed3 => 3,
+		BedKind::Bed4 => 4,
+		BedKind::Bed5 => 5,
+		BedKind::Bed6 => 6,
+		BedKind::Bed12 | BedKind::BedMethyl => 12,
+	};
+
+	let value = &mut record.value;
+
+	if kind != value.kind && columns(kind) >= columns(value.kind)
+	{
+		return;
+	}
+
+	// the columns after the kind's own, BED6+N extras among them
+	value.extra = None;
+	value.fields = None;
+
+	if kind == value.kind
+	{
+		return;
+	}
+
+	if columns(kind) < 4
+	{
+		value.name = None;
+	}
+
+	if columns(kind) < 5
+	{
+		value.score = None;
+	}
+
+	if columns(kind) < 6
+	{
+		record.strand = Strand::Both;
+	}
+
+	value.n_valid_cov = None;
+	value.frac_mod = None;
+	value.n_mod = None;
+	value.n_canonical = None;
+	value.n_other_mod = None;
+	value.n_delete = None;
+	value.n_fail = None;
+	value.n_diff = None;
+	value.n_nocall = None;
+	value.bed12 = None;
+	value.kind = kind;
+}
@@ -0,0 +1,186 @@
+#![cfg(feature = "python")]
+
+// Build the extension with maturin, e.g. `maturin develop --features python`.
+
+use std::sync::Arc;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+use tokio::fs::File;
+
+use pufferfish::prelude::*;
+
+use crate::bed::oneshotreader::ReaderOptions;
+use crate::bed::{BedRecord, CollectSink, ScoreField, Strand};
+use crate::blocking::AutoOneShotBlockReader;
+use crate::error;
+use crate::region::Region;
+
+const BLOCKS_PER_BATCH: usize = 64;
+const QUERY_PARALLELISM: usize = 4;
+
+impl From<error::Error> for PyErr
+{
+	fn from(e: error::Error) -> Self
+	{
+		// the message keeps the context, the exception type follows the underlying error
+		match e.root()
+		{
+			error::Error::Io(_) => PyIOError::new_err(e.to_string()),
+			_ => PyValueError::new_err(e.to_string()),
+		}
+	}
+}
+
+#[pyclass(name = "BedRecord", module = "sandman", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PyBedRecord
+{
+	chrom: String,
+	start: u64,
+	end: u64,
+	strand: &'static str,
+	name: Option<String>,
+	score: Option<u32>,
+	frac_mod: Option<f32>,
+	n_valid_cov: Option<u32>,
+}
+
+#[pymethods]
+impl PyBedRecord
+{
+	fn __repr__(&self) -> String
+	{
+		format!(
+			"BedRecord(chrom={:?}, start={}, end={}, strand={:?})",
+			self.chrom, self.start, self.end, self.strand
+		)
+	}
+}
+
+impl From<BedRecord<String>> for PyBedRecord
+{
+	fn from(record: BedRecord<String>) -> Self
+	{
+		let score = record.value.get_u32(ScoreField::Score);
+		let frac_mod = record.value.get_f32(ScoreField::FracMod);
+		let n_valid_cov = record.value.get_u32(ScoreField::NValidCov);
+
+		Self {
+			chrom: record.tid,
+			start: record.start,
+			end: record.end,
+			strand: match record.strand
+			{
+				Strand::Plus => "+",
+				Strand::Minus => "-",
+				Strand::Both => ".",
+			},
+			name: record.value.name,
+			score,
+			frac_mod,
+			n_valid_cov,
+		}
+	}
+}
+
+// python objects live on the interpreter thread, the reader owns a private runtime
+#[pyclass(name = "SandmanReader", module = "sandman", unsendable)]
+pub struct PyReader
+{
+	path: String,
+	reader: AutoOneShotBlockReader<File, ()>,
+	sink: CollectSink<String>,
+}
+
+fn open_reader(path: &str) -> error::Result<AutoOneShotBlockReader<File, ()>>
+{
+	let pool = Arc::new(pool::BgzfBlockPool::new(1024, 64 * 1024));
+	AutoOneShotBlockReader::from_path_indexed(path, None, pool, ReaderOptions::<()>::default())
+}
+
+impl PyReader
+{
+	fn next_record(&mut self) -> error::Result<Option<BedRecord<String>>>
+	{
+		while self.sink.is_empty()
+		{
+			match self.reader.next_bgzf_blocks(BLOCKS_PER_BATCH)?
+			{
+				Some(block) =>
+				{
+					self.reader.read_tids_in_block_sink(block, &mut self.sink)?;
+				}
+				None => return Ok(None),
+			}
+		}
+
+		Ok(self.sink.pop())
+	}
+}
+
+#[pymethods]
+impl PyReader
+{
+	#[staticmethod]
+	fn open(path: &str) -> PyResult<Self>
+	{
+		Ok(Self {
+			path: path.to_owned(),
+			reader: open_reader(path)?,
+			sink: CollectSink::new(),
+		})
+	}
+
+	#[getter]
+	fn name(&self) -> String
+	{
+		self.reader.name()
+	}
+
+	#[getter]
+	fn path(&self) -> &str
+	{
+		&self.path
+	}
+
+	// Records on `chrom` overlapping start..end, 0-based half-open, read through the index
+	// beside the file. Iteration is not disturbed.
+	fn fetch(&self, py: Python<'_>, chrom: &str, start: u64, end: u64) -> PyResult<Vec<PyBedRecord>>
+	{
+		if start > end
+		{
+			return Err(PyValueError::new_err(format!(
+				"start {start} is greater than end {end}"
+			)));
+		}
+
+		let region = Region::zero_based(chrom, start, end);
+		let reader = &self.reader;
+
+		let records = py.allow_threads(move || {
+			reader.query_region_concurrent(&region, QUERY_PARALLELISM)
+		})?;
+
+		Ok(records.into_iter().map(PyBedRecord::from).collect())
+	}
+
+	fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self>
+	{
+		slf
+	}
+
+	fn __next__(&mut self) -> PyResult<Option<PyBedRecord>>
+	{
+		Ok(self.next_record()?.map(PyBedRecord::from))
+	}
+}
+
+#[pymodule]
+fn sandman(m: &Bound<'_, PyModule>) -> PyResult<()>
+{
+	m.add_class::<PyReader>()?;
+	m.add_class::<PyBedRecord>()?;
+	Ok(())
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+
+use crate::bed::{BedRecord, GenomicInterval};
+use crate::error;
+
+// BED and tabix are 0-based half-open, GFF, VCF and samtools style region strings are
+// 1-based closed. Everything here converts through 0-based half-open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoordinateSystem
+{
+	ZeroBasedHalfOpen,
+	OneBasedClosed,
+}
+
+impl CoordinateSystem
+{
+	// the same interval expressed in another system
+	pub fn convert(self, start: u64, end: u64, to: CoordinateSystem) -> (u64, u64)
+	{
+		match (self, to)
+		{
+			(CoordinateSystem::ZeroBasedHalfOpen, CoordinateSystem::OneBasedClosed) =>
+			{
+				(start.saturating_add(1), end)
+			}
+			(CoordinateSystem::OneBasedClosed, CoordinateSystem::ZeroBasedHalfOpen) =>
+			{
+				(start.saturating_sub(1), end)
+			}
+			_ => (start, end),
+		}
+	}
+
+	// what readers hand to sinks, see ReaderOptions::with_one_indexed
+	pub fn of_reader(one_indexed: bool) -> Self
+	{
+		if one_indexed
+		{
+			CoordinateSystem::ZeroBasedHalfOpen
+		}
+		else
+		{
+			CoordinateSystem::OneBasedClosed
+		}
+	}
+}
+
+// A 0-based coordinate. Positions are u64 throughout, lossless from u32 and usize sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Position(pub u64);
+
+impl Position
+{
+	// largest coordinate a tabix (as opposed to CSI) index can address, 512 Mb
+	pub const TABIX_MAX: Position = Position(1 << 29);
+
+	pub fn get(self) -> u64
+	{
+		self.0
+	}
+}
+
+impl From<u64> for Position
+{
+	fn from(position: u64) -> Self
+	{
+		Position(position)
+	}
+}
+
+impl From<u32> for Position
+{
+	fn from(position: u32) -> Self
+	{
+		Position(position as u64)
+	}
+}
+
+impl From<usize> for Position
+{
+	fn from(position: usize) -> Self
+	{
+		Position(position as u64)
+	}
+}
+
+impl From<Position> for u64
+{
+	fn from(position: Position) -> Self
+	{
+		position.0
+	}
+}
+
+impl fmt::Display for Position
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "{}", self.0)
+	}
+}
+
+// A query interval on a named contig, held 0-based half-open.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Region
+{
+	pub tid: String,
+	pub start: u64,
+	pub end: u64,
+}
+
+impl Region
+{
+	pub fn zero_based(tid: impl Into<String>, start: u64, end: u64) -> Self
+	{
+		Self {
+			tid: tid.into(),
+			start,
+			end,
+		}
+	}
+
+	// e.g. GFF or VCF positions, both ends inclusive
+	pub fn one_based(tid: impl Into<String>, start: u64, end: u64) -> Self
+	{
+		Self::new_in(tid, start, end, CoordinateSystem::OneBasedClosed)
+	}
+
+	pub fn new_in(tid: impl Into<String>, start: u64, end: u64, system: CoordinateSystem) -> Self
+	{
+		let (start, end) = system.convert(start, end, CoordinateSystem::ZeroBasedHalfOpen);
+		Self::zero_based(tid, start, end)
+	}
+
+	// the whole contig
+	pub fn contig(tid: impl Into<String>) -> Self
+	{
+		Self::zero_based(tid, 0, u64::MAX)
+	}
+
+	// samtools style "chr1", "chr1:1000" or "chr1:1,000-2,000", 1-based closed
+	pub fn parse(region: &str) -> error::Result<Self>
+	{
+		let invalid = || error::Error::InvalidRegion(region.to_string());
+
+		let Some((tid, range)) = region.rsplit_once(':')
+		else
+		{
+			return Ok(Self::contig(region));
+		};
+
+		let parse = |value: &str| value.replace(',', "").trim().parse::<u64>().map_err(|_| invalid());
+
+		let (start, end) = match range.split_once('-')
+		{
+			Some((start, end)) => (parse(start)?, parse(end)?),
+			None =>
+			{
+				let start = parse(range)?;
+				(start, u64::MAX)
+			}
+		};
+
+		if start == 0 || start > end
+		{
+			return Err(invalid());
+		}
+
+		Ok(Self::one_based(tid, start, end))
+	}
+
+	pub fn start_position(&self) -> Position
+	{
+		Position(self.start)
+	}
+
+	pub fn end_position(&self) -> Position
+	{
+		Position(self.end)
+	}
+
+	pub fn in_system(&self, system: CoordinateSystem) -> (u64, u64)
+	{
+		CoordinateSystem::ZeroBasedHalfOpen.convert(self.start, self.end, system)
+	}
+}
+
+impl fmt::Display for Region
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		let (start, end) = self.in_system(CoordinateSystem::OneBasedClosed);
+
+		if end == u64::MAX
+		{
+			write!(f, "{}:{}", self.tid, start)
+		}
+		else
+		{
+			write!(f, "{}:{}-{}", self.tid, start, end)
+		}
+	}
+}
+
+impl GenomicInterval for Region
+{
+	type Tid = String;
+
+	fn tid(&self) -> &String
+	{
+		&self.tid
+	}
+
+	fn start(&self) -> u64
+	{
+		self.start
+	}
+
+	fn end(&self) -> u64
+	{
+		self.end
+	}
+}
+
+impl<Tid> BedRecord<Tid>
+{
+	// copy of the record with its coordinates moved from one system to another
+	pub fn converted(mut self, from: CoordinateSystem, to: CoordinateSystem) -> Self
+	{
+		(self.start, self.end) = from.convert(self.start, self.end, to);
+		self
+	}
+}
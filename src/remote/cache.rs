@@ -0,0 +1,263 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use bytes::Bytes;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats
+{
+	pub hits: u64,
+	pub misses: u64,
+	pub entries: usize,
+	pub bytes: u64,
+}
+
+// ObjectStoreReader calls get and put on tokio's blocking pool, so they may block on I/O
+pub trait RangeCache: Debug + Send + Sync
+{
+	fn get(&self, url: &str, range: &Range<u64>) -> Option<Bytes>;
+	fn put(&self, url: &str, range: Range<u64>, bytes: Bytes);
+	fn stats(&self) -> CacheStats;
+}
+
+type CacheKey = (String, u64, u64);
+
+#[derive(Debug, Default)]
+struct LruState
+{
+	entries: HashMap<CacheKey, (Bytes, u64)>,
+	order: BTreeMap<u64, CacheKey>,
+	tick: u64,
+	bytes: u64,
+}
+
+#[derive(Debug)]
+pub struct MemoryCache
+{
+	capacity_bytes: u64,
+	state: Mutex<LruState>,
+	hits: AtomicU64,
+	misses: AtomicU64,
+}
+
+impl MemoryCache
+{
+	pub fn new(capacity_bytes: u64) -> Self
+	{
+		Self {
+			capacity_bytes,
+			state: Mutex::new(LruState::default()),
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
+		}
+	}
+}
+
+impl RangeCache for MemoryCache
+{
+	fn get(&self, url: &str, range: &Range<u64>) -> Option<Bytes>
+	{
+		let key = (url.to_string(), range.start, range.end);
+		let mut state = self.state.lock().expect("cache lock poisoned");
+		state.tick += 1;
+		let tick = state.tick;
+
+		let LruState { entries, order, .. } = &mut *state;
+
+		match entries.get_mut(&key)
+		{
+			Some((bytes, last_used)) =>
+			{
+				order.remove(last_used);
+				*last_used = tick;
+				order.insert(tick, key);
+				self.hits.fetch_add(1, Ordering::Relaxed);
+				Some(bytes.clone())
+			}
+			None =>
+			{
+				self.misses.fetch_add(1, Ordering::Relaxed);
+				None
+			}
+		}
+	}
+
+	fn put(&self, url: &str, range: Range<u64>, bytes: Bytes)
+	{
+		let size = bytes.len() as u64;
+		if size > self.capacity_bytes
+		{
+			return;
+		}
+
+		let key = (url.to_string(), range.start, range.end);
+		let mut state = self.state.lock().expect("cache lock poisoned");
+		state.tick += 1;
+		let tick = state.tick;
+
+		if let Some((old, last_used)) = state.entries.remove(&key)
+		{
+			state.order.remove(&last_used);
+			state.bytes -= old.len() as u64;
+		}
+
+		while state.bytes + size > self.capacity_bytes
+		{
+			let Some((_, oldest)) = state.order.pop_first()
+			else
+			{
+				break;
+			};
+
+			if let Some((evicted, _)) = state.entries.remove(&oldest)
+			{
+				state.bytes -= evicted.len() as u64;
+			}
+		}
+
+		state.order.insert(tick, key.clone());
+		state.entries.insert(key, (bytes, tick));
+		state.bytes += size;
+	}
+
+	fn stats(&self) -> CacheStats
+	{
+		let state = self.state.lock().expect("cache lock poisoned");
+
+		CacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			entries: state.entries.len(),
+			bytes: state.bytes,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct DiskCache
+{
+	dir: PathBuf,
+	hits: AtomicU64,
+	misses: AtomicU64,
+}
+
+impl DiskCache
+{
+	pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self>
+	{
+		let dir = dir.into();
+		std::fs::create_dir_all(&dir)?;
+
+		Ok(Self {
+			dir,
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
+		})
+	}
+
+	fn entry_path(&self, url: &str, range: &Range<u64>) -> PathBuf
+	{
+		let mut hasher = DefaultHasher::new();
+		url.hash(&mut hasher);
+
+		self.dir
+			.join(format!("{:016x}_{}_{}", hasher.finish(), range.start, range.end))
+	}
+}
+
+impl RangeCache for DiskCache
+{
+	fn get(&self, url: &str, range: &Range<u64>) -> Option<Bytes>
+	{
+		match std::fs::read(self.entry_path(url, range))
+		{
+			Ok(bytes) if bytes.len() as u64 == range.end - range.start =>
+			{
+				self.hits.fetch_add(1, Ordering::Relaxed);
+				Some(Bytes::from(bytes))
+			}
+			_ =>
+			{
+				self.misses.fetch_add(1, Ordering::Relaxed);
+				None
+			}
+		}
+	}
+
+	fn put(&self, url: &str, range: Range<u64>, bytes: Bytes)
+	{
+		let path = self.entry_path(url, &range);
+		let tmp = path.with_extension("tmp");
+
+		// a failed write only costs a refetch later
+		if std::fs::write(&tmp, &bytes).is_ok()
+		{
+			let _ = std::fs::rename(&tmp, &path);
+		}
+	}
+
+	fn stats(&self) -> CacheStats
+	{
+		let (entries, bytes) = std::fs::read_dir(&self.dir)
+			.map(|dir| {
+				dir.filter_map(|entry| entry.ok()?.metadata().ok())
+					.filter(|meta| meta.is_file())
+					.fold((0, 0), |(n, total), meta| (n + 1, total + meta.len()))
+			})
+			.unwrap_or((0, 0));
+
+		CacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			entries,
+			bytes,
+		}
+	}
+}
+
+// memory first, falling back to (and populating from) disk
+#[derive(Debug)]
+pub struct TieredCache
+{
+	pub memory: MemoryCache,
+	pub disk: DiskCache,
+}
+
+impl RangeCache for TieredCache
+{
+	fn get(&self, url: &str, range: &Range<u64>) -> Option<Bytes>
+	{
+		if let Some(bytes) = self.memory.get(url, range)
+		{
+			return Some(bytes);
+		}
+
+		let bytes = self.disk.get(url, range)?;
+		self.memory.put(url, range.clone(), bytes.clone());
+		Some(bytes)
+	}
+
+	fn put(&self, url: &str, range: Range<u64>, bytes: Bytes)
+	{
+		self.disk.put(url, range.clone(), bytes.clone());
+		self.memory.put(url, range, bytes);
+	}
+
+	fn stats(&self) -> CacheStats
+	{
+		let memory = self.memory.stats();
+		let disk = self.disk.stats();
+
+		CacheStats {
+			hits: memory.hits + disk.hits,
+			misses: disk.misses,
+			entries: disk.entries,
+			bytes: disk.bytes,
+		}
+	}
+}
@@ -0,0 +1,276 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use js_sys::Uint8Array;
+use send_wrapper::SendWrapper;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, Response, WorkerGlobalScope};
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf, SeekFrom};
+
+use crate::error;
+use crate::tabix::{IndexFormat, IndexSearch};
+
+const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+// JavaScript futures are tied to the thread they were made on, which on wasm32 is the only
+// one, so the wrapper's Send is never tested
+type FetchFuture = SendWrapper<Pin<Box<dyn Future<Output = io::Result<Vec<u8>>>>>>;
+type PendingFetch = (u64, FetchFuture);
+
+// A file on a web server read through the browser's fetch with Range requests, a chunk at a
+// time, for wasm32 where there are no files or threads. Cross origin, the server has to
+// allow the Range header and expose Content-Length.
+pub struct FetchReader
+{
+	url: String,
+	size: u64,
+	position: u64,
+	chunk_size: u64,
+	buffer: Vec<u8>,
+	buffer_start: u64,
+	pending: Option<PendingFetch>,
+}
+
+fn js_error(value: JsValue) -> io::Error
+{
+	io::Error::other(value.as_string().unwrap_or_else(|| format!("{value:?}")))
+}
+
+// fetch from a page or a worker, whichever this is running in
+async fn fetch(url: &str, method: &str, range: Option<(u64, u64)>) -> io::Result<Response>
+{
+	let init = RequestInit::new();
+	init.set_method(method);
+
+	let request = Request::new_with_str_and_init(url, &init).map_err(js_error)?;
+
+	if let Some((start, end)) = range
+	{
+		let value = format!("bytes={start}-{}", end - 1);
+		request.headers().set("Range", &value).map_err(js_error)?;
+	}
+
+	let promise = match web_sys::window()
+	{
+		Some(window) => window.fetch_with_request(&request),
+		None => js_sys::global()
+			.unchecked_into::<WorkerGlobalScope>()
+			.fetch_with_request(&request),
+	};
+
+	let response: Response = JsFuture::from(promise).await.map_err(js_error)?.unchecked_into();
+
+	match response.status()
+	{
+		200..=299 => Ok(response),
+		404 => Err(io::Error::new(io::ErrorKind::NotFound, url.to_string())),
+		status => Err(io::Error::other(format!("{url} answered {status}"))),
+	}
+}
+
+async fn fetch_range(url: String, start: u64, end: u64) -> io::Result<Vec<u8>>
+{
+	let response = fetch(&url, "GET", Some((start, end))).await?;
+	let body = JsFuture::from(response.array_buffer().map_err(js_error)?)
+		.await
+		.map_err(js_error)?;
+	let bytes = Uint8Array::new(&body).to_vec();
+
+	// a server ignoring Range sends the whole file with a 200
+	match response.status()
+	{
+		206 => Ok(bytes),
+		_ => Ok(bytes
+			.get(start as usize..(end as usize).min(bytes.len()))
+			.unwrap_or_default()
+			.to_vec()),
+	}
+}
+
+impl FetchReader
+{
+	pub async fn open(url: &str) -> error::Result<Self>
+	{
+		Self::open_with_chunk_size(url, DEFAULT_CHUNK_SIZE).await
+	}
+
+	pub async fn open_with_chunk_size(url: &str, chunk_size: usize) -> error::Result<Self>
+	{
+		let response = fetch(url, "HEAD", None).await?;
+
+		let size = response
+			.headers()
+			.get("Content-Length")
+			.map_err(js_error)?
+			.and_then(|length| length.parse::<u64>().ok())
+			.ok_or_else(|| error::Error::InvalidUrl(format!("{url} has no Content-Length")))?;
+
+		Ok(Self {
+			url: url.to_string(),
+			size,
+			position: 0,
+			chunk_size: chunk_size.max(1) as u64,
+			buffer: Vec::new(),
+			buffer_start: 0,
+			pending: None,
+		})
+	}
+
+	// looks for an index next to the data, None when there isn't one
+	pub async fn open_index(url: &str) -> error::Result<Option<Self>>
+	{
+		Self::open_index_with(url, &IndexSearch::default()).await
+	}
+
+	// the first of search's candidates for url that exists
+	pub async fn open_index_with(url: &str, search: &IndexSearch) -> error::Result<Option<Self>>
+	{
+		// a CSI index would only fail later in tabix::Reader, so another candidate is tried
+		for candidate in search.candidates(url)
+		{
+			if IndexFormat::from_name(&candidate) == Some(IndexFormat::Csi)
+			{
+				continue;
+			}
+
+			match Self::open(&candidate).await
+			{
+				Ok(reader) => return Ok(Some(reader)),
+				Err(error::Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => (),
+				Err(e) => return Err(e),
+			}
+		}
+
+		Ok(None)
+	}
+
+	// a reader over the same file from its start, for ReaderOptions::with_reopen
+	pub fn try_clone(&self) -> Self
+	{
+		Self {
+			url: self.url.clone(),
+			size: self.size,
+			position: 0,
+			chunk_size: self.chunk_size,
+			buffer: Vec::new(),
+			buffer_start: 0,
+			pending: None,
+		}
+	}
+
+	pub fn url(&self) -> &str
+	{
+		&self.url
+	}
+
+	pub fn size(&self) -> u64
+	{
+		self.size
+	}
+
+	fn buffer_offset(&self) -> Option<usize>
+	{
+		let buffer_end = self.buffer_start + self.buffer.len() as u64;
+
+		if self.position >= self.buffer_start && self.position < buffer_end
+		{
+			Some((self.position - self.buffer_start) as usize)
+		}
+		else
+		{
+			None
+		}
+	}
+}
+
+impl AsyncRead for FetchReader
+{
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>>
+	{
+		let this = &mut *self;
+
+		loop
+		{
+			if let Some(offset) = this.buffer_offset()
+			{
+				let available = &this.buffer[offset..];
+				let n = available.len().min(buf.remaining());
+				buf.put_slice(&available[..n]);
+				this.position += n as u64;
+				return Poll::Ready(Ok(()));
+			}
+
+			if this.position >= this.size
+			{
+				return Poll::Ready(Ok(()));
+			}
+
+			if this.pending.is_none()
+			{
+				let start = this.position - this.position % this.chunk_size;
+				let end = (start + this.chunk_size).min(this.size);
+				let future: Pin<Box<dyn Future<Output = io::Result<Vec<u8>>>>> =
+					Box::pin(fetch_range(this.url.clone(), start, end));
+
+				this.pending = Some((start, SendWrapper::new(future)));
+			}
+
+			let (start, future) = this.pending.as_mut().expect("pending fetch");
+			let start = *start;
+
+			let fetched = ready!((**future).as_mut().poll(cx));
+			this.pending = None;
+
+			let bytes = fetched?;
+
+			if bytes.is_empty()
+			{
+				return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+			}
+
+			this.buffer = bytes;
+			this.buffer_start = start;
+		}
+	}
+}
+
+impl AsyncSeek for FetchReader
+{
+	fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()>
+	{
+		let this = &mut *self;
+
+		let target = match position
+		{
+			SeekFrom::Start(n) => Some(n),
+			SeekFrom::End(n) => this.size.checked_add_signed(n),
+			SeekFrom::Current(n) => this.position.checked_add_signed(n),
+		}
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+
+		// a fetch of another chunk is dropped and its answer never read
+		if this
+			.pending
+			.as_ref()
+			.is_some_and(|(start, _)| *start != target - target % this.chunk_size)
+		{
+			this.pending = None;
+		}
+
+		this.position = target;
+		Ok(())
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>>
+	{
+		Poll::Ready(Ok(self.position))
+	}
+}
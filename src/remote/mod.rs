@@ -0,0 +1,13 @@
+#[cfg(feature = "object-store")]
+mod cache;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod fetch;
+#[cfg(feature = "object-store")]
+mod objectstore;
+
+#[cfg(feature = "object-store")]
+pub use cache::*;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use fetch::FetchReader;
+#[cfg(feature = "object-store")]
+pub use objectstore::*;
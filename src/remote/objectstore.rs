@@ -0,0 +1,379 @@
+use std::future::Future;
+use std::io;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf, SeekFrom};
+use tokio::task::JoinHandle;
+
+use url::Url;
+
+use crate::error;
+use crate::remote::{CacheStats, RangeCache};
+use crate::retry::RetryPolicy;
+use crate::tabix::{IndexFormat, IndexSearch};
+
+const DEFAULT_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ObjectStoreOptions
+{
+	pub anonymous: bool,
+	pub chunk_size: usize,
+	pub config: Vec<(String, String)>,
+	pub cache: Option<Arc<dyn RangeCache>>,
+	pub retry_policy: RetryPolicy,
+}
+
+impl Default for ObjectStoreOptions
+{
+	fn default() -> Self
+	{
+		Self {
+			anonymous: false,
+			chunk_size: DEFAULT_CHUNK_SIZE,
+			config: Vec::new(),
+			cache: None,
+			retry_policy: RetryPolicy::default(),
+		}
+	}
+}
+
+impl ObjectStoreOptions
+{
+	pub fn with_anonymous(mut self, anonymous: bool) -> Self
+	{
+		self.anonymous = anonymous;
+		self
+	}
+
+	pub fn with_chunk_size(mut self, chunk_size: usize) -> Self
+	{
+		self.chunk_size = chunk_size.max(1);
+		self
+	}
+
+	pub fn with_config(mut self, key: impl Into<String>, value: impl Into<String>) -> Self
+	{
+		self.config.push((key.into(), value.into()));
+		self
+	}
+
+	pub fn with_cache(mut self, cache: Arc<dyn RangeCache>) -> Self
+	{
+		self.cache = Some(cache);
+		self
+	}
+
+	pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self
+	{
+		self.retry_policy = retry_policy;
+		self
+	}
+}
+
+type PendingFetch = (u64, JoinHandle<error::Result<Bytes>>);
+
+pub struct ObjectStoreReader
+{
+	url: String,
+	store: Arc<dyn ObjectStore>,
+	path: ObjectPath,
+	size: u64,
+	position: u64,
+	chunk_size: u64,
+	buffer: Bytes,
+	buffer_start: u64,
+	pending: Option<PendingFetch>,
+	cache: Option<Arc<dyn RangeCache>>,
+	retry_policy: RetryPolicy,
+}
+
+// missing objects and bad credentials will not fix themselves
+fn retryable(e: &object_store::Error) -> bool
+{
+	!matches!(
+		e,
+		object_store::Error::NotFound { .. }
+			| object_store::Error::PermissionDenied { .. }
+			| object_store::Error::Unauthenticated { .. }
+			| object_store::Error::NotSupported { .. }
+	)
+}
+
+// Caches such as DiskCache read and write files, so they are asked on the blocking pool
+// rather than a runtime thread. A cache that panics counts as a miss.
+async fn cache_get(
+	cache: Option<&Arc<dyn RangeCache>>,
+	url: &str,
+	range: Range<u64>,
+) -> Option<Bytes>
+{
+	let (cache, url) = (Arc::clone(cache?), url.to_string());
+
+	tokio::task::spawn_blocking(move || cache.get(&url, &range)).await.ok().flatten()
+}
+
+impl ObjectStoreReader
+{
+	pub async fn open(url: &str, options: ObjectStoreOptions) -> error::Result<Self>
+	{
+		let (store, path) = Self::parse(url, &options)?;
+		let meta = options
+			.retry_policy
+			.run(url, || store.head(&path), retryable)
+			.await?;
+
+		Ok(Self {
+			url: url.to_string(),
+			store,
+			path,
+			size: meta.size,
+			position: 0,
+			chunk_size: options.chunk_size as u64,
+			buffer: Bytes::new(),
+			buffer_start: 0,
+			pending: None,
+			cache: options.cache,
+			retry_policy: options.retry_policy,
+		})
+	}
+
+	// looks for an index next to the data object, None when there isn't one
+	pub async fn open_index(url: &str, options: ObjectStoreOptions) -> error::Result<Option<Self>>
+	{
+		Self::open_index_with(url, &IndexSearch::default(), options).await
+	}
+
+	// the first of search's candidates for url that exists
+	pub async fn open_index_with(
+		url: &str,
+		search: &IndexSearch,
+		options: ObjectStoreOptions,
+	) -> error::Result<Option<Self>>
+	{
+		// a CSI index would only fail later in tabix::Reader, so another candidate is tried
+		for candidate in search.candidates(url)
+		{
+			if IndexFormat::from_name(&candidate) == Some(IndexFormat::Csi)
+			{
+				continue;
+			}
+
+			match Self::open(&candidate, options.clone()).await
+			{
+				Ok(reader) => return Ok(Some(reader)),
+				Err(error::Error::ObjectStore(object_store::Error::NotFound { .. })) => (),
+				Err(e) => return Err(e),
+			}
+		}
+
+		Ok(None)
+	}
+
+	// a reader over the same object from its start, sharing the store client and cache
+	pub fn try_clone(&self) -> Self
+	{
+		Self {
+			url: self.url.clone(),
+			store: Arc::clone(&self.store),
+			path: self.path.clone(),
+			size: self.size,
+			position: 0,
+			chunk_size: self.chunk_size,
+			buffer: Bytes::new(),
+			buffer_start: 0,
+			pending: None,
+			cache: self.cache.clone(),
+			retry_policy: self.retry_policy,
+		}
+	}
+
+	pub fn url(&self) -> &str
+	{
+		&self.url
+	}
+
+	pub fn size(&self) -> u64
+	{
+		self.size
+	}
+
+	pub fn cache_stats(&self) -> Option<CacheStats>
+	{
+		self.cache.as_ref().map(|cache| cache.stats())
+	}
+
+	fn parse(
+		url: &str,
+		options: &ObjectStoreOptions,
+	) -> error::Result<(Arc<dyn ObjectStore>, ObjectPath)>
+	{
+		let mut parsed = Url::parse(url).map_err(|_| error::Error::InvalidUrl(url.to_string()))?;
+		let mut config = options.config.clone();
+
+		if options.anonymous
+		{
+			match parsed.scheme()
+			{
+				"s3" | "s3a" => config.push(("aws_skip_signature".into(), "true".into())),
+				"gs" =>
+				{
+					// public buckets are served over plain https without credentials
+					let bucket = parsed
+						.host_str()
+						.ok_or_else(|| error::Error::InvalidUrl(url.to_string()))?
+						.to_string();
+					parsed = Url::parse(&format!(
+						"https://storage.googleapis.com/{}{}",
+						bucket,
+						parsed.path()
+					))
+					.map_err(|_| error::Error::InvalidUrl(url.to_string()))?;
+				}
+				_ =>
+				{}
+			}
+		}
+
+		let (store, path) = object_store::parse_url_opts(&parsed, config)?;
+
+		Ok((Arc::from(store), path))
+	}
+
+	fn buffer_offset(&self) -> Option<usize>
+	{
+		let buffer_end = self.buffer_start + self.buffer.len() as u64;
+
+		if self.position >= self.buffer_start && self.position < buffer_end
+		{
+			Some((self.position - self.buffer_start) as usize)
+		}
+		else
+		{
+			None
+		}
+	}
+}
+
+impl AsyncRead for ObjectStoreReader
+{
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>>
+	{
+		let this = &mut *self;
+
+		loop
+		{
+			if let Some(offset) = this.buffer_offset()
+			{
+				let available = &this.buffer[offset..];
+				let n = available.len().min(buf.remaining());
+				buf.put_slice(&available[..n]);
+				this.position += n as u64;
+				return Poll::Ready(Ok(()));
+			}
+
+			if this.position >= this.size
+			{
+				return Poll::Ready(Ok(()));
+			}
+
+			if this.pending.is_none()
+			{
+				// chunk aligned so repeated queries hit the same cache keys
+				let start = this.position - this.position % this.chunk_size;
+				let end = (start + this.chunk_size).min(this.size);
+				let store = this.store.clone();
+				let path = this.path.clone();
+				let cache = this.cache.clone();
+				let url = this.url.clone();
+				let retry_policy = this.retry_policy;
+
+				let handle = tokio::spawn(async move {
+					if let Some(bytes) = cache_get(cache.as_ref(), &url, start..end).await
+					{
+						return error::Result::Ok(bytes);
+					}
+
+					let bytes = retry_policy
+						.run(&url, || store.get_range(&path, start..end), retryable)
+						.await?;
+
+					if let Some(cache) = cache
+					{
+						// the chunk is handed back without waiting for the cache to store it
+						let cached = bytes.clone();
+						tokio::task::spawn_blocking(move || cache.put(&url, start..end, cached));
+					}
+
+					error::Result::Ok(bytes)
+				});
+				this.pending = Some((start, handle));
+			}
+
+			let (start, handle) = this.pending.as_mut().expect("pending fetch");
+			let start = *start;
+
+			let fetched = ready!(Pin::new(handle).poll(cx));
+			this.pending = None;
+
+			let bytes = fetched.map_err(io::Error::other)?.map_err(io::Error::other)?;
+
+			if bytes.is_empty()
+			{
+				return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+			}
+
+			this.buffer = bytes;
+			this.buffer_start = start;
+		}
+	}
+}
+
+impl AsyncSeek for ObjectStoreReader
+{
+	fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()>
+	{
+		let this = &mut *self;
+
+		let target = match position
+		{
+			SeekFrom::Start(n) => Some(n),
+			SeekFrom::End(n) => this.size.checked_add_signed(n),
+			SeekFrom::Current(n) => this.position.checked_add_signed(n),
+		}
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+
+		if let Some((start, handle)) = this.pending.take()
+		{
+			// an in-flight fetch of the same chunk is still useful
+			if start == target - target % this.chunk_size
+			{
+				this.pending = Some((start, handle));
+			}
+			else
+			{
+				handle.abort();
+			}
+		}
+
+		this.position = target;
+		Ok(())
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>>
+	{
+		Poll::Ready(Ok(self.position))
+	}
+}
@@ -0,0 +1,103 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error;
+
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// Per-operation timeout and retry settings. With no timeout set operations wait forever,
+// which is the behaviour readers had before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy
+{
+	pub timeout: Option<Duration>,
+	pub retries: u32,
+	pub backoff: Duration,
+}
+
+impl Default for RetryPolicy
+{
+	fn default() -> Self
+	{
+		Self {
+			timeout: None,
+			retries: 0,
+			backoff: DEFAULT_BACKOFF,
+		}
+	}
+}
+
+impl RetryPolicy
+{
+	pub fn with_timeout(mut self, timeout: Duration) -> Self
+	{
+		self.timeout = Some(timeout);
+		self
+	}
+
+	pub fn with_retries(mut self, retries: u32) -> Self
+	{
+		self.retries = retries;
+		self
+	}
+
+	pub fn with_backoff(mut self, backoff: Duration) -> Self
+	{
+		self.backoff = backoff;
+		self
+	}
+
+	// doubles per attempt, attempt starts at 1
+	pub fn backoff_for(&self, attempt: u32) -> Duration
+	{
+		self.backoff
+			.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+			.min(MAX_BACKOFF)
+	}
+
+	// Runs op until it succeeds, fails with an error retryable rejects, or runs out of
+	// attempts. Each attempt is bounded by the timeout.
+	pub async fn run<F, Fut, T, E>(
+		&self,
+		what: &str,
+		mut op: F,
+		retryable: impl Fn(&E) -> bool,
+	) -> error::Result<T>
+	where
+		F: FnMut() -> Fut,
+		Fut: Future<Output = Result<T, E>>,
+		E: Into<error::Error>,
+	{
+		let mut attempt = 0;
+
+		loop
+		{
+			let result = match self.timeout
+			{
+				Some(timeout) => tokio::time::timeout(timeout, op()).await.ok(),
+				None => Some(op().await),
+			};
+
+			let retry = match &result
+			{
+				Some(Ok(_)) => false,
+				Some(Err(e)) => retryable(e),
+				None => true,
+			};
+
+			if !retry || attempt >= self.retries
+			{
+				return match result
+				{
+					Some(result) => result.map_err(Into::into),
+					None => Err(error::Error::Timeout(what.to_string())),
+				};
+			}
+
+			attempt += 1;
+			log::debug!("{}: retrying, attempt {}", what, attempt);
+			tokio::time::sleep(self.backoff_for(attempt)).await;
+		}
+	}
+}
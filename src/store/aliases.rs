@@ -0,0 +1,206 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error;
+use crate::store::{TidLookup, TidResolver};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChrPrefix
+{
+	// 1 as chr1 and MT as chrM, UCSC style from Ensembl or NCBI style
+	Add,
+	// chr1 as 1 and chrM as MT
+	Strip,
+}
+
+// Contig names from one naming to another, an explicit alias before the chr prefix rule.
+// Names neither changes are kept as they are unless with_drop_unmapped is set.
+#[derive(Debug, Clone, Default)]
+pub struct ChromAliases
+{
+	names: HashMap<String, String>,
+	prefix: Option<ChrPrefix>,
+	drop_unmapped: bool,
+}
+
+impl ChromAliases
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	pub fn with_alias(mut self, from: impl Into<String>, to: impl Into<String>) -> Self
+	{
+		self.names.insert(from.into(), to.into());
+		self
+	}
+
+	pub fn with_add_chr(mut self) -> Self
+	{
+		self.prefix = Some(ChrPrefix::Add);
+		self
+	}
+
+	pub fn with_strip_chr(mut self) -> Self
+	{
+		self.prefix = Some(ChrPrefix::Strip);
+		self
+	}
+
+	// names without an alias dropped by the transform rather than kept
+	pub fn with_drop_unmapped(mut self, drop_unmapped: bool) -> Self
+	{
+		self.drop_unmapped = drop_unmapped;
+		self
+	}
+
+	// Whitespace separated from and to names a line, as UCSC's chromAlias.txt has them with
+	// any columns after ignored. Blank lines and # comments are skipped.
+	pub fn parse(name: &str, text: &[u8]) -> error::Result<Self>
+	{
+		let mut aliases = Self::default();
+
+		for line in text.split(|&b| b == b'\n')
+		{
+			let invalid = || {
+				let line = String::from_utf8_lossy(line).into_owned();
+				error::Error::AliasFormat(name.to_string(), line)
+			};
+
+			let line = std::str::from_utf8(line).map_err(|_| invalid())?.trim();
+
+			if line.is_empty() || line.starts_with('#')
+			{
+				continue;
+			}
+
+			let mut fields = line.split_whitespace();
+
+			let (Some(from), Some(to)) = (fields.next(), fields.next())
+			else
+			{
+				return Err(invalid());
+			};
+
+			aliases.names.insert(from.to_string(), to.to_string());
+		}
+
+		Ok(aliases)
+	}
+
+	pub async fn from_reader<R>(name: &str, mut reader: R) -> error::Result<Self>
+	where
+		R: AsyncRead + std::marker::Unpin,
+	{
+		let mut text = Vec::new();
+		reader.read_to_end(&mut text).await?;
+
+		Self::parse(name, &text)
+	}
+
+	#[cfg(feature = "fs")]
+	pub async fn from_path<P>(path: P) -> error::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
+		let text = tokio::fs::read(path.as_ref()).await?;
+
+		Self::parse(&path.as_ref().display().to_string(), &text)
+	}
+
+	// the name tid goes by, None for one with no alias when unmapped names are dropped
+	pub fn rename<'a>(&self, tid: &'a str) -> Option<Cow<'a, str>>
+	{
+		if let Some(to) = self.names.get(tid)
+		{
+			return Some(Cow::Owned(to.clone()));
+		}
+
+		match self.prefix
+		{
+			Some(ChrPrefix::Add) if tid == "MT" => Some(Cow::Borrowed("chrM")),
+			Some(ChrPrefix::Add) if tid.starts_with("chr") => Some(Cow::Borrowed(tid)),
+			Some(ChrPrefix::Add) => Some(Cow::Owned(format!("chr{tid}"))),
+			Some(ChrPrefix::Strip) if tid == "chrM" => Some(Cow::Borrowed("MT")),
+			Some(ChrPrefix::Strip) => Some(Cow::Borrowed(tid.strip_prefix("chr").unwrap_or(tid))),
+			None if self.drop_unmapped => None,
+			None => Some(Cow::Borrowed(tid)),
+		}
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.names.is_empty() && self.prefix.is_none()
+	}
+}
+
+// Renames contigs as a reader interns them, so records come out under the other naming.
+// Given to a reader through ReaderOptions::with_interner, and region queries name contigs
+// as the file does. Names are always kept here, with_drop_unmapped only affects transforms.
+#[derive(Debug, Clone, Default)]
+pub struct AliasResolver<T>
+{
+	inner: T,
+	aliases: ChromAliases,
+}
+
+impl<T> AliasResolver<T>
+{
+	pub fn new(inner: T, aliases: ChromAliases) -> Self
+	{
+		Self { inner, aliases }
+	}
+
+	pub fn aliases(&self) -> &ChromAliases
+	{
+		&self.aliases
+	}
+
+	pub fn into_inner(self) -> T
+	{
+		self.inner
+	}
+
+	fn renamed<'a>(&self, input: &'a str) -> Cow<'a, str>
+	{
+		self.aliases.rename(input).unwrap_or(Cow::Borrowed(input))
+	}
+}
+
+impl<T> TidLookup for AliasResolver<T>
+where
+	T: TidLookup,
+{
+	type Tid = T::Tid;
+
+	fn find(&self, input: &str) -> Option<Self::Tid>
+	{
+		self.inner.find(&self.renamed(input))
+	}
+
+	fn from_symbol_id<'a>(&'a self, input: &'a Self::Tid) -> Option<&'a str>
+	{
+		self.inner.from_symbol_id(input)
+	}
+}
+
+impl<T> TidResolver for AliasResolver<T>
+where
+	T: TidResolver,
+{
+	fn to_symbol_id(&mut self, input: &str) -> Self::Tid
+	{
+		let name = self.renamed(input);
+		self.inner.to_symbol_id(&name)
+	}
+
+	fn dummy_tid(&mut self) -> Self::Tid
+	{
+		self.inner.dummy_tid()
+	}
+}
@@ -1,7 +1,10 @@
+mod aliases;
 pub mod interning;
 
 use std::fmt::Debug;
 
+pub use crate::store::aliases::{AliasResolver, ChrPrefix, ChromAliases};
+
 #[cfg(feature = "interning")]
 pub use crate::store::interning::*;
 
@@ -11,18 +14,29 @@ pub type DefaultTid = string_interner::DefaultSymbol;
 #[cfg(not(feature = "interning"))]
 pub type DefaultTid = String;
 
-pub trait TidResolver
+#[cfg(feature = "interning")]
+pub type DefaultResolver = TidStore;
+
+#[cfg(not(feature = "interning"))]
+pub type DefaultResolver = ();
+
+// read half, only ever needs shared access to the store
+pub trait TidLookup
 {
 	type Tid: Clone + Debug + Send + Sync + PartialEq + Eq + Ord + PartialOrd;
 
 	fn find(&self, input: &str) -> Option<Self::Tid>;
-	fn to_symbol_id(&mut self, input: &str) -> Self::Tid;
 	fn from_symbol_id<'a>(&'a self, input: &'a Self::Tid) -> Option<&'a str>;
+}
+
+// write half, interning new names
+pub trait TidResolver: TidLookup
+{
+	fn to_symbol_id(&mut self, input: &str) -> Self::Tid;
 	fn dummy_tid(&mut self) -> Self::Tid;
 }
 
-#[cfg(not(feature = "interning"))]
-impl TidResolver for ()
+impl TidLookup for ()
 {
 	type Tid = String;
 
@@ -31,14 +45,17 @@ impl TidResolver for ()
 		Some(input.to_owned())
 	}
 
-	fn to_symbol_id(&mut self, input: &str) -> Self::Tid
+	fn from_symbol_id<'a>(&'a self, input: &'a Self::Tid) -> Option<&'a str>
 	{
-		input.to_owned()
+		Some(input)
 	}
+}
 
-	fn from_symbol_id<'a>(&'a self, input: &'a Self::Tid) -> Option<&'a str>
+impl TidResolver for ()
+{
+	fn to_symbol_id(&mut self, input: &str) -> Self::Tid
 	{
-		Some(input)
+		input.to_owned()
 	}
 
 	fn dummy_tid(&mut self) -> Self::Tid
@@ -48,7 +65,7 @@ impl TidResolver for ()
 }
 
 #[cfg(feature = "interning")]
-impl TidResolver for TidStore
+impl TidLookup for TidStore
 {
 	type Tid = string_interner::DefaultSymbol;
 
@@ -57,14 +74,18 @@ impl TidResolver for TidStore
 		self.find(input)
 	}
 
-	fn to_symbol_id(&mut self, input: &str) -> Self::Tid
+	fn from_symbol_id<'a>(&'a self, input: &'a Self::Tid) -> Option<&'a str>
 	{
-		self.intern(input)
+		self.resolve(input)
 	}
+}
 
-	fn from_symbol_id<'a>(&'a self, input: &'a Self::Tid) -> Option<&'a str>
+#[cfg(feature = "interning")]
+impl TidResolver for TidStore
+{
+	fn to_symbol_id(&mut self, input: &str) -> Self::Tid
 	{
-		self.resolve(input)
+		self.intern(input)
 	}
 
 	fn dummy_tid(&mut self) -> Self::Tid
@@ -0,0 +1,168 @@
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "fs")]
+use tokio::io::BufReader as TokioBufReader;
+
+#[cfg(feature = "fs")]
+use pufferfish::prelude::*;
+
+// extensions stripped from a name for the {stem} form of a pattern when the content cannot
+// be looked at, as with URLs
+const COMPRESSED_EXTENSIONS: [&str; 3] = [".gz", ".bgz", ".bgzf"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat
+{
+	Tabix,
+	Csi,
+}
+
+impl IndexFormat
+{
+	// by extension, for names found by IndexSearch
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		if name.ends_with(".tbi")
+		{
+			Some(Self::Tabix)
+		}
+		else if name.ends_with(".csi")
+		{
+			Some(Self::Csi)
+		}
+		else
+		{
+			None
+		}
+	}
+}
+
+// Where to look for the index of a data file. Patterns name a candidate with {path} for
+// the data file name as given and {stem} for it without its compression extension, so
+// "{stem}.tbi" finds regions.tbi next to regions.gz. Candidates are tried in order, an
+// explicit index first when one is set. CSI indexes cannot be read yet, so the default
+// patterns only look for .tbi ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSearch
+{
+	index: Option<String>,
+	patterns: Vec<String>,
+}
+
+impl Default for IndexSearch
+{
+	fn default() -> Self
+	{
+		Self {
+			index: None,
+			patterns: ["{path}.tbi", "{stem}.tbi"].map(String::from).to_vec(),
+		}
+	}
+}
+
+impl IndexSearch
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	// an index whose name has nothing to do with the data file, such as one downloaded
+	// separately
+	pub fn with_index(mut self, index: impl Into<String>) -> Self
+	{
+		self.index = Some(index.into());
+		self
+	}
+
+	pub fn with_patterns<I, S>(mut self, patterns: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		self.patterns = patterns.into_iter().map(Into::into).collect();
+		self
+	}
+
+	// every name to try for data, in order and without repeats. Works on URLs as well as
+	// paths since only the end of the name is touched.
+	pub fn candidates(&self, data: &str) -> Vec<String>
+	{
+		let stem = COMPRESSED_EXTENSIONS
+			.iter()
+			.find_map(|extension| data.strip_suffix(extension));
+
+		self.candidates_with_stem(data, stem)
+	}
+
+	fn candidates_with_stem(&self, data: &str, stem: Option<&str>) -> Vec<String>
+	{
+		let mut candidates: Vec<String> = self.index.iter().cloned().collect();
+
+		for pattern in &self.patterns
+		{
+			let candidate = match stem
+			{
+				Some(stem) => pattern.replace("{stem}", stem),
+				None if pattern.contains("{stem}") => continue,
+				None => pattern.clone(),
+			}
+			.replace("{path}", data);
+
+			if !candidates.contains(&candidate)
+			{
+				candidates.push(candidate);
+			}
+		}
+
+		candidates
+	}
+
+	// The first candidate that exists on disk. Only BGZF data can be indexed, which is told
+	// from the content, so any extension of a BGZF file counts as its compression
+	// extension and plain text never has an index.
+	#[cfg(feature = "fs")]
+	pub async fn find<P>(&self, data: P) -> Option<PathBuf>
+	where
+		P: AsRef<Path>,
+	{
+		self.find_all(data).await.into_iter().next()
+	}
+
+	// as find, every candidate that exists in order, for callers skipping ones they cannot read
+	#[cfg(feature = "fs")]
+	pub async fn find_all<P>(&self, data: P) -> Vec<PathBuf>
+	where
+		P: AsRef<Path>,
+	{
+		let Ok(file) = tokio::fs::File::open(data.as_ref()).await
+		else
+		{
+			return Vec::new();
+		};
+
+		if !TokioBufReader::new(file).is_bgz().await
+		{
+			return Vec::new();
+		}
+
+		let data = data.as_ref().to_string_lossy();
+		let stem = data
+			.rsplit_once('.')
+			.filter(|(_, extension)| !extension.contains(['/', '\\']))
+			.map(|(stem, _)| stem);
+
+		let mut found = Vec::new();
+
+		for candidate in self.candidates_with_stem(&data, stem)
+		{
+			if tokio::fs::try_exists(&candidate).await.unwrap_or(false)
+			{
+				found.push(PathBuf::from(candidate));
+			}
+		}
+
+		found
+	}
+}
@@ -1,10 +1,12 @@
 use std::io::Cursor;
+#[cfg(feature = "fs")]
 use std::path::Path;
-use std::collections::HashMap;
 use std::str::FromStr;
-use std::ops::Range;
+use std::ops::{Deref, Range};
 use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
 
+#[cfg(feature = "fs")]
 use tokio::fs::File as TokioFile;
 use tokio::io::{AsyncRead, AsyncSeek, BufReader as TokioBufReader};
 
@@ -13,6 +15,15 @@ use byteorder::{LittleEndian, ReadBytesExt};
 use pufferfish::prelude::*;
 
 use crate::error;
+use crate::error::{Context, ResultExt};
+use crate::region::Position;
+use crate::region::Region as QueryRegion;
+
+mod discover;
+
+pub use discover::{IndexFormat, IndexSearch};
+
+const METADATA_BIN: u64 = 37450;
 
 #[derive(Debug)]
 pub struct Header
@@ -25,16 +36,54 @@ pub struct Header
 	pub skip: i32,
 }
 
+// Bins flattened into one chunk array, bin i's chunks being
+// chunks[bin_starts[i]..bin_starts[i + 1]] and bins sorted so lookups are a binary search.
 #[derive(Debug)]
-pub struct Region
+pub struct Reference
 {
-	pub chunks: Vec<Range<u64>>,
+	bins: Vec<u32>,
+	bin_starts: Vec<u32>,
+	chunks: Vec<Range<u64>>,
+	// from the metadata pseudo-bin, which indexers such as htslib write but do not require
+	pub metadata: Option<ReferenceMetadata>,
 }
 
-#[derive(Debug)]
-pub struct Reference
+impl Reference
+{
+	pub fn bin(&self, bin: u64) -> Option<&[Range<u64>]>
+	{
+		let position = self.bins.binary_search(&u32::try_from(bin).ok()?).ok()?;
+		Some(self.bin_chunks(position))
+	}
+
+	// every bin with its chunks, in bin order
+	pub fn bins(&self) -> impl Iterator<Item = (u64, &[Range<u64>])>
+	{
+		self.bins
+			.iter()
+			.enumerate()
+			.map(|(position, bin)| (*bin as u64, self.bin_chunks(position)))
+	}
+
+	// the chunks of every bin
+	pub fn chunks(&self) -> &[Range<u64>]
+	{
+		&self.chunks
+	}
+
+	fn bin_chunks(&self, position: usize) -> &[Range<u64>]
+	{
+		&self.chunks[self.bin_starts[position] as usize..self.bin_starts[position + 1] as usize]
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct ReferenceMetadata
 {
-	pub bins: HashMap<u64, Region>,
+	// virtual offsets of the first and past the last record
+	pub offsets: Range<u64>,
+	pub n_mapped: u64,
+	pub n_unmapped: u64,
 }
 
 #[derive(Debug)]
@@ -47,57 +96,423 @@ pub struct Block
 	pub tids: Vec<usize>,
 }
 
+// What a region query will read, worked out from the index alone. A chunk's last block is
+// counted at the BGZF maximum since the index only records where blocks start.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryPlan
+{
+	// merged and sorted by start, as the query reads them
+	pub chunks: Vec<Range<u64>>,
+	// an upper bound on the compressed bytes read
+	pub compressed_bytes: u64,
+	// blocks the chunks start or end in, a lower bound as blocks between them are not indexed
+	pub blocks: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractOptions
+{
+	// report offsets a well formed index cannot produce instead of clamping them, for fuzzing
+	// and index validation
+	pub strict_offsets: bool,
+}
+
+impl ExtractOptions
+{
+	pub fn with_strict_offsets(mut self, strict_offsets: bool) -> Self
+	{
+		self.strict_offsets = strict_offsets;
+		self
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadOptions
+{
+	// parse only the header and sequence names up front and each reference's bins on its
+	// first query, for indexes over assemblies with many scaffolds
+	pub lazy: bool,
+}
+
+impl LoadOptions
+{
+	pub fn with_lazy(mut self, lazy: bool) -> Self
+	{
+		self.lazy = lazy;
+		self
+	}
+}
+
 #[derive(Debug)]
-pub struct Reader
+pub struct Index
 {
 	pub header: Header,
 
 	pub seqnames: Vec<String>,
-	pub ref_indices: Vec<Reference>,
+	references: Vec<OnceLock<Reference>>,
+	// the decompressed index and where each reference's tables lie in it, kept when lazy
+	raw: Vec<u8>,
+	spans: Vec<Range<usize>>,
+}
+
+// A parsed index behind an Arc, so clones are cheap and one parse can serve every reader
+// of a file. Queries are on Index, which Reader derefs to.
+#[derive(Debug, Clone)]
+pub struct Reader
+{
+	index: Arc<Index>,
+}
+
+impl Deref for Reader
+{
+	type Target = Index;
+
+	fn deref(&self) -> &Index
+	{
+		&self.index
+	}
 }
 
 impl Reader
 {
+	pub fn from_index(index: Arc<Index>) -> Self
+	{
+		Self { index }
+	}
+
+	pub fn index(&self) -> &Arc<Index>
+	{
+		&self.index
+	}
+
+	#[cfg(feature = "fs")]
 	pub async fn from_path<P>(path: P) -> error::Result<Self>
 	where
 		P: AsRef<Path> + std::marker::Copy,
 	{
-		let tabix_file = TokioFile::open(path).await?;
-		Self::from_reader(tabix_file).await
+		Self::from_path_with(path, &LoadOptions::default()).await
+	}
+
+	#[cfg(feature = "fs")]
+	pub async fn from_path_with<P>(path: P, options: &LoadOptions) -> error::Result<Self>
+	where
+		P: AsRef<Path> + std::marker::Copy,
+	{
+		let context = || {
+			Context::new("tabix header").with_file(path.as_ref().display().to_string())
+		};
+
+		let tabix_file = TokioFile::open(path).await.context(context)?;
+		Self::from_reader_with(tabix_file, options).await.context(context)
+	}
+
+	// the first index search finds for data that can be read, None when there is none. A
+	// CSI index is passed over for a tabix one further down the list.
+	#[cfg(feature = "fs")]
+	pub async fn for_data_path<P>(data: P, search: &IndexSearch) -> error::Result<Option<Self>>
+	where
+		P: AsRef<Path>,
+	{
+		for index in search.find_all(data).await
+		{
+			match Self::from_path(&index).await
+			{
+				Ok(reader) => return Ok(Some(reader)),
+				Err(e) if matches!(e.root(), error::Error::UnsupportedIndex(_)) => (),
+				Err(e) => return Err(e),
+			}
+		}
+
+		Ok(None)
 	}
 
 	pub async fn from_reader<R>(reader: R) -> error::Result<Self>
+	where
+		R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
+	{
+		Self::from_reader_with(reader, &LoadOptions::default()).await
+	}
+
+	#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+	pub async fn from_reader_with<R>(reader: R, options: &LoadOptions) -> error::Result<Self>
 	where
 		R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
 	{
 		let mut async_reader = TokioBufReader::new(reader);
 
-		let (header, seqnames, ref_indices) = Self::read_tabix(&mut async_reader).await?;
+		let index = Self::read_tabix(&mut async_reader, options).await?;
+
+		#[cfg(feature = "tracing")]
+		tracing::debug!(
+			references = index.seqnames.len(),
+			lazy = options.lazy,
+			"loaded tabix index"
+		);
 
-		Ok(Reader {
-			header,
+		Ok(Self::from_index(Arc::new(index)))
+	}
+
+	async fn read_tabix<R>(
+		reader: &mut TokioBufReader<R>,
+		options: &LoadOptions,
+	) -> error::Result<Index>
+	where
+		R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
+	{
+		let mut bytes = Vec::new();
+		loop
+		{
+			match reader
+				.read_and_decompress_bgzf_block(Some(is_bgzf_eof))
+				.await?
+			{
+				Some(block) =>
+				{
+					bytes.extend_from_slice(&block);
+				}
+				None => break,
+			};
+		}
+
+		let mut cursor = Cursor::new(bytes.as_slice());
+
+		let mut magic = [0u8; 4];
+		std::io::Read::read_exact(&mut cursor, &mut magic)?;
+
+		match &magic
+		{
+			b"TBI\x01" => (),
+			b"CSI\x01" => return Err(error::Error::UnsupportedIndex("CSI".into())),
+			_ => return Err(error::Error::TabixFormat("index".into())),
+		}
+
+		let n_ref = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let _ = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let col_seq = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let col_beg = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let col_end = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let meta = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let skip = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+		let l_nm = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+
+		// checked before allocating, a damaged length could otherwise ask for gigabytes
+		if l_nm < 0 || capacity(&cursor, l_nm, 1) < l_nm as usize
+		{
+			return Err(error::Error::TabixFormat("index".into()));
+		}
+
+		let mut seqnames = vec![0u8; l_nm as usize];
+		std::io::Read::read_exact(&mut cursor, &mut seqnames)?;
+
+		// names have to match the BED chrom column exactly, so no lossy fallback here
+		let seqnames = String::from_utf8(seqnames).map_err(|e| {
+			error::Error::InvalidUtf8("tabix sequence names".into(), e.utf8_error().valid_up_to())
+		})?;
+		let seqnames = seqnames
+			.split("\0")
+			.filter(|seqname| seqname != &"")
+			.map(|seqname| String::from_str(seqname).unwrap())
+			.collect::<Vec<_>>();
+
+		let mut references = Vec::with_capacity(capacity(&cursor, n_ref, 8));
+		let mut spans = Vec::new();
+
+		for _ in 0..n_ref
+		{
+			if options.lazy
+			{
+				// walked rather than parsed, so a short or damaged index still fails here
+				let start = cursor.position() as usize;
+				skip_reference(&mut cursor)?;
+				spans.push(start..cursor.position() as usize);
+				references.push(OnceLock::new());
+			}
+			else
+			{
+				references.push(OnceLock::from(read_reference(&mut cursor)?));
+			}
+		}
+
+		let raw = if options.lazy { bytes } else { Vec::new() };
+
+		Ok(Index {
+			header: Header {
+				n_ref,
+				col_seq,
+				col_beg,
+				col_end,
+				meta,
+				skip,
+			},
 			seqnames,
-			ref_indices,
+			references,
+			raw,
+			spans,
 		})
 	}
+}
+
+fn read_reference(cursor: &mut Cursor<&[u8]>) -> error::Result<Reference>
+{
+	let n_bin = ReadBytesExt::read_i32::<LittleEndian>(cursor)?;
+
+	// each bin with the range of its chunks, in file order
+	let mut entries: Vec<(u32, Range<usize>)> = Vec::with_capacity(capacity(cursor, n_bin, 8));
+	let mut chunks = Vec::new();
+	let mut metadata = None;
+
+	for _ in 0..n_bin
+	{
+		let bin = ReadBytesExt::read_u32::<LittleEndian>(cursor)?;
+		let n_chunk = ReadBytesExt::read_i32::<LittleEndian>(cursor)?;
+
+		let first = chunks.len();
+
+		for _ in 0..n_chunk
+		{
+			let cnk_beg = ReadBytesExt::read_u64::<LittleEndian>(cursor)?;
+			let cnk_end = ReadBytesExt::read_u64::<LittleEndian>(cursor)?;
+
+			chunks.push(Range {
+				start: cnk_beg,
+				end: cnk_end,
+			});
+		}
+
+		// the pseudo-bin holds offsets and counts, not chunks, so it stays out of the bins
+		if bin as u64 == METADATA_BIN
+		{
+			let pseudo = chunks.split_off(first);
+
+			if let [offsets, counts] = pseudo.as_slice()
+			{
+				metadata = Some(ReferenceMetadata {
+					offsets: offsets.clone(),
+					n_mapped: counts.start,
+					n_unmapped: counts.end,
+				});
+			}
+
+			continue;
+		}
+
+		entries.push((bin, first..chunks.len()));
+	}
+
+	// indexers write bins in order, anything else is laid out again to match
+	if !entries.is_sorted_by_key(|(bin, _)| *bin)
+	{
+		entries.sort_by_key(|(bin, _)| *bin);
+
+		let mut sorted = Vec::with_capacity(chunks.len());
+
+		for (_, range) in &mut entries
+		{
+			let first = sorted.len();
+			sorted.extend_from_slice(&chunks[range.clone()]);
+			*range = first..sorted.len();
+		}
+
+		chunks = sorted;
+	}
+
+	chunks.shrink_to_fit();
+
+	let n_intv = ReadBytesExt::read_i32::<LittleEndian>(cursor)?;
+	skip_bytes(cursor, n_intv, 8)?;
+
+	Ok(Reference {
+		bins: entries.iter().map(|(bin, _)| *bin).collect(),
+		bin_starts: entries
+			.iter()
+			.map(|(_, range)| range.start as u32)
+			.chain(std::iter::once(chunks.len() as u32))
+			.collect(),
+		chunks,
+		metadata,
+	})
+}
+
+// the same walk as read_reference without building anything
+fn skip_reference(cursor: &mut Cursor<&[u8]>) -> error::Result<()>
+{
+	let n_bin = ReadBytesExt::read_i32::<LittleEndian>(cursor)?;
+
+	for _ in 0..n_bin
+	{
+		let _bin = ReadBytesExt::read_u32::<LittleEndian>(cursor)?;
+		let n_chunk = ReadBytesExt::read_i32::<LittleEndian>(cursor)?;
+		skip_bytes(cursor, n_chunk, 16)?;
+	}
+
+	let n_intv = ReadBytesExt::read_i32::<LittleEndian>(cursor)?;
+	skip_bytes(cursor, n_intv, 8)
+}
+
+// how many of count items, each at least size bytes, the rest of the index could hold
+fn capacity(cursor: &Cursor<&[u8]>, count: i32, size: u64) -> usize
+{
+	let remaining = (cursor.get_ref().len() as u64).saturating_sub(cursor.position());
+
+	(count.max(0) as u64).min(remaining / size) as usize
+}
+
+fn skip_bytes(cursor: &mut Cursor<&[u8]>, count: i32, size: u64) -> error::Result<()>
+{
+	let position = cursor.position() + count.max(0) as u64 * size;
+
+	if position > cursor.get_ref().len() as u64
+	{
+		return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+	}
+
+	cursor.set_position(position);
+	Ok(())
+}
+
+impl Index
+{
+	// the tables of the idx-th reference, parsed now if the index was loaded lazily
+	pub fn reference(&self, idx: usize) -> Option<&Reference>
+	{
+		let slot = self.references.get(idx)?;
+
+		if let Some(reference) = slot.get()
+		{
+			return Some(reference);
+		}
+
+		let mut cursor = Cursor::new(&self.raw[self.spans[idx].clone()]);
+		let reference = read_reference(&mut cursor)
+			.expect("reference tables are walked when the index is read");
+
+		Some(slot.get_or_init(|| reference))
+	}
+
+	fn reference_for(&self, tid: &str) -> Option<&Reference>
+	{
+		self.reference(self.seqnames.iter().position(|s| s == tid)?)
+	}
+
+	fn references(&self) -> impl Iterator<Item = &Reference>
+	{
+		(0..self.references.len()).filter_map(|idx| self.reference(idx))
+	}
 
 	pub fn block_plan(&self) -> Vec<Block>
 	{
 		let mut map: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
 
-		for (tid, reference) in self.ref_indices.iter().enumerate()
+		for (tid, reference) in self.references().enumerate()
 		{
-			for region in reference.bins.values()
+			for chunk in reference.chunks()
 			{
-				for chunk in &region.chunks
-				{
-					let start_block = chunk.start >> 16;
-					let end_block = chunk.end >> 16;
+				let start_block = chunk.start >> 16;
+				let end_block = chunk.end >> 16;
 
-					for block in start_block..=end_block
-					{
-						map.entry(block).or_default().push(tid);
-					}
+				for block in start_block..=end_block
+				{
+					map.entry(block).or_default().push(tid);
 				}
 			}
 		}
@@ -113,54 +528,169 @@ impl Reader
 
 	pub fn offsets_for_tid(&self, tid: &str) -> error::Result<Option<Vec<Range<u64>>>>
 	{
-		let Some(idx) = self.seqnames.iter().position(|s| s == tid)
+		let Some(index) = self.reference_for(tid)
 		else
 		{
 			return Ok(None); // chromosome missing
 		};
 
-		let index = &self.ref_indices[idx];
-
-		let mut chunks = Vec::new();
-		for bin_entry in index.bins.values()
-		{
-			chunks.extend_from_slice(&bin_entry.chunks);
-		}
-
-		Ok(Some(chunks))
+		Ok(Some(index.chunks().to_vec()))
 	}
 
+	// 0-based half-open. Ends past the tabix limit are clamped, starts past it are an error
+	// since a .tbi cannot index them.
 	pub fn offsets_for_tid_region(
 		&self,
 		tid: &str,
-		start: u64,
-		end: u64,
+		start: impl Into<Position>,
+		end: impl Into<Position>,
 	) -> error::Result<Option<Vec<Range<u64>>>>
 	{
-		let Some(idx) = self.seqnames.iter().position(|s| s == tid)
+		let (start, end) = (start.into(), end.into());
+
+		if start >= Position::TABIX_MAX
+		{
+			return Err(error::Error::PositionOutOfRange(start.get()));
+		}
+
+		let Some(index) = self.reference_for(tid)
 		else
 		{
 			return Ok(None); // chromosome missing
 		};
 
-		let index = &self.ref_indices[idx];
-
 		let mut chunks = Vec::new();
 
-		for bin in Self::region_bins(start, end)
+		for bin in Self::region_bins(start.get(), end.get().min(Position::TABIX_MAX.get()))
 		{
-			if let Some(region) = index.bins.get(&bin)
+			if let Some(bin_chunks) = index.bin(bin)
 			{
-				chunks.extend_from_slice(&region.chunks);
+				chunks.extend_from_slice(bin_chunks);
 			}
 		}
 
 		Ok(Some(chunks))
 	}
 
+	pub fn offsets_for_region(
+		&self,
+		region: &QueryRegion,
+	) -> error::Result<Option<Vec<Range<u64>>>>
+	{
+		self.offsets_for_tid_region(&region.tid, region.start, region.end)
+	}
+
+	// the chunks for a region sorted by start with overlapping chunks merged, so each BGZF
+	// block is read once. Chunks ending before they start are dropped, or an error when strict.
+	pub fn chunks_for_region(
+		&self,
+		region: &QueryRegion,
+		options: &ExtractOptions,
+	) -> error::Result<Option<Vec<Range<u64>>>>
+	{
+		let context = || Context::new("region query").with_region(region);
+
+		let Some(chunks) = self.offsets_for_region(region).context(context)?
+		else
+		{
+			return Ok(None);
+		};
+
+		Ok(Some(merge_chunks(chunks, options).context(context)?))
+	}
+
+	// the chunks a query for tid over start..end would read, None when tid is not indexed
+	pub fn plan_region_query(
+		&self,
+		tid: &str,
+		start: impl Into<Position>,
+		end: impl Into<Position>,
+	) -> error::Result<Option<QueryPlan>>
+	{
+		const MAX_BLOCK_SIZE: u64 = 65536;
+
+		let Some(chunks) = self.offsets_for_tid_region(tid, start, end)?
+		else
+		{
+			return Ok(None);
+		};
+
+		let chunks = merge_chunks(chunks, &ExtractOptions::default())?;
+
+		let mut blocks: Vec<u64> = chunks
+			.iter()
+			.flat_map(|chunk| [chunk.start >> 16, chunk.end >> 16])
+			.collect();
+		blocks.dedup();
+
+		let compressed_bytes = chunks
+			.iter()
+			.map(|chunk| (chunk.end >> 16) - (chunk.start >> 16) + MAX_BLOCK_SIZE)
+			.sum();
+
+		Ok(Some(QueryPlan {
+			blocks: blocks.len() as u64,
+			compressed_bytes,
+			chunks,
+		}))
+	}
+
+	// records on tid counted by the indexer, None when the index has no metadata for it
+	pub fn record_count(&self, tid: &str) -> Option<u64>
+	{
+		self.reference_for(tid)?
+			.metadata
+			.as_ref()
+			.map(|metadata| metadata.n_mapped)
+	}
+
+	// every sequence name with its record count, in index order
+	pub fn record_counts(&self) -> impl Iterator<Item = (&str, Option<u64>)>
+	{
+		self.seqnames.iter().zip(self.references()).map(|(tid, reference)| {
+			let count = reference.metadata.as_ref().map(|metadata| metadata.n_mapped);
+			(tid.as_str(), count)
+		})
+	}
+
+	// None unless every sequence has metadata, a partial sum would undercount
+	pub fn total_records(&self) -> Option<u64>
+	{
+		self.record_counts().map(|(_, count)| count).sum()
+	}
+
+	// 0-based half-open bounds that every record on tid lies within, from the bins alone.
+	// Records only fit the smallest bin enclosing them, so a long record near the start
+	// loosens the bounds to its bin. Readers' extent() scans for the exact answer.
+	pub fn extent_bounds(&self, tid: &str) -> Option<(u64, u64)>
+	{
+		self.reference_for(tid)?
+			.bins()
+			.filter_map(|(bin, _)| Self::bin_range(bin))
+			.reduce(|(start, end), (bin_start, bin_end)| (start.min(bin_start), end.max(bin_end)))
+	}
+
+	// the interval a bin covers, None for bin numbers past the last level
+	fn bin_range(bin: u64) -> Option<(u64, u64)>
+	{
+		const BIN_OFFSETS: [u64; 6] = [0, 1, 9, 73, 585, 4681];
+		const BIN_SHIFTS: [u64; 6] = [29, 26, 23, 20, 17, 14];
+		const MAX_BIN: u64 = 37449;
+
+		if bin > MAX_BIN
+		{
+			return None;
+		}
+
+		let level = BIN_OFFSETS.iter().rposition(|offset| *offset <= bin)?;
+		let start = (bin - BIN_OFFSETS[level]) << BIN_SHIFTS[level];
+
+		Some((start, start + (1 << BIN_SHIFTS[level])))
+	}
+
 	fn region_bins(start: u64, end: u64) -> Vec<u64>
 	{
-		const MAX_POS: u64 = 1 << 29; // maximum coordinate (512 Mb)
+		const MAX_POS: u64 = Position::TABIX_MAX.0; // maximum coordinate (512 Mb)
 		const BIN_OFFSETS: [u64; 6] = [0, 1, 9, 73, 585, 4681];
 
 		let mut bins = Vec::new();
@@ -211,111 +741,89 @@ impl Reader
 
 		bins
 	}
+}
 
-	async fn read_tabix<R>(
-		reader: &mut TokioBufReader<R>,
-	) -> error::Result<(Header, Vec<String>, Vec<Reference>)>
-	where
-		R: AsyncRead + AsyncSeek + std::marker::Send + std::marker::Unpin,
+pub fn merge_chunks(
+	mut chunks: Vec<Range<u64>>,
+	options: &ExtractOptions,
+) -> error::Result<Vec<Range<u64>>>
+{
+	if options.strict_offsets
 	{
-		let mut bytes = Vec::new();
-		loop
+		if let Some(chunk) = chunks.iter().find(|chunk| chunk.start > chunk.end)
 		{
-			match reader
-				.read_and_decompress_bgzf_block(Some(is_bgzf_eof))
-				.await?
-			{
-				Some(block) =>
-				{
-					bytes.extend_from_slice(&block);
-				}
-				None => break,
-			};
+			return Err(error::Error::IndexOffset(format!(
+				"chunk {:#x}..{:#x} ends before it starts",
+				chunk.start, chunk.end
+			)));
 		}
+	}
 
-		let mut cursor = Cursor::new(bytes);
-
-		let mut magic = [0u8; 4];
-		std::io::Read::read_exact(&mut cursor, &mut magic)?;
-
-		//if magic != r"TBI\1"
-		// {
-		// 	bail!("Not a tabix file");
-		// }
-
-		let n_ref = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let _ = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let col_seq = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let col_beg = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let col_end = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let meta = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let skip = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-		let l_nm = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-
-		let mut seqnames = vec![0u8; l_nm as usize];
-		std::io::Read::read_exact(&mut cursor, &mut seqnames)?;
-
-		let seqnames = unsafe { std::str::from_utf8_unchecked(&seqnames).to_string() };
-		let seqnames = seqnames
-			.split("\0")
-			.filter(|seqname| seqname != &"")
-			.map(|seqname| String::from_str(seqname).unwrap())
-			.collect::<Vec<_>>();
+	chunks.retain(|chunk| chunk.start <= chunk.end);
+	chunks.sort_by_key(|chunk| chunk.start);
 
-		let mut ref_indices = Vec::with_capacity(n_ref as usize);
+	let mut merged: Vec<Range<u64>> = Vec::with_capacity(chunks.len());
 
-		for _ in 0..n_ref
+	for chunk in chunks
+	{
+		match merged.last_mut()
 		{
-			let n_bin = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-
-			let mut bins_map = HashMap::with_capacity(n_bin as usize);
-
-			for _ in 0..n_bin
-			{
-				let bin = ReadBytesExt::read_u32::<LittleEndian>(&mut cursor)? as u64;
-				let n_chunk = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
-
-				let mut chunks = Vec::with_capacity(n_chunk as usize);
+			Some(last) if chunk.start <= last.end => last.end = last.end.max(chunk.end),
+			_ => merged.push(chunk),
+		}
+	}
 
-				for _ in 0..n_chunk
-				{
-					let cnk_beg = ReadBytesExt::read_u64::<LittleEndian>(&mut cursor)?;
-					let cnk_end = ReadBytesExt::read_u64::<LittleEndian>(&mut cursor)?;
+	Ok(merged)
+}
 
-					chunks.push(Range {
-						start: cnk_beg,
-						end: cnk_end,
-					});
-				}
+// The part of a decompressed BGZF block, found at compressed offset block_offset, covered by
+// a chunk of virtual offsets. Offsets within the block are clamped to its length unless
+// strict_offsets is set, in which case anything out of bounds is an error.
+pub fn extract_subblock<'a>(
+	block: &'a [u8],
+	block_offset: u64,
+	chunk: &Range<u64>,
+	options: &ExtractOptions,
+) -> error::Result<&'a [u8]>
+{
+	let (chunk_start_block, chunk_end_block) = (chunk.start >> 16, chunk.end >> 16);
 
-				bins_map.insert(bin, Region { chunks });
-			}
+	if block_offset < chunk_start_block || block_offset > chunk_end_block
+	{
+		return Err(error::Error::IndexOffset(format!(
+			"block {block_offset:#x} is outside chunk {:#x}..{:#x}",
+			chunk.start, chunk.end
+		)));
+	}
 
-			ref_indices.push(Reference { bins: bins_map });
+	let start = if block_offset == chunk_start_block
+	{
+		(chunk.start & 0xffff) as usize
+	}
+	else
+	{
+		0
+	};
 
-			let n_intv = ReadBytesExt::read_i32::<LittleEndian>(&mut cursor)?;
+	let end = if block_offset == chunk_end_block
+	{
+		(chunk.end & 0xffff) as usize
+	}
+	else
+	{
+		block.len()
+	};
 
-			for _ in 0..n_intv
-			{
-				let _ioff = ReadBytesExt::read_u64::<LittleEndian>(&mut cursor)?;
-			}
-		}
+	if options.strict_offsets && (start > block.len() || end > block.len() || start > end)
+	{
+		return Err(error::Error::IndexOffset(format!(
+			"offsets {start}..{end} do not fit block {block_offset:#x} of {} bytes",
+			block.len()
+		)));
+	}
 
-		// for (bin, region) in &ref_indices[0].bins
-		// {
-		// }
+	let end = end.min(block.len());
+	let start = start.min(end);
 
-		Ok((
-			Header {
-				n_ref,
-				col_seq,
-				col_beg,
-				col_end,
-				meta,
-				skip,
-			},
-			seqnames,
-			ref_indices,
-		))
-	}
+	Ok(&block[start..end])
 }
@@ -0,0 +1,328 @@
+use std::fmt::Write as _;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use pufferfish::prelude::pool::BgzfBlockPool;
+
+use crate::bed::autooneshotreader::{self, AutoOneShotBlockReader};
+use crate::bed::oneshotreader::ReaderOptions;
+use crate::bed::{BedKind, BedRecord, BgzfWriter, compress_and_index};
+use crate::error;
+use crate::store::{DefaultResolver, TidResolver};
+use crate::tabix;
+
+pub mod roundtrip;
+
+// The shape of generated data. The same options and seed give the same bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticBed
+{
+	pub kind: BedKind,
+	pub contigs: Vec<(String, u64)>,
+	pub records_per_contig: usize,
+	// records are 1 to twice this long, BED12 ones split into up to three blocks
+	pub mean_length: u64,
+	pub seed: u64,
+}
+
+impl Default for SyntheticBed
+{
+	fn default() -> Self
+	{
+		Self {
+			kind: BedKind::Bed6,
+			contigs: vec![("chr1".into(), 1_000_000), ("chr2".into(), 500_000)],
+			records_per_contig: 1000,
+			mean_length: 200,
+			seed: 0,
+		}
+	}
+}
+
+impl SyntheticBed
+{
+	pub fn with_kind(mut self, kind: BedKind) -> Self
+	{
+		self.kind = kind;
+		self
+	}
+
+	pub fn with_contigs(mut self, contigs: Vec<(String, u64)>) -> Self
+	{
+		self.contigs = contigs;
+		self
+	}
+
+	pub fn with_records_per_contig(mut self, records_per_contig: usize) -> Self
+	{
+		self.records_per_contig = records_per_contig;
+		self
+	}
+
+	pub fn with_mean_length(mut self, mean_length: u64) -> Self
+	{
+		self.mean_length = mean_length;
+		self
+	}
+
+	pub fn with_seed(mut self, seed: u64) -> Self
+	{
+		self.seed = seed;
+		self
+	}
+
+	// the plain text, sorted by contig in the order given then start
+	pub fn text(&self) -> Vec<u8>
+	{
+		let mut rng = SplitMix64(self.seed);
+		let mut text = String::new();
+
+		for (contig, size) in &self.contigs
+		{
+			let size = (*size).max(1);
+			let mut starts: Vec<u64> = (0..self.records_per_contig)
+				.map(|_| rng.below(size))
+				.collect();
+			starts.sort_unstable();
+
+			for (i, start) in starts.into_iter().enumerate()
+			{
+				let length = 1 + rng.below(self.mean_length.max(1) * 2);
+				let end = (start + length).min(size).max(start + 1);
+
+				write_record(&mut text, &mut rng, self.kind, contig, i, start, end);
+			}
+		}
+
+		text.into_bytes()
+	}
+
+	// the data compressed with BGZF and indexed, without touching the filesystem
+	pub fn fixture(&self) -> error::Result<Fixture>
+	{
+		let text = self.text();
+		let (bgzf, tbi) = compress_and_index("synthetic", &text)?;
+
+		Ok(Fixture { text, bgzf, tbi })
+	}
+}
+
+// generated data in each of the forms a reader takes
+#[derive(Debug, Clone)]
+pub struct Fixture
+{
+	pub text: Vec<u8>,
+	pub bgzf: Vec<u8>,
+	pub tbi: Vec<u8>,
+}
+
+impl Fixture
+{
+	pub async fn index(&self) -> error::Result<tabix::Reader>
+	{
+		tabix::Reader::from_reader(Cursor::new(self.tbi.clone())).await
+	}
+
+	// a reader over the BGZF bytes with the index loaded
+	pub async fn reader(&self, pool: Arc<BgzfBlockPool>) -> error::Result<MemoryReader>
+	{
+		reader_in_memory(self.bgzf.clone(), &self.tbi, pool).await
+	}
+}
+
+pub type MemoryReader = AutoOneShotBlockReader<Cursor<Arc<[u8]>>, DefaultResolver>;
+
+// BED lines, in any order, as BGZF data and its .tbi. Sorting, compression and indexing all
+// happen in memory, so region queries can be tested without files.
+pub fn indexed_bed_in_memory<I, L>(lines: I) -> error::Result<(Vec<u8>, Vec<u8>)>
+where
+	I: IntoIterator<Item = L>,
+	L: AsRef<str>,
+{
+	let mut text = String::new();
+
+	for line in lines
+	{
+		text.push_str(line.as_ref().trim_end_matches('\n'));
+		text.push('\n');
+	}
+
+	compress_and_index("in memory", text.as_bytes())
+}
+
+// bytes as BGZF blocks with the EOF marker after them, for index and data files made by hand
+pub fn bgzf_in_memory(bytes: &[u8]) -> error::Result<Vec<u8>>
+{
+	let mut writer = BgzfWriter::new(Vec::new());
+	writer.write_all(bytes)?;
+
+	Ok(writer.finish()?)
+}
+
+// A reader over data from indexed_bed_in_memory, with its index loaded from tbi. It
+// reopens by sharing data, so query_region_concurrent works on it too.
+pub async fn reader_in_memory(
+	data: Vec<u8>,
+	tbi: &[u8],
+	pool: Arc<BgzfBlockPool>,
+) -> error::Result<MemoryReader>
+{
+	reader_in_memory_with(data, tbi, pool, ReaderOptions::default()).await
+}
+
+// as reader_in_memory, the index added to options
+pub async fn reader_in_memory_with<T>(
+	data: Vec<u8>,
+	tbi: &[u8],
+	pool: Arc<BgzfBlockPool>,
+	options: ReaderOptions<T>,
+) -> error::Result<AutoOneShotBlockReader<Cursor<Arc<[u8]>>, T>>
+where
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	let index = tabix::Reader::from_reader(Cursor::new(tbi.to_vec())).await?;
+	let options = options.with_index(Arc::clone(index.index()));
+
+	let data: Arc<[u8]> = data.into();
+	let reopened = Arc::clone(&data);
+
+	let reader = autooneshotreader::from_reader_with(
+		"in memory".into(),
+		Cursor::new(data),
+		None,
+		pool,
+		options,
+	)
+	.await?;
+
+	Ok(reader.with_reopen(move || Ok(Cursor::new(Arc::clone(&reopened)))))
+}
+
+pub type LinesReader<T = ()> = AutoOneShotBlockReader<Cursor<Arc<[u8]>>, T>;
+
+// A reader over BED lines indexed in memory with a pool of its own, records coming 0-based
+// half-open as written and tids as plain strings, which is what most tests want.
+pub async fn reader_for_lines<I, L>(lines: I) -> error::Result<LinesReader>
+where
+	I: IntoIterator<Item = L>,
+	L: AsRef<str>,
+{
+	reader_for_lines_with(lines, ReaderOptions::<()>::default().with_one_indexed(true)).await
+}
+
+// as reader_for_lines, read with options
+pub async fn reader_for_lines_with<I, L, T>(
+	lines: I,
+	options: ReaderOptions<T>,
+) -> error::Result<LinesReader<T>>
+where
+	I: IntoIterator<Item = L>,
+	L: AsRef<str>,
+	T: TidResolver + Default + Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+	let (data, tbi) = indexed_bed_in_memory(lines)?;
+	let pool = Arc::new(BgzfBlockPool::new(64, 64 * 1024));
+
+	reader_in_memory_with(data, &tbi, pool, options).await
+}
+
+// every record of lines, as reader_for_lines gives them
+pub async fn records_in_memory<I, L>(lines: I) -> error::Result<Vec<BedRecord<String>>>
+where
+	I: IntoIterator<Item = L>,
+	L: AsRef<str>,
+{
+	reader_for_lines(lines).await?.records(16).try_collect().await
+}
+
+fn write_record(
+	text: &mut String,
+	rng: &mut SplitMix64,
+	kind: BedKind,
+	contig: &str,
+	i: usize,
+	start: u64,
+	end: u64,
+)
+{
+	let _ = write!(text, "{contig}\t{start}\t{end}");
+
+	let strand = if rng.below(2) == 0
+	{
+		'+'
+	}
+	else
+	{
+		'-'
+	};
+	let score = rng.below(1001);
+
+	let _ = match kind
+	{
+		BedKind::Bed3 => Ok(()),
+		BedKind::Bed4 => write!(text, "\tfeature{i}"),
+		BedKind::Bed5 => write!(text, "\tfeature{i}\t{score}"),
+		BedKind::Bed6 => write!(text, "\tfeature{i}\t{score}\t{strand}"),
+		BedKind::Bed12 =>
+		{
+			let length = end - start;
+			// room for each block and a gap after all but the last
+			let blocks = 1 + rng.below(3.min(length.div_ceil(2)));
+
+			// blocks of equal size, the last running to the end
+			let size = length / (2 * blocks - 1);
+			let sizes: Vec<u64> = vec![size; blocks as usize];
+			let mut block_starts: Vec<u64> = (0..blocks).map(|block| block * 2 * size).collect();
+			let last = blocks as usize - 1;
+			block_starts[last] = length - sizes[last];
+
+			let join = |values: &[u64]| {
+				values.iter().map(u64::to_string).collect::<Vec<_>>().join(",") + ","
+			};
+
+			write!(
+				text,
+				"\tfeature{i}\t{score}\t{strand}\t{start}\t{end}\t0\t{blocks}\t{}\t{}",
+				join(&sizes),
+				join(&block_starts)
+			)
+		}
+		BedKind::BedMethyl =>
+		{
+			let n_valid = 1 + rng.below(50);
+			let n_mod = rng.below(n_valid + 1);
+			let frac_mod = n_mod as f64 * 100.0 / n_valid as f64;
+			let n_canonical = n_valid - n_mod;
+
+			// BED6 and the display columns, then modkit's counts
+			write!(text, "\tm\t{n_valid}\t{strand}\t{start}\t{end}\t255,0,0").and_then(|_| {
+				write!(text, "\t{n_valid}\t{frac_mod:.2}\t{n_mod}\t{n_canonical}\t0\t0\t0\t0\t0")
+			})
+		}
+	};
+
+	text.push('\n');
+}
+
+// a small seeded generator, statistical quality is not needed for fixtures
+struct SplitMix64(u64);
+
+impl SplitMix64
+{
+	fn next(&mut self) -> u64
+	{
+		self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	// in 0..bound, bound at least 1
+	fn below(&mut self, bound: u64) -> u64
+	{
+		self.next() % bound.max(1)
+	}
+}
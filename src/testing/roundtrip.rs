@@ -0,0 +1,270 @@
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+use pufferfish::prelude::pool::BgzfBlockPool;
+
+use crate::bed::oneshotreader::ReaderOptions;
+use crate::bed::{
+	BedKind, BedRecord, BedWriter, TrackType, compress_and_index, is_header_line,
+	track_attributes,
+};
+use crate::error;
+use crate::testing::reader_in_memory_with;
+
+// Parses text, writes the records back with BedWriter and parses that again, an error
+// naming the first record that came back different. Readers keep coordinates as written.
+pub async fn records(text: &[u8]) -> error::Result<()>
+{
+	let pool = Arc::new(BgzfBlockPool::new(64, 64 * 1024));
+	let before = parse(text, &pool).await?;
+
+	let mut writer = BedWriter::new(Vec::new());
+	for record in &before
+	{
+		writer.write(record)?;
+	}
+
+	let after = parse(&writer.into_inner(), &pool).await?;
+
+	if before.len() != after.len()
+	{
+		return Err(error::Error::RoundTrip(format!(
+			"the record count from {} to {}",
+			before.len(),
+			after.len()
+		)));
+	}
+
+	for (i, (before, after)) in before.iter().zip(&after).enumerate()
+	{
+		let (before, after) = (format!("{before:?}"), format!("{after:?}"));
+
+		if before != after
+		{
+			return Err(error::Error::RoundTrip(format!("record {i}: {before} became {after}")));
+		}
+	}
+
+	Ok(())
+}
+
+// the same for a track or browser line, track attributes and type compared after the trip
+pub fn header_line(line: &str) -> error::Result<()>
+{
+	let mut writer = BedWriter::new(Vec::new());
+
+	if let Some(before) = track_attributes(line.as_bytes())
+	{
+		let before: Vec<(&str, &str)> = before.collect();
+		writer.write_track_line(&before)?;
+
+		let written = String::from_utf8_lossy(&writer.into_inner()).into_owned();
+		let after: Vec<(&str, &str)> = track_attributes(written.trim_end().as_bytes())
+			.map(|attributes| attributes.collect())
+			.unwrap_or_default();
+
+		let track_type = TrackType::from_line(line.as_bytes());
+
+		if before != after || track_type != TrackType::from_line(written.as_bytes())
+		{
+			return Err(error::Error::RoundTrip(format!("{line:?}, written as {written:?}")));
+		}
+
+		return Ok(());
+	}
+
+	let Some(rest) = line
+		.trim_start()
+		.strip_prefix("browser")
+		.filter(|rest| rest.starts_with([' ', '\t']))
+	else
+	{
+		return Err(error::Error::Parse(line.to_string()));
+	};
+
+	writer.write_browser_line(rest)?;
+	let written = String::from_utf8_lossy(&writer.into_inner()).into_owned();
+
+	let words = |line: &str| line.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+
+	if !is_header_line(written.as_bytes()) || words(line) != words(&written)
+	{
+		return Err(error::Error::RoundTrip(format!("{line:?}, written as {written:?}")));
+	}
+
+	Ok(())
+}
+
+// Runs records over cases generated files of kind lines, so a new kind only needs an
+// arm in bed_line to be covered. The error holds the smallest failing file proptest found.
+pub fn check(kind: BedKind, cases: u32) -> Result<(), TestError<String>>
+{
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.expect("a current thread runtime builds");
+
+	let files = prop::collection::vec(bed_line(kind), 1..20).prop_map(|lines| lines.join("\n"));
+
+	TestRunner::new(Config::with_cases(cases)).run(&files, |text| {
+		runtime
+			.block_on(records(text.as_bytes()))
+			.map_err(|e| TestCaseError::fail(e.to_string()))
+	})
+}
+
+// header_line over generated track and browser lines
+pub fn check_header_lines(cases: u32) -> Result<(), TestError<String>>
+{
+	let lines = prop_oneof![track_line(), browser_line()];
+
+	TestRunner::new(Config::with_cases(cases)).run(&lines, |line| {
+		header_line(&line).map_err(|e| TestCaseError::fail(e.to_string()))
+	})
+}
+
+// a line the reader detects as kind, tab separated
+pub fn bed_line(kind: BedKind) -> BoxedStrategy<String>
+{
+	match kind
+	{
+		BedKind::Bed3 => (tid(), interval())
+			.prop_map(|(tid, (start, end))| format!("{tid}\t{start}\t{end}"))
+			.boxed(),
+		BedKind::Bed4 => (tid(), interval(), name())
+			.prop_map(|(tid, (start, end), name)| format!("{tid}\t{start}\t{end}\t{name}"))
+			.boxed(),
+		BedKind::Bed5 => (tid(), interval(), name(), 0u32..=1000)
+			.prop_map(|(tid, (start, end), name, score)| {
+				format!("{tid}\t{start}\t{end}\t{name}\t{score}")
+			})
+			.boxed(),
+		BedKind::Bed6 => (tid(), interval(), name(), 0u32..=1000, strand())
+			.prop_map(|(tid, (start, end), name, score, strand)| {
+				format!("{tid}\t{start}\t{end}\t{name}\t{score}\t{strand}")
+			})
+			.boxed(),
+		BedKind::Bed12 =>
+		{
+			// each block with the gap after it, the last gap unused
+			let blocks = prop::collection::vec((1u32..500, 1u32..500), 1..5);
+			let rgb = prop_oneof![
+				Just("0".to_string()),
+				any::<(u8, u8, u8)>().prop_map(|(r, g, b)| format!("{r},{g},{b}")),
+			];
+
+			(tid(), 0u64..1_000_000, name(), 0u32..=1000, strand(), rgb, blocks)
+				.prop_map(|(tid, start, name, score, strand, rgb, blocks)| {
+					let mut sizes = String::new();
+					let mut starts = String::new();
+					let mut offset = 0;
+
+					for (i, (size, gap)) in blocks.iter().enumerate()
+					{
+						sizes.push_str(&format!("{size},"));
+						starts.push_str(&format!("{offset},"));
+						offset += size;
+
+						if i + 1 < blocks.len()
+						{
+							offset += gap;
+						}
+					}
+
+					let end = start + offset as u64;
+
+					let bed6 = format!("{tid}\t{start}\t{end}\t{name}\t{score}\t{strand}");
+					let count = blocks.len();
+
+					format!("{bed6}\t{start}\t{end}\t{rgb}\t{count}\t{sizes}\t{starts}")
+				})
+				.boxed()
+		}
+		BedKind::BedMethyl =>
+		{
+			let counts = (1u32..200).prop_flat_map(|n_valid| {
+				(Just(n_valid), 0..=n_valid, prop::collection::vec(0u32..20, 5), any::<bool>())
+			});
+
+			(tid(), interval(), prop_oneof![Just("m"), Just("h"), Just("a")], strand(), counts)
+				.prop_map(|(tid, (start, end), code, strand, (n_valid, n_mod, rest, legacy))| {
+					let frac = format!("{:.2}", n_mod as f64 * 100.0 / n_valid as f64);
+					let bed6 = format!("{tid}\t{start}\t{end}\t{code}\t{n_valid}\t{strand}");
+					let line = format!("{bed6}\t{start}\t{end}\t255,0,0\t{n_valid}\t{frac}");
+
+					if legacy
+					{
+						return line;
+					}
+
+					let rest: Vec<String> = rest.iter().map(u32::to_string).collect();
+					format!("{line}\t{n_mod}\t{}\t{}", n_valid - n_mod, rest.join("\t"))
+				})
+				.boxed()
+		}
+	}
+}
+
+// key=value attributes, some quoted with a space, sometimes with a type
+pub fn track_line() -> BoxedStrategy<String>
+{
+	let value = prop_oneof!["[A-Za-z0-9_.,]{1,10}", "\"[a-z]{1,5} [a-z]{1,5}\""];
+	let attribute =
+		("[a-z][A-Za-z]{0,9}", value).prop_map(|(key, value)| format!("{key}={value}"));
+	let track_type = prop::option::of(prop_oneof![
+		Just("bed"),
+		Just("bedGraph"),
+		Just("narrowPeak"),
+		Just("bedMethyl"),
+		Just("wiggle_0"),
+	]);
+
+	(track_type, prop::collection::vec(attribute, 0..6))
+		.prop_map(|(track_type, attributes)| {
+			let track_type = track_type.map(|track_type| format!(" type={track_type}"));
+			let attributes: String =
+				attributes.iter().map(|attribute| format!(" {attribute}")).collect();
+
+			format!("track{}{attributes}", track_type.unwrap_or_default())
+		})
+		.boxed()
+}
+
+pub fn browser_line() -> BoxedStrategy<String>
+{
+	("(position|hide|dense|pack|full)", "[A-Za-z0-9:,-]{1,20}")
+		.prop_map(|(setting, value)| format!("browser {setting} {value}"))
+		.boxed()
+}
+
+fn interval() -> impl Strategy<Value = (u64, u64)>
+{
+	(0u64..1_000_000, 1u64..10_000).prop_map(|(start, length)| (start, start + length))
+}
+
+fn tid() -> impl Strategy<Value = String>
+{
+	"chr([1-9]|1[0-9]|2[0-2]|X|Y|M)"
+}
+
+// led by a letter so BED4 names are never taken for BED5 scores
+fn name() -> impl Strategy<Value = String>
+{
+	"[A-Za-z][A-Za-z0-9_.-]{0,11}"
+}
+
+fn strand() -> impl Strategy<Value = char>
+{
+	prop_oneof![Just('+'), Just('-'), Just('.')]
+}
+
+async fn parse(text: &[u8], pool: &Arc<BgzfBlockPool>) -> error::Result<Vec<BedRecord<String>>>
+{
+	let (data, tbi) = compress_and_index("round trip", text)?;
+	let options = ReaderOptions::<()>::default().with_one_indexed(true);
+	let reader = reader_in_memory_with(data, &tbi, Arc::clone(pool), options).await?;
+
+	reader.records(16).try_collect().await
+}
@@ -0,0 +1,224 @@
+#![cfg(all(feature = "io-uring", target_os = "linux"))]
+
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{ready, Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf, SeekFrom};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error;
+
+const DEFAULT_READ_SIZE: usize = 256 * 1024;
+
+struct ReadRequest
+{
+	offset: u64,
+	len: usize,
+	reply: oneshot::Sender<io::Result<Vec<u8>>>,
+}
+
+// tokio-uring futures are !Send and need their own runtime, so reads are handed to a
+// dedicated thread and answered over channels. That keeps UringFile usable anywhere a
+// tokio::fs::File is.
+pub struct UringFile
+{
+	path: PathBuf,
+	requests: mpsc::UnboundedSender<ReadRequest>,
+	size: u64,
+	position: u64,
+	read_size: usize,
+	buffer: Vec<u8>,
+	buffer_start: u64,
+	pending: Option<(u64, oneshot::Receiver<io::Result<Vec<u8>>>)>,
+}
+
+impl UringFile
+{
+	pub async fn open<P>(path: P) -> error::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
+		Self::open_with_read_size(path, DEFAULT_READ_SIZE).await
+	}
+
+	pub async fn open_with_read_size<P>(path: P, read_size: usize) -> error::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
+		let path = path.as_ref().to_path_buf();
+		let size = tokio::fs::metadata(&path).await?.len();
+
+		let (requests, mut receiver) = mpsc::unbounded_channel::<ReadRequest>();
+		let (opened_tx, opened_rx) = oneshot::channel::<io::Result<()>>();
+
+		let worker_path = path.clone();
+		std::thread::Builder::new()
+			.name("sandman-uring".into())
+			.spawn(move || {
+				tokio_uring::start(async move {
+					let file = match tokio_uring::fs::File::open(&worker_path).await
+					{
+						Ok(file) =>
+						{
+							let _ = opened_tx.send(Ok(()));
+							Rc::new(file)
+						}
+						Err(e) =>
+						{
+							let _ = opened_tx.send(Err(e));
+							return;
+						}
+					};
+
+					// the loop ends once every UringFile handle has been dropped
+					while let Some(request) = receiver.recv().await
+					{
+						let file = file.clone();
+						tokio_uring::spawn(async move {
+							let (result, mut buf) =
+								file.read_at(vec![0u8; request.len], request.offset).await;
+							let result = result.map(|n| {
+								buf.truncate(n);
+								buf
+							});
+							let _ = request.reply.send(result);
+						});
+					}
+				})
+			})?;
+
+		opened_rx
+			.await
+			.map_err(|_| io::Error::other("io_uring worker exited before opening the file"))??;
+
+		Ok(Self {
+			path,
+			requests,
+			size,
+			position: 0,
+			read_size: read_size.max(1),
+			buffer: Vec::new(),
+			buffer_start: 0,
+			pending: None,
+		})
+	}
+
+	pub fn path(&self) -> &Path
+	{
+		&self.path
+	}
+
+	pub fn size(&self) -> u64
+	{
+		self.size
+	}
+
+	fn buffer_offset(&self) -> Option<usize>
+	{
+		let buffer_end = self.buffer_start + self.buffer.len() as u64;
+
+		if self.position >= self.buffer_start && self.position < buffer_end
+		{
+			Some((self.position - self.buffer_start) as usize)
+		}
+		else
+		{
+			None
+		}
+	}
+}
+
+impl AsyncRead for UringFile
+{
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>>
+	{
+		let this = &mut *self;
+
+		loop
+		{
+			if let Some(offset) = this.buffer_offset()
+			{
+				let available = &this.buffer[offset..];
+				let n = available.len().min(buf.remaining());
+				buf.put_slice(&available[..n]);
+				this.position += n as u64;
+				return Poll::Ready(Ok(()));
+			}
+
+			if this.position >= this.size
+			{
+				return Poll::Ready(Ok(()));
+			}
+
+			if this.pending.is_none()
+			{
+				let (reply, receiver) = oneshot::channel();
+				let len = (this.size - this.position).min(this.read_size as u64) as usize;
+
+				this.requests
+					.send(ReadRequest {
+						offset: this.position,
+						len,
+						reply,
+					})
+					.map_err(|_| io::Error::other("io_uring worker has shut down"))?;
+
+				this.pending = Some((this.position, receiver));
+			}
+
+			let (start, receiver) = this.pending.as_mut().expect("pending read");
+			let start = *start;
+
+			let result = ready!(Pin::new(receiver).poll(cx));
+			this.pending = None;
+
+			let bytes = result.map_err(|_| io::Error::other("io_uring read was dropped"))??;
+
+			if bytes.is_empty()
+			{
+				return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+			}
+
+			this.buffer = bytes;
+			this.buffer_start = start;
+		}
+	}
+}
+
+impl AsyncSeek for UringFile
+{
+	fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()>
+	{
+		let this = &mut *self;
+
+		let target = match position
+		{
+			SeekFrom::Start(n) => Some(n),
+			SeekFrom::End(n) => this.size.checked_add_signed(n),
+			SeekFrom::Current(n) => this.position.checked_add_signed(n),
+		}
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+
+		// an in-flight read for another offset is simply left to complete unobserved
+		if matches!(this.pending, Some((start, _)) if start != target)
+		{
+			this.pending = None;
+		}
+
+		this.position = target;
+		Ok(())
+	}
+
+	fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>>
+	{
+		Poll::Ready(Ok(self.position))
+	}
+}
@@ -0,0 +1,71 @@
+use sandman::bed::{BedKind, BedMethylLayout, ParseOptions, ScoreField, parse_bedmethyl_sink_simd};
+use sandman::prelude::Strand;
+
+const TABBED: &str =
+	"chr1\t100\t101\tm\t20\t+\t100\t101\t255,0,0\t20\t25.00\t5\t14\t1\t0\t2\t3\t4\n";
+const SPACE_SEPARATED: &str =
+	"chr1\t100\t101\tm\t20\t+\t100\t101\t255,0,0\t20 25.00 5 14 1 0 2 3 4\n";
+const LEGACY: &str = "chr1\t100\t101\tm\t20\t+\t100\t101\t255,0,0\t20\t25\n";
+
+#[test]
+fn layouts_are_told_apart()
+{
+	assert_eq!(BedMethylLayout::detect(TABBED), Some(BedMethylLayout::Tabbed));
+	assert_eq!(
+		BedMethylLayout::detect(SPACE_SEPARATED),
+		Some(BedMethylLayout::SpaceSeparatedCounts)
+	);
+	assert_eq!(BedMethylLayout::detect(LEGACY), Some(BedMethylLayout::Legacy));
+	assert_eq!(BedMethylLayout::detect("chr1\t100\t101\tm\t20\t+\n"), None);
+}
+
+#[tokio::test]
+async fn both_modkit_layouts_parse_the_same()
+{
+	let options = ParseOptions::default();
+
+	for line in [TABBED, SPACE_SEPARATED]
+	{
+		let (rest, parsed) = parse_bedmethyl_sink_simd(line.as_bytes(), None, options)
+			.await
+			.expect("parses");
+		let (tid, strand, start, end, value) = parsed.expect("a record");
+
+		assert!(rest.is_empty());
+		assert_eq!((tid.as_ref(), strand, start, end), ("chr1", Strand::Plus, 100, 101));
+		assert_eq!(value.kind(), BedKind::BedMethyl);
+
+		let counts: Vec<Option<u32>> = [
+			ScoreField::NValidCov,
+			ScoreField::NMod,
+			ScoreField::NCanonical,
+			ScoreField::NOtherMod,
+			ScoreField::NDelete,
+			ScoreField::NFail,
+			ScoreField::NDiff,
+			ScoreField::NNoCall,
+		]
+		.into_iter()
+		.map(|field| value.get_u32(field))
+		.collect();
+
+		assert_eq!(
+			counts,
+			[Some(20), Some(5), Some(14), Some(1), Some(0), Some(2), Some(3), Some(4)]
+		);
+		assert_eq!(value.get_f32(ScoreField::FracMod), Some(25.0));
+	}
+}
+
+#[tokio::test]
+async fn legacy_layout_derives_the_modified_count()
+{
+	let (_, parsed) = parse_bedmethyl_sink_simd(LEGACY.as_bytes(), None, ParseOptions::default())
+		.await
+		.expect("parses");
+	let (_, _, _, _, value) = parsed.expect("a record");
+
+	assert_eq!(value.get_u32(ScoreField::NValidCov), Some(20));
+	assert_eq!(value.get_u32(ScoreField::NMod), Some(5));
+	assert_eq!(value.get_u32(ScoreField::NCanonical), None);
+}
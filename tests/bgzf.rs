@@ -0,0 +1,60 @@
+#![cfg(feature = "testing")]
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+
+use sandman::error;
+use sandman::prelude::*;
+use sandman::pufferfish::pool::BgzfBlockPool;
+use sandman::testing::bgzf_in_memory;
+
+// the EOF marker bgzf_in_memory ends with
+const EOF_LEN: usize = 28;
+
+// a block header whose BSIZE leaves no room for the header and trailer it must hold
+fn short_block() -> Vec<u8>
+{
+	let mut block = vec![
+		0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02,
+		0x00,
+	];
+	block.extend_from_slice(&19u16.to_le_bytes());
+	block.extend_from_slice(&[0x03, 0x00]);
+	block
+}
+
+#[tokio::test]
+async fn short_block_is_reported_as_corrupt()
+{
+	let good = bgzf_in_memory(b"chr1\t100\t200\ta\nchr1\t300\t400\tb\n").expect("bgzf");
+	let offset = (good.len() - EOF_LEN) as u64;
+
+	let mut data = good[..good.len() - EOF_LEN].to_vec();
+	data.extend(short_block());
+	data.extend(bgzf_in_memory(b"chr1\t500\t600\tc\n").expect("bgzf"));
+
+	let pool = Arc::new(BgzfBlockPool::new(64, 64 * 1024));
+	let options = ReaderOptions::<()>::default().with_one_indexed(true);
+	let reader = autooneshotreader::from_reader_with(
+		"short block".into(),
+		Cursor::new(data),
+		None,
+		pool,
+		options,
+	)
+	.await
+	.expect("reader");
+
+	let e = reader
+		.records(16)
+		.try_collect::<Vec<BedRecord<String>>>()
+		.await
+		.expect_err("short block");
+
+	assert!(
+		matches!(e.root(), error::Error::CorruptBlock { offset: at } if *at == offset),
+		"{e}"
+	);
+}
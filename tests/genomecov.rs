@@ -0,0 +1,127 @@
+#![cfg(feature = "testing")]
+
+use futures::{StreamExt, TryStreamExt, stream};
+
+use sandman::error::Error;
+use sandman::ops::{genomecov, genomecov_with};
+use sandman::prelude::*;
+use sandman::testing::{reader_for_lines_with, records_in_memory};
+
+fn sizes(sizes: &[(&str, u64)]) -> Vec<(String, u64)>
+{
+	sizes.iter().map(|(tid, size)| (tid.to_string(), *size)).collect()
+}
+
+async fn runs<S>(runs: S) -> Vec<(String, u64, u64, f64)>
+where
+	S: futures::Stream<Item = sandman::error::Result<BedGraphRecord<String>>>,
+{
+	runs.map_ok(|run| (run.tid, run.start, run.end, run.value))
+		.try_collect()
+		.await
+		.expect("coverage")
+}
+
+fn run(tid: &str, start: u64, end: u64, value: f64) -> (String, u64, u64, f64)
+{
+	(tid.to_string(), start, end, value)
+}
+
+const LINES: [&str; 3] = ["chr1\t10\t20", "chr1\t15\t30", "chr3\t0\t5"];
+
+#[tokio::test]
+async fn depth_runs_cover_every_contig()
+{
+	let records = records_in_memory(LINES).await.expect("records");
+	let records = stream::iter(records.into_iter().map(Ok));
+	let sizes = sizes(&[("chr1", 40), ("chr2", 10), ("chr3", 5)]);
+
+	assert_eq!(
+		runs(genomecov(records, sizes)).await,
+		[
+			run("chr1", 0, 10, 0.0),
+			run("chr1", 10, 15, 1.0),
+			run("chr1", 15, 20, 2.0),
+			run("chr1", 20, 30, 1.0),
+			run("chr1", 30, 40, 0.0),
+			run("chr2", 0, 10, 0.0),
+			run("chr3", 0, 5, 1.0),
+		]
+	);
+}
+
+#[tokio::test]
+async fn zero_runs_can_be_left_out()
+{
+	let records = records_in_memory(LINES).await.expect("records");
+	let records = stream::iter(records.into_iter().map(Ok));
+	let sizes = sizes(&[("chr1", 40), ("chr2", 10), ("chr3", 5)]);
+	let options = CoverageOptions::default().with_include_zero(false);
+
+	assert_eq!(
+		runs(genomecov_with(records, sizes, options)).await,
+		[
+			run("chr1", 10, 15, 1.0),
+			run("chr1", 15, 20, 2.0),
+			run("chr1", 20, 30, 1.0),
+			run("chr3", 0, 5, 1.0),
+		]
+	);
+}
+
+#[tokio::test]
+async fn split_counts_only_bed12_blocks()
+{
+	let lines = ["chr1\t0\t100\tgene\t0\t+\t0\t100\t0\t2\t10,10,\t0,90,"];
+	let sizes = sizes(&[("chr1", 100)]);
+
+	let whole = records_in_memory(lines).await.expect("records");
+	let whole = stream::iter(whole.into_iter().map(Ok));
+	assert_eq!(runs(genomecov(whole, sizes.clone())).await, [run("chr1", 0, 100, 1.0)]);
+
+	let split = records_in_memory(lines).await.expect("records");
+	let split = stream::iter(split.into_iter().map(Ok));
+	let options = CoverageOptions::default().with_split(true);
+	assert_eq!(
+		runs(genomecov_with(split, sizes, options)).await,
+		[run("chr1", 0, 10, 1.0), run("chr1", 10, 90, 0.0), run("chr1", 90, 100, 1.0)]
+	);
+}
+
+#[tokio::test]
+async fn records_on_an_unknown_contig_fail()
+{
+	let records = records_in_memory(LINES).await.expect("records");
+	let records = stream::iter(records.into_iter().map(Ok));
+	let results: Vec<_> = genomecov(records, sizes(&[("chr1", 40)])).collect().await;
+
+	assert!(matches!(results.last(), Some(Err(Error::TidNotFound(tid))) if tid == "chr3"));
+}
+
+#[tokio::test]
+async fn records_from_a_default_reader_are_taken_as_written()
+{
+	let reader = reader_for_lines_with(LINES, ReaderOptions::<()>::default())
+		.await
+		.expect("reader");
+
+	let system = CoordinateSystem::of_reader(reader.describe().one_indexed);
+	let records: Vec<BedRecord<String>> =
+		reader.records(16).try_collect().await.expect("records");
+
+	let records = stream::iter(records.into_iter().map(Ok));
+	let sizes = sizes(&[("chr1", 40), ("chr3", 5)]);
+	let options = CoverageOptions::default()
+		.with_include_zero(false)
+		.with_coordinate_system(system);
+
+	assert_eq!(
+		runs(genomecov_with(records, sizes, options)).await,
+		[
+			run("chr1", 10, 15, 1.0),
+			run("chr1", 15, 20, 2.0),
+			run("chr1", 20, 30, 1.0),
+			run("chr3", 0, 5, 1.0),
+		]
+	);
+}
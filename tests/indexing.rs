@@ -0,0 +1,66 @@
+#![cfg(feature = "indexing")]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use sandman::blocking::{self, AutoOneShotBlockReader};
+use sandman::prelude::*;
+use sandman::pufferfish::pool::BgzfBlockPool;
+use sandman::region::Region;
+
+// a plain BED under the temp dir, named for the test so parallel tests keep apart
+fn plain_bed(name: &str, lines: &[&str]) -> PathBuf
+{
+	let dir = std::env::temp_dir().join(format!("sandman-indexing-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).expect("temp dir");
+
+	let path = dir.join(name);
+	std::fs::write(&path, lines.join("\n") + "\n").expect("plain bed");
+	path
+}
+
+#[test]
+fn index_bed_writes_a_queryable_copy()
+{
+	let path = plain_bed(
+		"unsorted.bed",
+		&[
+			"chr2\t100\t200\te",
+			"chr1\t300\t400\tc",
+			"chr1\t100\t200\ta",
+			"chr1\t150\t250\tb",
+		],
+	);
+
+	let data = blocking::index_bed(&path).expect("indexed");
+	assert_eq!(data, PathBuf::from(format!("{}.gz", path.display())));
+	assert!(PathBuf::from(format!("{}.tbi", data.display())).exists());
+
+	let pool = Arc::new(BgzfBlockPool::new(64, 64 * 1024));
+	let options = ReaderOptions::<()>::default().with_one_indexed(true);
+	let reader =
+		AutoOneShotBlockReader::from_path_indexed(&data, None, pool, options).expect("reader");
+
+	let names = |region: Region| -> Vec<String> {
+		reader
+			.query_region_concurrent(&region, 2)
+			.expect("query")
+			.iter()
+			.map(|record| record.value.get_name().unwrap_or_default().to_string())
+			.collect()
+	};
+
+	assert_eq!(names(Region::zero_based("chr1", 180, 320)), ["a", "b", "c"]);
+	assert_eq!(names(Region::zero_based("chr1", 220, 300)), ["b"]);
+	assert_eq!(names(Region::contig("chr2")), ["e"]);
+	assert!(names(Region::contig("chr3")).is_empty());
+}
+
+#[test]
+fn index_bed_refuses_compressed_input()
+{
+	let path = plain_bed("compressed.bed", &["chr1\t100\t200"]);
+	let data = blocking::index_bed(&path).expect("indexed");
+
+	assert!(blocking::index_bed(&data).is_err());
+}
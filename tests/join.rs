@@ -0,0 +1,98 @@
+#![cfg(feature = "testing")]
+
+use futures::{TryStreamExt, stream};
+
+use sandman::ops::join;
+use sandman::prelude::*;
+use sandman::testing::records_in_memory;
+
+const LEFT: [&str; 3] = ["chr1\t100\t200\ta", "chr1\t300\t400\tb", "chr2\t100\t200\tc"];
+
+const RIGHT: [&str; 4] = [
+	"chr1\t150\t160\tx",
+	"chr1\t190\t310\ty",
+	"chr1\t500\t600\tz",
+	"chr3\t0\t10\tw",
+];
+
+fn name(record: &Option<BedRecord<String>>) -> Option<String>
+{
+	record.as_ref().and_then(|record| record.value.get_name()).map(str::to_string)
+}
+
+async fn joined(kind: JoinKind) -> Vec<(Option<String>, Option<String>)>
+{
+	let left = records_in_memory(LEFT).await.expect("records");
+	let right = records_in_memory(RIGHT).await.expect("records");
+
+	join(
+		stream::iter(left.into_iter().map(Ok)),
+		stream::iter(right.into_iter().map(Ok)),
+		kind,
+		OverlapOptions::default(),
+	)
+	.map_ok(|pair| (name(&pair.left), name(&pair.right)))
+	.try_collect()
+	.await
+	.expect("joined")
+}
+
+fn pairs(expected: &[(Option<&str>, Option<&str>)]) -> Vec<(Option<String>, Option<String>)>
+{
+	expected
+		.iter()
+		.map(|(left, right)| (left.map(str::to_string), right.map(str::to_string)))
+		.collect()
+}
+
+#[tokio::test]
+async fn inner_join_pairs_overlaps_only()
+{
+	assert_eq!(
+		joined(JoinKind::Inner).await,
+		pairs(&[(Some("a"), Some("x")), (Some("a"), Some("y")), (Some("b"), Some("y"))])
+	);
+}
+
+#[tokio::test]
+async fn left_join_keeps_unmatched_left_records()
+{
+	assert_eq!(
+		joined(JoinKind::Left).await,
+		pairs(&[
+			(Some("a"), Some("x")),
+			(Some("a"), Some("y")),
+			(Some("b"), Some("y")),
+			(Some("c"), None),
+		])
+	);
+}
+
+#[tokio::test]
+async fn outer_join_releases_right_records_as_they_fall_behind()
+{
+	assert_eq!(
+		joined(JoinKind::Outer).await,
+		pairs(&[
+			(Some("a"), Some("x")),
+			(Some("a"), Some("y")),
+			(Some("b"), Some("y")),
+			(None, Some("z")),
+			(Some("c"), None),
+			(None, Some("w")),
+		])
+	);
+}
+
+#[tokio::test]
+async fn min_overlap_counts_shared_bases()
+{
+	let left = records_in_memory(LEFT).await.expect("records");
+	let right = records_in_memory(RIGHT).await.expect("records");
+	let (a, y) = (&left[0], &right[1]);
+
+	assert!(OverlapOptions::default().with_min_overlap(10).matches(a, y));
+	assert!(!OverlapOptions::default().with_min_overlap(11).matches(a, y));
+	assert!(OverlapOptions::default().with_max_gap(301).matches(a, &right[2]));
+	assert!(!OverlapOptions::default().matches(a, &right[2]));
+}
@@ -0,0 +1,118 @@
+use sandman::bed::Strand;
+use sandman::liftover::{ChainMap, LiftOptions, LiftedInterval, Unmapped};
+
+// chr1 0-1000 onto chrA with a 10 base target gap and 20 base query gap after the first
+// block, and chr1 2000-3000 onto the minus strand of chrB
+const CHAINS: &str = "\
+chain 1000 chr1 10000 + 0 1000 chrA 20000 + 5000 6010 1
+400 10 20
+590
+
+chain 900 chr1 10000 + 2000 3000 chrB 5000 - 100 1100 2
+1000
+";
+
+// a second chain over chr1's first block
+const OVERLAPPING: &str = "\
+chain 500 chr1 10000 + 0 400 chrC 1000 + 0 400 3
+400
+";
+
+fn chains(text: &str) -> ChainMap
+{
+	ChainMap::parse("test.chain", text.as_bytes()).expect("chains parse")
+}
+
+fn interval(tid: &str, start: u64, end: u64, strand: Strand) -> LiftedInterval
+{
+	LiftedInterval {
+		tid: tid.into(),
+		start,
+		end,
+		strand,
+	}
+}
+
+#[test]
+fn chains_are_read_with_their_blocks()
+{
+	let map = chains(CHAINS);
+	let chr1 = map.chains("chr1");
+
+	assert_eq!(chr1.len(), 2);
+	assert_eq!(chr1[0].blocks.len(), 2);
+	assert_eq!((chr1[0].blocks[1].t_start, chr1[0].blocks[1].q_start), (410, 5420));
+	assert_eq!(chr1[1].q_strand, Strand::Minus);
+	assert!(map.chains("chr2").is_empty());
+}
+
+#[test]
+fn intervals_lift_through_plus_and_minus_chains()
+{
+	let map = chains(CHAINS);
+	let options = LiftOptions::default();
+
+	assert_eq!(
+		map.lift("chr1", 100, 200, Strand::Plus, &options),
+		Ok(vec![interval("chrA", 5100, 5200, Strand::Plus)])
+	);
+	assert_eq!(
+		map.lift("chr1", 2100, 2200, Strand::Plus, &options),
+		Ok(vec![interval("chrB", 4700, 4800, Strand::Minus)])
+	);
+	assert_eq!(
+		map.lift("chr1", 2100, 2200, Strand::Minus, &options),
+		Ok(vec![interval("chrB", 4700, 4800, Strand::Plus)])
+	);
+}
+
+#[test]
+fn min_match_and_split_across_a_gap()
+{
+	let map = chains(CHAINS);
+
+	// 20 of the 30 bases sit in blocks
+	assert_eq!(
+		map.lift("chr1", 390, 420, Strand::Both, &LiftOptions::default()),
+		Err(Unmapped::PartiallyDeleted)
+	);
+
+	let options = LiftOptions::default().with_min_match(0.5);
+	assert_eq!(
+		map.lift("chr1", 390, 420, Strand::Both, &options),
+		Ok(vec![interval("chrA", 5390, 5430, Strand::Both)])
+	);
+	assert_eq!(
+		map.lift("chr1", 390, 420, Strand::Both, &options.with_split(true)),
+		Ok(vec![
+			interval("chrA", 5390, 5400, Strand::Both),
+			interval("chrA", 5420, 5430, Strand::Both),
+		])
+	);
+}
+
+#[test]
+fn unmapped_intervals_give_liftovers_reasons()
+{
+	let options = LiftOptions::default();
+
+	assert_eq!(
+		chains(CHAINS).lift("chr1", 5000, 5100, Strand::Both, &options),
+		Err(Unmapped::Deleted)
+	);
+	assert_eq!(
+		chains(CHAINS).lift("chr2", 0, 100, Strand::Both, &options),
+		Err(Unmapped::Deleted)
+	);
+
+	let duplicated = chains(&format!("{CHAINS}\n{OVERLAPPING}"));
+	assert_eq!(
+		duplicated.lift("chr1", 100, 200, Strand::Both, &options),
+		Err(Unmapped::Duplicated)
+	);
+
+	let lifted = duplicated
+		.lift("chr1", 100, 200, Strand::Both, &options.with_multiple(true))
+		.expect("both chains lift it");
+	assert_eq!(lifted.len(), 2);
+}
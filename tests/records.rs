@@ -0,0 +1,101 @@
+#![cfg(feature = "testing")]
+
+use futures::TryStreamExt;
+
+use sandman::prelude::*;
+use sandman::testing::{reader_for_lines, reader_for_lines_with};
+
+// a contig whose records change strand, including two at the same position
+const MIXED: [&str; 5] = [
+	"chr1\t100\t200\ta\t0\t+",
+	"chr1\t150\t250\tb\t0\t-",
+	"chr1\t150\t250\tc\t0\t+",
+	"chr1\t300\t400\td\t0\t.",
+	"chr2\t100\t200\te\t0\t-",
+];
+
+#[tokio::test]
+async fn records_keep_their_own_strand()
+{
+	let reader = reader_for_lines(MIXED).await.expect("reader");
+	let records: Vec<BedRecord<String>> =
+		reader.records(16).try_collect().await.expect("records");
+
+	let strands: Vec<(&str, Strand)> = records
+		.iter()
+		.map(|record| (record.value.get_name().unwrap_or_default(), record.strand))
+		.collect();
+
+	assert_eq!(
+		strands,
+		[
+			("a", Strand::Plus),
+			("b", Strand::Minus),
+			("c", Strand::Plus),
+			("d", Strand::Both),
+			("e", Strand::Minus),
+		]
+	);
+	assert_eq!((records[1].start, records[1].end), (150, 250));
+	assert_eq!((records[2].start, records[2].end), (150, 250));
+}
+
+#[tokio::test]
+async fn strand_filter_sees_minus_records_after_plus()
+{
+	let names: Vec<String> = reader_for_lines(MIXED)
+		.await
+		.expect("reader")
+		.records(16)
+		.filter_strand(Strand::Minus)
+		.map_ok(|record| record.value.get_name().unwrap_or_default().to_string())
+		.try_collect()
+		.await
+		.expect("records");
+
+	assert_eq!(names, ["b", "e"]);
+}
+
+#[tokio::test]
+async fn record_budget_applies_to_each_query()
+{
+	let budgeted = |max_records: u64| async move {
+		let options = ReaderOptions::<()>::default()
+			.with_one_indexed(true)
+			.with_max_records(max_records);
+		reader_for_lines_with(MIXED, options).await.expect("reader")
+	};
+
+	// the four records on chr1 fit, however many times they are asked for
+	let reader = budgeted(4).await;
+	for _ in 0..3
+	{
+		let records = reader
+			.query_region_concurrent(&Region::contig("chr1"), 2)
+			.await
+			.expect("within budget");
+		assert_eq!(records.len(), 4);
+	}
+
+	let e = budgeted(3)
+		.await
+		.query_region_concurrent(&Region::contig("chr1"), 2)
+		.await
+		.expect_err("four records");
+	assert!(matches!(e.root(), sandman::error::Error::QueryTooLarge(_, 3)), "{e}");
+}
+
+#[tokio::test]
+async fn byte_budget_bounds_what_a_query_fetches()
+{
+	let lines: Vec<String> =
+		(0..5000).map(|i| format!("chr1\t{}\t{}\tname{i}", i * 10, i * 10 + 5)).collect();
+	let options = ReaderOptions::<()>::default().with_one_indexed(true).with_max_result_bytes(1024);
+	let reader = reader_for_lines_with(&lines, options).await.expect("reader");
+
+	let e = reader
+		.query_region_concurrent(&Region::contig("chr1"), 2)
+		.await
+		.expect_err("more than a kilobyte");
+	assert!(matches!(e.root(), sandman::error::Error::ResultTooLarge(_, 1024)), "{e}");
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "testing")]
+
+use sandman::bed::BedKind;
+use sandman::testing::roundtrip;
+
+const CASES: u32 = 64;
+
+#[test]
+fn records_round_trip_for_every_kind()
+{
+	for kind in [
+		BedKind::Bed3,
+		BedKind::Bed4,
+		BedKind::Bed5,
+		BedKind::Bed6,
+		BedKind::Bed12,
+		BedKind::BedMethyl,
+	]
+	{
+		if let Err(e) = roundtrip::check(kind, CASES)
+		{
+			panic!("{kind} did not round trip: {e}");
+		}
+	}
+}
+
+#[test]
+fn header_lines_round_trip()
+{
+	if let Err(e) = roundtrip::check_header_lines(CASES)
+	{
+		panic!("header line did not round trip: {e}");
+	}
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "testing")]
+
+use sandman::bed::StrandCounts;
+use sandman::prelude::*;
+use sandman::testing::reader_for_lines;
+
+// strands change within each contig, two records sharing a position
+const MIXED: [&str; 6] = [
+	"chr1\t100\t200\ta\t10\t+",
+	"chr1\t150\t250\tb\t20\t-",
+	"chr1\t150\t250\tc\t30\t+",
+	"chr1\t300\t400\td\t40\t.",
+	"chr2\t100\t200\te\t50\t-",
+	"chr2\t300\t400\tf\t60\t+",
+];
+
+#[tokio::test]
+async fn summary_counts_each_record_strand()
+{
+	let mut reader = reader_for_lines(MIXED).await.expect("reader");
+	let summary = reader.summarize().await.expect("summary");
+
+	assert_eq!(summary.records, 6);
+	assert_eq!(
+		summary.strands,
+		StrandCounts {
+			plus: 3,
+			minus: 2,
+			unstranded: 1,
+		}
+	);
+	assert_eq!(summary.contigs["chr1"].records, 4);
+	assert_eq!(summary.contigs["chr1"].bases_covered, 250);
+	assert_eq!(summary.contigs["chr2"].records, 2);
+	assert_eq!((summary.min_score, summary.max_score), (Some(10), Some(60)));
+}
+
+#[tokio::test]
+async fn batch_strand_column_follows_each_record()
+{
+	let mut reader = reader_for_lines(MIXED).await.expect("reader");
+	let mut batch = RecordBatch::new();
+
+	while let Some(block) = reader.next_bgzf_blocks(16).await.expect("blocks")
+	{
+		reader.read_tids_in_block_sink(block, &mut batch).await.expect("records");
+	}
+
+	assert_eq!(batch.len(), 6);
+	assert_eq!(
+		batch.strands,
+		[Strand::Plus, Strand::Minus, Strand::Plus, Strand::Both, Strand::Minus, Strand::Plus]
+	);
+	assert_eq!(batch.tids, ["chr1", "chr1", "chr1", "chr1", "chr2", "chr2"]);
+	assert_eq!(batch.starts, [100, 150, 150, 300, 100, 300]);
+	assert_eq!(batch.ends, [200, 250, 250, 400, 200, 400]);
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "testing")]
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use sandman::error;
+use sandman::prelude::*;
+use sandman::pufferfish::pool::BgzfBlockPool;
+use sandman::tabix::{self, LoadOptions};
+use sandman::testing::{SyntheticBed, bgzf_in_memory, indexed_bed_in_memory, reader_in_memory_with};
+
+fn tbi() -> Vec<u8>
+{
+	SyntheticBed::default()
+		.with_contigs(vec![
+			("chr1".into(), 2_000_000),
+			("chr2".into(), 300_000),
+			("chrM".into(), 16_569),
+		])
+		.with_records_per_contig(5000)
+		.fixture()
+		.expect("synthetic fixture")
+		.tbi
+}
+
+async fn load(tbi: &[u8], options: LoadOptions) -> tabix::Reader
+{
+	tabix::Reader::from_reader_with(Cursor::new(tbi.to_vec()), &options)
+		.await
+		.expect("index loads")
+}
+
+#[tokio::test]
+async fn lazy_index_answers_as_the_eager_one()
+{
+	let tbi = tbi();
+	let eager = load(&tbi, LoadOptions::default()).await;
+	let lazy = load(&tbi, LoadOptions::default().with_lazy(true)).await;
+
+	assert_eq!(eager.seqnames, lazy.seqnames);
+	assert_eq!(eager.seqnames, ["chr1", "chr2", "chrM"]);
+
+	for tid in &eager.seqnames
+	{
+		for (start, end) in [(0, 1000), (10_000, 250_000), (1_500_000, 3_000_000)]
+		{
+			assert_eq!(
+				eager.offsets_for_tid_region(tid, start, end).expect("eager query"),
+				lazy.offsets_for_tid_region(tid, start, end).expect("lazy query"),
+				"{tid}:{start}-{end}"
+			);
+		}
+
+		assert_eq!(
+			eager.offsets_for_tid(tid).expect("eager query"),
+			lazy.offsets_for_tid(tid).expect("lazy query")
+		);
+	}
+
+	assert_eq!(eager.total_records(), lazy.total_records());
+	assert!(lazy.offsets_for_tid("chrUn").expect("missing tid").is_none());
+}
+
+#[tokio::test]
+async fn flattened_bins_match_their_chunks()
+{
+	let index = load(&tbi(), LoadOptions::default()).await;
+
+	for idx in 0..index.seqnames.len()
+	{
+		let reference = index.reference(idx).expect("a reference per name");
+		let bins: Vec<(u64, Vec<std::ops::Range<u64>>)> = reference
+			.bins()
+			.map(|(bin, chunks)| (bin, chunks.to_vec()))
+			.collect();
+
+		assert!(!bins.is_empty());
+		assert!(bins.windows(2).all(|pair| pair[0].0 < pair[1].0), "bins in order");
+
+		for (bin, chunks) in &bins
+		{
+			assert_eq!(reference.bin(*bin), Some(chunks.as_slice()));
+		}
+
+		let flattened: Vec<std::ops::Range<u64>> =
+			bins.into_iter().flat_map(|(_, chunks)| chunks).collect();
+		assert_eq!(reference.chunks(), flattened.as_slice());
+		assert_eq!(reference.bin(u64::MAX), None);
+	}
+}
+
+#[tokio::test]
+async fn chunks_past_the_data_are_refused()
+{
+	let lines = |n: u64| -> Vec<String> {
+		(0..n)
+			.map(|i| format!("chr1\t{}\t{}\tname{i}", i * 100, i * 100 + 50))
+			.collect()
+	};
+
+	// an index for many blocks of data handed only the first few records of it
+	let (_, tbi) = indexed_bed_in_memory(lines(20_000)).expect("large");
+	let (data, _) = indexed_bed_in_memory(lines(10)).expect("small");
+
+	let pool = Arc::new(BgzfBlockPool::new(64, 64 * 1024));
+	let options = ReaderOptions::<()>::default().with_one_indexed(true);
+	let reader = reader_in_memory_with(data, &tbi, pool, options).await.expect("reader");
+
+	let e = reader
+		.query_region_concurrent(&Region::zero_based("chr1", 1_900_000, 1_900_100), 2)
+		.await
+		.expect_err("chunk past the data");
+
+	assert!(matches!(e.root(), error::Error::IndexOffset(_)), "{e}");
+}
+
+// a CSI index beside the data is passed over for the tabix one, wherever it comes in the search
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn csi_beside_the_data_falls_back_to_tbi()
+{
+	let dir = std::env::temp_dir().join(format!("sandman-csi-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).expect("temp dir");
+
+	let (data, tbi) = indexed_bed_in_memory(["chr1\t100\t200"]).expect("indexed");
+	let csi = bgzf_in_memory(b"CSI\x01\x0e\0\0\0\x05\0\0\0").expect("csi");
+
+	let path = dir.join("x.gz");
+	std::fs::write(&path, data).expect("data");
+	std::fs::write(dir.join("x.gz.csi"), csi).expect("csi");
+	std::fs::write(dir.join("x.tbi"), tbi).expect("tbi");
+
+	for search in [
+		tabix::IndexSearch::default(),
+		tabix::IndexSearch::default().with_patterns(["{path}.csi", "{stem}.tbi"]),
+	]
+	{
+		let index = tabix::Reader::for_data_path(&path, &search)
+			.await
+			.expect("index")
+			.expect("tbi found");
+
+		assert_eq!(index.seqnames, ["chr1"]);
+	}
+}
+
+#[test]
+fn positions_past_the_tabix_limit_are_refused()
+{
+	let end = Position::TABIX_MAX.get() + 1;
+	let e = indexed_bed_in_memory([format!("chr1\t100\t{end}")]).expect_err("past the limit");
+
+	assert!(matches!(e.root(), error::Error::PositionOutOfRange(at) if *at == end), "{e}");
+}
+
+#[test]
+fn track_lines_after_records_are_refused()
+{
+	let lines = ["track name=a", "chr1\t100\t200", "track name=b", "chr1\t50\t60"];
+	let e = indexed_bed_in_memory(lines).expect_err("second track");
+
+	assert!(matches!(e.root(), error::Error::Parse(_)), "{e}");
+	assert!(indexed_bed_in_memory(&lines[..2]).is_ok());
+}